@@ -0,0 +1,49 @@
+// Ambient loops layered under the music: birds in the forest, the fort's creaking door, the
+// waterfall at the pool and the chanting at the summoning. Each chapter's loop is named in its
+// `CHAPTERS` entry, starts when its cutscene or fight is entered and stops when it's left.
+use bevy::audio::Volume;
+use bevy::prelude::*;
+
+use super::GameState;
+use crate::chapters::CHAPTERS;
+
+// Kept quiet so the loops sit under the music and the typewriter
+const AMBIENCE_VOLUME: f32 = 0.4;
+
+#[derive(Component)]
+struct Ambience;
+
+pub fn ambience_plugin(app: &mut App) {
+    for chapter in &CHAPTERS {
+        for state in [chapter.start, chapter.fight] {
+            app.add_systems(OnEnter(state), start_ambience)
+                .add_systems(OnExit(state), stop_ambience);
+        }
+    }
+}
+
+fn start_ambience(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    state: Res<State<GameState>>,
+) {
+    let Some(chapter) = CHAPTERS
+        .iter()
+        .find(|chapter| chapter.start == *state.get() || chapter.fight == *state.get())
+    else {
+        return;
+    };
+    commands.spawn((
+        AudioBundle {
+            source: asset_server.load(chapter.ambience),
+            settings: PlaybackSettings::LOOP.with_volume(Volume::new(AMBIENCE_VOLUME)),
+        },
+        Ambience,
+    ));
+}
+
+fn stop_ambience(mut commands: Commands, ambience_query: Query<Entity, With<Ambience>>) {
+    for entity in &ambience_query {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,280 @@
+// Arena mode, started from the menu with one of a few starting decks. Waves made by the endless
+// generator are fought one after another on the same background, and between waves the player
+// picks one of three quick upgrades instead of visiting the deck screen. `ArenaWaves` keeps the
+// count and sets up each wave, so no chapter is involved. Arena fights aren't kept in the save.
+use bevy::prelude::*;
+
+use super::GameState;
+use crate::combat::{add_combat_state, combat_setup, CardType, Encounter};
+use crate::deck::Deck;
+use crate::endless::generate_encounter;
+use crate::leaderboard::{Board, Leaderboards};
+use crate::profile::Profiles;
+use crate::rng::RunRng;
+use crate::run_effects::{RunEffect, RunEffects};
+use crate::shared_assets::SharedAssets;
+use crate::theme::Theme;
+use crate::ui;
+
+const ARENA_BACKGROUND: &str = "textures/2.png";
+
+// Upgrades offered between waves
+const UPGRADE_CHOICES: usize = 3;
+
+// Relics the upgrades can offer, each only until it's been taken
+const ARENA_RELICS: [RunEffect; 4] = [
+    RunEffect::RuneWard,
+    RunEffect::HeartStone,
+    RunEffect::Windcharm,
+    RunEffect::PhoenixFeather,
+];
+
+pub struct ArenaDeck {
+    pub name: &'static str,
+    pub cards: &'static [CardType],
+}
+
+pub const ARENA_DECKS: [ArenaDeck; 4] = [
+    ArenaDeck {
+        name: "Balanced",
+        cards: &[
+            CardType::Earth,
+            CardType::Crystal,
+            CardType::Fire,
+            CardType::Ice,
+        ],
+    },
+    ArenaDeck {
+        name: "Inferno",
+        cards: &[
+            CardType::Fire,
+            CardType::Fire,
+            CardType::Fire,
+            CardType::Air,
+        ],
+    },
+    ArenaDeck {
+        name: "Bulwark",
+        cards: &[
+            CardType::Earth,
+            CardType::Earth,
+            CardType::Ice,
+            CardType::Heal,
+        ],
+    },
+    ArenaDeck {
+        name: "Tempest",
+        cards: &[
+            CardType::Air,
+            CardType::Air,
+            CardType::Crystal,
+            CardType::Ice,
+        ],
+    },
+];
+
+// The arena run under way: the wave being fought, and the seed the waves are made from
+#[derive(Resource)]
+pub struct ArenaWaves {
+    wave: u32,
+    seed: u64,
+}
+
+impl ArenaWaves {
+    pub fn new(rng: &RunRng) -> Self {
+        Self {
+            wave: 1,
+            seed: rng.seed(),
+        }
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+enum Upgrade {
+    Card(CardType),
+    Relic(RunEffect),
+}
+
+impl Upgrade {
+    fn label(self) -> String {
+        match self {
+            Upgrade::Card(card_type) => format!("Add a {card_type:?} card"),
+            Upgrade::Relic(effect) => format!("{}\n{}", effect.name(), effect.description()),
+        }
+    }
+}
+
+pub fn arena_plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Arena), wave_setup.before(combat_setup))
+        .add_systems(OnEnter(GameState::ArenaUpgrade), upgrade_setup)
+        .add_systems(
+            Update,
+            pick_upgrade.run_if(in_state(GameState::ArenaUpgrade)),
+        )
+        .add_systems(OnEnter(GameState::Menu), end_arena);
+    add_combat_state(app, GameState::Arena);
+}
+
+fn wave_setup(mut commands: Commands, waves: Res<ArenaWaves>) {
+    commands.insert_resource(Encounter {
+        background: ARENA_BACKGROUND,
+        // Winning goes to the upgrade picks rather than the deck screen
+        next_state: GameState::ArenaUpgrade,
+        card_rewards: Vec::new(),
+        ..generate_encounter(waves.wave, waves.seed)
+    });
+}
+
+// Two different cards, and a relic not taken yet while any are left
+fn roll_upgrades(rng: &mut RunRng, run_effects: &RunEffects) -> Vec<Upgrade> {
+    let mut cards = CardType::ALL.to_vec();
+    rng.shuffle(&mut cards);
+    let mut upgrades: Vec<Upgrade> = cards.into_iter().map(Upgrade::Card).collect();
+    let relics: Vec<RunEffect> = ARENA_RELICS
+        .into_iter()
+        .filter(|relic| !run_effects.effects().contains(relic))
+        .collect();
+    if !relics.is_empty() {
+        upgrades.insert(2, Upgrade::Relic(relics[rng.below(relics.len())]));
+    }
+    upgrades.truncate(UPGRADE_CHOICES);
+    upgrades
+}
+
+fn upgrade_setup(
+    mut commands: Commands,
+    mut waves: ResMut<ArenaWaves>,
+    mut rng: ResMut<RunRng>,
+    run_effects: Res<RunEffects>,
+    shared_assets: Res<SharedAssets>,
+    theme: Res<Theme>,
+) {
+    let cleared = waves.wave;
+    waves.wave += 1;
+    let upgrades = roll_upgrades(&mut rng, &run_effects);
+    let text_style = theme.text_style(30.0);
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(20.0),
+                    ..default()
+                },
+                background_color: Color::srgb(0.05, 0.05, 0.08).into(),
+                ..default()
+            },
+            StateScoped(GameState::ArenaUpgrade),
+        ))
+        .with_children(|parent| {
+            parent.spawn(ui::title_text(&theme, format!("Wave {cleared} cleared")));
+            parent.spawn(TextBundle::from_section(
+                "Pick an upgrade",
+                text_style.clone(),
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        column_gap: Val::Px(24.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for upgrade in upgrades {
+                        parent
+                            .spawn((
+                                ui::button(
+                                    &theme,
+                                    Style {
+                                        width: Val::Px(260.0),
+                                        height: Val::Px(280.0),
+                                        flex_direction: FlexDirection::Column,
+                                        justify_content: JustifyContent::Center,
+                                        align_items: AlignItems::Center,
+                                        row_gap: Val::Px(12.0),
+                                        padding: UiRect::all(Val::Px(12.0)),
+                                        ..default()
+                                    },
+                                ),
+                                upgrade,
+                            ))
+                            .with_children(|parent| {
+                                if let Upgrade::Card(card_type) = upgrade {
+                                    let (image, atlas) = shared_assets.card(card_type);
+                                    parent.spawn((
+                                        ImageBundle {
+                                            style: Style {
+                                                width: Val::Px(120.0),
+                                                height: Val::Px(167.0),
+                                                ..default()
+                                            },
+                                            image,
+                                            ..default()
+                                        },
+                                        atlas,
+                                    ));
+                                }
+                                parent.spawn(
+                                    TextBundle::from_section(
+                                        upgrade.label(),
+                                        TextStyle {
+                                            font_size: 24.0,
+                                            ..text_style.clone()
+                                        },
+                                    )
+                                    .with_text_justify(JustifyText::Center),
+                                );
+                            });
+                    }
+                });
+        });
+}
+
+// Take the upgrade and go straight on to the next wave
+fn pick_upgrade(
+    mut interaction_query: ui::ChangedActionButtons<Upgrade>,
+    mut deck: ResMut<Deck>,
+    mut run_effects: ResMut<RunEffects>,
+    mut game_state: ResMut<NextState<GameState>>,
+    theme: Res<Theme>,
+) {
+    for (interaction, upgrade, mut color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                match *upgrade {
+                    Upgrade::Card(card_type) => deck.add(card_type),
+                    Upgrade::Relic(effect) => {
+                        run_effects.add(effect);
+                    }
+                }
+                game_state.set(GameState::Arena);
+            }
+            Interaction::Hovered => *color = theme.button_hovered.into(),
+            Interaction::None => *color = theme.button.into(),
+        }
+    }
+}
+
+// The run is over once the player leaves for the menu, so its waves go to the leaderboard
+fn end_arena(
+    mut commands: Commands,
+    waves: Option<Res<ArenaWaves>>,
+    mut leaderboards: ResMut<Leaderboards>,
+    profiles: Res<Profiles>,
+) {
+    let Some(waves) = waves else {
+        return;
+    };
+    let cleared = waves.wave - 1;
+    if cleared > 0 {
+        leaderboards.submit(Board::Arena, profiles.active_name(), cleared as f32);
+    }
+    commands.remove_resource::<ArenaWaves>();
+}
@@ -0,0 +1,271 @@
+// Benchmark scene, for catching slowdowns in the animation and UI systems. Starting the game with
+// `BENCH_ARG` skips the profiles and menus for a fight loaded as heavily as the game ever gets:
+// every cutscene sprite sheet animating at once, a full hand, `DAMAGE_TEXTS` floating numbers
+// and a long line of dialogue typing out a character a frame, at all times. After the given
+// number of frames (`DEFAULT_FRAMES` if none) the frame times are printed and the game quits.
+// Vsync is turned off so the numbers aren't capped.
+use std::env;
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy::window::{PresentMode, PrimaryWindow};
+
+use super::{DisplayQuality, GameState};
+use crate::combat::{
+    add_combat_state, combat_setup, spawn_damage_text, AddToHand, CardType, Encounter, EnemyMove,
+    LootTable, MonsterSpec, OPENING_HAND_SIZE,
+};
+use crate::cutscene::{
+    animate_sprite, spawn_background, spawn_text_entity, type_text, CutsceneLine,
+    TextSequenceState, TypingText,
+};
+use crate::deck::Deck;
+use crate::inventory::Inventory;
+use crate::rng::RunRng;
+use crate::shared_assets::SharedAssets;
+use crate::story::{StoryFlag, StoryFlags};
+use crate::texture_quality::{sheet_layout, texture_path};
+
+pub const BENCH_ARG: &str = "--bench";
+
+const DEFAULT_FRAMES: u32 = 600;
+// Frames left out of the numbers at the start, while the scene settles
+const WARMUP_FRAMES: u32 = 60;
+
+const SEED: u64 = 2975;
+
+const SHEETS: [&str; 4] = [
+    "textures/intro_game_sprite.png",
+    "textures/forest_fort.png",
+    "textures/pool.png",
+    "textures/summoning.png",
+];
+
+// Each number lives a second, so this many are spawned every second
+const DAMAGE_TEXTS: usize = 50;
+
+// Typed with no delay between characters, and started again once it's done. Long, as typing
+// gets slower the further into a line it is if each character costs the whole line.
+const DIALOGUE: &str =
+    "The forest was quiet, too quiet. Somewhere beyond the old fort the wind carried voices \
+    that did not belong to anyone living, and the path ahead twisted between roots older \
+    than the kingdom itself. Every step forward felt like a step further from home, and yet \
+    there was nothing to do but keep walking, listening, and hoping the fire would still be \
+    burning when the night was over.";
+
+// One of every card
+const HAND_SIZE: usize = CardType::ALL.len();
+
+#[derive(Resource)]
+struct BenchRun {
+    frames: u32,
+    warmup: u32,
+    frame_times: Vec<f64>,
+    damage_timer: Timer,
+}
+
+pub fn bench_plugin(app: &mut App) {
+    let Some(frames) = bench_arg() else {
+        return;
+    };
+    app.insert_resource(BenchRun {
+        frames,
+        warmup: WARMUP_FRAMES,
+        frame_times: Vec::new(),
+        damage_timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+    })
+    .add_systems(Startup, turn_off_vsync)
+    .add_systems(PreUpdate, skip_to_bench)
+    .add_systems(OnEnter(GameState::Bench), bench_setup.before(combat_setup))
+    .add_systems(OnEnter(GameState::Bench), fill_scene.after(combat_setup))
+    .add_systems(
+        Update,
+        (
+            animate_sprite,
+            keep_damage_texts,
+            (keep_typing, type_text).chain(),
+            measure_frames,
+        )
+            .run_if(in_state(GameState::Bench)),
+    );
+    add_combat_state(app, GameState::Bench);
+}
+
+// The number of frames to measure, if the game was started to benchmark
+fn bench_arg() -> Option<u32> {
+    let mut args = env::args().skip(1);
+    if args.next()? != BENCH_ARG {
+        return None;
+    }
+    Some(
+        args.next()
+            .and_then(|frames| frames.parse().ok())
+            .filter(|frames| *frames > 0)
+            .unwrap_or(DEFAULT_FRAMES),
+    )
+}
+
+fn turn_off_vsync(mut window_query: Query<&mut Window, With<PrimaryWindow>>) {
+    for mut window in window_query.iter_mut() {
+        window.present_mode = PresentMode::AutoNoVsync;
+    }
+}
+
+// Once loading is done, the bench goes where the profile screen would have
+fn skip_to_bench(mut next_state: ResMut<NextState<GameState>>) {
+    if matches!(*next_state, NextState::Pending(GameState::Profiles)) {
+        next_state.set(GameState::Bench);
+    }
+}
+
+fn bench_setup(
+    mut commands: Commands,
+    mut deck: ResMut<Deck>,
+    mut rng: ResMut<RunRng>,
+    display_quality: Res<DisplayQuality>,
+    asset_server: Res<AssetServer>,
+    mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+) {
+    *deck = Deck::from_cards(CardType::ALL.to_vec());
+    *rng = RunRng::from_seed(SEED);
+    commands.insert_resource(TextSequenceState::new(
+        vec![CutsceneLine::new(DIALOGUE).with_speed(0.0)],
+        &StoryFlags::default(),
+        &Inventory::default(),
+    ));
+
+    // The same fight every time, that lasts: the monsters have plenty of health and only defend
+    let monster = |texture, offset| MonsterSpec {
+        texture,
+        health: 1000.0,
+        damage: 0.0,
+        size: 250.0,
+        offset: Vec2::new(offset, 0.0),
+        lift: -75.0,
+        label_height: 120.0,
+        health_bar_depth: -100.0,
+        moves: vec![EnemyMove::Defend(5.0)],
+        loot: LootTable::default(),
+    };
+    commands.insert_resource(Encounter {
+        background: "textures/1.png",
+        monsters: vec![
+            monster("textures/monster.png", 1.0 / 8.0),
+            monster("textures/monster_2.png", -1.0 / 8.0),
+        ],
+        next_state: GameState::Menu,
+        victory_flag: StoryFlag::ForestCleared,
+        wounded_flag: None,
+        reward: None,
+        gold_reward: 0,
+        card_rewards: Vec::new(),
+        enrage_after: None,
+        ritual: None,
+        effect_reward: None,
+        intro: None,
+    });
+
+    // Laid out as the cutscenes lay theirs out
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let layout = atlas_layouts.add(sheet_layout(*display_quality));
+    for sheet in SHEETS {
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Vw(100.0),
+                        height: Val::Vh(100.0),
+                        position_type: PositionType::Absolute,
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    ..default()
+                },
+                StateScoped(GameState::Bench),
+            ))
+            .with_children(|parent| {
+                spawn_background(
+                    parent,
+                    None,
+                    asset_server.load(texture_path(sheet, *display_quality)),
+                    layout.clone(),
+                    &asset_server,
+                    window,
+                );
+            });
+    }
+}
+
+// The rest of the hand on top of the opening one, and the first numbers
+fn fill_scene(mut commands: Commands, mut add_to_hand: EventWriter<AddToHand>) {
+    for card_type in CardType::ALL
+        .into_iter()
+        .take(HAND_SIZE - OPENING_HAND_SIZE)
+    {
+        add_to_hand.send(AddToHand {
+            card_type,
+            amount: 1,
+        });
+    }
+    spawn_damage_texts(&mut commands);
+}
+
+fn spawn_damage_texts(commands: &mut Commands) {
+    for damage in 1..=DAMAGE_TEXTS {
+        spawn_damage_text(commands, damage as f32);
+    }
+}
+
+fn keep_damage_texts(mut commands: Commands, time: Res<Time>, mut bench: ResMut<BenchRun>) {
+    if bench.damage_timer.tick(time.delta()).just_finished() {
+        spawn_damage_texts(&mut commands);
+    }
+}
+
+fn keep_typing(
+    mut commands: Commands,
+    typing_query: Query<(Entity, &TypingText)>,
+    shared_assets: Res<SharedAssets>,
+) {
+    if typing_query.iter().any(|(_, typing)| !typing.completed()) {
+        return;
+    }
+    for (entity, _) in typing_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    spawn_text_entity(&mut commands, 0, &shared_assets);
+}
+
+fn measure_frames(
+    time: Res<Time<Real>>,
+    mut bench: ResMut<BenchRun>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if bench.warmup > 0 {
+        bench.warmup -= 1;
+        return;
+    }
+    bench.frame_times.push(time.delta_seconds_f64() * 1000.0);
+    if bench.frame_times.len() < bench.frames as usize {
+        return;
+    }
+
+    let mut frame_times = bench.frame_times.clone();
+    frame_times.sort_by(f64::total_cmp);
+    let average = frame_times.iter().sum::<f64>() / frame_times.len() as f64;
+    let percentile =
+        |share: f64| frame_times[((frame_times.len() - 1) as f64 * share).round() as usize];
+    println!(
+        "Bench: {} frames, average {average:.2} ms ({:.0} FPS), median {:.2} ms, 99th percentile {:.2} ms, worst {:.2} ms",
+        frame_times.len(),
+        1000.0 / average,
+        percentile(0.5),
+        percentile(0.99),
+        frame_times[frame_times.len() - 1],
+    );
+    exit.send(AppExit::Success);
+}
@@ -0,0 +1,331 @@
+// A short intro before a boss fight, played in the fight's own state over the fight that's
+// already set up: a title card, the boss sliding in, its name plate, then a few lines typed out
+// like any other cutscene. The fight is covered until it ends, or the player clicks to skip it.
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+
+use super::{despawn_screen, GameState};
+use crate::combat::{combat_setup, Boss, Encounter};
+use crate::cutscene::{
+    manage_text_sequence, spawn_text_entity, type_text, CutsceneLine, Cutscenes, TextSequenceState,
+    TypingText,
+};
+use crate::game_window::GameWindow;
+use crate::gold::GOLD_COLOR;
+use crate::save::SaveData;
+use crate::shared_assets::SharedAssets;
+use crate::theme::Theme;
+
+// Seconds into the intro each part shows up
+const TITLE_FADE: f32 = 0.5;
+const TITLE_END: f32 = 2.5;
+const SLIDE_START: f32 = 1.0;
+const SLIDE_END: f32 = 2.5;
+const NAME_PLATE_START: f32 = 2.5;
+const LINES_START: f32 = 3.0;
+
+// How long the last line stays up before the fight begins
+const LAST_LINE_HOLD: f32 = 2.0;
+
+// Share of the screen each letterbox bar covers
+const LETTERBOX_HEIGHT: f32 = 22.0;
+
+// What a boss fight's intro shows, written like any cutscene
+#[derive(Clone)]
+pub struct BossIntro {
+    pub title: &'static str,
+    pub name: &'static str,
+    pub epithet: &'static str,
+    pub lines: Vec<CutsceneLine>,
+}
+
+// The intro being played, and how far it has got
+#[derive(Resource, Default)]
+struct IntroPlaying {
+    elapsed: f32,
+    // The boss and where it stands once it has slid in
+    boss: Option<(Entity, Vec3)>,
+    slide_distance: f32,
+    lines_started: bool,
+    // Seconds the last line has been fully typed
+    last_line_shown: f32,
+}
+
+#[derive(Component)]
+struct IntroScreen;
+
+#[derive(Component)]
+struct TitleCard;
+
+#[derive(Component)]
+struct NamePlate;
+
+// Plays the `Encounter`'s intro, if it has one, before the fight in `state` starts. Call it
+// after `add_combat_state` for the same state.
+pub fn add_boss_intro(app: &mut App, state: GameState) {
+    app.add_systems(OnEnter(state), queue_intro.before(combat_setup))
+        .add_systems(
+            OnEnter(state),
+            start_intro
+                .after(combat_setup)
+                .run_if(resource_exists::<IntroPlaying>),
+        )
+        .add_systems(
+            Update,
+            (
+                play_intro,
+                fade_intro_text,
+                manage_text_sequence.run_if(lines_started),
+                type_text.run_if(lines_started),
+                raise_intro_text,
+                finish_intro,
+            )
+                .chain()
+                .run_if(in_state(state))
+                .run_if(resource_exists::<IntroPlaying>),
+        )
+        .add_systems(
+            OnExit(state),
+            (
+                end_intro,
+                despawn_screen::<IntroScreen>,
+                despawn_screen::<TypingText>,
+            ),
+        );
+}
+
+// Bevy checks a system's own conditions even when its set's have already failed, so this is
+// asked outside of an intro too
+fn lines_started(intro: Option<Res<IntroPlaying>>) -> bool {
+    intro.is_some_and(|intro| intro.lines_started)
+}
+
+// Not when picking a fight back up, as the intro was seen when it first started
+fn queue_intro(
+    mut commands: Commands,
+    encounter: Res<Encounter>,
+    save_data: Res<SaveData>,
+    state: Res<State<GameState>>,
+) {
+    let resuming = save_data
+        .fight
+        .as_ref()
+        .is_some_and(|fight| fight.state == *state.get());
+    if encounter.intro.is_some() && !resuming {
+        commands.insert_resource(IntroPlaying::default());
+    }
+}
+
+fn start_intro(
+    mut commands: Commands,
+    encounter: Res<Encounter>,
+    mut intro: ResMut<IntroPlaying>,
+    mut boss_query: Query<(Entity, &mut Transform), With<Boss>>,
+    game_window: GameWindow,
+    theme: Res<Theme>,
+) {
+    let Some(boss_intro) = &encounter.intro else {
+        return;
+    };
+    // Start the boss off the right edge of the screen
+    intro.slide_distance = game_window.get().map_or(1000.0, |window| window.width());
+    if let Ok((boss, mut transform)) = boss_query.get_single_mut() {
+        intro.boss = Some((boss, transform.translation));
+        transform.translation.x += intro.slide_distance;
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::SpaceBetween,
+                    ..default()
+                },
+                // Nothing in the fight can be clicked until the intro is over
+                focus_policy: FocusPolicy::Block,
+                z_index: ZIndex::Global(20),
+                ..default()
+            },
+            Interaction::default(),
+            IntroScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(letterbox_bar()).with_children(|bar| {
+                bar.spawn((
+                    TextBundle::from_sections([
+                        TextSection::new(
+                            boss_intro.name,
+                            TextStyle {
+                                font_size: 56.0,
+                                color: GOLD_COLOR.with_alpha(0.0),
+                                ..default()
+                            },
+                        ),
+                        TextSection::new(
+                            format!("\n{}", boss_intro.epithet),
+                            TextStyle {
+                                font_size: 26.0,
+                                color: theme.text.with_alpha(0.0),
+                                ..default()
+                            },
+                        ),
+                    ])
+                    .with_text_justify(JustifyText::Center),
+                    NamePlate,
+                ));
+            });
+            parent.spawn((
+                TextBundle::from_section(
+                    boss_intro.title,
+                    TextStyle {
+                        font_size: 72.0,
+                        color: theme.text.with_alpha(0.0),
+                        ..default()
+                    },
+                )
+                .with_style(Style {
+                    align_self: AlignSelf::Center,
+                    ..default()
+                }),
+                TitleCard,
+            ));
+            parent.spawn(letterbox_bar()).with_children(|bar| {
+                bar.spawn(
+                    TextBundle::from_section(
+                        "Click to skip",
+                        TextStyle {
+                            font_size: 18.0,
+                            color: theme.text.with_alpha(0.5),
+                            ..default()
+                        },
+                    )
+                    .with_style(Style {
+                        position_type: PositionType::Absolute,
+                        right: Val::Px(20.0),
+                        bottom: Val::Px(12.0),
+                        ..default()
+                    }),
+                );
+            });
+        });
+}
+
+fn letterbox_bar() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            width: Val::Percent(100.0),
+            height: Val::Percent(LETTERBOX_HEIGHT),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        background_color: Color::BLACK.into(),
+        ..default()
+    }
+}
+
+// Slide the boss in, and start the lines once the name plate is up
+fn play_intro(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut intro: ResMut<IntroPlaying>,
+    encounter: Res<Encounter>,
+    cutscenes: Cutscenes,
+    shared_assets: Res<SharedAssets>,
+    mut boss_query: Query<&mut Transform, With<Boss>>,
+) {
+    intro.elapsed += time.delta_seconds();
+
+    if let Some((boss, home)) = intro.boss {
+        if let Ok(mut transform) = boss_query.get_mut(boss) {
+            let t = ((intro.elapsed - SLIDE_START) / (SLIDE_END - SLIDE_START)).clamp(0.0, 1.0);
+            let eased = 1.0 - (1.0 - t) * (1.0 - t);
+            transform.translation.x = home.x + intro.slide_distance * (1.0 - eased);
+        }
+    }
+
+    if !intro.lines_started && intro.elapsed >= LINES_START {
+        intro.lines_started = true;
+        let Some(boss_intro) = &encounter.intro else {
+            return;
+        };
+        let sequence = cutscenes.sequence(boss_intro.lines.clone());
+        if !sequence.texts.is_empty() {
+            spawn_text_entity(&mut commands, 0, &shared_assets);
+        }
+        commands.insert_resource(sequence);
+    }
+}
+
+fn fade_intro_text(
+    intro: Res<IntroPlaying>,
+    mut title_query: Query<&mut Text, (With<TitleCard>, Without<NamePlate>)>,
+    mut name_query: Query<&mut Text, With<NamePlate>>,
+    theme: Res<Theme>,
+) {
+    let title_alpha = (intro.elapsed / TITLE_FADE)
+        .min((TITLE_END - intro.elapsed) / TITLE_FADE)
+        .clamp(0.0, 1.0);
+    for mut text in title_query.iter_mut() {
+        text.sections[0].style.color = theme.text.with_alpha(title_alpha);
+    }
+    let name_alpha = ((intro.elapsed - NAME_PLATE_START) / TITLE_FADE).clamp(0.0, 1.0);
+    for mut text in name_query.iter_mut() {
+        text.sections[0].style.color = GOLD_COLOR.with_alpha(name_alpha);
+        text.sections[1].style.color = theme.text.with_alpha(name_alpha);
+    }
+}
+
+// The cutscene text is laid out on its own, so lift it above the letterbox bars
+fn raise_intro_text(mut commands: Commands, text_query: Query<Entity, Added<TypingText>>) {
+    for entity in text_query.iter() {
+        commands.entity(entity).insert(ZIndex::Global(21));
+    }
+}
+
+// Hand over to the fight once the last line has been read, or straight away on a click
+fn finish_intro(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut intro: ResMut<IntroPlaying>,
+    sequence: Option<Res<TextSequenceState>>,
+    typing_query: Query<(Entity, &TypingText)>,
+    screen_query: Query<(Entity, &Interaction), With<IntroScreen>>,
+    mut boss_query: Query<&mut Transform, With<Boss>>,
+) {
+    let skipped = screen_query
+        .iter()
+        .any(|(_, interaction)| *interaction == Interaction::Pressed);
+    if intro.lines_started && !skipped {
+        let on_last_line =
+            sequence.is_none_or(|sequence| sequence.current_sequence + 1 >= sequence.texts.len());
+        if on_last_line && typing_query.iter().all(|(_, typing)| typing.completed()) {
+            intro.last_line_shown += time.delta_seconds();
+        }
+    }
+    if !skipped && intro.last_line_shown < LAST_LINE_HOLD {
+        return;
+    }
+
+    if let Some((boss, home)) = intro.boss {
+        if let Ok(mut transform) = boss_query.get_mut(boss) {
+            transform.translation = home;
+        }
+    }
+    for (entity, _) in screen_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    for (entity, _) in typing_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.remove_resource::<IntroPlaying>();
+}
+
+// Leaving the fight while the intro plays drops it
+fn end_intro(mut commands: Commands) {
+    commands.remove_resource::<IntroPlaying>();
+}
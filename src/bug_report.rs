@@ -0,0 +1,276 @@
+// Bug reports from testers. "Report issue" in the pause menu bundles a screenshot, the state the
+// game is in, the run's seed, the last few combat events and the recent log into a zip under
+// `REPORTS_DIR`, so a report says what happened without anyone having to describe it. Setting
+// `REPORT_URL_VAR` to an `http://host[:port]/path` address also POSTs each zip there.
+use std::collections::VecDeque;
+use std::env;
+use std::fmt::Debug;
+use std::fs;
+use std::io::Cursor;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::log::tracing_subscriber::layer::{Context, Layer};
+use bevy::log::BoxedLayer;
+use bevy::prelude::*;
+use bevy::render::view::screenshot::ScreenshotManager;
+use bevy::utils::tracing::field::{Field, Visit};
+use bevy::utils::tracing::{Event as LogEvent, Subscriber};
+use bevy::window::PrimaryWindow;
+
+use super::GameState;
+use crate::crash::{recent_fight_events, BEVY_VERSION};
+use crate::platform::data_path;
+use crate::profile::active_profile_dir;
+use crate::rng::RunRng;
+use crate::save_backend::Address;
+use crate::theme::Theme;
+
+const REPORTS_DIR: &str = "reports";
+
+pub const REPORT_URL_VAR: &str = "SPRITED_TOWARDS_REPORT_URL";
+
+const LOG_LINES_KEPT: usize = 500;
+
+const NOTICE_SECONDS: f32 = 4.0;
+
+// The newest log lines, kept by the log layer for the next report
+static RECENT_LOG: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+// Where each report went once it's written, picked up by the game to tell the player
+static FINISHED_REPORTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+// Sent by the pause menu, with the menu already gone so the screenshot shows the game
+#[derive(Event)]
+pub struct ReportIssue;
+
+#[derive(Component)]
+struct ReportNotice {
+    timer: Timer,
+}
+
+pub fn bug_report_plugin(app: &mut App) {
+    app.add_event::<ReportIssue>()
+        .add_systems(Update, (capture_report, show_finished_reports));
+}
+
+// Handed to the `LogPlugin`, so every line logged is kept for reports as well as printed
+pub fn log_layer(_app: &mut App) -> Option<BoxedLayer> {
+    Some(Box::new(LogKeeper))
+}
+
+struct LogKeeper;
+
+impl<S: Subscriber> Layer<S> for LogKeeper {
+    fn on_event(&self, event: &LogEvent<'_>, _context: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut line = format!("{} {}:", metadata.level(), metadata.target());
+        event.record(&mut LineWriter(&mut line));
+        if let Ok(mut log) = RECENT_LOG.lock() {
+            log.push_back(line);
+            if log.len() > LOG_LINES_KEPT {
+                log.pop_front();
+            }
+        }
+    }
+}
+
+// Writes an event's message and fields onto its log line
+struct LineWriter<'a>(&'a mut String);
+
+impl Visit for LineWriter<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "message" {
+            self.0.push_str(&format!(" {value:?}"));
+        } else {
+            self.0.push_str(&format!(" {}={value:?}", field.name()));
+        }
+    }
+}
+
+fn capture_report(
+    mut reports: EventReader<ReportIssue>,
+    mut screenshots: ResMut<ScreenshotManager>,
+    window_query: Query<Entity, With<PrimaryWindow>>,
+    game_state: Res<State<GameState>>,
+    rng: Res<RunRng>,
+) {
+    if reports.read().count() == 0 {
+        return;
+    }
+
+    let created = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let mut report = format!(
+        "Sprited Towards {} bug report\nBevy {BEVY_VERSION}\nTime: {created} seconds since the Unix epoch\nProfile: {}\nState: {:?}\nSeed: {}\nLast combat events:\n",
+        env!("CARGO_PKG_VERSION"),
+        active_profile_dir(),
+        game_state.get(),
+        rng.seed(),
+    );
+    for event in recent_fight_events() {
+        report += &format!("  {event}\n");
+    }
+    let log: String = RECENT_LOG
+        .lock()
+        .map(|log| log.iter().map(|line| format!("{line}\n")).collect())
+        .unwrap_or_default();
+    let name = format!("report-{created}.zip");
+
+    // The screenshot comes back once the frame is drawn, and the zip is written from there
+    let taken = window_query.get_single().ok().and_then(|window| {
+        let (report, log, name) = (report.clone(), log.clone(), name.clone());
+        screenshots
+            .take_screenshot(window, move |image| {
+                let mut png = Vec::new();
+                match image.try_into_dynamic() {
+                    Ok(image) => {
+                        if let Err(err) =
+                            image.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+                        {
+                            warn!("Could not encode the report's screenshot: {err}");
+                        }
+                    }
+                    Err(err) => warn!("Could not read the report's screenshot: {err:?}"),
+                }
+                thread::spawn(move || write_report(&name, &report, &log, &png));
+            })
+            .ok()
+    });
+    if taken.is_none() {
+        warn!("Could not take a screenshot for the report");
+        thread::spawn(move || write_report(&name, &report, &log, &[]));
+    }
+}
+
+fn write_report(name: &str, report: &str, log: &str, png: &[u8]) {
+    let mut files = vec![
+        ("report.txt", report.as_bytes()),
+        ("log.txt", log.as_bytes()),
+    ];
+    if !png.is_empty() {
+        files.push(("screenshot.png", png));
+    }
+    let contents = zip(&files);
+
+    let dir = data_path(REPORTS_DIR);
+    let path = dir.join(name);
+    if let Err(err) = fs::create_dir_all(&dir).and_then(|_| fs::write(&path, &contents)) {
+        warn!("Could not write the report {}: {err}", path.display());
+        return;
+    }
+    info!("Wrote the report to {}", path.display());
+
+    let mut notice = format!("Report saved to {}", path.display());
+    if let Ok(url) = env::var(REPORT_URL_VAR) {
+        match Address::from_url(&url) {
+            Some(address) => {
+                match address.send("POST", address.path(), "application/zip", &contents) {
+                    Ok((200..=299, _)) => notice += " and sent",
+                    Ok((status, _)) => warn!("Sending the report failed with status {status}"),
+                    Err(err) => warn!("Sending the report failed: {err}"),
+                }
+            }
+            None => warn!("Ignoring {REPORT_URL_VAR}: {url} is not an http:// address"),
+        }
+    }
+    if let Ok(mut finished) = FINISHED_REPORTS.lock() {
+        finished.push(notice);
+    }
+}
+
+// A zip of the files as they are, without compression, which every unzip tool reads
+fn zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut archive = Vec::new();
+    let mut directory = Vec::new();
+    for (name, contents) in files {
+        let offset = archive.len() as u32;
+        let crc = crc32(contents);
+        let size = contents.len() as u32;
+        // Version needed, flags, stored, time and date (1980-01-01), then the sizes
+        let fields = |entry: &mut Vec<u8>| {
+            entry.extend_from_slice(&[20, 0, 0, 0, 0, 0, 0, 0, 0x21, 0]);
+            entry.extend_from_slice(&crc.to_le_bytes());
+            entry.extend_from_slice(&size.to_le_bytes());
+            entry.extend_from_slice(&size.to_le_bytes());
+            entry.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            entry.extend_from_slice(&[0, 0]);
+        };
+
+        archive.extend_from_slice(&0x04034b50u32.to_le_bytes());
+        fields(&mut archive);
+        archive.extend_from_slice(name.as_bytes());
+        archive.extend_from_slice(contents);
+
+        directory.extend_from_slice(&0x02014b50u32.to_le_bytes());
+        // Made by version 2.0
+        directory.extend_from_slice(&[20, 0]);
+        fields(&mut directory);
+        // Comment length, disk, internal and external attributes, then where the entry starts
+        directory.extend_from_slice(&[0; 10]);
+        directory.extend_from_slice(&offset.to_le_bytes());
+        directory.extend_from_slice(name.as_bytes());
+    }
+
+    let directory_offset = archive.len() as u32;
+    archive.extend_from_slice(&directory);
+    archive.extend_from_slice(&0x06054b50u32.to_le_bytes());
+    archive.extend_from_slice(&[0; 4]);
+    archive.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    archive.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    archive.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+    archive.extend_from_slice(&directory_offset.to_le_bytes());
+    archive.extend_from_slice(&[0, 0]);
+    archive
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb88320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn show_finished_reports(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut notice_query: Query<(Entity, &mut ReportNotice)>,
+    theme: Res<Theme>,
+) {
+    for (entity, mut notice) in notice_query.iter_mut() {
+        if notice.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    let finished = match FINISHED_REPORTS.lock() {
+        Ok(mut finished) => std::mem::take(&mut *finished),
+        Err(_) => return,
+    };
+    for notice in finished {
+        commands.spawn((
+            TextBundle::from_section(notice, theme.text_style(24.0))
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(24.0),
+                    left: Val::Px(24.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    ..default()
+                })
+                .with_background_color(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+            ReportNotice {
+                timer: Timer::from_seconds(NOTICE_SECONDS, TimerMode::Once),
+            },
+        ));
+    }
+}
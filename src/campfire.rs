@@ -0,0 +1,349 @@
+// Campfire between the chapters of a run, after the shop. The player takes one of three
+// rests: heal part of their health, upgrade a card, or burn a card out of the deck, then goes on
+// to the next part of the story. Upgrades are kept on the deck, so the save picks them up with it.
+use bevy::prelude::*;
+
+use super::GameState;
+use crate::combat::{CardType, Encounter, PlayerState};
+use crate::deck::{Deck, MIN_DECK_SIZE};
+use crate::shared_assets::SharedAssets;
+use crate::theme::Theme;
+use crate::ui;
+
+const CAMPFIRE_BACKGROUND: &str = "textures/1_b.png";
+
+// Share of the maximum health a rest heals
+const REST_HEAL_SHARE: f32 = 0.3;
+
+const CARD_WIDTH: f32 = 120.0;
+const CARD_HEIGHT: f32 = 167.0;
+
+// Warms the night forest up around the fire
+const FIRELIGHT_TINT: Color = Color::srgb(1.0, 0.7, 0.45);
+
+#[derive(Component)]
+struct OnCampfireScreen;
+
+// Everything over the background, rebuilt whenever the visit changes
+#[derive(Component)]
+struct CampfireContent;
+
+#[derive(Component, Clone, Copy)]
+enum CampfireButton {
+    Rest,
+    Smith,
+    Burn,
+    // The deck card at this index, while picking one to upgrade or burn
+    Card(usize),
+    Back,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Pick {
+    Upgrade,
+    Burn,
+}
+
+#[derive(Resource)]
+struct CampfireVisit {
+    picking: Option<Pick>,
+    message: Option<String>,
+}
+
+pub fn campfire_plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Campfire), campfire_setup)
+        .add_systems(
+            Update,
+            (rebuild_campfire_screen, campfire_buttons)
+                .chain()
+                .run_if(in_state(GameState::Campfire)),
+        );
+}
+
+fn campfire_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(CampfireVisit {
+        picking: None,
+        message: None,
+    });
+
+    commands.spawn((
+        ImageBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            image: UiImage::new(asset_server.load(CAMPFIRE_BACKGROUND)).with_color(FIRELIGHT_TINT),
+            ..default()
+        },
+        OnCampfireScreen,
+        StateScoped(GameState::Campfire),
+    ));
+}
+
+// The deck cards a pick can take: one of each type not yet upgraded, or every card to burn
+fn pick_cards(pick: Pick, deck: &Deck) -> Vec<(usize, CardType)> {
+    let cards = deck.cards().iter().copied().enumerate();
+    match pick {
+        Pick::Upgrade => {
+            let mut seen = Vec::new();
+            cards
+                .filter(|(_, card_type)| {
+                    let first = !seen.contains(card_type);
+                    seen.push(*card_type);
+                    first && card_type.upgradable() && !deck.is_upgraded(*card_type)
+                })
+                .collect()
+        }
+        Pick::Burn => cards.collect(),
+    }
+}
+
+fn rebuild_campfire_screen(
+    mut commands: Commands,
+    visit: Res<CampfireVisit>,
+    deck: Res<Deck>,
+    player_state: Res<PlayerState>,
+    screen: ui::ScreenContent<OnCampfireScreen, CampfireContent>,
+    shared_assets: Res<SharedAssets>,
+    theme: Res<Theme>,
+) {
+    if !visit.is_changed() {
+        return;
+    }
+    let Some(root) = screen.clear(&mut commands) else {
+        return;
+    };
+
+    let text_style = theme.text_style(30.0);
+    let health = match player_state.health() {
+        Some(health) => format!(
+            "Health {} / {}",
+            health.ceil(),
+            player_state.maximum().ceil()
+        ),
+        None => "Health full".to_string(),
+    };
+
+    commands.entity(root).with_children(|parent| {
+        parent
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        row_gap: Val::Px(20.0),
+                        ..default()
+                    },
+                    background_color: Color::srgba(0.0, 0.0, 0.0, 0.4).into(),
+                    ..default()
+                },
+                CampfireContent,
+            ))
+            .with_children(|parent| {
+                parent.spawn(ui::title_text(&theme, "Campfire"));
+                parent.spawn(TextBundle::from_section(health, text_style.clone()));
+
+                match visit.picking {
+                    None => {
+                        parent.spawn(TextBundle::from_section(
+                            "Rest a while before going on",
+                            text_style.clone(),
+                        ));
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    column_gap: Val::Px(24.0),
+                                    ..default()
+                                },
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                let heal = (REST_HEAL_SHARE * 100.0).round();
+                                let options = [
+                                    (
+                                        CampfireButton::Rest,
+                                        format!("Rest\nHeal {heal}% of your health"),
+                                    ),
+                                    (CampfireButton::Smith, "Smith\nUpgrade a card".to_string()),
+                                    (CampfireButton::Burn, "Burn\nRemove a card".to_string()),
+                                ];
+                                for (button, label) in options {
+                                    spawn_button(
+                                        parent,
+                                        button,
+                                        &label,
+                                        &text_style,
+                                        260.0,
+                                        &theme,
+                                    );
+                                }
+                            });
+                    }
+                    Some(pick) => {
+                        parent.spawn(TextBundle::from_section(
+                            match pick {
+                                Pick::Upgrade => {
+                                    "Pick a card to upgrade. Every copy of it hits or heals harder"
+                                }
+                                Pick::Burn => "Pick a card to burn",
+                            },
+                            text_style.clone(),
+                        ));
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    column_gap: Val::Px(16.0),
+                                    ..default()
+                                },
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                for (index, card_type) in pick_cards(pick, &deck) {
+                                    let (image, atlas) = shared_assets.card(card_type);
+                                    parent
+                                        .spawn((
+                                            ui::button(
+                                                &theme,
+                                                Style {
+                                                    padding: UiRect::all(Val::Px(8.0)),
+                                                    ..default()
+                                                },
+                                            ),
+                                            CampfireButton::Card(index),
+                                        ))
+                                        .with_children(|parent| {
+                                            parent.spawn((
+                                                ImageBundle {
+                                                    style: Style {
+                                                        width: Val::Px(CARD_WIDTH),
+                                                        height: Val::Px(CARD_HEIGHT),
+                                                        ..default()
+                                                    },
+                                                    image,
+                                                    ..default()
+                                                },
+                                                atlas,
+                                            ));
+                                        });
+                                }
+                            });
+                        spawn_button(
+                            parent,
+                            CampfireButton::Back,
+                            "Back",
+                            &text_style,
+                            250.0,
+                            &theme,
+                        );
+                    }
+                }
+
+                if let Some(message) = &visit.message {
+                    parent.spawn(TextBundle::from_section(
+                        message.clone(),
+                        TextStyle {
+                            font_size: 24.0,
+                            color: Color::srgb(1.0, 0.4, 0.3),
+                            ..default()
+                        },
+                    ));
+                }
+            });
+    });
+}
+
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    button: CampfireButton,
+    label: &str,
+    text_style: &TextStyle,
+    width: f32,
+    theme: &Theme,
+) {
+    parent
+        .spawn((
+            ui::button(
+                theme,
+                Style {
+                    width: Val::Px(width),
+                    padding: UiRect::all(Val::Px(16.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+            ),
+            button,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(label, text_style.clone())
+                    .with_text_justify(JustifyText::Center),
+            );
+        });
+}
+
+fn campfire_buttons(
+    mut interaction_query: ui::ChangedActionButtons<CampfireButton>,
+    mut visit: ResMut<CampfireVisit>,
+    mut deck: ResMut<Deck>,
+    mut player_state: ResMut<PlayerState>,
+    encounter: Res<Encounter>,
+    mut game_state: ResMut<NextState<GameState>>,
+    theme: Res<Theme>,
+) {
+    for (interaction, button, mut color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {}
+            Interaction::Hovered => {
+                *color = theme.button_hovered.into();
+                continue;
+            }
+            Interaction::None => {
+                *color = theme.button.into();
+                continue;
+            }
+        }
+
+        visit.message = None;
+        match *button {
+            CampfireButton::Rest => {
+                let amount = player_state.maximum() * REST_HEAL_SHARE;
+                player_state.heal(amount);
+                game_state.set(encounter.next_state);
+            }
+            CampfireButton::Smith => {
+                if pick_cards(Pick::Upgrade, &deck).is_empty() {
+                    visit.message = Some("Every card in your deck is upgraded".to_string());
+                } else {
+                    visit.picking = Some(Pick::Upgrade);
+                }
+            }
+            CampfireButton::Burn => {
+                if deck.cards().len() <= MIN_DECK_SIZE {
+                    visit.message =
+                        Some(format!("Your deck needs at least {} cards", MIN_DECK_SIZE));
+                } else {
+                    visit.picking = Some(Pick::Burn);
+                }
+            }
+            CampfireButton::Card(index) => {
+                let card_type = deck.cards()[index];
+                let done = match visit.picking {
+                    Some(Pick::Upgrade) => deck.upgrade(card_type),
+                    Some(Pick::Burn) => deck.remove(index),
+                    None => false,
+                };
+                if done {
+                    game_state.set(encounter.next_state);
+                }
+            }
+            CampfireButton::Back => visit.picking = None,
+        }
+        return;
+    }
+}
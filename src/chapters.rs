@@ -0,0 +1,136 @@
+// The chapters a new run can start from. Each past the first is locked until the fight before it
+// has been won on some run, as kept in the save's progress. Locked chapters, and endless mode
+// before the campaign is finished, can't be entered by any route, unless the game is built with
+// the `debug` feature for testing.
+use bevy::prelude::*;
+
+use super::GameState;
+use crate::endless;
+use crate::save::SaveData;
+use crate::story::{StoryFlag, StoryFlags};
+
+pub struct Chapter {
+    pub name: &'static str,
+    // The cutscene the chapter opens with, and its fight
+    pub start: GameState,
+    pub fight: GameState,
+    // Set by winning the fight before, which unlocks the chapter
+    pub requires: Option<StoryFlag>,
+    // Shown on a locked chapter to say how to unlock it
+    pub requirement: &'static str,
+    // Looped under the music through the cutscene and the fight
+    pub ambience: &'static str,
+}
+
+pub const CHAPTERS: [Chapter; 4] = [
+    Chapter {
+        name: "Chapter 1: The Forest",
+        start: GameState::Game,
+        fight: GameState::Chapter1,
+        requires: None,
+        requirement: "",
+        ambience: "sounds/ambience/forest.wav",
+    },
+    Chapter {
+        name: "Chapter 2: The Fort",
+        start: GameState::Game2,
+        fight: GameState::Chapter2,
+        requires: Some(StoryFlag::ForestCleared),
+        requirement: "Clear the forest to unlock",
+        ambience: "sounds/ambience/fort.wav",
+    },
+    Chapter {
+        name: "Chapter 3: The Pool",
+        start: GameState::Game3,
+        fight: GameState::Chapter3,
+        requires: Some(StoryFlag::FortCleared),
+        requirement: "Take the fort to unlock",
+        ambience: "sounds/ambience/waterfall.wav",
+    },
+    Chapter {
+        name: "Chapter 4: The Summoning",
+        start: GameState::Game4,
+        fight: GameState::Chapter4,
+        requires: Some(StoryFlag::PoolCleared),
+        requirement: "Win at the pool to unlock",
+        ambience: "sounds/ambience/chanting.wav",
+    },
+];
+
+impl Chapter {
+    // The run under way counts too, for saves made before progress was kept
+    pub fn is_unlocked(&self, save_data: &SaveData, story_flags: &StoryFlags) -> bool {
+        self.requires
+            .is_none_or(|flag| save_data.progress.is_set(flag) || story_flags.is_set(flag))
+    }
+}
+
+pub fn chapters_plugin(app: &mut App) {
+    // Before the state changes, so a locked chapter's setup never runs
+    app.add_systems(PreUpdate, refuse_locked_chapters);
+}
+
+fn refuse_locked_chapters(
+    mut next_state: ResMut<NextState<GameState>>,
+    save_data: Res<SaveData>,
+    story_flags: Res<StoryFlags>,
+) {
+    if cfg!(feature = "debug") {
+        return;
+    }
+    let NextState::Pending(state) = *next_state else {
+        return;
+    };
+    let locked = CHAPTERS.iter().find(|chapter| {
+        (chapter.start == state || chapter.fight == state)
+            && !chapter.is_unlocked(&save_data, &story_flags)
+    });
+    if let Some(chapter) = locked {
+        warn!("Not going to {state:?}, as {} is locked", chapter.name);
+        next_state.reset();
+    } else if state == GameState::Endless && !endless::is_unlocked(&save_data, &story_flags) {
+        warn!("Not going to {state:?}, as endless mode is locked");
+        next_state.reset();
+    }
+}
+
+// A padlock drawn from UI nodes: a shackle ring above a solid body
+pub fn spawn_padlock(parent: &mut ChildBuilder, color: Color) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|padlock| {
+            padlock.spawn(NodeBundle {
+                style: Style {
+                    width: Val::Px(16.0),
+                    height: Val::Px(14.0),
+                    border: UiRect {
+                        left: Val::Px(3.0),
+                        right: Val::Px(3.0),
+                        top: Val::Px(3.0),
+                        bottom: Val::Px(0.0),
+                    },
+                    ..default()
+                },
+                border_color: color.into(),
+                border_radius: BorderRadius::top(Val::Px(8.0)),
+                ..default()
+            });
+            padlock.spawn(NodeBundle {
+                style: Style {
+                    width: Val::Px(26.0),
+                    height: Val::Px(20.0),
+                    ..default()
+                },
+                background_color: color.into(),
+                border_radius: BorderRadius::all(Val::Px(3.0)),
+                ..default()
+            });
+        });
+}
@@ -0,0 +1,296 @@
+// Developer cheats, only compiled with the `dev-tools` feature. Cheats are commands in a small
+// console registry: each is a name and a function run on the world with the words typed after
+// it. The panel, toggled with F2, is a row of buttons that queue command lines, and shows what
+// the last one answered. Other modules can register commands of their own the same way.
+use bevy::prelude::*;
+
+use crate::chapters::CHAPTERS;
+use crate::combat::{self, CardType};
+use crate::gold::Gold;
+use crate::platform::SafeAnchor;
+use crate::save::SaveData;
+use crate::theme::Theme;
+use crate::ui;
+
+const TOGGLE_KEY: KeyCode = KeyCode::F2;
+
+// Gold added by `gold` when no amount is given
+const DEFAULT_GOLD: u32 = 100;
+
+// Whether the player takes no damage in fights
+#[derive(Resource, Default)]
+pub struct GodMode(pub bool);
+
+pub struct DevCommand {
+    pub name: &'static str,
+    // The words a command line is split into after the name, and what to answer with
+    pub run: fn(&mut World, &[&str]) -> Result<String, String>,
+}
+
+#[derive(Resource, Default)]
+pub struct DevCommands {
+    commands: Vec<DevCommand>,
+    // Command lines waiting for the world to be free, run in order
+    queued: Vec<String>,
+    // What the last command answered
+    last_answer: String,
+}
+
+impl DevCommands {
+    pub fn register(&mut self, command: DevCommand) {
+        self.commands.push(command);
+    }
+
+    pub fn queue(&mut self, line: impl Into<String>) {
+        self.queued.push(line.into());
+    }
+}
+
+#[derive(Component)]
+struct CheatsPanel;
+
+#[derive(Component)]
+struct CheatsAnswer;
+
+// Queues this command line when pressed
+#[derive(Component)]
+struct CheatButton(String);
+
+pub fn cheats_plugin(app: &mut App) {
+    let mut commands = DevCommands::default();
+    commands.register(DevCommand {
+        name: "god",
+        run: toggle_god_mode,
+    });
+    commands.register(DevCommand {
+        name: "gold",
+        run: add_gold,
+    });
+    commands.register(DevCommand {
+        name: "card",
+        run: give_card,
+    });
+    commands.register(DevCommand {
+        name: "kill",
+        run: kill_enemies,
+    });
+    commands.register(DevCommand {
+        name: "unlock",
+        run: unlock_chapters,
+    });
+    app.insert_resource(commands)
+        .init_resource::<GodMode>()
+        .add_systems(Startup, spawn_cheats_panel)
+        .add_systems(
+            Update,
+            (
+                toggle_cheats_panel,
+                cheat_buttons,
+                run_dev_commands,
+                show_answer,
+            )
+                .chain(),
+        );
+}
+
+fn toggle_god_mode(world: &mut World, _: &[&str]) -> Result<String, String> {
+    let mut god_mode = world.resource_mut::<GodMode>();
+    god_mode.0 = !god_mode.0;
+    Ok(format!(
+        "God mode {}",
+        if god_mode.0 { "on" } else { "off" }
+    ))
+}
+
+fn add_gold(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let amount = match args.first() {
+        Some(amount) => amount
+            .parse()
+            .map_err(|_| format!("Not an amount of gold: {amount}"))?,
+        None => DEFAULT_GOLD,
+    };
+    world.resource_mut::<Gold>().earn(amount);
+    Ok(format!("Added {amount} gold"))
+}
+
+fn give_card(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let name = args.first().ok_or("Which card?")?;
+    let card_type = CardType::ALL
+        .into_iter()
+        .find(|card_type| format!("{card_type:?}").eq_ignore_ascii_case(name))
+        .ok_or(format!("No card called {name}"))?;
+    if !combat::cheat_card(world, card_type) {
+        return Err("There's no hand to put it in outside a fight".to_string());
+    }
+    Ok(format!("{card_type:?} added to the hand"))
+}
+
+fn kill_enemies(world: &mut World, _: &[&str]) -> Result<String, String> {
+    match combat::cheat_kill_monsters(world) {
+        0 => Err("Nothing left to kill".to_string()),
+        killed => Ok(format!("Killed {killed} enemies")),
+    }
+}
+
+fn unlock_chapters(world: &mut World, _: &[&str]) -> Result<String, String> {
+    let mut save_data = world.resource_mut::<SaveData>();
+    for flag in CHAPTERS.iter().filter_map(|chapter| chapter.requires) {
+        save_data.progress.set(flag);
+    }
+    save_data.save();
+    Ok("Every chapter unlocked".to_string())
+}
+
+fn spawn_cheats_panel(mut commands: Commands, theme: Res<Theme>) {
+    let text_style = theme.text_style(18.0);
+    let mut lines = vec![
+        ("God mode".to_string(), "god".to_string()),
+        (
+            format!("+{DEFAULT_GOLD} gold"),
+            format!("gold {DEFAULT_GOLD}"),
+        ),
+        ("Kill enemies".to_string(), "kill".to_string()),
+        ("Unlock chapters".to_string(), "unlock".to_string()),
+    ];
+    lines.extend(
+        CardType::ALL
+            .into_iter()
+            .map(|card_type| (format!("{card_type:?} card"), format!("card {card_type:?}"))),
+    );
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(16.0),
+                    right: Val::Px(16.0),
+                    width: Val::Px(300.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(8.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.8).into(),
+                visibility: Visibility::Hidden,
+                z_index: ZIndex::Global(40),
+                ..default()
+            },
+            CheatsPanel,
+            SafeAnchor::top_right(16.0, 16.0),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Cheats",
+                TextStyle {
+                    font_size: 24.0,
+                    ..text_style.clone()
+                },
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_wrap: FlexWrap::Wrap,
+                        column_gap: Val::Px(6.0),
+                        row_gap: Val::Px(6.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for (label, line) in lines {
+                        ui::styled_button(
+                            parent,
+                            &theme,
+                            Style {
+                                padding: UiRect::all(Val::Px(6.0)),
+                                ..default()
+                            },
+                            label,
+                            text_style.clone(),
+                            CheatButton(line),
+                        );
+                    }
+                });
+            parent.spawn((
+                TextBundle::from_section("", text_style.clone()),
+                CheatsAnswer,
+            ));
+        });
+}
+
+fn toggle_cheats_panel(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut panel_query: Query<&mut Visibility, With<CheatsPanel>>,
+) {
+    if !keyboard.just_pressed(TOGGLE_KEY) {
+        return;
+    }
+    for mut visibility in panel_query.iter_mut() {
+        *visibility = if *visibility == Visibility::Hidden {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn cheat_buttons(
+    mut interaction_query: Query<
+        (&Interaction, &CheatButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    mut dev_commands: ResMut<DevCommands>,
+    theme: Res<Theme>,
+) {
+    for (interaction, button, mut color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => dev_commands.queue(button.0.clone()),
+            Interaction::Hovered => *color = theme.button_hovered.into(),
+            Interaction::None => *color = theme.button.into(),
+        }
+    }
+}
+
+// Commands get the whole world, so they run here rather than from the systems queuing them
+fn run_dev_commands(world: &mut World) {
+    let queued = std::mem::take(&mut world.resource_mut::<DevCommands>().queued);
+    for line in queued {
+        let mut words = line.split_whitespace();
+        let name = words.next().unwrap_or_default();
+        let args: Vec<&str> = words.collect();
+        let run = world
+            .resource::<DevCommands>()
+            .commands
+            .iter()
+            .find(|command| command.name == name)
+            .map(|command| command.run);
+        let answer = match run {
+            Some(run) => run(world, &args),
+            None => Err(format!("Unknown command: {name}")),
+        };
+        let answer = match answer {
+            Ok(answer) => {
+                info!("Cheat `{line}`: {answer}");
+                answer
+            }
+            Err(err) => {
+                warn!("Cheat `{line}` failed: {err}");
+                err
+            }
+        };
+        world.resource_mut::<DevCommands>().last_answer = answer;
+    }
+}
+
+fn show_answer(
+    dev_commands: Res<DevCommands>,
+    mut text_query: Query<&mut Text, With<CheatsAnswer>>,
+) {
+    if !dev_commands.is_changed() {
+        return;
+    }
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = dev_commands.last_answer.clone();
+    }
+}
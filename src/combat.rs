@@ -0,0 +1,5663 @@
+// Card combat shared by the four chapters. A chapter describes its fight as an `Encounter`
+// resource when its state is entered, and `add_combat_state` runs the fight in that state.
+use std::f32::consts::PI;
+
+use bevy::app::AppExit;
+use bevy::ecs::system::{ParamSet, SystemParam};
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use serde::{Deserialize, Serialize};
+
+use super::{despawn_screen, DisplayQuality, GameState, TextSize};
+use crate::boss_intro::BossIntro;
+#[cfg(feature = "dev-tools")]
+use crate::cheats::GodMode;
+use crate::cursor::{pick_cursor, CursorStyle};
+use crate::deck::Deck;
+use crate::difficulty::Difficulty;
+use crate::ending::RunRecord;
+use crate::game_window::GameWindow;
+use crate::gold::{Gold, GOLD_COLOR};
+use crate::inventory::{ItemPickedUp, KeyItem};
+use crate::platform::SafeAnchor;
+use crate::reveal::{CardsEarned, RevealCeremony};
+use crate::rng::RunRng;
+use crate::rules::{tick_burn, Keyword, VULNERABLE_MULTIPLIER};
+use crate::run_effects::{RunEffect, RunEffects, PHOENIX_REVIVE_SHARE};
+use crate::save::SaveData;
+use crate::shared_assets::SharedAssets;
+use crate::story::{StoryFlag, StoryFlags};
+use crate::texture_quality::texture_path;
+use crate::theme::Theme;
+use crate::tween::{animate_tweens, Ease, Pose, Tween, TweenFinished, TweenTarget};
+use crate::ui;
+
+#[derive(Component, Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CardType {
+    Fire,
+    Ice,
+    Air,
+    Earth,
+    Crystal,
+    Heal,
+    // A power card, which sets up its `Power` for the rest of the fight instead of dealing damage
+    Inferno,
+    // Summons a wisp to fight beside the player for the rest of the fight
+    Wisp,
+    // A light hit that leaves the target Vulnerable
+    Shatter,
+    // Grants temporary health instead of dealing damage
+    Aegis,
+    // Can't be played, and hurts the player for every turn it's left in hand. Shuffled in by
+    // hexing enemies and wounds, and never offered as a reward.
+    Curse,
+}
+
+impl CardType {
+    // The cards that can be won
+    pub const ALL: [CardType; 10] = [
+        CardType::Fire,
+        CardType::Ice,
+        CardType::Air,
+        CardType::Earth,
+        CardType::Crystal,
+        CardType::Heal,
+        CardType::Inferno,
+        CardType::Wisp,
+        CardType::Shatter,
+        CardType::Aegis,
+    ];
+
+    pub fn texture(self) -> &'static str {
+        match self {
+            CardType::Fire => "textures/Game Icons/Fire.png",
+            CardType::Ice => "textures/Game Icons/Frost.png",
+            CardType::Air => "textures/Game Icons/air.png",
+            CardType::Earth => "textures/Game Icons/Earth.png",
+            CardType::Crystal => "textures/Game Icons/Crystal.png",
+            CardType::Heal => "textures/Game Icons/Heal.png",
+            CardType::Inferno => "textures/Game Icons/Inferno.png",
+            CardType::Wisp => "textures/Game Icons/Wisp.png",
+            CardType::Shatter => "textures/Game Icons/Shatter.png",
+            CardType::Aegis => "textures/Game Icons/Aegis.png",
+            CardType::Curse => "textures/Game Icons/Curse.png",
+        }
+    }
+
+    // The lasting effect playing the card sets up, for power cards
+    pub fn power(self) -> Option<Power> {
+        match self {
+            CardType::Inferno => Some(Power::Inferno),
+            _ => None,
+        }
+    }
+
+    // Energy the card takes to play, before relics have their say
+    pub fn cost(self) -> u32 {
+        match self {
+            CardType::Inferno | CardType::Wisp => 2,
+            CardType::Curse => 0,
+            _ => 1,
+        }
+    }
+
+    // Whether the card hits or heals, so an upgrade has something to add to
+    pub fn upgradable(self) -> bool {
+        !matches!(self, CardType::Wisp | CardType::Aegis | CardType::Curse)
+            && self.power().is_none()
+    }
+
+    // The card's keywords, which explain themselves on its tooltip
+    pub fn keywords(self) -> Vec<Keyword> {
+        match self {
+            CardType::Fire => vec![
+                Keyword::FirstStrike(FIRE_FIRST_CARD_BONUS),
+                Keyword::Burn(FIRE_BURN),
+            ],
+            CardType::Ice => vec![Keyword::Fizzle(CardType::Earth)],
+            CardType::Inferno | CardType::Wisp => vec![Keyword::Exhaust],
+            CardType::Shatter => vec![Keyword::Vulnerable(SHATTER_VULNERABLE_TURNS)],
+            _ => Vec::new(),
+        }
+    }
+
+    // Effects playing the card leaves behind to fire at later turn boundaries
+    fn triggers(self) -> Vec<ActiveTrigger> {
+        match self {
+            CardType::Earth => vec![ActiveTrigger {
+                when: TurnTrigger::StartOfTurn,
+                effect: TriggeredEffect::GainBlock(EARTH_TURN_BLOCK),
+                uses_left: None,
+            }],
+            CardType::Crystal => vec![ActiveTrigger {
+                when: TurnTrigger::EndOfTurn,
+                effect: TriggeredEffect::DamageAllEnemies(CRYSTAL_ECHO_DAMAGE),
+                uses_left: Some(1),
+            }],
+            _ => Vec::new(),
+        }
+    }
+}
+
+// Effects power cards set up, which last until the end of the fight
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Power {
+    // Every Fire card hits harder
+    Inferno,
+}
+
+impl Power {
+    fn card(self) -> CardType {
+        match self {
+            Power::Inferno => CardType::Inferno,
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Power::Inferno => "Inferno: Fire cards +3",
+        }
+    }
+
+    // Added to the damage of a card played while the power is up
+    fn bonus(self, card_type: CardType) -> f32 {
+        match (self, card_type) {
+            (Power::Inferno, CardType::Fire) => INFERNO_FIRE_BONUS,
+            _ => 0.0,
+        }
+    }
+}
+
+// The turn boundaries a card's effect can wait for
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum TurnTrigger {
+    StartOfTurn,
+    EndOfTurn,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum TriggeredEffect {
+    DamageAllEnemies(f32),
+    GainBlock(f32),
+}
+
+// A played card's effect, fired at each `when` until its uses run out
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct ActiveTrigger {
+    when: TurnTrigger,
+    effect: TriggeredEffect,
+    // `None` lasts the rest of the fight
+    uses_left: Option<u32>,
+}
+
+// One monster in an encounter. It is placed relative to the player character, by a fraction
+// of the window size plus `lift` pixels.
+#[derive(Clone)]
+pub struct MonsterSpec {
+    pub texture: &'static str,
+    pub health: f32,
+    pub damage: f32,
+    pub size: f32,
+    pub offset: Vec2,
+    pub lift: f32,
+    // Height of the intent label above the sprite, and of the health bar below it
+    pub label_height: f32,
+    pub health_bar_depth: f32,
+    // Moves used in turn, looping back to the first. With none it always attacks for `damage`.
+    pub moves: Vec<EnemyMove>,
+    pub loot: LootTable,
+}
+
+// What a monster may drop when it dies, rolled with the run's random numbers
+#[derive(Clone, Default)]
+pub struct LootTable {
+    // Gold dropped, from the first amount to the second
+    pub gold: (u32, u32),
+    // Cards that may drop, each with its percent chance
+    pub cards: Vec<(CardType, usize)>,
+    // A rare relic and its percent chance
+    pub relic: Option<(RunEffect, usize)>,
+}
+
+impl LootTable {
+    fn roll(&self, rng: &mut RunRng, loot: &mut FightLoot) {
+        let (least, most) = self.gold;
+        loot.gold += least + rng.below((most.saturating_sub(least) + 1) as usize) as u32;
+        for &(card_type, chance) in &self.cards {
+            if rng.below(100) < chance {
+                loot.cards.push(card_type);
+            }
+        }
+        if let Some((relic, chance)) = self.relic {
+            if rng.below(100) < chance && !loot.relics.contains(&relic) {
+                loot.relics.push(relic);
+            }
+        }
+    }
+}
+
+// What the monsters killed so far this fight dropped, handed out on victory
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+struct FightLoot {
+    gold: u32,
+    cards: Vec<CardType>,
+    relics: Vec<RunEffect>,
+}
+
+impl FightLoot {
+    // The cards and relics, for the victory screen
+    fn describe(&self) -> Option<String> {
+        let names: Vec<String> = self
+            .cards
+            .iter()
+            .map(|card_type| format!("{card_type:?} card"))
+            .chain(self.relics.iter().map(|relic| relic.name().to_string()))
+            .collect();
+        (!names.is_empty()).then(|| format!("Dropped: {}", names.join(", ")))
+    }
+}
+
+// The loot table of the monster it's on
+#[derive(Component)]
+struct Loot(LootTable);
+
+// Something a monster does on the enemy turn. The next one is shown above it as its intent.
+#[derive(Clone)]
+pub enum EnemyMove {
+    Attack(f32),
+    // Brings more monsters into the fight, lined up beside the summoner
+    Summon(Vec<MonsterSpec>),
+    // Restores the monster's own health, up to its maximum
+    Heal(f32),
+    // Gains strength, added to every attack after it
+    Buff(f32),
+    // Gains block, which soaks up card damage and halves at the start of each enemy turn
+    Defend(f32),
+    // Shuffles this many curses into the player's draw pile for the rest of the fight
+    Hex(usize),
+}
+
+// What a monster weighs up when settling on its next move
+struct BoardState {
+    hand_size: usize,
+    player_health_share: f32,
+}
+
+impl EnemyMove {
+    // How strongly the board calls for the move, before the moves' own order is counted
+    fn score(&self, board: &BoardState, own_health_share: f32) -> f32 {
+        match self {
+            // Go for the kill on a player nearly beaten
+            EnemyMove::Attack(_) if board.player_health_share <= LOW_HEALTH_SHARE => REACTION_SCORE,
+            // Brace for a big hand
+            EnemyMove::Defend(_) if board.hand_size >= BIG_HAND_SIZE => REACTION_SCORE,
+            // Healing at full health is wasted
+            EnemyMove::Heal(_) if own_health_share >= 1.0 => -REACTION_SCORE,
+            _ => 0.0,
+        }
+    }
+
+    fn label(&self, behavior: &Behavior) -> String {
+        match self {
+            EnemyMove::Attack(damage) => format!("{}", behavior.attack_damage(*damage)),
+            EnemyMove::Summon(_) => "Summon".to_string(),
+            EnemyMove::Heal(amount) => format!("+{}", amount),
+            EnemyMove::Buff(amount) => format!("+{} str", amount),
+            EnemyMove::Defend(amount) => format!("+{} block", amount),
+            EnemyMove::Hex(count) => format!("Hex {}", count),
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            EnemyMove::Attack(_) => DAMAGE_COLOR,
+            EnemyMove::Summon(_) => RITUAL_COLOR,
+            EnemyMove::Heal(_) => HEAL_COLOR,
+            EnemyMove::Buff(_) => BUFF_COLOR,
+            EnemyMove::Defend(_) => BLOCK_COLOR,
+            EnemyMove::Hex(_) => CURSE_COLOR,
+        }
+    }
+
+    fn icon(&self) -> &'static str {
+        match self {
+            EnemyMove::Attack(_) => "textures/Game Icons/fire.png",
+            EnemyMove::Summon(_) => "textures/Game Icons/card.png",
+            EnemyMove::Heal(_) => "textures/Game Icons/Heal.png",
+            EnemyMove::Buff(_) => "textures/Game Icons/crystal.png",
+            EnemyMove::Defend(_) => "textures/Game Icons/earth.png",
+            EnemyMove::Hex(_) => "textures/Game Icons/Curse.png",
+        }
+    }
+}
+
+// A spell the boss charges over several enemy turns, wiping out the player when it completes
+#[derive(Clone)]
+pub struct Ritual {
+    pub turns: i32,
+    // Dealing this much damage to the boss in one turn breaks its concentration
+    pub interrupt_damage: f32,
+    // As does playing this card while it chants
+    pub interrupt_card: Option<CardType>,
+}
+
+// Everything that differs between the chapter fights
+#[derive(Resource, Clone)]
+pub struct Encounter {
+    pub background: &'static str,
+    pub monsters: Vec<MonsterSpec>,
+    // Where winning the fight leads
+    pub next_state: GameState,
+    // Set on victory, and `wounded_flag` too if the player ends it under half health
+    pub victory_flag: StoryFlag,
+    pub wounded_flag: Option<StoryFlag>,
+    // Key item found after the fight, if any
+    pub reward: Option<KeyItem>,
+    // Gold awarded for winning
+    pub gold_reward: u32,
+    // Cards revealed after the fight, for the player to add on the deck screen
+    pub card_rewards: Vec<CardType>,
+    // Player turns the boss (the first monster) allows before it enrages, if it ever does
+    pub enrage_after: Option<i32>,
+    // Ritual the boss charges, if any
+    pub ritual: Option<Ritual>,
+    // Passive effect the rest of the run gains from winning, if any
+    pub effect_reward: Option<RunEffect>,
+    // Played before the fight starts, for boss fights
+    pub intro: Option<BossIntro>,
+}
+
+// A fight caught between player actions and kept in the save, so quitting part-way through
+// picks up from the same spot
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FightSnapshot {
+    // The chapter the fight belongs to
+    pub state: GameState,
+    player_health: f32,
+    #[serde(default)]
+    player_temporary_health: f32,
+    hand: Vec<CardType>,
+    mulligan: bool,
+    turn: TurnState,
+    monsters: Vec<MonsterSnapshot>,
+    #[serde(default)]
+    allies: Vec<AllySnapshot>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct MonsterSnapshot {
+    spec_path: Vec<usize>,
+    position: [f32; 3],
+    health: f32,
+    block: f32,
+    next_move: usize,
+    strength: f32,
+    summoned: bool,
+    // Ritual progress and damage taken towards interrupting it this turn
+    ritual: Option<(i32, f32)>,
+    #[serde(default)]
+    burn: f32,
+    #[serde(default)]
+    vulnerable: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct AllySnapshot {
+    position: [f32; 3],
+    health: f32,
+    next_action: usize,
+}
+
+// The player as they left their last fight won this run, so damage carries into the next one
+#[derive(Resource, Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct PlayerState {
+    // Health left, or `None` to start the next fight at full health
+    health: Option<f32>,
+    // The most health the player had in that fight
+    maximum: f32,
+}
+
+impl PlayerState {
+    pub fn health(&self) -> Option<f32> {
+        self.health
+    }
+
+    pub fn maximum(&self) -> f32 {
+        self.maximum
+    }
+
+    // Back to full health, for a new run
+    pub fn clear(&mut self) {
+        self.health = None;
+    }
+
+    // Heal by up to `amount`, never past the maximum of the last fight
+    pub fn heal(&mut self, amount: f32) {
+        if let Some(health) = &mut self.health {
+            *health = (*health + amount).min(self.maximum);
+        }
+    }
+}
+
+// Limits on what the player may do, set by guided fights like the tutorial
+#[derive(Resource, Default)]
+pub struct PlayLock {
+    // Only this card may be played, and it is always dealt in the opening hand
+    pub only_card: Option<CardType>,
+    pub end_turn_locked: bool,
+}
+
+// Sent as the player moves the fight along, for anything that follows their progress
+#[derive(Event, Clone, Copy, PartialEq, Debug)]
+pub enum FightEvent {
+    MulliganDone,
+    CardPlayed(CardType),
+    // The player's turn begins, after the enemies have moved
+    TurnStarted,
+    TurnEnded,
+    // Damage that got through to an enemy, with which one, and to the player
+    DamageDealt(Entity, f32),
+    DamageTaken(f32),
+    // With the share of health the player had left
+    Won(f32),
+    Lost,
+}
+
+// What the player asks of the fight through its buttons
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum FightAction {
+    PlayCard(Entity),
+    ConfirmMulligan,
+    EndTurn,
+}
+
+// The buttons are read every frame but the rules only run on the fixed step, so a press waits in
+// `pending` for however many frames go by until the next one. Each step takes them all and acts
+// on them, so a press is never missed or acted on twice.
+#[derive(Resource, Default)]
+struct FightActions {
+    pending: Vec<FightAction>,
+    this_step: Vec<FightAction>,
+}
+
+// What the rules read and change as a fight plays out: the presses they act on, the encounter,
+// the turn and the run's numbers. The rules run one after another on the fixed step, so each
+// one takes the lot.
+#[derive(SystemParam)]
+struct FightRules<'w> {
+    actions: Res<'w, FightActions>,
+    play_lock: Res<'w, PlayLock>,
+    encounter: Res<'w, Encounter>,
+    fight_state: ResMut<'w, FightState>,
+    turn_state: ResMut<'w, TurnState>,
+    draw_rules: Res<'w, DrawRules>,
+    run_effects: ResMut<'w, RunEffects>,
+    rng: ResMut<'w, RunRng>,
+    fight_events: EventWriter<'w, FightEvent>,
+}
+
+// What the fight's pieces are drawn with, for the systems that spawn them
+#[derive(SystemParam)]
+pub struct FightAssets<'w> {
+    asset_server: Res<'w, AssetServer>,
+    shared_assets: Res<'w, SharedAssets>,
+    theme: Res<'w, Theme>,
+}
+
+// What a fight is dealt from: the encounter, the run so far and how the game is being played
+#[derive(SystemParam)]
+pub struct FightSetup<'w> {
+    state: Res<'w, State<GameState>>,
+    encounter: Res<'w, Encounter>,
+    deck: Res<'w, Deck>,
+    play_lock: Res<'w, PlayLock>,
+    difficulty: Res<'w, Difficulty>,
+    run_effects: Res<'w, RunEffects>,
+    player_state: Res<'w, PlayerState>,
+    hot_seat: Option<Res<'w, HotSeat>>,
+}
+
+// What the player carries out of a won fight as soon as it's won: the gold, and their health
+#[derive(SystemParam)]
+struct Winnings<'w> {
+    gold: ResMut<'w, Gold>,
+    player_state: ResMut<'w, PlayerState>,
+}
+
+// What a won fight's victory screen hands on to the rest of the run as it closes
+#[derive(SystemParam)]
+struct VictorySpoils<'w> {
+    encounter: Res<'w, Encounter>,
+    turn_state: Res<'w, TurnState>,
+    run_record: ResMut<'w, RunRecord>,
+    story_flags: ResMut<'w, StoryFlags>,
+    run_effects: ResMut<'w, RunEffects>,
+    deck: ResMut<'w, Deck>,
+    pickups: EventWriter<'w, ItemPickedUp>,
+    cards_earned: EventWriter<'w, CardsEarned>,
+}
+
+// The victory screen, its text, and the reveal of the cards it hands out
+#[derive(SystemParam)]
+struct VictoryScreenParts<'w, 's> {
+    screens: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static mut BackgroundColor,
+            &'static mut FadeInEffect,
+            &'static mut ReturnToMenuVictoryTimer,
+        ),
+        With<VictoryScreen>,
+    >,
+    text: Query<'w, 's, &'static mut Text, With<VictoryText>>,
+    ceremony: Query<'w, 's, (), With<RevealCeremony>>,
+}
+
+// The enemies a card can hit, with everything a hit changes on them
+type TargetableMonsters<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static mut Health,
+        &'static mut Block,
+        &'static mut Vulnerable,
+        Option<&'static mut RitualCharge>,
+        Option<&'static mut Burning>,
+        &'static Loot,
+        &'static GlobalTransform,
+    ),
+    With<Monster>,
+>;
+
+// The player, with where they stand for anything spawned beside them
+type PlayerCharacter<'w, 's> = Query<
+    'w,
+    's,
+    (&'static Parent, &'static Transform, &'static mut Health),
+    (With<SideCharacter>, Without<Monster>),
+>;
+
+// The player, the enemies and the allies as the enemies' turn plays out. They all have health,
+// so only one of them can be borrowed at a time.
+type TurnCombatants<'w, 's> = ParamSet<
+    'w,
+    's,
+    (
+        Query<'static, 'static, (&'static mut Health, &'static mut Block), With<SideCharacter>>,
+        ActingMonsters<'static, 'static>,
+        Query<'static, 'static, (Entity, &'static mut Health, &'static mut Ally)>,
+    ),
+>;
+
+// The enemies as they take their turn, with everything their moves and their burns change
+type ActingMonsters<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static mut Health,
+        &'static mut Behavior,
+        &'static mut Block,
+        &'static mut Vulnerable,
+        Option<&'static mut RitualCharge>,
+        &'static SpecPath,
+        &'static Transform,
+        &'static GlobalTransform,
+        &'static Parent,
+        Option<&'static mut Burning>,
+        &'static Loot,
+    ),
+    With<Monster>,
+>;
+
+// The cards in hand
+type HandCards<'w, 's> = Query<'w, 's, (Entity, &'static CardType), With<Card>>;
+
+// The cards whose interaction changed this frame
+type ChangedCards<'w, 's> =
+    Query<'w, 's, (Entity, &'static Interaction), (Changed<Interaction>, With<Card>)>;
+
+// Whether the fight is over, either way
+type FinishedScreens<'w, 's> = Query<'w, 's, (), Or<(With<VictoryScreen>, With<DeathScreen>)>>;
+
+// Components
+#[derive(Component)]
+pub struct OnCombatScreen;
+
+#[derive(Component)]
+struct Card;
+
+#[derive(Component)]
+struct HandContainer;
+
+// The powers in play, as a row of small cards under the hand
+#[derive(Component)]
+struct PowerRow;
+
+// The energy left this turn, under the Hint button
+#[derive(Component)]
+struct EnergyText;
+
+// A card's energy cost, in its top left corner
+#[derive(Component)]
+struct CostBadge(u32);
+
+// Explains the keywords of the card under the pointer, below the powers
+#[derive(Component)]
+struct CardTooltip;
+
+#[derive(Component)]
+struct SideCharacter;
+
+#[derive(Component)]
+struct Monster;
+
+#[derive(Component)]
+struct Health {
+    current: f32,
+    maximum: f32,
+    // Soaks up damage before `current` does, and wears off turn by turn. Can go past `maximum`.
+    temporary: f32,
+}
+
+impl Health {
+    // Take damage out of the temporary health first, then the real health
+    fn hurt(&mut self, damage: f32) {
+        let absorbed = self.temporary.min(damage);
+        self.temporary -= absorbed;
+        self.current = (self.current - (damage - absorbed)).max(0.0);
+    }
+}
+
+// What a health bar shows, as shares of maximum health. The bar eases from `from` to `to` when
+// health changes, and health just lost stays behind it as a pale ghost out to `ghost` until
+// `ghost_timer` runs out.
+#[derive(Component)]
+struct HealthBarContainer {
+    from: f32,
+    to: f32,
+    elapsed: f32,
+    temporary: f32,
+    ghost: f32,
+    ghost_timer: Timer,
+}
+
+impl Default for HealthBarContainer {
+    fn default() -> Self {
+        Self {
+            from: 1.0,
+            to: 1.0,
+            elapsed: HEALTH_DRAIN_SECONDS,
+            temporary: 0.0,
+            ghost: 1.0,
+            ghost_timer: Timer::from_seconds(DAMAGE_GHOST_SECONDS, TimerMode::Once),
+        }
+    }
+}
+
+impl HealthBarContainer {
+    // How much of the bar is filled right now
+    fn shown(&self) -> f32 {
+        let t = (self.elapsed / HEALTH_DRAIN_SECONDS).min(1.0);
+        self.from + (self.to - self.from) * Ease::Out.apply(t)
+    }
+}
+
+#[derive(Component)]
+struct HealthBar;
+
+// The lighter segment after the health bar for temporary health
+#[derive(Component)]
+struct TemporaryHealthBar;
+
+// Behind the health bar, the pale stretch of health just lost
+#[derive(Component)]
+struct DamageGhost;
+
+// The health left as numbers, under the bar
+#[derive(Component)]
+struct HealthReadout;
+
+#[derive(Component)]
+struct EndTurnButton;
+
+// Says why End Turn can't be pressed, while the pointer is over it
+#[derive(Component)]
+struct EndTurnTooltip;
+
+#[derive(Component)]
+struct HintButton;
+
+// The card a hint points at, pulsing until the player plays a card or ends the turn
+#[derive(Component)]
+struct HintPulse;
+
+#[derive(Component)]
+struct ButtonText;
+
+#[derive(Component)]
+struct Behavior {
+    moves: Vec<EnemyMove>,
+    next: usize,
+    // Where the moves' own order is up to. `next` strays from it when the board calls for it.
+    scripted: usize,
+    strength: f32,
+    enraged: bool,
+    // Multiplies attack damage, from the adaptive difficulty
+    scale: f32,
+}
+
+impl Behavior {
+    fn from_spec(spec: &MonsterSpec, scale: f32) -> Self {
+        let moves = if spec.moves.is_empty() {
+            vec![EnemyMove::Attack(spec.damage)]
+        } else {
+            spec.moves.clone()
+        };
+        Self {
+            moves,
+            next: 0,
+            scripted: 0,
+            strength: 0.0,
+            enraged: false,
+            scale,
+        }
+    }
+
+    fn intent(&self) -> &EnemyMove {
+        &self.moves[self.next]
+    }
+
+    fn advance(&mut self) {
+        self.scripted = (self.scripted + 1) % self.moves.len();
+        self.next = self.scripted;
+    }
+
+    // The move that scores best against the board, with the moves' own order breaking ties
+    fn best_move(&self, board: &BoardState, own_health_share: f32) -> usize {
+        self.moves
+            .iter()
+            .enumerate()
+            .map(|(i, enemy_move)| {
+                let mut score = enemy_move.score(board, own_health_share);
+                if i == self.scripted {
+                    score += SCRIPTED_MOVE_SCORE;
+                }
+                (i, score)
+            })
+            .fold((self.scripted, f32::MIN), |best, (i, score)| {
+                if score > best.1 {
+                    (i, score)
+                } else {
+                    best
+                }
+            })
+            .0
+    }
+
+    // What an attack of `damage` really hits for, after difficulty, strength and enrage
+    fn attack_damage(&self, damage: f32) -> f32 {
+        let damage = damage * self.scale + self.strength;
+        if self.enraged {
+            damage * ENRAGE_MULTIPLIER
+        } else {
+            damage
+        }
+    }
+}
+
+// Where a monster's spec lives: its index in the encounter, followed by the move and summon
+// index for each summon that brought it in
+#[derive(Component, Clone)]
+struct SpecPath(Vec<usize>);
+
+// Damage a monster, or the player, can soak up before losing health
+#[derive(Component, Default)]
+struct Block(f32);
+
+impl Block {
+    // Returns the damage left over once the block has taken its share. Healing passes through.
+    fn absorb(&mut self, damage: f32) -> f32 {
+        if damage <= 0.0 {
+            return damage;
+        }
+        let absorbed = self.0.min(damage);
+        self.0 -= absorbed;
+        damage - absorbed
+    }
+}
+
+#[derive(Component)]
+struct BlockBadge;
+
+#[derive(Component)]
+struct BlockBadgeText;
+
+// Fights beside the player once summoned by a Wisp card, taking one action each enemy phase:
+// the next of `ALLY_ACTIONS`, round and round
+#[derive(Component)]
+struct Ally {
+    next: usize,
+}
+
+#[derive(Clone, Copy)]
+enum AllyAction {
+    // Damage to the first living monster, through its block
+    Attack(f32),
+    // Block for the player
+    Guard(f32),
+}
+
+const ALLY_ACTIONS: [AllyAction; 2] = [
+    AllyAction::Attack(ALLY_ATTACK_DAMAGE),
+    AllyAction::Guard(ALLY_GUARD_BLOCK),
+];
+
+// A monster set burning by a card with the Burn keyword, losing health each enemy turn
+#[derive(Component)]
+struct Burning(f32);
+
+// A monster taking more damage from every hit while `turns` is above 0. Wears off by one turn
+// each enemy turn.
+#[derive(Component, Default)]
+struct Vulnerable {
+    turns: u32,
+}
+
+impl Vulnerable {
+    fn amplify(&self, damage: f32) -> f32 {
+        if self.turns > 0 && damage > 0.0 {
+            damage * VULNERABLE_MULTIPLIER
+        } else {
+            damage
+        }
+    }
+}
+
+#[derive(Component)]
+struct VulnerableBadge;
+
+#[derive(Component)]
+struct VulnerableBadgeText;
+
+// A monster brought in mid-fight by a Summon move
+#[derive(Component)]
+struct Summoned;
+
+// Shown under the monster single-target cards will hit
+#[derive(Component)]
+struct TargetMarker;
+
+// The monster that enrages when the encounter's turn limit runs out
+#[derive(Component)]
+pub struct Boss;
+
+// Shown above the boss once it has enraged
+#[derive(Component)]
+struct EnragedStatus;
+
+// Turns left before the boss enrages
+#[derive(Component)]
+struct EnrageCountdown;
+
+#[derive(Component)]
+struct RitualCharge {
+    ritual: Ritual,
+    progress: i32,
+    damage_this_turn: f32,
+}
+
+#[derive(Component)]
+struct RitualBar;
+
+#[derive(Component)]
+struct RitualText;
+
+#[derive(Component)]
+struct DeathScreen;
+
+#[derive(Component)]
+struct DeathText;
+
+#[derive(Component)]
+struct FadeInEffect {
+    timer: Timer,
+}
+
+#[derive(Component)]
+struct PhoenixFlash {
+    timer: Timer,
+}
+
+#[derive(Component, Clone, Copy)]
+enum DeathAction {
+    Retry,
+    AbandonRun,
+    Quit,
+}
+
+// What merciful mode took to spare the player, shown on the death screen
+#[derive(Component)]
+struct Spared {
+    gold: u32,
+    card: Option<CardType>,
+}
+
+// The fight to go back into from `GameState::RetryFight`
+#[derive(Resource)]
+struct Retrying(GameState);
+
+#[derive(Component)]
+struct IntentLabel;
+
+#[derive(Component)]
+struct IntentIcon;
+
+#[derive(Component)]
+struct DamageText {
+    timer: Timer,
+}
+
+// A small number with its effect's icon, for damage a status deals as it ticks. It waits out
+// `delay` hidden, so ticks landing together show one after another, then drifts up and goes.
+#[derive(Component)]
+struct StatusTick {
+    delay: Timer,
+    timer: Timer,
+}
+
+// Floating text that has finished, hidden until it is reused. It lives across fights, so new
+// numbers don't spawn an entity each.
+#[derive(Resource, Default)]
+struct FloatingTextPool(Vec<Entity>);
+
+#[derive(Component)]
+struct VictoryScreen;
+
+#[derive(Component)]
+struct VictoryText;
+
+// The victory screen waits for Continue, or moves on by itself once `timer` runs out
+#[derive(Component)]
+struct ReturnToMenuVictoryTimer {
+    timer: Timer,
+    continued: bool,
+    // The player rested instead of taking the cards on offer
+    healed: bool,
+    rewards_shown: bool,
+}
+
+#[derive(Component, Clone, Copy, PartialEq)]
+enum VictoryChoice {
+    Continue,
+    Heal,
+}
+
+#[derive(Component)]
+struct FatigueWarning;
+
+#[derive(Component)]
+struct MulliganPanel;
+
+#[derive(Component)]
+struct MulliganButton;
+
+#[derive(Component)]
+struct MulliganButtonText;
+
+// A card in the opening hand picked to be redrawn
+#[derive(Component)]
+struct MulliganSelected;
+
+// Cards to put at the end of the hand, by anything that makes cards mid-fight: Air cards at the
+// end of a turn, the bench filling its hand and the cheats
+#[derive(Event)]
+pub struct AddToHand {
+    pub card_type: CardType,
+    pub amount: u32,
+}
+
+// One side's cards and turn bookkeeping. Playing a card resolves through here, for the card
+// fight and the duel alike.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+pub struct TurnState {
+    first_card_played: bool,
+    cards_played_this_turn: Vec<CardType>,
+    crystal_power: i32,
+    turn_count: i32,
+    // Air cards earned this turn, handed over at its end
+    pending_air_cards: u32,
+    // Cards still to be drawn this fight, drawn from the end
+    draw_pile: Vec<CardType>,
+    // Played and discarded cards, shuffled back in when the draw pile runs out
+    discard_pile: Vec<CardType>,
+    // Draws attempted with no cards left anywhere; each one hurts more than the last
+    fatigue: i32,
+    // Effects left behind by played cards, waiting for a turn boundary
+    #[serde(default)]
+    triggers: Vec<ActiveTrigger>,
+    #[serde(default)]
+    loot: FightLoot,
+    // Card types upgraded at a campfire, which hit or heal harder
+    #[serde(default)]
+    upgraded: Vec<CardType>,
+    // Powers played this fight, in the order they were played
+    #[serde(default)]
+    powers: Vec<Power>,
+    // Left this turn for playing cards
+    #[serde(default = "full_energy")]
+    energy: u32,
+}
+
+fn full_energy() -> u32 {
+    ENERGY_PER_TURN
+}
+
+impl TurnState {
+    pub fn new(draw_pile: Vec<CardType>) -> Self {
+        Self {
+            draw_pile,
+            ..default()
+        }
+    }
+
+    // Play the deck's upgraded cards as upgraded
+    pub fn with_upgrades(mut self, upgraded: &[CardType]) -> Self {
+        self.upgraded = upgraded.to_vec();
+        self
+    }
+
+    // Put a card somewhere in the draw pile part-way through the fight
+    pub fn shuffle_in(&mut self, card_type: CardType, rng: &mut RunRng) {
+        let index = rng.below(self.draw_pile.len() + 1);
+        self.draw_pile.insert(index, card_type);
+    }
+
+    pub fn energy(&self) -> u32 {
+        self.energy
+    }
+
+    pub fn spend_energy(&mut self, cost: u32) {
+        self.energy = self.energy.saturating_sub(cost);
+    }
+
+    pub fn draw(&mut self, rng: &mut RunRng) -> Option<CardType> {
+        if self.draw_pile.is_empty() {
+            self.draw_pile.append(&mut self.discard_pile);
+            rng.shuffle(&mut self.draw_pile);
+        }
+        self.draw_pile.pop()
+    }
+
+    // Begin a turn and draw its cards. Returns them, and the fatigue damage for draws that
+    // found no cards, even after reshuffling the discards.
+    pub fn start_turn(&mut self, cards: usize, rng: &mut RunRng) -> (Vec<CardType>, f32) {
+        self.turn_count += 1;
+        self.first_card_played = true;
+        self.cards_played_this_turn.clear();
+        self.energy = ENERGY_PER_TURN;
+
+        let mut drawn = Vec::new();
+        let mut fatigue_damage = 0.0;
+        for _ in 0..cards {
+            match self.draw(rng) {
+                Some(card_type) => drawn.push(card_type),
+                None => {
+                    self.fatigue += 1;
+                    fatigue_damage += FATIGUE_DAMAGE_STEP * self.fatigue as f32;
+                }
+            }
+        }
+        (drawn, fatigue_damage)
+    }
+
+    // Play a card from a hand of `cards_in_hand`, counting the card itself. Returns the damage it
+    // deals, or heals when negative, and keeps what it leaves behind for later in the turn.
+    pub fn play(
+        &mut self,
+        card_type: CardType,
+        cards_in_hand: usize,
+        target_at_full_health: bool,
+    ) -> f32 {
+        let damage = card_damage(card_type, self, cards_in_hand, target_at_full_health);
+        if matches!(card_type, CardType::Air) {
+            self.pending_air_cards += 2;
+        }
+        self.triggers.extend(card_type.triggers());
+        self.cards_played_this_turn.push(card_type);
+        self.first_card_played = false;
+        // A power stays in play, and an exhausted card is gone for the rest of the fight
+        if let Some(power) = card_type.power() {
+            self.powers.push(power);
+        }
+        if !card_type
+            .keywords()
+            .iter()
+            .any(|keyword| keyword.exhausts())
+        {
+            self.discard_pile.push(card_type);
+        }
+        damage
+    }
+
+    // The effects waiting for the turn boundary just crossed, dropping the ones used up
+    pub fn fire(&mut self, when: TurnTrigger) -> Vec<TriggeredEffect> {
+        let mut effects = Vec::new();
+        for trigger in self
+            .triggers
+            .iter_mut()
+            .filter(|trigger| trigger.when == when)
+        {
+            effects.push(trigger.effect);
+            if let Some(uses_left) = &mut trigger.uses_left {
+                *uses_left = uses_left.saturating_sub(1);
+            }
+        }
+        self.triggers.retain(|trigger| trigger.uses_left != Some(0));
+        effects
+    }
+
+    // End the turn with the cards let go from the hand. Returns the Air cards to add to the
+    // hand for the next turn.
+    pub fn end_turn(&mut self, discarded: Vec<CardType>) -> u32 {
+        self.discard_pile.extend(discarded);
+        std::mem::take(&mut self.pending_air_cards)
+    }
+}
+
+// How the hand is refilled between turns
+#[derive(Resource)]
+pub struct DrawRules {
+    // Cards drawn at the start of each player turn after the first
+    pub cards_per_turn: usize,
+    // Whether unplayed cards are discarded when the turn ends, rather than kept
+    pub discard_hand_at_end_of_turn: bool,
+}
+
+impl Default for DrawRules {
+    fn default() -> Self {
+        Self {
+            cards_per_turn: 3,
+            discard_hand_at_end_of_turn: true,
+        }
+    }
+}
+
+impl Default for TurnState {
+    fn default() -> Self {
+        Self {
+            first_card_played: true,
+            cards_played_this_turn: Vec::new(),
+            crystal_power: 0,
+            turn_count: 0,
+            pending_air_cards: 0,
+            draw_pile: Vec::new(),
+            discard_pile: Vec::new(),
+            fatigue: 0,
+            triggers: Vec::new(),
+            loot: FightLoot::default(),
+            upgraded: Vec::new(),
+            powers: Vec::new(),
+            energy: ENERGY_PER_TURN,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct Autoplay {
+    timer: Timer,
+    // Where to go once the fight is over
+    exit_state: GameState,
+}
+
+#[derive(Resource)]
+struct FightState {
+    current_turn: Turn,
+    selected_card: Option<usize>,
+    // The monster single-target cards hit: the one last clicked, or else the left-most alive
+    target: Option<Entity>,
+    // Whether two players share this fight, whose turn it is, and whether the other one still
+    // plays before the enemies move
+    hot_seat: bool,
+    seat: usize,
+    partner_to_play: bool,
+}
+
+// Two players at the same keyboard, each with their own hand, piles and health. They take their
+// turns one after the other before the enemies move, and the enemies strike whoever played last,
+// who then opens the next round. Either one falling loses the fight.
+#[derive(Resource)]
+pub struct HotSeat;
+
+// Which player a character is in a hot-seat fight, counted from 0
+#[derive(Component)]
+struct Seat(usize);
+
+// The player waiting for their turn in a hot-seat fight, with their hand and piles put away.
+// Only the player whose turn it is is a `SideCharacter`.
+#[derive(Component)]
+struct BenchedPlayer {
+    hand: Vec<CardType>,
+    turn_state: TurnState,
+}
+
+// Names whose turn it is in a hot-seat fight
+#[derive(Component)]
+struct ActivePlayerBanner;
+
+// Flashes a monster or the player white and knocks them back a little as a hit lands. They
+// return to where they stood, in their own color, once it's over.
+#[derive(Component)]
+struct HitReaction {
+    timer: Timer,
+    origin: Vec3,
+    color: Color,
+    // Which way along x they're knocked, away from whoever hit them
+    direction: f32,
+}
+
+// Slides across the middle of the screen when the turn passes, then away again once `leaving`
+#[derive(Component)]
+struct TurnBanner {
+    leaving: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Turn {
+    // Choosing cards to redraw from the opening hand, before turn 1
+    Mulligan,
+    Player,
+    Enemy,
+}
+
+// The turn the last banner announced, so it's only announced once however often the fight
+// state changes during it
+#[derive(Resource, Default)]
+struct AnnouncedTurn(Option<(Turn, usize)>);
+
+impl Default for FightState {
+    fn default() -> Self {
+        Self {
+            current_turn: Turn::Player,
+            selected_card: None,
+            target: None,
+            hot_seat: false,
+            seat: 0,
+            partner_to_play: false,
+        }
+    }
+}
+
+pub const PLAYER_BASE_HEALTH: f32 = 100.0;
+
+// Constants for base damage values
+const FIRE_BASE_DAMAGE: f32 = 8.0;
+const FIRE_FIRST_CARD_BONUS: f32 = 7.0;
+const FIRE_BURN: f32 = 2.0;
+const ICE_BASE_DAMAGE: f32 = 6.0;
+const CRYSTAL_BASE_DAMAGE: f32 = 4.0;
+const AIR_BASE_DAMAGE: f32 = 2.0;
+const EARTH_BASE_DAMAGE: f32 = 5.0;
+const HEAL_BASE_DAMAGE: f32 = 8.0;
+const SHATTER_BASE_DAMAGE: f32 = 3.0;
+const SHATTER_VULNERABLE_TURNS: u32 = 2;
+const AEGIS_TEMPORARY_HEALTH: f32 = 8.0;
+// Added to every Fire card once Inferno is in play
+const INFERNO_FIRE_BONUS: f32 = 3.0;
+// Lost at the end of the player's turn for each curse in hand
+const CURSE_DAMAGE: f32 = 2.0;
+// A summoned wisp's health and what it does on its turns
+const ALLY_HEALTH: f32 = 15.0;
+const ALLY_ATTACK_DAMAGE: f32 = 5.0;
+const ALLY_GUARD_BLOCK: f32 = 4.0;
+// Added to the damage or healing of an upgraded card
+const CARD_UPGRADE_BONUS: f32 = 3.0;
+
+// What cards leave behind: Earth shields the player at the start of every later turn, and
+// Crystal echoes against every enemy once, as the turn ends
+const EARTH_TURN_BLOCK: f32 = 1.0;
+const CRYSTAL_ECHO_DAMAGE: f32 = 3.0;
+
+// Colours of floating numbers and intents
+const DAMAGE_COLOR: Color = Color::srgb(1.0, 0.0, 0.0);
+const HEAL_COLOR: Color = Color::srgb(0.2, 0.9, 0.2);
+const BUFF_COLOR: Color = Color::srgb(0.3, 0.6, 1.0);
+const BLOCK_COLOR: Color = Color::srgb(0.75, 0.8, 0.9);
+const CURSE_COLOR: Color = Color::srgb(0.6, 0.2, 0.7);
+const BURN_COLOR: Color = Color::srgb(1.0, 0.5, 0.1);
+const BURN_ICON: &str = "textures/Game Icons/fire.png";
+
+// Seconds between one status tick's number and the next, and how long each one floats
+const STATUS_TICK_STAGGER: f32 = 0.3;
+const STATUS_TICK_SECONDS: f32 = 0.9;
+const STATUS_TICK_SIZE: f32 = 24.0;
+const VULNERABLE_COLOR: Color = Color::srgb(0.55, 0.85, 1.0);
+const TEMPORARY_HEALTH_COLOR: Color = Color::srgb(0.7, 1.0, 0.8);
+const DAMAGE_GHOST_COLOR: Color = Color::srgb(1.0, 0.95, 0.85);
+const PHOENIX_COLOR: Color = Color::srgba(1.0, 0.45, 0.1, 0.8);
+const PHOENIX_FLASH_SECONDS: f32 = 2.0;
+// Temporary health god mode holds in front of the player, in multiples of their maximum health
+#[cfg(feature = "dev-tools")]
+const GOD_MODE_SHIELD: f32 = 10.0;
+const ENERGY_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+
+// Share of block kept from one turn to the next, for monsters and the player alike
+pub const BLOCK_DECAY: f32 = 0.5;
+// Share of the player's temporary health kept as each enemy turn ends
+const TEMPORARY_HEALTH_DECAY: f32 = 0.5;
+
+// How monsters weigh up their next move: the next in their own order scores
+// `SCRIPTED_MOVE_SCORE`, and a move the board calls for scores `REACTION_SCORE` more, which is
+// enough to break from the order. A player with this share of their health left is attacked,
+// and a hand this big is braced for.
+const SCRIPTED_MOVE_SCORE: f32 = 1.0;
+const REACTION_SCORE: f32 = 2.0;
+const LOW_HEALTH_SHARE: f32 = 0.3;
+const BIG_HAND_SIZE: usize = 5;
+
+// An enraged boss's attacks hit this many times harder
+const ENRAGE_MULTIPLIER: f32 = 2.0;
+const ENRAGE_COLOR: Color = Color::srgb(1.0, 0.3, 0.1);
+
+const RITUAL_COLOR: Color = Color::srgb(0.7, 0.3, 1.0);
+
+const HINT_COLOR: Color = Color::srgb(1.0, 0.9, 0.4);
+
+// A hit flashes its target for the first part of the reaction and knocks it back this many
+// pixels and back again over the whole of it. The flash tint is brighter than white, which
+// washes the sprite out towards white.
+const HIT_FLASH_SECONDS: f32 = 0.1;
+const HIT_REACTION_SECONDS: f32 = 0.2;
+const HIT_KNOCKBACK: f32 = 12.0;
+const HIT_FLASH_COLOR: Color = Color::linear_rgb(4.0, 4.0, 4.0);
+
+// The turn banner slides in, holds in the middle of the screen, then slides out the other side
+const TURN_BANNER_SLIDE_SECONDS: f32 = 0.35;
+const TURN_BANNER_HOLD_SECONDS: f32 = 0.7;
+const TURN_BANNER_HEIGHT: f32 = 110.0;
+const ENEMY_TURN_COLOR: Color = Color::srgb(1.0, 0.35, 0.3);
+
+// End Turn while it can't be pressed
+const DISABLED_BUTTON: Color = Color::srgb(0.3, 0.3, 0.3);
+const DISABLED_TEXT: Color = Color::srgb(0.55, 0.55, 0.55);
+
+// Cards that can be played right now glow softly; the rest are greyed out
+const PLAYABLE_GLOW: Color = Color::srgba(1.0, 0.95, 0.7, 0.45);
+const PLAYABLE_GLOW_WIDTH: f32 = 4.0;
+const UNPLAYABLE_TINT: Color = Color::srgb(0.4, 0.4, 0.45);
+
+// Each hot-seat player's colour, and how the one waiting for their turn is dimmed
+const SEAT_COLORS: [Color; 2] = [Color::srgb(0.4, 0.7, 1.0), Color::srgb(1.0, 0.6, 0.3)];
+const BENCHED_TINT: Color = Color::srgb(0.45, 0.45, 0.45);
+
+// Summoned wisps are a ghostly, see-through copy of the player
+const WISP_TINT: Color = Color::srgba(0.6, 0.85, 1.0, 0.7);
+
+// Width of every health bar at full health
+const HEALTH_BAR_WIDTH: f32 = 150.0;
+// How long a health bar takes to catch up with a change, and how long the ghost of lost health
+// lingers after a hit, fading over its last `DAMAGE_GHOST_FADE_SECONDS`
+const HEALTH_DRAIN_SECONDS: f32 = 0.4;
+const DAMAGE_GHOST_SECONDS: f32 = 0.9;
+const DAMAGE_GHOST_FADE_SECONDS: f32 = 0.3;
+// The health readout at the normal text size, and how far under the middle of its bar it sits
+const HEALTH_READOUT_FONT_SIZE: f32 = 16.0;
+const HEALTH_READOUT_DEPTH: f32 = 16.0;
+
+// A hovered card is held a little bigger, lifted out of the hand and tilted, easing there over
+// this many seconds
+const CARD_HOVER_POSE: Pose = Pose {
+    scale: 1.1,
+    lift: 30.0,
+    tilt: -0.05,
+};
+const CARD_HOVER_SECONDS: f32 = 0.12;
+
+// Cards drawn from the shuffled deck when a fight starts
+pub const OPENING_HAND_SIZE: usize = 3;
+// Energy the player has to play cards with each turn
+const ENERGY_PER_TURN: u32 = 3;
+
+// Damage for the first failed draw, growing by this much with each one after
+const FATIGUE_DAMAGE_STEP: f32 = 3.0;
+
+// Seconds the autoplayer waits between actions, so a watcher can follow along
+const AUTOPLAY_DELAY: f32 = 1.2;
+
+// Seconds the victory screen waits for Continue before moving on by itself
+const VICTORY_AUTO_CONTINUE: f32 = 15.0;
+
+// Health the victory screen offers in place of the fight's cards
+const VICTORY_HEAL: f32 = 20.0;
+
+// Share of the purse merciful mode takes for sparing the player
+const MERCY_GOLD_SHARE: f32 = 0.25;
+
+pub fn combat_plugin(app: &mut App) {
+    app.init_resource::<FightState>()
+        .init_resource::<TurnState>()
+        .init_resource::<AnnouncedTurn>()
+        .init_resource::<DrawRules>()
+        .init_resource::<PlayLock>()
+        .init_resource::<FloatingTextPool>()
+        .add_event::<FightEvent>()
+        .init_resource::<FightActions>()
+        .add_event::<AddToHand>()
+        .configure_sets(
+            Update,
+            (
+                CombatSet::Input,
+                CombatSet::Presentation,
+                CombatSet::Cleanup,
+            )
+                .chain(),
+        )
+        .add_systems(FixedUpdate, take_fight_actions.before(CombatSet::Resolve))
+        .add_systems(OnEnter(GameState::Menu), end_hot_seat)
+        .add_systems(OnEnter(GameState::RetryFight), retry_fight);
+    #[cfg(feature = "dev-tools")]
+    app.add_systems(Update, hold_god_mode);
+}
+
+fn take_fight_actions(mut actions: ResMut<FightActions>) {
+    actions.this_step = std::mem::take(&mut actions.pending);
+}
+
+// Back at the menu, the next run is one player's unless they pick co-op again
+fn end_hot_seat(mut commands: Commands) {
+    commands.remove_resource::<HotSeat>();
+}
+
+// Which chapter a fight belongs to, counted from 1, or 0 for the demo
+fn chapter_number(state: GameState) -> u32 {
+    match state {
+        GameState::Chapter1 => 1,
+        GameState::Chapter2 => 2,
+        GameState::Chapter3 => 3,
+        GameState::Chapter4 => 4,
+        _ => 0,
+    }
+}
+
+// The stages of a fight's frame, run in this order. A new system goes in the stage for what it
+// does, and only orders itself against others in the same stage when it depends on them, so the
+// rest can run side by side. The rules run on the fixed step rather than every frame, so a fight
+// plays out the same at any frame rate; the other stages run every frame. Nothing the rules
+// change moves by itself, so there's nothing to interpolate between steps: the presentation
+// eases the screen towards whatever the last step left, and a press waits at most one step.
+#[derive(SystemSet, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum CombatSet {
+    // Reading the player: hovering, mulligan picks, aiming and the victory choices
+    Input,
+    // The rules: cards played, turns taken, damage dealt, and whether the fight is over. In
+    // `FixedUpdate`, acting on the `FightActions` pressed since the last step
+    Resolve,
+    // Bringing the screen in step with the fight
+    Presentation,
+    // After everything else has settled, keeping the save in step
+    Cleanup,
+}
+
+// Runs the card fight described by the current `Encounter` while in `state`. The chapter
+// must insert its `Encounter` in a system ordered `.before(combat_setup)`.
+pub fn add_combat_state(app: &mut App, state: GameState) {
+    app.add_systems(OnEnter(state), (reset_fight, combat_setup).chain())
+        .add_systems(
+            Update,
+            (
+                update_card_hover,
+                toggle_mulligan_card,
+                read_fight_buttons,
+                (aim_at_enemies, pick_target, keep_target, show_target)
+                    .chain()
+                    .after(pick_cursor),
+                continue_from_victory,
+            )
+                .in_set(CombatSet::Input)
+                .run_if(in_state(state)),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                confirm_mulligan,
+                add_to_hand,
+                // A turn's flow, in the order a turn goes, then what follows from it
+                (
+                    (
+                        handle_card_click,
+                        process_turn,
+                        handle_end_turn_button,
+                        fire_turn_triggers,
+                        hand_off_turn,
+                    )
+                        .chain(),
+                    (plan_enemy_moves, check_victory_condition, update_enrage),
+                )
+                    .chain(),
+            )
+                .in_set(CombatSet::Resolve)
+                .run_if(in_state(state)),
+        )
+        .add_systems(
+            Update,
+            (
+                (
+                    (update_health_bars, animate_health_bars).chain(),
+                    update_health_readouts,
+                ),
+                update_end_turn_button,
+                (animate_damage_text, animate_status_ticks),
+                update_victory_screen,
+                update_fatigue_warning,
+                update_intent_labels,
+                update_block_badges,
+                update_ritual_bars,
+                update_vulnerable_badges,
+                show_powers,
+                show_card_tooltip,
+                show_energy,
+                // After the hot-seat tint, so a reaction returns its target to the right color
+                (
+                    show_active_player,
+                    start_hit_reactions,
+                    animate_hit_reactions,
+                )
+                    .chain(),
+                // Replacing a banner despawns it, so that comes after anything sending it off
+                (slide_out_turn_banner.after(animate_tweens), announce_turn).chain(),
+                animate_phoenix_flash,
+                (show_hint, pulse_hint, show_playable_cards).chain(),
+                (
+                    update_death_screen,
+                    // The demo plays with the run's purse and deck, which it mustn't lose
+                    spare_player.run_if(not(in_state(GameState::Demo))),
+                    add_death_buttons,
+                    death_buttons,
+                )
+                    .chain(),
+            )
+                .in_set(CombatSet::Presentation)
+                .run_if(in_state(state)),
+        )
+        .add_systems(
+            Update,
+            suspend_fight
+                .in_set(CombatSet::Cleanup)
+                .run_if(in_state(state)),
+        )
+        .add_systems(
+            OnExit(state),
+            (
+                despawn_screen::<OnCombatScreen>,
+                release_floating_text,
+                clear_turn_triggers,
+            ),
+        );
+}
+
+// Lets a simple AI play the fight in `state` by pressing the same buttons a player would: it
+// keeps its opening hand, plays the strongest card until none deals damage, then ends the
+// turn. Once the fight is won or lost it moves on to `exit_state`.
+pub fn add_autoplay(app: &mut App, state: GameState, exit_state: GameState) {
+    app.add_systems(OnEnter(state), move |mut commands: Commands| {
+        commands.insert_resource(Autoplay {
+            timer: Timer::from_seconds(AUTOPLAY_DELAY, TimerMode::Repeating),
+            exit_state,
+        });
+    })
+    .add_systems(
+        Update,
+        // Ahead of everything reading the player, as its presses stand in for theirs
+        autoplay.before(CombatSet::Input).run_if(in_state(state)),
+    );
+}
+
+// What the hint and the autoplayer go by to pick a card: the turn, the run's passives and how
+// hurt the enemies are
+#[derive(SystemParam)]
+struct CardAdvisor<'w, 's> {
+    fight_state: Res<'w, FightState>,
+    turn_state: Res<'w, TurnState>,
+    run_effects: Res<'w, RunEffects>,
+    monster_query: Query<'w, 's, &'static Health, With<Monster>>,
+}
+
+impl CardAdvisor<'_, '_> {
+    fn best_card(&self, hand: &[CardType]) -> Option<usize> {
+        let any_monster_at_full_health = self
+            .monster_query
+            .iter()
+            .any(|health| (health.current - health.maximum).abs() < f32::EPSILON);
+        best_card(
+            hand,
+            &self.turn_state,
+            &self.run_effects,
+            any_monster_at_full_health,
+        )
+    }
+}
+
+// The card in `hand` that deals the most damage right now, if any deals some
+fn best_card(
+    hand: &[CardType],
+    turn_state: &TurnState,
+    run_effects: &RunEffects,
+    any_monster_at_full_health: bool,
+) -> Option<usize> {
+    hand.iter()
+        .enumerate()
+        .filter(|(_, card_type)| run_effects.card_cost(**card_type) <= turn_state.energy)
+        .map(|(i, card_type)| {
+            let damage = card_damage(
+                *card_type,
+                turn_state,
+                hand.len(),
+                any_monster_at_full_health,
+            );
+            (i, damage)
+        })
+        .filter(|(_, damage)| *damage > 0.0)
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(i, _)| i)
+}
+
+// Point out the best card in hand for a player who asks. In a guided fight that's the card
+// they have to play.
+fn show_hint(
+    mut commands: Commands,
+    mut interaction_query: ui::ChangedButtons<HintButton>,
+    advisor: CardAdvisor,
+    play_lock: Res<PlayLock>,
+    card_query: Query<(Entity, &CardType), With<Card>>,
+    hinted_query: Query<Entity, With<HintPulse>>,
+    theme: Res<Theme>,
+) {
+    let mut pressed = false;
+    for (interaction, mut color) in interaction_query.iter_mut() {
+        *color = match interaction {
+            Interaction::Pressed => {
+                pressed = true;
+                Color::srgb(0.35, 0.35, 0.35).into()
+            }
+            Interaction::Hovered => theme.button_hovered.into(),
+            Interaction::None => theme.button.into(),
+        };
+    }
+    if !pressed || advisor.fight_state.current_turn != Turn::Player || !hinted_query.is_empty() {
+        return;
+    }
+
+    let cards: Vec<(Entity, CardType)> = card_query
+        .iter()
+        .map(|(entity, card_type)| (entity, *card_type))
+        .collect();
+    let index = match play_lock.only_card {
+        Some(only_card) => cards
+            .iter()
+            .position(|(_, card_type)| *card_type == only_card),
+        None => {
+            let hand: Vec<CardType> = cards.iter().map(|(_, card_type)| *card_type).collect();
+            advisor.best_card(&hand)
+        }
+    };
+    if let Some((entity, _)) = index.and_then(|index| cards.get(index)) {
+        commands.entity(*entity).insert(HintPulse);
+    }
+}
+
+fn pulse_hint(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut fight_events: EventReader<FightEvent>,
+    mut hinted_query: Query<(Entity, &mut UiImage), With<HintPulse>>,
+) {
+    let moved_on = fight_events
+        .read()
+        .any(|event| matches!(event, FightEvent::CardPlayed(_) | FightEvent::TurnEnded));
+    let glow = 0.5 + 0.5 * (time.elapsed_seconds() * 5.0).sin();
+    for (entity, mut image) in hinted_query.iter_mut() {
+        if moved_on {
+            image.color = Color::WHITE;
+            commands.entity(entity).remove::<HintPulse>();
+        } else {
+            image.color = Color::WHITE.mix(&HINT_COLOR, glow);
+        }
+    }
+}
+
+// The mulligan's confirm button and End Turn, and which of the two each is
+type AutoplayButtons<'w, 's> = Query<
+    'w,
+    's,
+    (&'static mut Interaction, Has<MulliganButton>),
+    (
+        Or<(With<MulliganButton>, With<EndTurnButton>)>,
+        Without<Card>,
+    ),
+>;
+
+fn autoplay(
+    time: Res<Time>,
+    mut autoplay: ResMut<Autoplay>,
+    advisor: CardAdvisor,
+    mut card_query: Query<(&CardType, &mut Interaction), With<Card>>,
+    mut button_query: AutoplayButtons,
+    finished_query: FinishedScreens,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    if !autoplay.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    if !finished_query.is_empty() {
+        game_state.set(autoplay.exit_state);
+        return;
+    }
+
+    // Which button to press, if any: the mulligan button to keep the opening hand, or End Turn
+    // once no card is worth playing
+    let press_mulligan_button = match advisor.fight_state.current_turn {
+        Turn::Mulligan => Some(true),
+        Turn::Player => {
+            let hand: Vec<CardType> = card_query.iter().map(|(card_type, _)| *card_type).collect();
+            match advisor.best_card(&hand) {
+                Some(index) => {
+                    if let Some((_, mut interaction)) = card_query.iter_mut().nth(index) {
+                        *interaction = Interaction::Pressed;
+                    }
+                    None
+                }
+                None => Some(false),
+            }
+        }
+        Turn::Enemy => None,
+    };
+    if let Some(mulligan) = press_mulligan_button {
+        for (mut interaction, is_mulligan_button) in button_query.iter_mut() {
+            if is_mulligan_button == mulligan {
+                *interaction = Interaction::Pressed;
+            }
+        }
+    }
+}
+
+// Every blow that can leave the player at 0 health checks here before the death screen comes up.
+// Returns whether they fell for good: a Phoenix Feather, while the run has one, burns up to bring
+// them back instead.
+fn player_falls(
+    commands: &mut Commands,
+    health: &mut Health,
+    run_effects: &mut RunEffects,
+) -> bool {
+    if health.current > 0.0 {
+        return false;
+    }
+    if !run_effects.consume(RunEffect::PhoenixFeather) {
+        return true;
+    }
+    health.current = (health.maximum * PHOENIX_REVIVE_SHARE).ceil();
+    spawn_phoenix_flash(commands);
+    false
+}
+
+// A burst of firelight over the whole screen as the feather burns, fading as the player rises
+fn spawn_phoenix_flash(commands: &mut Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: PHOENIX_COLOR.into(),
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            PhoenixFlash {
+                timer: Timer::from_seconds(PHOENIX_FLASH_SECONDS, TimerMode::Once),
+            },
+            OnCombatScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Reborn from the ashes",
+                TextStyle {
+                    font_size: 80.0,
+                    color: Color::srgb(1.0, 0.95, 0.7),
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn animate_phoenix_flash(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut flash_query: Query<(Entity, &mut PhoenixFlash, &mut BackgroundColor, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (entity, mut flash, mut background, children) in flash_query.iter_mut() {
+        flash.timer.tick(time.delta());
+        if flash.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let fade = 1.0 - flash.timer.fraction();
+        background.0 = PHOENIX_COLOR.with_alpha(PHOENIX_COLOR.alpha() * fade);
+        for child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                let color = text.sections[0].style.color;
+                text.sections[0].style.color = color.with_alpha(fade);
+            }
+        }
+    }
+}
+
+fn spawn_death_screen(commands: &mut Commands) {
+    // Main container
+    commands
+        .spawn((
+            NodeBundle {
+                // The lost fight can't be played on under it
+                focus_policy: FocusPolicy::Block,
+                ..ui::modal()
+            },
+            DeathScreen,
+            FadeInEffect {
+                timer: Timer::from_seconds(2.0, TimerMode::Once),
+            },
+            OnCombatScreen,
+        ))
+        .with_children(|parent| {
+            // "YOU DIED" text
+            parent.spawn((
+                TextBundle::from_section(
+                    "YOU DIED",
+                    TextStyle {
+                        font_size: 120.0,
+                        color: Color::srgba(0.8, 0.0, 0.0, 0.0), // Start transparent
+                        ..default()
+                    },
+                ),
+                DeathText,
+            ));
+        });
+}
+
+// Fades the death screen in
+fn update_death_screen(
+    time: Res<Time>,
+    mut query: Query<(&mut BackgroundColor, &mut FadeInEffect), With<DeathScreen>>,
+    mut text_query: Query<&mut Text, With<DeathText>>,
+) {
+    for (mut bg_color, mut fade) in query.iter_mut() {
+        // Update fade effect
+        fade.timer.tick(time.delta());
+        let alpha = fade.timer.fraction();
+        bg_color.0 = Color::srgba(0.0, 0.0, 0.0, alpha * 0.7);
+
+        // Update text color
+        if let Ok(mut text) = text_query.get_single_mut() {
+            text.sections[0].style.color = Color::srgba(0.8, 0.0, 0.0, alpha);
+        }
+    }
+}
+
+// In merciful mode, dying costs part of the purse and a random card instead of the run
+fn spare_player(
+    mut commands: Commands,
+    screen_query: Query<Entity, Added<DeathScreen>>,
+    difficulty: Res<Difficulty>,
+    save_data: Res<SaveData>,
+    mut gold: ResMut<Gold>,
+    mut deck: ResMut<Deck>,
+    mut rng: ResMut<RunRng>,
+) {
+    if !difficulty.merciful || save_data.daily.is_some() {
+        return;
+    }
+    for screen in screen_query.iter() {
+        let lost_gold = (gold.amount() as f32 * MERCY_GOLD_SHARE) as u32;
+        gold.spend(lost_gold);
+        let index = rng.below(deck.cards().len());
+        let card = deck.cards().get(index).copied();
+        let card = card.filter(|_| deck.remove(index));
+        commands.entity(screen).insert(Spared {
+            gold: lost_gold,
+            card,
+        });
+    }
+}
+
+// A daily run is the same for everyone and gets one try, so its fights can't be retried
+fn add_death_buttons(
+    mut commands: Commands,
+    screen_query: Query<(Entity, Option<&Spared>), Added<DeathScreen>>,
+    mut text_query: Query<&mut Text, With<DeathText>>,
+    save_data: Res<SaveData>,
+    theme: Res<Theme>,
+) {
+    for (screen, spared) in screen_query.iter() {
+        commands.entity(screen).with_children(|parent| {
+            if let Some(spared) = spared {
+                if let Ok(mut text) = text_query.get_single_mut() {
+                    text.sections[0].value = "SPARED".to_string();
+                }
+                let mut cost = format!("-{} gold", spared.gold);
+                if let Some(card) = spared.card {
+                    cost += &format!("\nLost a {card:?} card");
+                }
+                parent.spawn(
+                    TextBundle::from_section(
+                        cost,
+                        TextStyle {
+                            font_size: 40.0,
+                            color: GOLD_COLOR,
+                            ..default()
+                        },
+                    )
+                    .with_text_justify(JustifyText::Center),
+                );
+                ui::modal_button(parent, &theme, "Try Again", DeathAction::Retry);
+            } else if save_data.daily.is_none() {
+                ui::modal_button(parent, &theme, "Retry Fight", DeathAction::Retry);
+            }
+            ui::modal_button(parent, &theme, "Abandon Run", DeathAction::AbandonRun);
+            ui::modal_button(parent, &theme, "Quit", DeathAction::Quit);
+        });
+    }
+}
+
+fn death_buttons(
+    mut commands: Commands,
+    mut interaction_query: Query<
+        (&Interaction, &DeathAction, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    state: Res<State<GameState>>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut save_data: ResMut<SaveData>,
+    mut app_exit_events: EventWriter<AppExit>,
+    theme: Res<Theme>,
+) {
+    for (interaction, action, mut color) in interaction_query.iter_mut() {
+        *color = ui::button_color(&theme, *interaction);
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match action {
+            DeathAction::Retry => {
+                commands.insert_resource(Retrying(*state.get()));
+                game_state.set(GameState::RetryFight);
+            }
+            // Nothing is left to continue from the menu
+            DeathAction::AbandonRun => {
+                save_data.fight = None;
+                save_data.cutscene = None;
+                save_data.endless = None;
+                save_data.save();
+                game_state.set(GameState::Menu);
+            }
+            DeathAction::Quit => {
+                app_exit_events.send(AppExit::Success);
+            }
+        }
+    }
+}
+
+// Straight back into the lost fight, which deals it again from the start
+fn retry_fight(
+    mut commands: Commands,
+    retrying: Option<Res<Retrying>>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    let Some(retrying) = retrying else {
+        game_state.set(GameState::Menu);
+        return;
+    };
+    game_state.set(retrying.0);
+    commands.remove_resource::<Retrying>();
+}
+
+// Resize and recolor a health bar to show `share` of maximum health
+fn set_health_bar(bar_sprite: &mut Sprite, share: f32, theme: &Theme) {
+    bar_sprite.custom_size = Some(Vec2::new(
+        HEALTH_BAR_WIDTH * share,
+        bar_sprite.custom_size.unwrap_or(Vec2::ZERO).y,
+    ));
+
+    bar_sprite.color = ui::health_color(theme, share);
+}
+
+// Temporary health picks up where the health bar ends, cut off at the end of the bar
+fn set_temporary_health_bar(
+    bar_sprite: &mut Sprite,
+    transform: &mut Transform,
+    start: f32,
+    temporary: f32,
+) {
+    let start = start.min(1.0);
+    let share = temporary.min(1.0 - start);
+    transform.translation.x = -HEALTH_BAR_WIDTH / 2.0 + HEALTH_BAR_WIDTH * start;
+    bar_sprite.custom_size = Some(Vec2::new(
+        HEALTH_BAR_WIDTH * share,
+        bar_sprite.custom_size.unwrap_or(Vec2::ZERO).y,
+    ));
+}
+
+// Point each health bar at the health it should show. A bar just spawned starts there; any other
+// eases there from wherever it is, and a hit leaves a ghost of what it took.
+fn update_health_bars(
+    query: Query<(Ref<Health>, &Children), Changed<Health>>,
+    mut container_query: Query<&mut HealthBarContainer>,
+) {
+    for (health, children) in query.iter() {
+        let share = health.current / health.maximum;
+        for child in children.iter() {
+            let Ok(mut bar) = container_query.get_mut(*child) else {
+                continue;
+            };
+            let shown = bar.shown();
+            if health.is_added() {
+                bar.from = share;
+                bar.ghost = share;
+            } else {
+                if share < shown {
+                    bar.ghost = bar.ghost.max(shown);
+                    bar.ghost_timer.reset();
+                }
+                bar.from = shown;
+            }
+            bar.to = share;
+            bar.elapsed = 0.0;
+            bar.temporary = health.temporary / health.maximum;
+        }
+    }
+}
+
+// Write out the health under each bar, as "72/100" with any temporary health after it, at the
+// size the text size setting asks for
+fn update_health_readouts(
+    health_query: Query<(Ref<Health>, &Children)>,
+    container_query: Query<&Children, With<HealthBarContainer>>,
+    mut readout_query: Query<&mut Text, With<HealthReadout>>,
+    text_size: Res<TextSize>,
+    theme: Res<Theme>,
+) {
+    for (health, children) in health_query.iter() {
+        if !health.is_changed() && !text_size.is_changed() {
+            continue;
+        }
+        let mut value = format!("{}/{}", health.current.ceil(), health.maximum.ceil());
+        if health.temporary > 0.0 {
+            value += &format!(" +{}", health.temporary.ceil());
+        }
+        let readouts = children
+            .iter()
+            .filter_map(|child| container_query.get(*child).ok())
+            .flat_map(|container| container.iter());
+        for readout in readouts {
+            let Ok(mut text) = readout_query.get_mut(*readout) else {
+                continue;
+            };
+            *text = Text::from_section(
+                value.clone(),
+                theme.text_style(HEALTH_READOUT_FONT_SIZE * text_size.scale()),
+            );
+        }
+    }
+}
+
+// The segment of a health bar showing temporary health, apart from the health bar itself
+type TemporaryBars<'w, 's> = Query<
+    'w,
+    's,
+    (&'static mut Sprite, &'static mut Transform),
+    (With<TemporaryHealthBar>, Without<HealthBar>),
+>;
+
+// The part of a health bar that lingers after a hit, apart from the bar's other segments
+type DamageGhosts<'w, 's> = Query<
+    'w,
+    's,
+    &'static mut Sprite,
+    (
+        With<DamageGhost>,
+        Without<HealthBar>,
+        Without<TemporaryHealthBar>,
+    ),
+>;
+
+fn animate_health_bars(
+    time: Res<Time>,
+    mut container_query: Query<(&mut HealthBarContainer, &Children)>,
+    mut health_bar_query: Query<&mut Sprite, With<HealthBar>>,
+    mut temporary_bar_query: TemporaryBars,
+    mut ghost_query: DamageGhosts,
+    theme: Res<Theme>,
+) {
+    for (mut bar, children) in container_query.iter_mut() {
+        bar.elapsed += time.delta_seconds();
+        let shown = bar.shown();
+        if bar.ghost_timer.tick(time.delta()).finished() {
+            bar.ghost = shown;
+        }
+        let ghost_left = bar.ghost_timer.remaining_secs();
+        for bar_entity in children.iter() {
+            if let Ok(mut bar_sprite) = health_bar_query.get_mut(*bar_entity) {
+                set_health_bar(&mut bar_sprite, shown, &theme);
+            }
+            if let Ok((mut bar_sprite, mut transform)) = temporary_bar_query.get_mut(*bar_entity) {
+                set_temporary_health_bar(&mut bar_sprite, &mut transform, shown, bar.temporary);
+            }
+            if let Ok(mut ghost_sprite) = ghost_query.get_mut(*bar_entity) {
+                let height = ghost_sprite.custom_size.unwrap_or(Vec2::ZERO).y;
+                ghost_sprite.custom_size =
+                    Some(Vec2::new(HEALTH_BAR_WIDTH * bar.ghost.min(1.0), height));
+                ghost_sprite
+                    .color
+                    .set_alpha((ghost_left / DAMAGE_GHOST_FADE_SECONDS).min(1.0));
+            }
+        }
+    }
+}
+
+// The cards in hand whose interaction changed, with the pose each is in
+type HoveredCards<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Interaction,
+        &'static Transform,
+        &'static Style,
+    ),
+    (With<Card>, Changed<Interaction>),
+>;
+
+// Cards ease up into a hover and back down, starting from wherever they are so that sweeping
+// across the hand doesn't make them jump. A pressed card is about to be played, so it stays put.
+fn update_card_hover(
+    mut commands: Commands,
+    card_query: HoveredCards,
+    fight_state: Res<FightState>,
+) {
+    for (entity, interaction, transform, style) in card_query.iter() {
+        let to = match *interaction {
+            Interaction::Pressed => continue,
+            Interaction::Hovered if fight_state.current_turn == Turn::Player => CARD_HOVER_POSE,
+            _ => Pose::REST,
+        };
+        let from = Pose::of(transform, style);
+        if from != to {
+            commands.entity(entity).insert(Tween::new(
+                TweenTarget::Pose { from, to },
+                CARD_HOVER_SECONDS,
+                Ease::Out,
+            ));
+        }
+    }
+}
+
+// Whether pressing the card now would play it, by the same rules `handle_card_click` goes by. A
+// card a combo would cancel counts as unplayable too, since playing it only wastes the energy.
+fn card_playable(
+    card_type: CardType,
+    fight_state: &FightState,
+    turn_state: &TurnState,
+    play_lock: &PlayLock,
+    run_effects: &RunEffects,
+) -> bool {
+    fight_state.current_turn == Turn::Player
+        && !play_lock
+            .only_card
+            .is_some_and(|only_card| only_card != card_type)
+        && card_type != CardType::Curse
+        && run_effects.card_cost(card_type) <= turn_state.energy()
+        && !card_type
+            .keywords()
+            .into_iter()
+            .any(|keyword| keyword.fizzles(&turn_state.cards_played_this_turn))
+}
+
+// The cards in hand as they're drawn, and whether a hint is pulsing on each
+type HandCardImages<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static CardType,
+        &'static mut UiImage,
+        Has<HintPulse>,
+    ),
+    With<Card>,
+>;
+
+// Greys out the cards that can't be played and lights up the rest, again whenever the turn,
+// the energy or the hand changes. During the mulligan the shading marks the cards to redraw
+// instead, and a pulsing hint keeps its own color until it ends.
+fn show_playable_cards(
+    mut commands: Commands,
+    fight_state: Res<FightState>,
+    turn_state: Res<TurnState>,
+    play_lock: Res<PlayLock>,
+    run_effects: Res<RunEffects>,
+    mut card_query: HandCardImages,
+    new_cards: Query<(), Added<Card>>,
+) {
+    let changed = fight_state.is_changed()
+        || turn_state.is_changed()
+        || play_lock.is_changed()
+        || run_effects.is_changed();
+    if (!changed && new_cards.is_empty()) || fight_state.current_turn == Turn::Mulligan {
+        return;
+    }
+
+    for (entity, card_type, mut image, hinted) in card_query.iter_mut() {
+        let playable = card_playable(
+            *card_type,
+            &fight_state,
+            &turn_state,
+            &play_lock,
+            &run_effects,
+        );
+        if playable {
+            commands.entity(entity).insert(Outline::new(
+                Val::Px(PLAYABLE_GLOW_WIDTH),
+                Val::ZERO,
+                PLAYABLE_GLOW,
+            ));
+        } else {
+            commands.entity(entity).remove::<Outline>();
+        }
+        if !hinted {
+            image.color = if playable {
+                Color::WHITE
+            } else {
+                UNPLAYABLE_TINT
+            };
+        }
+    }
+}
+
+// Damage a card deals given what has been played so far this turn. A negative value heals.
+fn card_damage(
+    card_type: CardType,
+    turn_state: &TurnState,
+    cards_in_hand: usize,
+    any_monster_at_full_health: bool,
+) -> f32 {
+    let mut damage = base_card_damage(
+        card_type,
+        turn_state,
+        cards_in_hand,
+        any_monster_at_full_health,
+    );
+    for keyword in card_type.keywords() {
+        damage = keyword.damage(
+            damage,
+            turn_state.first_card_played,
+            &turn_state.cards_played_this_turn,
+        );
+    }
+    // Upgrades and powers add to whatever the card does, but can't bring back damage a combo
+    // cancelled
+    if damage == 0.0 {
+        return damage;
+    }
+    let mut bonus: f32 = turn_state
+        .powers
+        .iter()
+        .map(|power| power.bonus(card_type))
+        .sum();
+    if turn_state.upgraded.contains(&card_type) {
+        bonus += CARD_UPGRADE_BONUS;
+    }
+    damage + bonus.copysign(damage)
+}
+
+fn base_card_damage(
+    card_type: CardType,
+    turn_state: &TurnState,
+    cards_in_hand: usize,
+    any_monster_at_full_health: bool,
+) -> f32 {
+    match card_type {
+        CardType::Fire => FIRE_BASE_DAMAGE,
+        CardType::Ice => {
+            let mut damage = ICE_BASE_DAMAGE;
+
+            if let Some(last_card) = turn_state.cards_played_this_turn.last() {
+                if matches!(last_card, CardType::Fire) {
+                    damage *= 2.0;
+                }
+            }
+
+            damage
+        }
+        CardType::Crystal => {
+            let effects_bonus = (turn_state.cards_played_this_turn.len() as f32) * 2.0;
+            let turn_bonus = turn_state.crystal_power as f32;
+            CRYSTAL_BASE_DAMAGE + effects_bonus + turn_bonus
+        }
+        CardType::Air => AIR_BASE_DAMAGE,
+        CardType::Shatter => SHATTER_BASE_DAMAGE,
+        CardType::Earth => {
+            let turn_bonus = turn_state.turn_count as f32;
+            EARTH_BASE_DAMAGE + cards_in_hand as f32 + turn_bonus
+        }
+        CardType::Heal => {
+            if any_monster_at_full_health {
+                HEAL_BASE_DAMAGE
+            } else {
+                -HEAL_BASE_DAMAGE
+            }
+        }
+        CardType::Inferno | CardType::Wisp | CardType::Aegis | CardType::Curse => 0.0,
+    }
+}
+
+// The monster under the pointer, if any
+fn monster_under_cursor<'a>(
+    game_window: &GameWindow,
+    camera_query: &Query<(&Camera, &GlobalTransform)>,
+    monsters: impl Iterator<Item = (Entity, &'a GlobalTransform, &'a Sprite)>,
+) -> Option<Entity> {
+    let cursor = game_window.get().and_then(Window::cursor_position)?;
+    let (camera, camera_transform) = camera_query.get_single().ok()?;
+    let cursor = camera.viewport_to_world_2d(camera_transform, cursor)?;
+    monsters
+        .filter(|(_, transform, sprite)| {
+            let half_size = sprite.custom_size.unwrap_or(Vec2::ZERO) / 2.0;
+            (cursor - transform.translation().truncate())
+                .abs()
+                .cmple(half_size)
+                .all()
+        })
+        .map(|(entity, _, _)| entity)
+        .next()
+}
+
+// A sword while the pointer is over an enemy on the player's turn, since that's who the cards hit
+fn aim_at_enemies(
+    fight_state: Res<FightState>,
+    game_window: GameWindow,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    monster_query: Query<(Entity, &GlobalTransform, &Sprite), With<Monster>>,
+    mut cursor_style: ResMut<CursorStyle>,
+) {
+    if fight_state.current_turn != Turn::Player || *cursor_style != CursorStyle::Pointer {
+        return;
+    }
+    if monster_under_cursor(&game_window, &camera_query, monster_query.iter()).is_some() {
+        *cursor_style = CursorStyle::Sword;
+    }
+}
+
+// Clicking a monster makes it the target of the cards played after
+fn pick_target(
+    mouse: Res<ButtonInput<MouseButton>>,
+    cursor_style: Res<CursorStyle>,
+    game_window: GameWindow,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    monster_query: Query<(Entity, &GlobalTransform, &Sprite, &Health), With<Monster>>,
+    mut fight_state: ResMut<FightState>,
+) {
+    // The sword only shows over a monster with nothing clickable in the way
+    if !mouse.just_pressed(MouseButton::Left) || *cursor_style != CursorStyle::Sword {
+        return;
+    }
+    let living = monster_query
+        .iter()
+        .filter(|(_, _, _, health)| health.current > 0.0)
+        .map(|(entity, transform, sprite, _)| (entity, transform, sprite));
+    if let Some(monster) = monster_under_cursor(&game_window, &camera_query, living) {
+        fight_state.target = Some(monster);
+    }
+}
+
+// The monster the target moves on to when there's none yet or it has died
+fn left_most_living<'a>(
+    monsters: impl Iterator<Item = (Entity, &'a GlobalTransform, &'a Health)>,
+) -> Option<Entity> {
+    monsters
+        .filter(|(_, _, health)| health.current > 0.0)
+        .min_by(|(_, a, _), (_, b, _)| a.translation().x.total_cmp(&b.translation().x))
+        .map(|(entity, _, _)| entity)
+}
+
+// Move the target on to the left-most living monster when there's none yet or it has died
+fn keep_target(
+    monster_query: Query<(Entity, &GlobalTransform, &Health), With<Monster>>,
+    mut fight_state: ResMut<FightState>,
+) {
+    let alive = |entity| {
+        monster_query
+            .get(entity)
+            .is_ok_and(|(_, _, health)| health.current > 0.0)
+    };
+    if fight_state.target.is_some_and(alive) {
+        return;
+    }
+    let left_most = left_most_living(monster_query.iter());
+    if fight_state.target != left_most {
+        fight_state.target = left_most;
+    }
+}
+
+// Mark the target with a sword at its feet
+fn show_target(
+    mut commands: Commands,
+    fight_state: Res<FightState>,
+    asset_server: Res<AssetServer>,
+    marker_query: Query<(Entity, &Parent), With<TargetMarker>>,
+    monster_query: Query<&Sprite, With<Monster>>,
+) {
+    if !fight_state.is_changed() {
+        return;
+    }
+    let marked = marker_query
+        .get_single()
+        .ok()
+        .map(|(_, parent)| parent.get());
+    if marked == fight_state.target {
+        return;
+    }
+    for (marker, _) in marker_query.iter() {
+        commands.entity(marker).despawn_recursive();
+    }
+    let Some(target) = fight_state.target else {
+        return;
+    };
+    let Ok(sprite) = monster_query.get(target) else {
+        return;
+    };
+    let height = sprite.custom_size.unwrap_or(Vec2::ZERO).y;
+    commands.entity(target).with_children(|monster| {
+        monster.spawn((
+            SpriteBundle {
+                texture: asset_server.load("textures/cursors/sword.png"),
+                sprite: Sprite {
+                    custom_size: Some(Vec2::splat(32.0)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, -height / 2.0 - 20.0, 0.2),
+                ..default()
+            },
+            TargetMarker,
+        ));
+    });
+}
+
+// Presses of the cards, End Turn and the mulligan's confirm button, for the rules to act on
+fn read_fight_buttons(
+    card_query: ChangedCards,
+    end_turn_query: Query<&Interaction, (Changed<Interaction>, With<EndTurnButton>)>,
+    mulligan_query: Query<&Interaction, (Changed<Interaction>, With<MulliganButton>)>,
+    mut actions: ResMut<FightActions>,
+) {
+    let pressed = |interaction: &Interaction| *interaction == Interaction::Pressed;
+    for (card, _) in card_query
+        .iter()
+        .filter(|(_, interaction)| pressed(interaction))
+    {
+        actions.pending.push(FightAction::PlayCard(card));
+    }
+    if end_turn_query.iter().any(pressed) {
+        actions.pending.push(FightAction::EndTurn);
+    }
+    if mulligan_query.iter().any(pressed) {
+        actions.pending.push(FightAction::ConfirmMulligan);
+    }
+}
+
+fn handle_card_click(
+    mut commands: Commands,
+    rules: FightRules,
+    card_query: Query<(Entity, &CardType), With<Card>>,
+    mut monster_query: TargetableMonsters,
+    mut player_query: PlayerCharacter,
+    ally_query: Query<(), With<Ally>>,
+    assets: FightAssets,
+) {
+    let FightRules {
+        actions,
+        play_lock,
+        mut fight_state,
+        mut turn_state,
+        run_effects,
+        mut rng,
+        mut fight_events,
+        ..
+    } = rules;
+    let FightAssets {
+        asset_server,
+        theme,
+        ..
+    } = assets;
+    // Cards clicked out of turn are dropped rather than played once it comes round
+    let mut played: Vec<Entity> = actions
+        .this_step
+        .iter()
+        .filter_map(|action| match action {
+            FightAction::PlayCard(card) => Some(*card),
+            _ => None,
+        })
+        .collect();
+    if fight_state.current_turn != Turn::Player {
+        return;
+    }
+    // A card pressed twice before the step is still only played once
+    let mut pressed = Vec::with_capacity(played.len());
+    played.retain(|card| {
+        let first_press = !pressed.contains(card);
+        pressed.push(*card);
+        first_press
+    });
+
+    // The presses are played in the order they came in, each by the rules as the ones before
+    // it left them. Cards played this step are only despawned once it's over, so the hand
+    // count leaves them out here.
+    let mut cards_played = 0;
+    for (card_entity, card_type) in card_query.iter_many(&played) {
+        if play_lock
+            .only_card
+            .is_some_and(|only_card| only_card != *card_type)
+        {
+            continue;
+        }
+        if *card_type == CardType::Curse {
+            spawn_floating_text(
+                &mut commands,
+                "Curses can't be played".to_string(),
+                CURSE_COLOR,
+            );
+            continue;
+        }
+        let cost = run_effects.card_cost(*card_type);
+        if cost > turn_state.energy() {
+            spawn_floating_text(&mut commands, "Not enough energy".to_string(), ENERGY_COLOR);
+            continue;
+        }
+        turn_state.spend_energy(cost);
+
+        let any_monster_at_full_health = monster_query
+            .iter()
+            .any(|(_, health, ..)| (health.current - health.maximum).abs() < f32::EPSILON);
+        let damage = turn_state.play(
+            *card_type,
+            card_query.iter().count() - cards_played,
+            any_monster_at_full_health,
+        );
+        cards_played += 1;
+
+        // A card played earlier this step may have killed the target, and it only moves on to
+        // the next monster after the step
+        if !fight_state.target.is_some_and(|target| {
+            monster_query
+                .get(target)
+                .is_ok_and(|(_, health, ..)| health.current > 0.0)
+        }) {
+            fight_state.target = left_most_living(
+                monster_query
+                    .iter()
+                    .map(|(entity, health, .., transform)| (entity, transform, health)),
+            );
+        }
+
+        // A power only sets itself up, Aegis shields the player, a wisp joins the fight in
+        // front of the player, and otherwise damage goes to the target alone
+        if let Some(power) = card_type.power() {
+            spawn_floating_text(&mut commands, power.description().to_string(), BUFF_COLOR);
+        } else if *card_type == CardType::Aegis {
+            if let Ok((.., mut health)) = player_query.get_single_mut() {
+                health.temporary += AEGIS_TEMPORARY_HEALTH;
+                spawn_floating_text(
+                    &mut commands,
+                    format!("+{AEGIS_TEMPORARY_HEALTH} temporary health"),
+                    TEMPORARY_HEALTH_COLOR,
+                );
+            }
+        } else if *card_type == CardType::Wisp {
+            if let Ok((parent, transform, _)) = player_query.get_single() {
+                let count = ally_query.iter().count() as f32;
+                let position = transform.translation + Vec3::new(220.0 + 120.0 * count, -90.0, 0.1);
+                commands.entity(parent.get()).with_children(|parent| {
+                    spawn_ally(parent, position, ALLY_HEALTH, 0, &asset_server, &theme);
+                });
+                spawn_floating_text(&mut commands, "A wisp joins you".to_string(), BUFF_COLOR);
+            }
+        } else if let Some((
+            entity,
+            mut monster_health,
+            mut block,
+            mut vulnerable,
+            charge,
+            burning,
+            loot,
+            _,
+        )) = fight_state
+            .target
+            .and_then(|target| monster_query.get_mut(target).ok())
+        {
+            let damage = block.absorb(vulnerable.amplify(damage));
+            monster_health.current = (monster_health.current - damage).max(0.0);
+            if damage == 0.0 {
+                spawn_floating_text(&mut commands, "Blocked".to_string(), BLOCK_COLOR);
+            } else {
+                spawn_damage_text(&mut commands, damage);
+                fight_events.send(FightEvent::DamageDealt(entity, damage));
+            }
+
+            if let Some(mut charge) = charge {
+                charge.damage_this_turn += damage.max(0.0);
+                let interrupted = charge.damage_this_turn >= charge.ritual.interrupt_damage
+                    || charge.ritual.interrupt_card == Some(*card_type);
+                if interrupted && charge.progress > 0 {
+                    charge.progress = 0;
+                    spawn_floating_text(
+                        &mut commands,
+                        "Ritual interrupted!".to_string(),
+                        RITUAL_COLOR,
+                    );
+                }
+            }
+
+            // Burn sets in on a target still standing, on top of any it already has
+            let burn: f32 = card_type.keywords().iter().map(|k| k.burn()).sum();
+            if burn > 0.0 && monster_health.current > 0.0 {
+                match burning {
+                    Some(mut burning) => burning.0 += burn,
+                    None => {
+                        commands.entity(entity).insert(Burning(burn));
+                    }
+                }
+                spawn_floating_text(&mut commands, format!("Burn {burn}"), BURN_COLOR);
+            }
+
+            let turns: u32 = card_type.keywords().iter().map(|k| k.vulnerable()).sum();
+            if turns > 0 && monster_health.current > 0.0 {
+                vulnerable.turns += turns;
+                spawn_floating_text(&mut commands, "Vulnerable".to_string(), VULNERABLE_COLOR);
+            }
+
+            // If monster dies, drop its loot and despawn it
+            if monster_health.current <= 0.0 {
+                loot.0.roll(&mut rng, &mut turn_state.loot);
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+
+        fight_events.send(FightEvent::CardPlayed(*card_type));
+
+        // Destroy the played card
+        commands.entity(card_entity).despawn_recursive();
+    }
+}
+
+fn process_turn(
+    mut commands: Commands,
+    rules: FightRules,
+    hand_query: Query<Entity, With<HandContainer>>,
+    summoned_query: Query<(), With<Summoned>>,
+    assets: FightAssets,
+    difficulty: Res<Difficulty>,
+    mut query_set: TurnCombatants,
+) {
+    let FightRules {
+        mut fight_state,
+        mut turn_state,
+        draw_rules,
+        mut run_effects,
+        mut rng,
+        mut fight_events,
+        ..
+    } = rules;
+    if fight_state.current_turn == Turn::Enemy {
+        // Allies act first, so a monster one finishes off never makes its move
+        let mut ally_attacks = Vec::new();
+        let mut ally_block = 0.0;
+        for (_, health, mut ally) in query_set.p2().iter_mut() {
+            if health.current <= 0.0 {
+                continue;
+            }
+            match ALLY_ACTIONS[ally.next % ALLY_ACTIONS.len()] {
+                AllyAction::Attack(damage) => ally_attacks.push(damage),
+                AllyAction::Guard(amount) => ally_block += amount,
+            }
+            ally.next += 1;
+        }
+        if ally_block > 0.0 {
+            if let Ok((_, mut player_block)) = query_set.p0().get_single_mut() {
+                player_block.0 += ally_block;
+                spawn_floating_text(&mut commands, format!("+{ally_block} block"), BLOCK_COLOR);
+            }
+        }
+        for damage in ally_attacks {
+            let mut monsters = query_set.p1();
+            let Some((entity, mut health, _, mut block, vulnerable, .., loot)) = monsters
+                .iter_mut()
+                .find(|(_, health, ..)| health.current > 0.0)
+            else {
+                break;
+            };
+            let damage = block.absorb(vulnerable.amplify(damage));
+            health.current = (health.current - damage).max(0.0);
+            if damage == 0.0 {
+                spawn_floating_text(&mut commands, "Blocked".to_string(), BLOCK_COLOR);
+            } else {
+                spawn_damage_text(&mut commands, damage);
+                fight_events.send(FightEvent::DamageDealt(entity, damage));
+            }
+            if health.current <= 0.0 {
+                loot.0.roll(&mut rng, &mut turn_state.loot);
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+
+        // Then every living monster makes its move
+        let mut monster_attacks = Vec::new();
+        let mut summons = Vec::new();
+        let mut ritual_complete = false;
+        // Status damage shows one tick at a time, in the order the monsters stand
+        let mut ticks = 0;
+        for (
+            entity,
+            mut health,
+            mut behavior,
+            mut block,
+            mut vulnerable,
+            charge,
+            path,
+            transform,
+            global_transform,
+            parent,
+            burning,
+            loot,
+        ) in query_set.p1().iter_mut()
+        {
+            block.0 = (block.0 * BLOCK_DECAY).floor();
+            if vulnerable.turns > 0 {
+                vulnerable.turns -= 1;
+            }
+            if health.current <= 0.0 {
+                continue;
+            }
+            // A burn ticks before the monster moves, and may leave it no move to make
+            if let Some(mut burning) = burning {
+                let (damage, left) = tick_burn(burning.0);
+                burning.0 = left;
+                if left <= 0.0 {
+                    commands.entity(entity).remove::<Burning>();
+                }
+                health.current = (health.current - damage).max(0.0);
+                spawn_status_tick(
+                    &mut commands,
+                    assets.asset_server.load(BURN_ICON),
+                    BURN_COLOR,
+                    damage,
+                    global_transform.translation(),
+                    ticks as f32 * STATUS_TICK_STAGGER,
+                );
+                ticks += 1;
+                fight_events.send(FightEvent::DamageDealt(entity, damage));
+                if health.current <= 0.0 {
+                    loot.0.roll(&mut rng, &mut turn_state.loot);
+                    commands.entity(entity).despawn_recursive();
+                    continue;
+                }
+            }
+            // The ritual goes on whatever else the boss does
+            if let Some(mut charge) = charge {
+                charge.damage_this_turn = 0.0;
+                charge.progress += 1;
+                if charge.progress >= charge.ritual.turns {
+                    ritual_complete = true;
+                } else {
+                    spawn_floating_text(
+                        &mut commands,
+                        "Stella luminara...".to_string(),
+                        RITUAL_COLOR,
+                    );
+                }
+            }
+            match behavior.intent().clone() {
+                EnemyMove::Attack(damage) => monster_attacks.push(behavior.attack_damage(damage)),
+                EnemyMove::Summon(specs) => {
+                    for (i, spec) in specs.into_iter().enumerate() {
+                        let mut spec_path = path.0.clone();
+                        spec_path.extend([behavior.next, i]);
+                        summons.push((parent.get(), transform.translation, spec, spec_path));
+                    }
+                }
+                EnemyMove::Heal(amount) => {
+                    health.current = (health.current + amount).min(health.maximum);
+                    spawn_floating_text(&mut commands, format!("+{}", amount), HEAL_COLOR);
+                }
+                EnemyMove::Buff(amount) => {
+                    behavior.strength += amount;
+                    spawn_floating_text(&mut commands, format!("+{} str", amount), BUFF_COLOR);
+                }
+                EnemyMove::Defend(amount) => {
+                    block.0 += amount;
+                    spawn_floating_text(&mut commands, format!("+{} block", amount), BLOCK_COLOR);
+                }
+                EnemyMove::Hex(count) => {
+                    for _ in 0..count {
+                        turn_state.shuffle_in(CardType::Curse, &mut rng);
+                    }
+                    spawn_floating_text(&mut commands, "Hexed".to_string(), CURSE_COLOR);
+                }
+            }
+            behavior.advance();
+        }
+
+        // New monsters line up to the right of the ones already summoned
+        let mut summoned_count = summoned_query.iter().count();
+        for (parent, summoner_position, spec, spec_path) in summons {
+            summoned_count += 1;
+            let position =
+                summoner_position + Vec3::new(spec.size * 0.8 * summoned_count as f32, 0.0, 0.0);
+            let mut monster = Entity::PLACEHOLDER;
+            commands.entity(parent).with_children(|parent| {
+                monster = spawn_monster(
+                    parent,
+                    &spec,
+                    spec_path,
+                    position,
+                    difficulty.enemy_scale(),
+                    run_effects.enemy_health_scale(),
+                    &assets,
+                );
+            });
+            commands.entity(monster).insert(Summoned);
+        }
+
+        // The first attack of the phase falls on an ally, while one is still standing
+        if !monster_attacks.is_empty() {
+            let mut allies = query_set.p2();
+            if let Some((entity, mut health, _)) = allies
+                .iter_mut()
+                .find(|(_, health, _)| health.current > 0.0)
+            {
+                let damage = monster_attacks.remove(0);
+                health.current = (health.current - damage).max(0.0);
+                spawn_damage_text(&mut commands, damage);
+                if health.current <= 0.0 {
+                    spawn_floating_text(&mut commands, "The wisp fades".to_string(), BUFF_COLOR);
+                    commands.entity(entity).despawn_recursive();
+                }
+            }
+        }
+
+        // Then apply damage to the player
+        if let Ok((mut character_health, mut player_block)) = query_set.p0().get_single_mut() {
+            let was_alive = character_health.current > 0.0;
+            for damage in monster_attacks {
+                let damage = player_block.absorb(damage);
+                if damage == 0.0 {
+                    spawn_floating_text(&mut commands, "Blocked".to_string(), BLOCK_COLOR);
+                    continue;
+                }
+                character_health.hurt(damage);
+                println!(
+                    "Player health: {}/{}",
+                    character_health.current, character_health.maximum
+                );
+
+                spawn_damage_text(&mut commands, damage);
+                fight_events.send(FightEvent::DamageTaken(damage));
+
+                // Check for player death
+                if player_falls(&mut commands, &mut character_health, &mut run_effects) {
+                    spawn_death_screen(&mut commands);
+                    break;
+                }
+            }
+
+            // No feather saves the player from a finished ritual
+            if ritual_complete && character_health.current > 0.0 {
+                character_health.current = 0.0;
+                spawn_floating_text(
+                    &mut commands,
+                    "The ritual is complete".to_string(),
+                    RITUAL_COLOR,
+                );
+                spawn_death_screen(&mut commands);
+            }
+
+            // Switch back to player turn, with the player's block wearing off like the monsters'
+            fight_state.current_turn = Turn::Player;
+            fight_state.partner_to_play = fight_state.hot_seat;
+            player_block.0 = (player_block.0 * BLOCK_DECAY).floor();
+            character_health.temporary =
+                (character_health.temporary * TEMPORARY_HEALTH_DECAY).floor();
+            if let Ok(hand) = hand_query.get_single() {
+                let fatigue_damage = start_player_turn(
+                    &mut commands,
+                    hand,
+                    &mut turn_state,
+                    &draw_rules,
+                    &mut rng,
+                    &assets.shared_assets,
+                );
+                if fatigue_damage > 0.0 && character_health.current > 0.0 {
+                    character_health.hurt(fatigue_damage);
+                    spawn_damage_text(&mut commands, fatigue_damage);
+                    fight_events.send(FightEvent::DamageTaken(fatigue_damage));
+                    if player_falls(&mut commands, &mut character_health, &mut run_effects) {
+                        spawn_death_screen(&mut commands);
+                    }
+                }
+            }
+            if was_alive && character_health.current <= 0.0 {
+                fight_events.send(FightEvent::Lost);
+            } else {
+                fight_events.send(FightEvent::TurnStarted);
+            }
+        }
+    }
+}
+
+// Reset the per-turn bookkeeping and draw the new turn's cards. Returns the fatigue damage
+// the player takes for draws that found no cards, even after reshuffling the discards.
+fn start_player_turn(
+    commands: &mut Commands,
+    hand: Entity,
+    turn_state: &mut TurnState,
+    draw_rules: &DrawRules,
+    rng: &mut RunRng,
+    shared_assets: &SharedAssets,
+) -> f32 {
+    let (drawn, fatigue_damage) = turn_state.start_turn(draw_rules.cards_per_turn, rng);
+    for card_type in drawn {
+        spawn_card(commands, hand, card_type, shared_assets);
+    }
+    fatigue_damage
+}
+
+// Fire the effects waiting for the turn boundary just crossed, and drop the ones used up
+fn fire_turn_triggers(
+    mut commands: Commands,
+    mut fight_events: EventReader<FightEvent>,
+    mut turn_state: ResMut<TurnState>,
+    mut player_query: Query<&mut Block, (With<SideCharacter>, Without<Monster>)>,
+    mut monster_query: Query<(Entity, &mut Health, &mut Block, &Vulnerable, &Loot), With<Monster>>,
+    mut rng: ResMut<RunRng>,
+) {
+    for event in fight_events.read() {
+        let when = match event {
+            FightEvent::TurnStarted => TurnTrigger::StartOfTurn,
+            FightEvent::TurnEnded => TurnTrigger::EndOfTurn,
+            _ => continue,
+        };
+        for effect in turn_state.fire(when) {
+            match effect {
+                TriggeredEffect::DamageAllEnemies(damage) => {
+                    for (entity, mut health, mut block, vulnerable, loot) in
+                        monster_query.iter_mut()
+                    {
+                        if health.current <= 0.0 {
+                            continue;
+                        }
+                        let damage = block.absorb(vulnerable.amplify(damage));
+                        health.current = (health.current - damage).max(0.0);
+                        if damage == 0.0 {
+                            spawn_floating_text(&mut commands, "Blocked".to_string(), BLOCK_COLOR);
+                        } else {
+                            spawn_damage_text(&mut commands, damage);
+                        }
+                        if health.current <= 0.0 {
+                            loot.0.roll(&mut rng, &mut turn_state.loot);
+                            commands.entity(entity).despawn_recursive();
+                        }
+                    }
+                }
+                TriggeredEffect::GainBlock(amount) => {
+                    if let Ok(mut block) = player_query.get_single_mut() {
+                        block.0 += amount;
+                        spawn_floating_text(&mut commands, format!("+{amount} block"), BLOCK_COLOR);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Nothing a card left behind follows the player out of the fight
+fn clear_turn_triggers(mut turn_state: ResMut<TurnState>) {
+    turn_state.triggers.clear();
+}
+
+// Count down the turns until the boss enrages, then enrage it
+fn update_enrage(
+    encounter: Res<Encounter>,
+    turn_state: Res<TurnState>,
+    mut boss_query: Query<(&mut Behavior, &mut Sprite, &Children), With<Boss>>,
+    mut status_query: Query<&mut Visibility, With<EnragedStatus>>,
+    mut countdown_query: Query<&mut Text, With<EnrageCountdown>>,
+) {
+    if !turn_state.is_changed() {
+        return;
+    }
+    let Some(enrage_after) = encounter.enrage_after else {
+        return;
+    };
+    let turns_left = enrage_after - turn_state.turn_count;
+
+    if let Ok(mut text) = countdown_query.get_single_mut() {
+        text.sections[0].value = match turns_left {
+            1 => "The boss enrages next turn".to_string(),
+            turns_left if turns_left > 1 => format!("The boss enrages in {} turns", turns_left),
+            _ => "The boss is enraged: double damage!".to_string(),
+        };
+    }
+    if turns_left > 0 {
+        return;
+    }
+    for (mut behavior, mut sprite, children) in boss_query.iter_mut() {
+        if behavior.enraged {
+            continue;
+        }
+        behavior.enraged = true;
+        sprite.color = Color::srgb(1.0, 0.55, 0.5);
+        for child in children.iter() {
+            if let Ok(mut visibility) = status_query.get_mut(*child) {
+                *visibility = Visibility::Inherited;
+            }
+        }
+    }
+}
+
+// Warn the player once there is nothing left to draw, and say what the next draw will cost
+fn update_fatigue_warning(
+    turn_state: Res<TurnState>,
+    mut warning_query: Query<(&mut Text, &mut Visibility), With<FatigueWarning>>,
+) {
+    if !turn_state.is_changed() {
+        return;
+    }
+    let Ok((mut text, mut visibility)) = warning_query.get_single_mut() else {
+        return;
+    };
+    if turn_state.draw_pile.is_empty() && turn_state.discard_pile.is_empty() {
+        text.sections[0].value = format!(
+            "No cards left to draw! Fatigue: {} damage per draw",
+            FATIGUE_DAMAGE_STEP * (turn_state.fatigue + 1) as f32
+        );
+        *visibility = Visibility::Inherited;
+    } else {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+pub fn spawn_damage_text(commands: &mut Commands, damage: f32) {
+    spawn_floating_text(commands, format!("-{}", damage), DAMAGE_COLOR);
+}
+
+// A number that drifts up and disappears, like damage text. Reuses a finished one when there is
+// one in the pool.
+fn spawn_floating_text(commands: &mut Commands, value: String, color: Color) {
+    commands.add(move |world: &mut World| {
+        let text = Text::from_section(
+            value,
+            TextStyle {
+                font_size: 30.0,
+                color,
+                ..default()
+            },
+        );
+        let transform = Transform::from_xyz(0.0, 0.0, 10.0);
+        let damage_text = DamageText {
+            timer: Timer::from_seconds(1.0, TimerMode::Once),
+        };
+
+        let pooled = world.resource_mut::<FloatingTextPool>().0.pop();
+        match pooled.and_then(|entity| world.get_entity_mut(entity)) {
+            Some(mut entity) => {
+                entity.insert((text, transform, damage_text, Visibility::Inherited));
+            }
+            None => {
+                world.spawn((
+                    Text2dBundle {
+                        text,
+                        transform,
+                        ..default()
+                    },
+                    damage_text,
+                ));
+            }
+        }
+    });
+}
+
+// Shows `damage` from a status ticking on whoever stands at `position`, after `delay` seconds
+fn spawn_status_tick(
+    commands: &mut Commands,
+    icon: Handle<Image>,
+    color: Color,
+    damage: f32,
+    position: Vec3,
+    delay: f32,
+) {
+    commands
+        .spawn((
+            SpriteBundle {
+                texture: icon,
+                sprite: Sprite {
+                    custom_size: Some(Vec2::splat(STATUS_TICK_SIZE)),
+                    ..default()
+                },
+                transform: Transform::from_translation(position.truncate().extend(10.0)),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            StatusTick {
+                delay: Timer::from_seconds(delay, TimerMode::Once),
+                timer: Timer::from_seconds(STATUS_TICK_SECONDS, TimerMode::Once),
+            },
+            OnCombatScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(Text2dBundle {
+                text: Text::from_section(
+                    format!("-{damage}"),
+                    TextStyle {
+                        font_size: STATUS_TICK_SIZE,
+                        color,
+                        ..default()
+                    },
+                ),
+                text_anchor: bevy::sprite::Anchor::CenterLeft,
+                transform: Transform::from_xyz(STATUS_TICK_SIZE * 0.75, 0.0, 0.0),
+                ..default()
+            });
+        });
+}
+
+fn animate_status_ticks(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Transform, &mut StatusTick, &mut Visibility)>,
+) {
+    for (entity, mut transform, mut tick, mut visibility) in query.iter_mut() {
+        if !tick.delay.tick(time.delta()).finished() {
+            continue;
+        }
+        *visibility = Visibility::Inherited;
+        transform.translation.y += 60.0 * time.delta_seconds();
+        if tick.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn animate_damage_text(
+    time: Res<Time>,
+    mut pool: ResMut<FloatingTextPool>,
+    mut query: Query<(Entity, &mut Transform, &mut DamageText, &mut Visibility)>,
+) {
+    for (entity, mut transform, mut damage_text, mut visibility) in query.iter_mut() {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+        damage_text.timer.tick(time.delta());
+
+        // Move the text upward
+        transform.translation.y += 100.0 * time.delta_seconds();
+
+        // Put the text back in the pool when the timer is finished
+        if damage_text.timer.finished() {
+            *visibility = Visibility::Hidden;
+            pool.0.push(entity);
+        }
+    }
+}
+
+// Text still drifting when a fight ends goes back in the pool for the next one
+fn release_floating_text(
+    mut pool: ResMut<FloatingTextPool>,
+    mut query: Query<(Entity, &mut Visibility), With<DamageText>>,
+) {
+    for (entity, mut visibility) in query.iter_mut() {
+        if *visibility != Visibility::Hidden {
+            *visibility = Visibility::Hidden;
+            pool.0.push(entity);
+        }
+    }
+}
+
+fn handle_end_turn_button(
+    rules: FightRules,
+    mut commands: Commands,
+    cards_in_hand: Query<(Entity, &CardType), With<Card>>,
+    mut player_query: Query<&mut Health, With<SideCharacter>>,
+    mut add_to_hand: EventWriter<AddToHand>,
+) {
+    let FightRules {
+        actions,
+        play_lock,
+        mut fight_state,
+        mut turn_state,
+        draw_rules,
+        mut run_effects,
+        mut fight_events,
+        ..
+    } = rules;
+    for _ in actions
+        .this_step
+        .iter()
+        .filter(|action| matches!(action, FightAction::EndTurn))
+    {
+        if play_lock.end_turn_locked || end_turn_blocker(&fight_state).is_some() {
+            continue;
+        }
+
+        // Every curse still in hand hurts, past any block
+        let curses = cards_in_hand
+            .iter()
+            .filter(|(_, card_type)| **card_type == CardType::Curse)
+            .count();
+        if let (true, Ok(mut health)) = (curses > 0, player_query.get_single_mut()) {
+            let was_alive = health.current > 0.0;
+            let damage = CURSE_DAMAGE * curses as f32;
+            health.hurt(damage);
+            spawn_damage_text(&mut commands, damage);
+            fight_events.send(FightEvent::DamageTaken(damage));
+            if was_alive && player_falls(&mut commands, &mut health, &mut run_effects) {
+                spawn_death_screen(&mut commands);
+                fight_events.send(FightEvent::Lost);
+            }
+        }
+
+        let mut discarded = Vec::new();
+        if draw_rules.discard_hand_at_end_of_turn {
+            for (entity, card_type) in cards_in_hand.iter() {
+                discarded.push(*card_type);
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+
+        // Add air cards before changing turn
+        let air_cards = turn_state.end_turn(discarded);
+        if air_cards > 0 {
+            add_to_hand.send(AddToHand {
+                card_type: CardType::Air,
+                amount: air_cards,
+            });
+        }
+
+        fight_state.current_turn = Turn::Enemy;
+        fight_events.send(FightEvent::TurnEnded);
+    }
+}
+
+// Why End Turn can't be pressed right now, if it can't
+fn end_turn_blocker(fight_state: &FightState) -> Option<&'static str> {
+    match fight_state.current_turn {
+        Turn::Mulligan => Some("Keep or redraw your opening hand first"),
+        Turn::Enemy => Some("Wait for the enemies to finish their turn"),
+        Turn::Player => None,
+    }
+}
+
+// Update the button's appearance based on turn state. While it can't be pressed it's greyed
+// out, and hovering it explains why.
+fn update_end_turn_button(
+    fight_state: Res<FightState>,
+    mut button_query: Query<(&Interaction, &mut BackgroundColor), With<EndTurnButton>>,
+    mut text_query: Query<&mut Text, (With<ButtonText>, Without<EndTurnTooltip>)>,
+    mut tooltip_query: Query<(&mut Style, &mut Text), With<EndTurnTooltip>>,
+    theme: Res<Theme>,
+) {
+    let blocked = end_turn_blocker(&fight_state);
+    let mut hovered = false;
+    if let Ok((interaction, mut color)) = button_query.get_single_mut() {
+        hovered = *interaction != Interaction::None;
+        *color = match blocked {
+            Some(_) => DISABLED_BUTTON.into(),
+            None => ui::button_color(&theme, *interaction),
+        };
+    }
+
+    if let Ok(mut text) = text_query.get_single_mut() {
+        text.sections[0].value = match fight_state.current_turn {
+            Turn::Mulligan => "Mulligan",
+            Turn::Player => "End Turn",
+            Turn::Enemy => "Enemy Turn",
+        }
+        .to_string();
+        text.sections[0].style.color = match blocked {
+            Some(_) => DISABLED_TEXT,
+            None => Color::WHITE,
+        };
+    }
+
+    let Ok((mut style, mut text)) = tooltip_query.get_single_mut() else {
+        return;
+    };
+    let (display, reason) = match blocked {
+        Some(reason) if hovered => (Display::Flex, reason),
+        _ => (Display::None, ""),
+    };
+    // Only touch the node when the tooltip changes, as with the card tooltip
+    if style.display != display {
+        style.display = display;
+    }
+    if text.sections[0].value != reason {
+        text.sections[0].value = reason.to_string();
+    }
+}
+
+// In a hot-seat fight, the first player ending their turn hands over to the other player rather
+// than the enemies. Runs after the turn's end triggers, so those fire for the player they belong to.
+fn hand_off_turn(
+    mut commands: Commands,
+    rules: FightRules,
+    shared_assets: Res<SharedAssets>,
+    hand_query: Query<Entity, With<HandContainer>>,
+    cards_in_hand: HandCards,
+    active_query: Query<Entity, With<SideCharacter>>,
+    mut bench_query: Query<(Entity, &mut BenchedPlayer, &mut Health)>,
+) {
+    let FightRules {
+        mut fight_state,
+        mut turn_state,
+        draw_rules,
+        mut run_effects,
+        mut rng,
+        mut fight_events,
+        ..
+    } = rules;
+    if fight_state.current_turn != Turn::Enemy || !fight_state.partner_to_play {
+        return;
+    }
+    let (Ok(hand), Ok(active), Ok((partner, mut bench, mut partner_health))) = (
+        hand_query.get_single(),
+        active_query.get_single(),
+        bench_query.get_single_mut(),
+    ) else {
+        return;
+    };
+
+    // Put away the hand with the player's piles, and bring out the partner's
+    let mut hand_cards = Vec::new();
+    for (entity, card_type) in cards_in_hand.iter() {
+        hand_cards.push(*card_type);
+        commands.entity(entity).despawn_recursive();
+    }
+    for card_type in &bench.hand {
+        spawn_card(&mut commands, hand, *card_type, &shared_assets);
+    }
+    let mut partner_turn = std::mem::take(&mut bench.turn_state);
+    // What the fight drops is shared
+    partner_turn.loot = std::mem::take(&mut turn_state.loot);
+    let benched = BenchedPlayer {
+        hand: hand_cards,
+        turn_state: std::mem::replace(&mut *turn_state, partner_turn),
+    };
+    commands
+        .entity(active)
+        .remove::<SideCharacter>()
+        .insert(benched);
+    commands
+        .entity(partner)
+        .remove::<BenchedPlayer>()
+        .insert(SideCharacter);
+
+    fight_state.seat = 1 - fight_state.seat;
+    fight_state.partner_to_play = false;
+    fight_state.current_turn = Turn::Player;
+    fight_state.selected_card = None;
+    spawn_floating_text(
+        &mut commands,
+        format!("Player {}'s turn", fight_state.seat + 1),
+        SEAT_COLORS[fight_state.seat],
+    );
+
+    let fatigue_damage = start_player_turn(
+        &mut commands,
+        hand,
+        &mut turn_state,
+        &draw_rules,
+        &mut rng,
+        &shared_assets,
+    );
+    if fatigue_damage > 0.0 {
+        partner_health.hurt(fatigue_damage);
+        spawn_damage_text(&mut commands, fatigue_damage);
+        fight_events.send(FightEvent::DamageTaken(fatigue_damage));
+        if player_falls(&mut commands, &mut partner_health, &mut run_effects) {
+            spawn_death_screen(&mut commands);
+            fight_events.send(FightEvent::Lost);
+            return;
+        }
+    }
+    fight_events.send(FightEvent::TurnStarted);
+}
+
+// Name the player whose turn it is, and dim the one waiting
+fn show_active_player(
+    fight_state: Res<FightState>,
+    mut banner_query: Query<&mut Text, With<ActivePlayerBanner>>,
+    mut seat_query: Query<(&mut Sprite, &Seat)>,
+) {
+    if !fight_state.hot_seat || !fight_state.is_changed() {
+        return;
+    }
+    for mut text in banner_query.iter_mut() {
+        let section = &mut text.sections[0];
+        if fight_state.current_turn == Turn::Enemy {
+            section.value = "Enemy turn".to_string();
+            section.style.color = Color::WHITE;
+        } else {
+            section.value = format!("Player {}", fight_state.seat + 1);
+            section.style.color = SEAT_COLORS[fight_state.seat];
+        }
+    }
+    for (mut sprite, seat) in seat_query.iter_mut() {
+        sprite.color = if seat.0 == fight_state.seat {
+            Color::WHITE
+        } else {
+            BENCHED_TINT
+        };
+    }
+}
+
+// Anyone a hit can land on, and their reaction if one is already playing
+type HitTargets<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Transform,
+        &'static Sprite,
+        Option<&'static mut HitReaction>,
+    ),
+    Or<(With<Monster>, With<SideCharacter>)>,
+>;
+
+// Sets off a hit reaction on whoever a hit got through to. A second hit while the first is
+// still playing starts it over, from where the first one started.
+fn start_hit_reactions(
+    mut commands: Commands,
+    mut fight_events: EventReader<FightEvent>,
+    mut target_query: HitTargets,
+    player_query: Query<Entity, With<SideCharacter>>,
+) {
+    for event in fight_events.read() {
+        let (target, direction) = match *event {
+            FightEvent::DamageDealt(monster, _) => (monster, 1.0),
+            FightEvent::DamageTaken(_) => match player_query.get_single() {
+                Ok(player) => (player, -1.0),
+                Err(_) => continue,
+            },
+            _ => continue,
+        };
+        // A monster the hit killed is already gone
+        let Ok((transform, sprite, reaction)) = target_query.get_mut(target) else {
+            continue;
+        };
+        match reaction {
+            Some(mut reaction) => reaction.timer.reset(),
+            None => {
+                commands.entity(target).insert(HitReaction {
+                    timer: Timer::from_seconds(HIT_REACTION_SECONDS, TimerMode::Once),
+                    origin: transform.translation,
+                    color: sprite.color,
+                    direction,
+                });
+            }
+        }
+    }
+}
+
+fn animate_hit_reactions(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut reaction_query: Query<(Entity, &mut HitReaction, &mut Transform, &mut Sprite)>,
+) {
+    for (entity, mut reaction, mut transform, mut sprite) in reaction_query.iter_mut() {
+        reaction.timer.tick(time.delta());
+        if reaction.timer.finished() {
+            transform.translation = reaction.origin;
+            sprite.color = reaction.color;
+            commands.entity(entity).remove::<HitReaction>();
+            continue;
+        }
+        let knockback = (reaction.timer.fraction() * PI).sin() * HIT_KNOCKBACK;
+        transform.translation = reaction.origin + Vec3::X * reaction.direction * knockback;
+        sprite.color = if reaction.timer.elapsed_secs() < HIT_FLASH_SECONDS {
+            HIT_FLASH_COLOR
+        } else {
+            reaction.color
+        };
+    }
+}
+
+// Slide a banner in naming whose turn it is whenever the turn passes, to the enemies or to a
+// player. The mulligan isn't announced, as its panel already says what to do.
+fn announce_turn(
+    mut commands: Commands,
+    fight_state: Res<FightState>,
+    mut announced: ResMut<AnnouncedTurn>,
+    banner_query: Query<Entity, With<TurnBanner>>,
+    game_window: GameWindow,
+) {
+    let turn = (fight_state.current_turn, fight_state.seat);
+    if !fight_state.is_changed() || announced.0 == Some(turn) {
+        return;
+    }
+    announced.0 = Some(turn);
+    let (label, color) = match fight_state.current_turn {
+        Turn::Mulligan => return,
+        Turn::Player if fight_state.hot_seat => (
+            format!("PLAYER {}'S TURN", fight_state.seat + 1),
+            SEAT_COLORS[fight_state.seat],
+        ),
+        Turn::Player => ("YOUR TURN".to_string(), SEAT_COLORS[0]),
+        Turn::Enemy => ("ENEMY TURN".to_string(), ENEMY_TURN_COLOR),
+    };
+    let Some(window) = game_window.get() else {
+        return;
+    };
+
+    // A banner still on its way out makes way for the new one
+    for banner in banner_query.iter() {
+        commands.entity(banner).despawn_recursive();
+    }
+    let top = (window.height() - TURN_BANNER_HEIGHT) / 2.0;
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(-window.width()),
+                    top: Val::Px(top),
+                    width: Val::Vw(100.0),
+                    height: Val::Px(TURN_BANNER_HEIGHT),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.7).into(),
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            TurnBanner { leaving: false },
+            Tween::new(
+                TweenTarget::Offset {
+                    from: Vec2::new(-window.width(), top),
+                    to: Vec2::new(0.0, top),
+                },
+                TURN_BANNER_SLIDE_SECONDS,
+                Ease::Out,
+            ),
+            OnCombatScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                label,
+                TextStyle {
+                    font_size: 64.0,
+                    color,
+                    ..default()
+                },
+            ));
+        });
+}
+
+// Once the turn banner has slid in, hold it a moment and send it off the other side
+fn slide_out_turn_banner(
+    mut commands: Commands,
+    mut finished: EventReader<TweenFinished>,
+    mut banner_query: Query<(&mut TurnBanner, &Style)>,
+    game_window: GameWindow,
+) {
+    for TweenFinished(entity) in finished.read() {
+        let Ok((mut banner, style)) = banner_query.get_mut(*entity) else {
+            continue;
+        };
+        if banner.leaving {
+            continue;
+        }
+        banner.leaving = true;
+        let top = match style.top {
+            Val::Px(top) => top,
+            _ => 0.0,
+        };
+        let width = game_window.get().map_or(0.0, Window::width);
+        commands.entity(*entity).insert(
+            Tween::new(
+                TweenTarget::Offset {
+                    from: Vec2::new(0.0, top),
+                    to: Vec2::new(width, top),
+                },
+                TURN_BANNER_SLIDE_SECONDS,
+                Ease::In,
+            )
+            .with_delay(TURN_BANNER_HOLD_SECONDS)
+            .despawn_when_done(),
+        );
+    }
+}
+
+// The cheats' way into a fight, which they can't reach from outside this module. Adds a card to
+// the hand, if there is one.
+#[cfg(feature = "dev-tools")]
+pub fn cheat_card(world: &mut World, card_type: CardType) -> bool {
+    let mut hand_query = world.query_filtered::<(), With<HandContainer>>();
+    if hand_query.iter(world).next().is_none() {
+        return false;
+    }
+    world.send_event(AddToHand {
+        card_type,
+        amount: 1,
+    });
+    true
+}
+
+// Drops every living monster to 0 health, for the victory check to find. Returns how many.
+#[cfg(feature = "dev-tools")]
+pub fn cheat_kill_monsters(world: &mut World) -> usize {
+    let mut monster_query = world.query_filtered::<&mut Health, With<Monster>>();
+    let mut killed = 0;
+    for mut health in monster_query.iter_mut(world) {
+        if health.current > 0.0 {
+            health.current = 0.0;
+            killed += 1;
+        }
+    }
+    killed
+}
+
+// Keeps the player at full health with a wall of temporary health in front of it, so whatever a
+// whole enemy turn deals is soaked up before the death check sees it
+#[cfg(feature = "dev-tools")]
+fn hold_god_mode(
+    god_mode: Res<GodMode>,
+    mut player_query: Query<&mut Health, With<SideCharacter>>,
+) {
+    for mut health in player_query.iter_mut() {
+        if god_mode.0 {
+            let shield = health.maximum * GOD_MODE_SHIELD;
+            if health.current < health.maximum || health.temporary < shield {
+                health.current = health.maximum;
+                health.temporary = shield;
+            }
+        } else if god_mode.is_changed() {
+            health.temporary = 0.0;
+        }
+    }
+}
+
+// Events are left unread until there's a hand, so cards sent as the fight is set up still arrive
+fn add_to_hand(
+    mut commands: Commands,
+    mut events: EventReader<AddToHand>,
+    hand_query: Query<Entity, With<HandContainer>>,
+    shared_assets: Res<SharedAssets>,
+) {
+    let Ok(hand) = hand_query.get_single() else {
+        return;
+    };
+    for event in events.read() {
+        for _ in 0..event.amount {
+            spawn_card(&mut commands, hand, event.card_type, &shared_assets);
+        }
+    }
+}
+
+fn card_bundle(card_type: CardType, shared_assets: &SharedAssets, x_position: f32) -> impl Bundle {
+    let (image, atlas) = shared_assets.card(card_type);
+    (
+        ImageBundle {
+            style: Style {
+                width: Val::Px(180.0),
+                height: Val::Px(250.0),
+                margin: UiRect::horizontal(Val::Px(10.0)),
+                ..default()
+            },
+            image,
+            background_color: Color::WHITE.into(),
+            transform: Transform::from_xyz(x_position, 0.0, 0.0),
+            ..default()
+        },
+        atlas,
+        Interaction::None,
+        Card,
+        card_type,
+    )
+}
+
+// Add a card to the end of the hand
+fn spawn_card(
+    commands: &mut Commands,
+    hand: Entity,
+    card_type: CardType,
+    shared_assets: &SharedAssets,
+) {
+    commands.entity(hand).with_children(|parent| {
+        parent.spawn(card_bundle(card_type, shared_assets, 0.0));
+    });
+}
+
+// Spawns a monster with its damage label and health bar
+fn spawn_monster(
+    parent: &mut ChildBuilder,
+    spec: &MonsterSpec,
+    spec_path: Vec<usize>,
+    position: Vec3,
+    scale: f32,
+    health_scale: f32,
+    assets: &FightAssets,
+) -> Entity {
+    let FightAssets {
+        asset_server,
+        theme,
+        ..
+    } = assets;
+    let behavior = Behavior::from_spec(spec, scale);
+    let intent = behavior.intent().clone();
+    let intent_label = intent.label(&behavior);
+    parent
+        .spawn((
+            SpriteBundle {
+                texture: asset_server.load(spec.texture),
+                transform: Transform::from_translation(position),
+                sprite: Sprite {
+                    custom_size: Some(Vec2::splat(spec.size)),
+                    anchor: bevy::sprite::Anchor::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            Monster,
+            Health {
+                current: spec.health * scale * health_scale,
+                maximum: spec.health * scale * health_scale,
+                temporary: 0.0,
+            },
+            behavior,
+            Block::default(),
+            Vulnerable::default(),
+            SpecPath(spec_path),
+            Loot(spec.loot.clone()),
+        ))
+        .with_children(|monster| {
+            // Spawn the black background sprite
+            monster.spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: Color::BLACK,
+                    custom_size: Some(Vec2::new(100.0, 30.0)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, spec.label_height, 0.0),
+                ..default()
+            });
+            monster.spawn((
+                SpriteBundle {
+                    texture: asset_server.load(intent.icon()),
+                    sprite: Sprite {
+                        custom_size: Some(Vec2::splat(30.0)),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(-70.0, spec.label_height, 0.1),
+                    ..default()
+                },
+                IntentIcon,
+            ));
+            // Spawn the intent text above monster
+            monster.spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        intent_label,
+                        TextStyle {
+                            font_size: 24.0,
+                            color: intent.color(),
+                            ..default()
+                        },
+                    ),
+                    transform: Transform::from_xyz(0.0, spec.label_height, 0.1),
+                    ..default()
+                },
+                IntentLabel,
+            ));
+            spawn_health_bar(monster, spec.health_bar_depth, theme);
+
+            // Shield badge at the left end of the health bar, shown while there is block
+            monster
+                .spawn((
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::srgb(0.25, 0.35, 0.55),
+                            custom_size: Some(Vec2::new(36.0, 24.0)),
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(
+                            -HEALTH_BAR_WIDTH / 2.0 - 24.0,
+                            spec.health_bar_depth,
+                            0.3,
+                        ),
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    },
+                    BlockBadge,
+                ))
+                .with_children(|badge| {
+                    badge.spawn((
+                        Text2dBundle {
+                            text: Text::from_section(
+                                "0",
+                                TextStyle {
+                                    font_size: 18.0,
+                                    color: Color::WHITE,
+                                    ..default()
+                                },
+                            ),
+                            transform: Transform::from_xyz(0.0, 0.0, 0.1),
+                            ..default()
+                        },
+                        BlockBadgeText,
+                    ));
+                });
+            // Vulnerable's icon and turns left, at the other end of the health bar
+            monster
+                .spawn((
+                    SpriteBundle {
+                        texture: asset_server.load("textures/Game Icons/frost.png"),
+                        sprite: Sprite {
+                            custom_size: Some(Vec2::splat(28.0)),
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(
+                            HEALTH_BAR_WIDTH / 2.0 + 22.0,
+                            spec.health_bar_depth,
+                            0.3,
+                        ),
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    },
+                    VulnerableBadge,
+                ))
+                .with_children(|badge| {
+                    badge.spawn((
+                        Text2dBundle {
+                            text: Text::from_section(
+                                "0",
+                                TextStyle {
+                                    font_size: 18.0,
+                                    color: VULNERABLE_COLOR,
+                                    ..default()
+                                },
+                            ),
+                            transform: Transform::from_xyz(14.0, -12.0, 0.1),
+                            ..default()
+                        },
+                        VulnerableBadgeText,
+                    ));
+                });
+        })
+        .id()
+}
+
+fn update_block_badges(
+    monster_query: Query<(&Block, &Children), Changed<Block>>,
+    mut badge_query: Query<(&mut Visibility, &Children), With<BlockBadge>>,
+    mut text_query: Query<&mut Text, With<BlockBadgeText>>,
+) {
+    for (block, children) in monster_query.iter() {
+        for child in children.iter() {
+            let Ok((mut visibility, badge_children)) = badge_query.get_mut(*child) else {
+                continue;
+            };
+            *visibility = if block.0 > 0.0 {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+            for text_entity in badge_children.iter() {
+                if let Ok(mut text) = text_query.get_mut(*text_entity) {
+                    text.sections[0].value = format!("{}", block.0);
+                }
+            }
+        }
+    }
+}
+
+fn update_vulnerable_badges(
+    monster_query: Query<(&Vulnerable, &Children), Changed<Vulnerable>>,
+    mut badge_query: Query<(&mut Visibility, &Children), With<VulnerableBadge>>,
+    mut text_query: Query<&mut Text, With<VulnerableBadgeText>>,
+) {
+    for (vulnerable, children) in monster_query.iter() {
+        for child in children.iter() {
+            let Ok((mut visibility, badge_children)) = badge_query.get_mut(*child) else {
+                continue;
+            };
+            *visibility = if vulnerable.turns > 0 {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+            for text_entity in badge_children.iter() {
+                if let Ok(mut text) = text_query.get_mut(*text_entity) {
+                    text.sections[0].value = vulnerable.turns.to_string();
+                }
+            }
+        }
+    }
+}
+
+// Monsters settle on their next move once the player's new hand is dealt, so they can react to
+// it and to how the player is holding up
+fn plan_enemy_moves(
+    mut fight_events: EventReader<FightEvent>,
+    card_query: Query<(), With<Card>>,
+    player_query: Query<&Health, With<SideCharacter>>,
+    mut monster_query: Query<(&mut Behavior, &Health), With<Monster>>,
+) {
+    if !fight_events
+        .read()
+        .any(|event| matches!(event, FightEvent::TurnStarted))
+    {
+        return;
+    }
+    let Ok(player_health) = player_query.get_single() else {
+        return;
+    };
+    let board = BoardState {
+        hand_size: card_query.iter().count(),
+        player_health_share: player_health.current / player_health.maximum,
+    };
+    for (mut behavior, health) in monster_query.iter_mut() {
+        let best = behavior.best_move(&board, health.current / health.maximum);
+        if best != behavior.next {
+            behavior.next = best;
+        }
+    }
+}
+
+// Show each monster's next move once its last one is done
+fn update_intent_labels(
+    monster_query: Query<(&Behavior, &Children), Changed<Behavior>>,
+    mut label_query: Query<&mut Text, With<IntentLabel>>,
+    mut icon_query: Query<&mut Handle<Image>, With<IntentIcon>>,
+    asset_server: Res<AssetServer>,
+) {
+    for (behavior, children) in monster_query.iter() {
+        let intent = behavior.intent();
+        for child in children.iter() {
+            if let Ok(mut text) = label_query.get_mut(*child) {
+                text.sections[0].value = intent.label(behavior);
+                text.sections[0].style.color = intent.color();
+            }
+            if let Ok(mut icon) = icon_query.get_mut(*child) {
+                *icon = asset_server.load(intent.icon());
+            }
+        }
+    }
+}
+
+fn spawn_health_bar(parent: &mut ChildBuilder, depth: f32, theme: &Theme) {
+    let size = Vec2::new(HEALTH_BAR_WIDTH, 10.0);
+    // The ghost goes between the track and the bar, so only the health just lost shows
+    let mut ghost = ui::bar_segment(DAMAGE_GHOST_COLOR, size, 0.0);
+    ghost.transform.translation.z = 0.15;
+    ui::health_bar(parent, theme, size, depth, HealthBar)
+        .insert(HealthBarContainer::default())
+        .with_children(|container| {
+            container.spawn((
+                ui::bar_segment(TEMPORARY_HEALTH_COLOR, size, 0.0),
+                TemporaryHealthBar,
+            ));
+            container.spawn((ghost, DamageGhost));
+            container.spawn((
+                Text2dBundle {
+                    transform: Transform::from_xyz(0.0, -HEALTH_READOUT_DEPTH, 0.3),
+                    ..default()
+                },
+                HealthReadout,
+            ));
+        });
+}
+
+// A wisp beside the player, `health` left of its `ALLY_HEALTH`
+fn spawn_ally(
+    parent: &mut ChildBuilder,
+    position: Vec3,
+    health: f32,
+    next: usize,
+    asset_server: &AssetServer,
+    theme: &Theme,
+) {
+    parent
+        .spawn((
+            SpriteBundle {
+                texture: asset_server.load("textures/character.png"),
+                transform: Transform::from_translation(position),
+                sprite: Sprite {
+                    custom_size: Some(Vec2::new(200.0, 200.0)),
+                    anchor: bevy::sprite::Anchor::Center,
+                    color: WISP_TINT,
+                    ..default()
+                },
+                ..default()
+            },
+            Ally { next },
+            Health {
+                current: health,
+                maximum: ALLY_HEALTH,
+                temporary: 0.0,
+            },
+        ))
+        .with_children(|ally| {
+            spawn_health_bar(ally, -90.0, theme);
+        });
+}
+
+// The combat resources are shared by every fight, so each one starts by clearing what the last
+// left behind, even if it was quit part-way through. Written in place rather than through
+// commands, so the rest of the setup already sees them cleared.
+fn reset_fight(
+    mut fight_state: ResMut<FightState>,
+    mut turn_state: ResMut<TurnState>,
+    mut draw_rules: ResMut<DrawRules>,
+    mut announced: ResMut<AnnouncedTurn>,
+    mut actions: ResMut<FightActions>,
+) {
+    *fight_state = FightState::default();
+    *turn_state = TurnState::default();
+    *draw_rules = DrawRules::default();
+    *announced = AnnouncedTurn::default();
+    *actions = FightActions::default();
+}
+
+pub fn combat_setup(
+    mut commands: Commands,
+    assets: FightAssets,
+    setup: FightSetup,
+    mut rng: ResMut<RunRng>,
+    mut save_data: ResMut<SaveData>,
+    display_quality: Res<DisplayQuality>,
+    game_window: GameWindow,
+) {
+    let FightSetup {
+        state,
+        encounter,
+        deck,
+        play_lock,
+        difficulty,
+        run_effects,
+        player_state,
+        hot_seat,
+    } = setup;
+    let max_health = PLAYER_BASE_HEALTH + run_effects.bonus_health(chapter_number(*state.get()));
+    // Pick up a fight that was quit part-way through, or deal a fresh one
+    let resuming = save_data
+        .fight
+        .as_ref()
+        .is_some_and(|snapshot| snapshot.state == *state.get());
+    let snapshot = if resuming {
+        save_data.fight.take()
+    } else {
+        None
+    };
+    let (opening_hand, mulligan, player_health) = match &snapshot {
+        Some(snapshot) => {
+            commands.insert_resource(snapshot.turn.clone());
+            (
+                snapshot.hand.clone(),
+                snapshot.mulligan,
+                snapshot.player_health,
+            )
+        }
+        None => {
+            let mut draw_pile = deck.cards().to_vec();
+            rng.shuffle(&mut draw_pile);
+            let mut opening_hand =
+                draw_pile.split_off(draw_pile.len().saturating_sub(OPENING_HAND_SIZE));
+            // Swap in the card a guided fight asks for, if the shuffle left it out
+            if let Some(only_card) = play_lock.only_card {
+                let in_pile = draw_pile
+                    .iter()
+                    .position(|card_type| *card_type == only_card);
+                if let (Some(index), false) = (in_pile, opening_hand.contains(&only_card)) {
+                    if let Some(first) = opening_hand.first_mut() {
+                        std::mem::swap(first, &mut draw_pile[index]);
+                    }
+                }
+            }
+            commands.insert_resource(TurnState::new(draw_pile).with_upgrades(deck.upgraded()));
+            let player_health = player_state
+                .health
+                .map_or(max_health, |health| health.min(max_health));
+            (opening_hand, true, player_health)
+        }
+    };
+    commands.insert_resource(FightState {
+        current_turn: if mulligan {
+            Turn::Mulligan
+        } else {
+            Turn::Player
+        },
+        selected_card: None,
+        target: None,
+        hot_seat: hot_seat.is_some(),
+        seat: 0,
+        partner_to_play: hot_seat.is_some(),
+    });
+    // The second player draws from their own shuffle of the deck
+    let partner_pile = hot_seat.is_some().then(|| {
+        let mut pile = deck.cards().to_vec();
+        rng.shuffle(&mut pile);
+        pile
+    });
+    let Some(window) = game_window.get() else {
+        return;
+    };
+    let mut boss = None;
+    let mut restored_monsters = Vec::new();
+
+    // Calculate positions
+    let char_x = window.width() * -0.25;
+    let char_y = window.height() * -0.75;
+
+    // Spawn main container
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::SpaceBetween,
+                    ..default()
+                },
+                ..default()
+            },
+            OnCombatScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Vw(100.0),
+                        height: Val::Vh(100.0),
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(SpriteBundle {
+                        texture: assets
+                            .asset_server
+                            .load(texture_path(encounter.background, *display_quality)),
+                        transform: Transform::from_xyz(
+                            -window.width() / 2.0,
+                            -window.height() / 2.0,
+                            1.0,
+                        ),
+                        sprite: Sprite {
+                            custom_size: Some(Vec2::new(1920.0, 1080.0)),
+                            anchor: bevy::sprite::Anchor::Center,
+                            ..default()
+                        },
+                        ..default()
+                    });
+                });
+
+            // Side character with health
+            parent
+                .spawn((
+                    SpriteBundle {
+                        texture: assets.asset_server.load("textures/character.png"),
+                        transform: Transform::from_xyz(-window.width() / 2.0 + char_x, char_y, 2.0),
+                        sprite: Sprite {
+                            custom_size: Some(Vec2::new(400.0, 400.0)),
+                            anchor: bevy::sprite::Anchor::Center,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    SideCharacter,
+                    Seat(0),
+                    Health {
+                        current: player_health,
+                        maximum: max_health,
+                        temporary: snapshot
+                            .as_ref()
+                            .map_or(0.0, |snapshot| snapshot.player_temporary_health),
+                    },
+                    // A resumed fight already had its starting block
+                    Block(if resuming {
+                        0.0
+                    } else {
+                        run_effects.starting_block()
+                    }),
+                ))
+                .with_children(|character| {
+                    spawn_health_bar(character, -175.0, &assets.theme);
+                });
+
+            if let Some(draw_pile) = partner_pile {
+                let mut partner = parent.spawn((
+                    SpriteBundle {
+                        texture: assets.asset_server.load("textures/character.png"),
+                        // A step behind the first player
+                        transform: Transform::from_xyz(
+                            -window.width() / 2.0 + char_x - 220.0,
+                            char_y + 40.0,
+                            1.9,
+                        ),
+                        sprite: Sprite {
+                            custom_size: Some(Vec2::new(340.0, 340.0)),
+                            anchor: bevy::sprite::Anchor::Center,
+                            color: BENCHED_TINT,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    Seat(1),
+                    BenchedPlayer {
+                        hand: Vec::new(),
+                        turn_state: TurnState::new(draw_pile).with_upgrades(deck.upgraded()),
+                    },
+                    Health {
+                        current: max_health,
+                        maximum: max_health,
+                        temporary: 0.0,
+                    },
+                    Block(run_effects.starting_block()),
+                ));
+                partner.with_children(|character| {
+                    spawn_health_bar(character, -150.0, &assets.theme);
+                });
+                parent.spawn((
+                    TextBundle::from_section(
+                        "Player 1",
+                        TextStyle {
+                            font_size: 40.0,
+                            color: SEAT_COLORS[0],
+                            ..default()
+                        },
+                    )
+                    .with_style(Style {
+                        position_type: PositionType::Absolute,
+                        top: Val::Px(20.0),
+                        align_self: AlignSelf::Center,
+                        ..default()
+                    }),
+                    ActivePlayerBanner,
+                ));
+            }
+
+            if let Some(snapshot) = &snapshot {
+                for saved in &snapshot.monsters {
+                    let Some(spec) = find_spec(&encounter.monsters, &saved.spec_path) else {
+                        continue;
+                    };
+                    let position = Vec3::from_array(saved.position);
+                    let monster = spawn_monster(
+                        parent,
+                        spec,
+                        saved.spec_path.clone(),
+                        position,
+                        difficulty.enemy_scale(),
+                        run_effects.enemy_health_scale(),
+                        &assets,
+                    );
+                    if saved.spec_path == [0] {
+                        boss = Some((monster, spec.label_height));
+                    }
+                    restored_monsters.push((monster, spec, saved));
+                }
+                for saved in &snapshot.allies {
+                    spawn_ally(
+                        parent,
+                        Vec3::from_array(saved.position),
+                        saved.health,
+                        saved.next_action,
+                        &assets.asset_server,
+                        &assets.theme,
+                    );
+                }
+            } else {
+                for (i, spec) in encounter.monsters.iter().enumerate() {
+                    let position = Vec3::new(
+                        char_x + window.width() * spec.offset.x,
+                        char_y + window.height() * spec.offset.y + spec.lift,
+                        2.0,
+                    );
+                    let monster = spawn_monster(
+                        parent,
+                        spec,
+                        vec![i],
+                        position,
+                        difficulty.enemy_scale(),
+                        run_effects.enemy_health_scale(),
+                        &assets,
+                    );
+                    if boss.is_none() {
+                        boss = Some((monster, spec.label_height));
+                    }
+                }
+            }
+
+            // End turn button
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            right: Val::Px(20.0),
+                            top: Val::Px(20.0),
+                            width: Val::Px(150.0),
+                            height: Val::Px(50.0),
+                            align_items: AlignItems::Center,
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        background_color: assets.theme.button.into(),
+                        ..default()
+                    },
+                    SafeAnchor::top_right(20.0, 20.0),
+                ))
+                .with_children(|parent| {
+                    parent
+                        .spawn((
+                            ui::button(
+                                &assets.theme,
+                                Style {
+                                    width: Val::Percent(100.0),
+                                    height: Val::Percent(100.0),
+                                    align_items: AlignItems::Center,
+                                    justify_content: JustifyContent::Center,
+                                    ..default()
+                                },
+                            ),
+                            EndTurnButton,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn((
+                                TextBundle::from_section(
+                                    "End Turn",
+                                    TextStyle {
+                                        font_size: 20.0,
+                                        color: Color::WHITE,
+                                        ..default()
+                                    },
+                                ),
+                                ButtonText,
+                            ));
+                        });
+                    parent.spawn((
+                        TextBundle::from_section("", assets.theme.text_style(18.0))
+                            .with_style(Style {
+                                display: Display::None,
+                                position_type: PositionType::Absolute,
+                                right: Val::Px(160.0),
+                                top: Val::Px(0.0),
+                                width: Val::Px(240.0),
+                                padding: UiRect::all(Val::Px(8.0)),
+                                ..default()
+                            })
+                            .with_background_color(Color::srgba(0.0, 0.0, 0.0, 0.75)),
+                        EndTurnTooltip,
+                    ));
+                });
+
+            // Hint button, for players who want to be shown a good card
+            parent
+                .spawn((
+                    ui::button(
+                        &assets.theme,
+                        Style {
+                            position_type: PositionType::Absolute,
+                            right: Val::Px(20.0),
+                            top: Val::Px(80.0),
+                            width: Val::Px(150.0),
+                            height: Val::Px(40.0),
+                            align_items: AlignItems::Center,
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                    ),
+                    HintButton,
+                    SafeAnchor::top_right(80.0, 20.0),
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Hint",
+                        TextStyle {
+                            font_size: 20.0,
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                    ));
+                });
+
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 24.0,
+                        color: ENERGY_COLOR,
+                        ..default()
+                    },
+                )
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(20.0),
+                    top: Val::Px(130.0),
+                    ..default()
+                }),
+                EnergyText,
+                SafeAnchor::top_right(130.0, 20.0),
+            ));
+
+            // Cards container
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Percent(100.0),
+                            height: Val::Px(200.0),
+                            position_type: PositionType::Absolute,
+                            top: Val::Px(20.0),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(20.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    HandContainer,
+                ))
+                .with_children(|parent| {
+                    for (i, card_type) in opening_hand.iter().enumerate() {
+                        let x_position = (i as f32 - 1.0) * 220.0;
+                        parent.spawn(card_bundle(*card_type, &assets.shared_assets, x_position));
+                    }
+                });
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        position_type: PositionType::Absolute,
+                        top: Val::Px(290.0),
+                        justify_content: JustifyContent::Center,
+                        column_gap: Val::Px(12.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                PowerRow,
+            ));
+            parent.spawn((
+                TextBundle::from_section("", assets.theme.text_style(20.0))
+                    .with_style(Style {
+                        display: Display::None,
+                        position_type: PositionType::Absolute,
+                        top: Val::Px(380.0),
+                        align_self: AlignSelf::Center,
+                        max_width: Val::Px(640.0),
+                        padding: UiRect::all(Val::Px(10.0)),
+                        ..default()
+                    })
+                    .with_background_color(Color::srgba(0.0, 0.0, 0.0, 0.75)),
+                CardTooltip,
+            ));
+
+            if mulligan {
+                spawn_mulligan_panel(parent, &assets.theme);
+            }
+
+            parent.spawn((
+                TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font_size: 24.0,
+                            color: Color::srgb(1.0, 0.4, 0.3),
+                            ..default()
+                        },
+                    ),
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        right: Val::Px(20.0),
+                        top: Val::Px(80.0),
+                        ..default()
+                    },
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                FatigueWarning,
+            ));
+
+            if encounter.enrage_after.is_some() {
+                parent.spawn((
+                    TextBundle {
+                        text: Text::from_section(
+                            "",
+                            TextStyle {
+                                font_size: 24.0,
+                                color: ENRAGE_COLOR,
+                                ..default()
+                            },
+                        ),
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            left: Val::Px(16.0),
+                            top: Val::Px(64.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    EnrageCountdown,
+                ));
+            }
+        });
+
+    if let Some((boss, label_height)) = boss {
+        spawn_boss_status(&mut commands, &encounter, boss, label_height, &assets.theme);
+    }
+
+    // Restored monsters carry on from where they were left
+    for (monster, spec, saved) in restored_monsters {
+        let mut behavior = Behavior::from_spec(spec, difficulty.enemy_scale());
+        behavior.next = saved.next_move % behavior.moves.len();
+        behavior.scripted = behavior.next;
+        behavior.strength = saved.strength;
+        commands.entity(monster).insert((
+            Health {
+                current: saved.health,
+                maximum: spec.health * difficulty.enemy_scale() * run_effects.enemy_health_scale(),
+                temporary: 0.0,
+            },
+            Block(saved.block),
+            Vulnerable {
+                turns: saved.vulnerable,
+            },
+            behavior,
+        ));
+        if saved.summoned {
+            commands.entity(monster).insert(Summoned);
+        }
+        if saved.burn > 0.0 {
+            commands.entity(monster).insert(Burning(saved.burn));
+        }
+        if let (Some((progress, damage_this_turn)), Some(ritual)) =
+            (saved.ritual, &encounter.ritual)
+        {
+            commands.entity(monster).insert(RitualCharge {
+                ritual: ritual.clone(),
+                progress,
+                damage_this_turn,
+            });
+        }
+    }
+}
+
+// Mark the boss, and give it its enrage status and ritual bar if the encounter has them
+fn spawn_boss_status(
+    commands: &mut Commands,
+    encounter: &Encounter,
+    boss: Entity,
+    label_height: f32,
+    theme: &Theme,
+) {
+    commands.entity(boss).insert(Boss);
+    if encounter.enrage_after.is_some() {
+        commands.entity(boss).with_children(|boss| {
+            boss.spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        "ENRAGED",
+                        TextStyle {
+                            font_size: 28.0,
+                            color: ENRAGE_COLOR,
+                            ..default()
+                        },
+                    ),
+                    transform: Transform::from_xyz(0.0, label_height + 35.0, 0.1),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                EnragedStatus,
+            ));
+        });
+    }
+    if let Some(ritual) = &encounter.ritual {
+        commands
+            .entity(boss)
+            .insert(RitualCharge {
+                ritual: ritual.clone(),
+                progress: 0,
+                damage_this_turn: 0.0,
+            })
+            .with_children(|boss| spawn_ritual_bar(boss, label_height + 70.0, theme));
+    }
+}
+
+// Look up a monster's spec by its `SpecPath`
+fn find_spec<'a>(monsters: &'a [MonsterSpec], path: &[usize]) -> Option<&'a MonsterSpec> {
+    let (index, rest) = path.split_first()?;
+    let spec = monsters.get(*index)?;
+    match rest {
+        [] => Some(spec),
+        [move_index, rest @ ..] => match spec.moves.get(*move_index)? {
+            EnemyMove::Summon(specs) => find_spec(specs, rest),
+            _ => None,
+        },
+    }
+}
+
+// The enemies with everything a suspended fight keeps of them
+type SavedMonsters<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static SpecPath,
+        &'static Transform,
+        &'static Health,
+        &'static Block,
+        &'static Behavior,
+        Option<&'static RitualCharge>,
+        Option<&'static Burning>,
+        &'static Vulnerable,
+        Has<Summoned>,
+    ),
+    With<Monster>,
+>;
+
+// The fighters and the hand on screen, which a suspended fight is saved from
+#[derive(SystemParam)]
+struct FightOnScreen<'w, 's> {
+    player: Query<'w, 's, &'static Health, With<SideCharacter>>,
+    hand: Query<'w, 's, &'static Children, With<HandContainer>>,
+    cards: Query<'w, 's, &'static CardType, With<Card>>,
+    monsters: SavedMonsters<'w, 's>,
+    allies: Query<'w, 's, (&'static Transform, &'static Health, &'static Ally)>,
+}
+
+// Keep a copy of the fight in the save whenever the player's side of it changes, and drop it
+// once the fight is won or lost
+fn suspend_fight(
+    fight_state: Res<FightState>,
+    turn_state: Res<TurnState>,
+    state: Res<State<GameState>>,
+    mut save_data: ResMut<SaveData>,
+    on_screen: FightOnScreen,
+    finished_query: FinishedScreens,
+) {
+    let FightOnScreen {
+        player: player_query,
+        hand: hand_query,
+        cards: card_query,
+        monsters: monster_query,
+        allies: ally_query,
+    } = on_screen;
+    if !finished_query.is_empty() {
+        if save_data.fight.take().is_some() {
+            save_data.save();
+        }
+        return;
+    }
+    // Demo, arena and hot-seat fights are never kept
+    if fight_state.current_turn == Turn::Enemy
+        || fight_state.hot_seat
+        || matches!(state.get(), GameState::Demo | GameState::Arena)
+        || !(fight_state.is_changed() || turn_state.is_changed())
+    {
+        return;
+    }
+    let Ok(player_health) = player_query.get_single() else {
+        return;
+    };
+
+    let hand = hand_query
+        .iter()
+        .flat_map(|children| children.iter())
+        .filter_map(|child| card_query.get(*child).ok().copied())
+        .collect();
+    let monsters = monster_query
+        .iter()
+        .filter(|(_, _, health, ..)| health.current > 0.0)
+        .map(
+            |(path, transform, health, block, behavior, charge, burning, vulnerable, summoned)| {
+                MonsterSnapshot {
+                    spec_path: path.0.clone(),
+                    position: transform.translation.to_array(),
+                    health: health.current,
+                    block: block.0,
+                    next_move: behavior.next,
+                    strength: behavior.strength,
+                    summoned,
+                    ritual: charge.map(|charge| (charge.progress, charge.damage_this_turn)),
+                    burn: burning.map_or(0.0, |burning| burning.0),
+                    vulnerable: vulnerable.turns,
+                }
+            },
+        )
+        .collect();
+    let allies = ally_query
+        .iter()
+        .filter(|(_, health, _)| health.current > 0.0)
+        .map(|(transform, health, ally)| AllySnapshot {
+            position: transform.translation.to_array(),
+            health: health.current,
+            next_action: ally.next,
+        })
+        .collect();
+
+    save_data.fight = Some(FightSnapshot {
+        state: *state.get(),
+        player_health: player_health.current,
+        player_temporary_health: player_health.temporary,
+        hand,
+        mulligan: fight_state.current_turn == Turn::Mulligan,
+        turn: turn_state.clone(),
+        monsters,
+        allies,
+    });
+    save_data.save();
+}
+
+// The boss's ritual progress, high above it so it clears the intent and any status
+fn spawn_ritual_bar(parent: &mut ChildBuilder, height: f32, theme: &Theme) {
+    parent
+        .spawn(ui::bar_track(
+            theme,
+            Vec2::new(HEALTH_BAR_WIDTH, 12.0),
+            height,
+        ))
+        .with_children(|container| {
+            container.spawn((
+                ui::bar_segment(RITUAL_COLOR, Vec2::new(HEALTH_BAR_WIDTH, 12.0), 0.0),
+                RitualBar,
+            ));
+            container.spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font_size: 20.0,
+                            color: RITUAL_COLOR,
+                            ..default()
+                        },
+                    ),
+                    transform: Transform::from_xyz(0.0, 22.0, 0.2),
+                    ..default()
+                },
+                RitualText,
+            ));
+        });
+}
+
+fn update_ritual_bars(
+    charge_query: Query<(&RitualCharge, &Children), Changed<RitualCharge>>,
+    container_query: Query<&Children, Without<RitualCharge>>,
+    mut bar_query: Query<&mut Sprite, With<RitualBar>>,
+    mut text_query: Query<&mut Text, With<RitualText>>,
+) {
+    for (charge, children) in charge_query.iter() {
+        let fraction = charge.progress as f32 / charge.ritual.turns as f32;
+        for container in children.iter() {
+            let Ok(parts) = container_query.get(*container) else {
+                continue;
+            };
+            for part in parts.iter() {
+                if let Ok(mut sprite) = bar_query.get_mut(*part) {
+                    sprite.custom_size = Some(Vec2::new(HEALTH_BAR_WIDTH * fraction, 12.0));
+                }
+                if let Ok(mut text) = text_query.get_mut(*part) {
+                    text.sections[0].value =
+                        format!("Ritual {}/{}", charge.progress, charge.ritual.turns);
+                }
+            }
+        }
+    }
+}
+
+// Offered once, before turn 1: click cards in the opening hand to mark them, then redraw
+fn spawn_mulligan_panel(parent: &mut ChildBuilder, theme: &Theme) {
+    parent
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(300.0),
+                    width: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(12.0),
+                    ..default()
+                },
+                ..default()
+            },
+            MulliganPanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Click cards to redraw them",
+                TextStyle {
+                    font_size: 30.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+            parent
+                .spawn((
+                    ui::button(
+                        theme,
+                        Style {
+                            width: Val::Px(200.0),
+                            height: Val::Px(50.0),
+                            align_items: AlignItems::Center,
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                    ),
+                    MulliganButton,
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle::from_section(
+                            "Keep Hand",
+                            TextStyle {
+                                font_size: 20.0,
+                                color: Color::WHITE,
+                                ..default()
+                            },
+                        ),
+                        MulliganButtonText,
+                    ));
+                });
+        });
+}
+
+// The cards pressed during the mulligan, and whether each is already marked to redraw
+type MulliganCards<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Interaction,
+        &'static CardType,
+        &'static mut UiImage,
+        Has<MulliganSelected>,
+    ),
+    (Changed<Interaction>, With<Card>),
+>;
+
+fn toggle_mulligan_card(
+    mut commands: Commands,
+    fight_state: Res<FightState>,
+    mut card_query: MulliganCards,
+    selected_query: Query<(), With<MulliganSelected>>,
+    mut text_query: Query<&mut Text, With<MulliganButtonText>>,
+    play_lock: Res<PlayLock>,
+) {
+    if fight_state.current_turn != Turn::Mulligan {
+        return;
+    }
+
+    let mut selected = selected_query.iter().count();
+    for (entity, interaction, card_type, mut image, is_selected) in card_query.iter_mut() {
+        // The one card a guided fight asks for has to stay in hand
+        if *interaction != Interaction::Pressed || play_lock.only_card == Some(*card_type) {
+            continue;
+        }
+        if is_selected {
+            commands.entity(entity).remove::<MulliganSelected>();
+            image.color = Color::WHITE;
+            selected -= 1;
+        } else {
+            commands.entity(entity).insert(MulliganSelected);
+            image.color = Color::srgb(0.4, 0.4, 0.4);
+            selected += 1;
+        }
+
+        if let Ok(mut text) = text_query.get_single_mut() {
+            text.sections[0].value = if selected == 0 {
+                "Keep Hand".to_string()
+            } else {
+                format!("Redraw {}", selected)
+            };
+        }
+    }
+}
+
+// Shuffle the marked cards back into the draw pile, draw as many again, and start turn 1
+fn confirm_mulligan(
+    mut commands: Commands,
+    rules: FightRules,
+    selected_query: Query<(Entity, &CardType), With<MulliganSelected>>,
+    hand_query: Query<Entity, With<HandContainer>>,
+    panel_query: Query<Entity, With<MulliganPanel>>,
+    shared_assets: Res<SharedAssets>,
+) {
+    let FightRules {
+        actions,
+        mut fight_state,
+        mut turn_state,
+        mut rng,
+        mut fight_events,
+        ..
+    } = rules;
+    let confirmed = actions
+        .this_step
+        .iter()
+        .any(|action| matches!(action, FightAction::ConfirmMulligan));
+    if fight_state.current_turn != Turn::Mulligan || !confirmed {
+        return;
+    }
+    let Ok(hand) = hand_query.get_single() else {
+        return;
+    };
+
+    let mut redraws = 0;
+    for (entity, card_type) in selected_query.iter() {
+        turn_state.draw_pile.push(*card_type);
+        commands.entity(entity).despawn_recursive();
+        redraws += 1;
+    }
+    rng.shuffle(&mut turn_state.draw_pile);
+    for _ in 0..redraws {
+        if let Some(card_type) = turn_state.draw_pile.pop() {
+            spawn_card(&mut commands, hand, card_type, &shared_assets);
+        }
+    }
+
+    for panel in panel_query.iter() {
+        commands.entity(panel).despawn_recursive();
+    }
+    fight_state.current_turn = Turn::Player;
+    fight_events.send(FightEvent::MulliganDone);
+}
+
+fn update_victory_screen(
+    mut commands: Commands,
+    time: Res<Time>,
+    parts: VictoryScreenParts,
+    mut game_state: ResMut<NextState<GameState>>,
+    player_query: Query<&Health, With<SideCharacter>>,
+    spoils: VictorySpoils,
+    theme: Res<Theme>,
+) {
+    let VictoryScreenParts {
+        screens: mut query,
+        text: mut text_query,
+        ceremony: ceremony_query,
+    } = parts;
+    let VictorySpoils {
+        encounter,
+        turn_state,
+        mut run_record,
+        mut story_flags,
+        mut run_effects,
+        mut deck,
+        mut pickups,
+        mut cards_earned,
+    } = spoils;
+    for (entity, mut bg_color, mut fade, mut return_timer) in query.iter_mut() {
+        fade.timer.tick(time.delta());
+        let alpha = fade.timer.fraction();
+        bg_color.0 = Color::srgba(0.0, 0.0, 0.0, alpha * 0.7);
+
+        if let Ok(mut text) = text_query.get_single_mut() {
+            text.sections[0].style.color = Color::srgba(0.0, 0.8, 0.0, alpha);
+            text.sections[1].style.color = GOLD_COLOR.with_alpha(alpha);
+            text.sections[2].style.color = theme.text.with_alpha(alpha);
+        }
+
+        return_timer.timer.tick(time.delta());
+        if !return_timer.continued && !return_timer.timer.finished() {
+            continue;
+        }
+
+        // Reveal any cards won before leaving the fight, unless the player healed instead
+        if !return_timer.rewards_shown {
+            return_timer.rewards_shown = true;
+            if !return_timer.healed {
+                cards_earned.send(CardsEarned(cards_on_offer(&encounter, &turn_state)));
+            }
+            continue;
+        }
+        if !ceremony_query.is_empty() {
+            continue;
+        }
+
+        // Keep track of how the fight went for the ending cutscene
+        if let Ok(player_health) = player_query.get_single() {
+            run_record.health_remaining = player_health.current;
+            run_record.health_maximum = player_health.maximum;
+
+            // A bad wound festers into a curse the rest of the run carries
+            if let Some(wounded_flag) = encounter.wounded_flag {
+                if player_health.current < player_health.maximum / 2.0 {
+                    story_flags.set(wounded_flag);
+                    deck.add(CardType::Curse);
+                    spawn_floating_text(
+                        &mut commands,
+                        "Your wound festers into a curse".to_string(),
+                        CURSE_COLOR,
+                    );
+                }
+            }
+        }
+        story_flags.set(encounter.victory_flag);
+        if let Some(item) = encounter.reward {
+            pickups.send(ItemPickedUp(item));
+        }
+        for effect in encounter
+            .effect_reward
+            .iter()
+            .chain(&turn_state.loot.relics)
+        {
+            if run_effects.add(*effect) {
+                spawn_floating_text(&mut commands, effect.name().to_string(), BUFF_COLOR);
+            }
+        }
+        // There are no more fights to build a deck for after the last one, and the arena has
+        // its own picks between waves
+        if matches!(
+            encounter.next_state,
+            GameState::Ending | GameState::ArenaUpgrade
+        ) {
+            game_state.set(encounter.next_state);
+        } else {
+            game_state.set(GameState::DeckBuilding);
+        }
+        commands.entity(entity).despawn_recursive(); // Clean up victory screen
+    }
+}
+
+// The cards a won fight rewards, from the encounter and from what its enemies dropped
+fn cards_on_offer(encounter: &Encounter, turn_state: &TurnState) -> Vec<CardType> {
+    let mut cards = encounter.card_rewards.clone();
+    cards.extend(&turn_state.loot.cards);
+    cards
+}
+
+// With cards on offer the player either takes them or heals, and otherwise just continues
+fn spawn_victory_screen(
+    commands: &mut Commands,
+    gold_earned: u32,
+    dropped: Option<String>,
+    cards_offered: bool,
+    theme: &Theme,
+) {
+    commands
+        .spawn((
+            ui::modal(),
+            VictoryScreen,
+            FadeInEffect {
+                timer: Timer::from_seconds(2.0, TimerMode::Once),
+            },
+            ReturnToMenuVictoryTimer {
+                timer: Timer::from_seconds(VICTORY_AUTO_CONTINUE, TimerMode::Once),
+                continued: false,
+                healed: false,
+                rewards_shown: false,
+            },
+            OnCombatScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_sections([
+                    TextSection::new(
+                        "VICTORY!",
+                        TextStyle {
+                            font_size: 120.0,
+                            color: Color::srgba(0.0, 0.8, 0.0, 0.0), // Start transparent, but green
+                            ..default()
+                        },
+                    ),
+                    TextSection::new(
+                        format!("\n+{} gold", gold_earned),
+                        TextStyle {
+                            font_size: 50.0,
+                            color: GOLD_COLOR.with_alpha(0.0),
+                            ..default()
+                        },
+                    ),
+                    TextSection::new(
+                        dropped.map_or(String::new(), |dropped| format!("\n{dropped}")),
+                        TextStyle {
+                            font_size: 40.0,
+                            color: theme.text.with_alpha(0.0),
+                            ..default()
+                        },
+                    ),
+                ])
+                .with_text_justify(JustifyText::Center),
+                VictoryText,
+            ));
+            if cards_offered {
+                ui::modal_button(parent, theme, "Take the cards", VictoryChoice::Continue);
+                ui::modal_button(
+                    parent,
+                    theme,
+                    format!("Heal {VICTORY_HEAL} HP"),
+                    VictoryChoice::Heal,
+                );
+            } else {
+                ui::modal_button(parent, theme, "Continue", VictoryChoice::Continue);
+            }
+        });
+}
+
+// Rebuild the row of powers whenever the turn state changes, as one may have been played
+fn show_powers(
+    mut commands: Commands,
+    turn_state: Res<TurnState>,
+    row_query: Query<Entity, With<PowerRow>>,
+    shared_assets: Res<SharedAssets>,
+    theme: Res<Theme>,
+) {
+    if !turn_state.is_changed() {
+        return;
+    }
+    let Ok(row) = row_query.get_single() else {
+        return;
+    };
+    commands
+        .entity(row)
+        .despawn_descendants()
+        .with_children(|parent| {
+            for power in &turn_state.powers {
+                let (image, atlas) = shared_assets.card(power.card());
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn((
+                            ImageBundle {
+                                style: Style {
+                                    width: Val::Px(48.0),
+                                    height: Val::Px(67.0),
+                                    ..default()
+                                },
+                                image,
+                                ..default()
+                            },
+                            atlas,
+                        ));
+                        parent.spawn(TextBundle::from_section(
+                            power.description(),
+                            theme.text_style(16.0),
+                        ));
+                    });
+            }
+        });
+}
+
+// Keep the energy counter up to date, and badge each card dealt with what it costs. A badge
+// turns red while the energy left won't pay for its card.
+fn show_energy(
+    mut commands: Commands,
+    turn_state: Res<TurnState>,
+    run_effects: Res<RunEffects>,
+    new_card_query: Query<(Entity, &CardType), Added<Card>>,
+    mut energy_text_query: Query<&mut Text, (With<EnergyText>, Without<CostBadge>)>,
+    mut badge_query: Query<(&CostBadge, &mut Text)>,
+) {
+    let badge_color = |cost: u32| {
+        if cost > turn_state.energy() {
+            DAMAGE_COLOR
+        } else {
+            Color::WHITE
+        }
+    };
+    for (card, card_type) in new_card_query.iter() {
+        // Curses can't be played at any cost
+        if *card_type == CardType::Curse {
+            continue;
+        }
+        let cost = run_effects.card_cost(*card_type);
+        commands.entity(card).with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    cost.to_string(),
+                    TextStyle {
+                        font_size: 28.0,
+                        color: badge_color(cost),
+                        ..default()
+                    },
+                )
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(6.0),
+                    left: Val::Px(6.0),
+                    padding: UiRect::axes(Val::Px(8.0), Val::Px(2.0)),
+                    ..default()
+                })
+                .with_background_color(Color::srgba(0.05, 0.05, 0.2, 0.85)),
+                CostBadge(cost),
+            ));
+        });
+    }
+
+    if !turn_state.is_changed() {
+        return;
+    }
+    if let Ok(mut text) = energy_text_query.get_single_mut() {
+        text.sections[0].value = format!("Energy {}/{}", turn_state.energy(), ENERGY_PER_TURN);
+    }
+    for (badge, mut text) in badge_query.iter_mut() {
+        text.sections[0].style.color = badge_color(badge.0);
+    }
+}
+
+fn show_card_tooltip(
+    card_query: Query<(&Interaction, &CardType), With<Card>>,
+    mut tooltip_query: Query<(&mut Style, &mut Text), With<CardTooltip>>,
+) {
+    let Ok((mut style, mut text)) = tooltip_query.get_single_mut() else {
+        return;
+    };
+    let explained = card_query
+        .iter()
+        .find(|(interaction, _)| **interaction == Interaction::Hovered)
+        .map(|(_, card_type)| {
+            card_type
+                .keywords()
+                .into_iter()
+                .map(Keyword::explain)
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default();
+    let display = if explained.is_empty() {
+        Display::None
+    } else {
+        Display::Flex
+    };
+    // Only touch the node when the tooltip changes, so the layout isn't redone every frame
+    if style.display != display {
+        style.display = display;
+    }
+    if text.sections[0].value != explained {
+        text.sections[0].value = explained;
+    }
+}
+
+// Leave the victory screen on the Continue button, Enter or a gamepad's A button, or heal
+// instead of taking the cards with the Heal button, H or a gamepad's X button. The buttons are
+// hidden once one is pressed, while any cards won are revealed.
+fn continue_from_victory(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    mut button_query: Query<(
+        &Interaction,
+        &VictoryChoice,
+        &mut BackgroundColor,
+        &mut Visibility,
+    )>,
+    mut victory_query: Query<&mut ReturnToMenuVictoryTimer>,
+    mut player_state: ResMut<PlayerState>,
+    theme: Res<Theme>,
+) {
+    let gamepad_pressed = |button_type| {
+        gamepads
+            .iter()
+            .any(|gamepad| gamepad_buttons.just_pressed(GamepadButton::new(gamepad, button_type)))
+    };
+    let mut choice = None;
+    let mut can_heal = false;
+    for (interaction, victory_choice, mut color, visibility) in button_query.iter_mut() {
+        if *visibility == Visibility::Hidden {
+            return;
+        }
+        *color = ui::button_color(&theme, *interaction);
+        if *interaction == Interaction::Pressed {
+            choice = Some(*victory_choice);
+        }
+        can_heal |= *victory_choice == VictoryChoice::Heal;
+    }
+    if keyboard.just_pressed(KeyCode::Enter) || gamepad_pressed(GamepadButtonType::South) {
+        choice = choice.or(Some(VictoryChoice::Continue));
+    }
+    if can_heal
+        && (keyboard.just_pressed(KeyCode::KeyH) || gamepad_pressed(GamepadButtonType::West))
+    {
+        choice = choice.or(Some(VictoryChoice::Heal));
+    }
+    let Some(choice) = choice else {
+        return;
+    };
+    let healed = choice == VictoryChoice::Heal;
+    if healed {
+        player_state.heal(VICTORY_HEAL);
+    }
+    for mut return_timer in victory_query.iter_mut() {
+        return_timer.continued = true;
+        return_timer.healed = healed;
+    }
+    for (_, _, _, mut visibility) in button_query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+fn check_victory_condition(
+    monster_query: Query<&Health, With<Monster>>,
+    player_query: Query<&Health, (With<SideCharacter>, Without<Monster>)>,
+    victory_screen_query: Query<(), With<VictoryScreen>>,
+    mut commands: Commands,
+    rules: FightRules,
+    winnings: Winnings,
+    theme: Res<Theme>,
+) {
+    let FightRules {
+        encounter,
+        turn_state,
+        mut fight_events,
+        ..
+    } = rules;
+    let Winnings {
+        mut gold,
+        mut player_state,
+    } = winnings;
+    if victory_screen_query.is_empty() {
+        // Only check if victory screen isn't already shown
+        let all_monsters_dead = monster_query.iter().all(|health| health.current <= 0.0);
+
+        if all_monsters_dead {
+            let gold_earned = encounter.gold_reward + turn_state.loot.gold;
+            gold.earn(gold_earned);
+            spawn_victory_screen(
+                &mut commands,
+                gold_earned,
+                turn_state.loot.describe(),
+                !cards_on_offer(&encounter, &turn_state).is_empty(),
+                &theme,
+            );
+            if let Ok(player_health) = player_query.get_single() {
+                player_state.health = Some(player_health.current);
+                player_state.maximum = player_health.maximum;
+                fight_events.send(FightEvent::Won(
+                    player_health.current / player_health.maximum,
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+    use crate::test_app::TestApp;
+
+    // The first fight of a new game, past its mulligan, with Fire in hand and no tutorial
+    fn first_fight() -> TestApp {
+        let mut app = TestApp::new();
+        app.world_mut().resource_mut::<SaveData>().tutorial_seen = true;
+        app.world_mut().resource_mut::<PlayLock>().only_card = Some(CardType::Fire);
+        app.start_new_game();
+        *app.world_mut().resource_mut::<PlayLock>() = PlayLock::default();
+
+        let mulligan_button = app.single::<MulliganButton>();
+        click(&mut app, mulligan_button);
+        assert!(fight_state(&app).current_turn == Turn::Player);
+        app
+    }
+
+    // Presses `entity` and steps past the frame reading it to the fixed step acting on it
+    fn click(app: &mut TestApp, entity: Entity) {
+        app.press(entity);
+        app.step(2);
+    }
+
+    fn fight_state(app: &TestApp) -> &FightState {
+        app.world().resource::<FightState>()
+    }
+
+    fn card_in_hand(app: &mut TestApp, card_type: CardType) -> Entity {
+        let world = app.world_mut();
+        world
+            .query_filtered::<(Entity, &CardType), With<Card>>()
+            .iter(world)
+            .find(|(_, in_hand)| **in_hand == card_type)
+            .map(|(entity, _)| entity)
+            .unwrap_or_else(|| panic!("no {card_type:?} in hand"))
+    }
+
+    fn health(app: &TestApp, entity: Entity) -> f32 {
+        app.world().get::<Health>(entity).unwrap().current
+    }
+
+    fn player(app: &mut TestApp) -> Entity {
+        app.single::<SideCharacter>()
+    }
+
+    fn target(app: &TestApp) -> Entity {
+        fight_state(app).target.expect("a monster is targeted")
+    }
+
+    #[test]
+    fn fire_played_first_hits_harder() {
+        let mut app = first_fight();
+        let target = target(&app);
+        let before = health(&app, target);
+
+        let fire = card_in_hand(&mut app, CardType::Fire);
+        click(&mut app, fire);
+
+        assert_eq!(
+            before - health(&app, target),
+            FIRE_BASE_DAMAGE + FIRE_FIRST_CARD_BONUS
+        );
+        assert_eq!(
+            app.world().resource::<TurnState>().energy(),
+            ENERGY_PER_TURN - CardType::Fire.cost()
+        );
+    }
+
+    #[test]
+    fn a_card_played_between_fixed_steps_lands_once() {
+        let mut app = first_fight();
+        let target = target(&app);
+        let before = health(&app, target);
+        // Frames far shorter than the fixed step, as on a fast monitor
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(1)));
+
+        let fire = card_in_hand(&mut app, CardType::Fire);
+        app.press(fire);
+        app.step_until(40, |world| {
+            world.get::<Health>(target).unwrap().current < before
+        });
+        app.step(40);
+
+        assert_eq!(
+            before - health(&app, target),
+            FIRE_BASE_DAMAGE + FIRE_FIRST_CARD_BONUS
+        );
+        assert_eq!(
+            app.world().resource::<TurnState>().energy(),
+            ENERGY_PER_TURN - CardType::Fire.cost()
+        );
+    }
+
+    #[test]
+    fn a_press_waits_for_a_slow_fixed_step() {
+        let mut app = first_fight();
+        let target = target(&app);
+        let before = health(&app, target);
+        // Several frames go by between steps
+        app.world_mut()
+            .resource_mut::<Time<Fixed>>()
+            .set_timestep(Duration::from_millis(400));
+
+        let fire = card_in_hand(&mut app, CardType::Fire);
+        app.press(fire);
+        app.step(1);
+        assert!(!app.world().resource::<FightActions>().pending.is_empty());
+        app.step_until(20, |world| {
+            world.get::<Health>(target).unwrap().current < before
+        });
+        app.step(20);
+
+        assert_eq!(
+            before - health(&app, target),
+            FIRE_BASE_DAMAGE + FIRE_FIRST_CARD_BONUS
+        );
+        assert_eq!(
+            app.world().resource::<TurnState>().energy(),
+            ENERGY_PER_TURN - CardType::Fire.cost()
+        );
+    }
+
+    #[test]
+    fn every_card_pressed_before_a_fixed_step_is_played() {
+        let mut app = first_fight();
+        app.world_mut()
+            .resource_mut::<Time<Fixed>>()
+            .set_timestep(Duration::from_millis(400));
+
+        let fire = card_in_hand(&mut app, CardType::Fire);
+        let world = app.world_mut();
+        let (other, other_type) = world
+            .query_filtered::<(Entity, &CardType), With<Card>>()
+            .iter(world)
+            .find(|(entity, card_type)| {
+                *entity != fire
+                    && **card_type != CardType::Curse
+                    && card_type.cost() + CardType::Fire.cost() <= ENERGY_PER_TURN
+            })
+            .map(|(entity, card_type)| (entity, *card_type))
+            .expect("a second card the turn's energy covers");
+
+        // Fire twice over, as a double click would
+        for card in [fire, other, fire] {
+            app.press(card);
+            app.step(1);
+        }
+        app.step_until(20, |world| {
+            !world
+                .resource::<TurnState>()
+                .cards_played_this_turn
+                .is_empty()
+        });
+        app.step(1);
+
+        let turn_state = app.world().resource::<TurnState>();
+        assert_eq!(
+            turn_state.cards_played_this_turn,
+            vec![CardType::Fire, other_type]
+        );
+        assert_eq!(
+            turn_state.energy(),
+            ENERGY_PER_TURN - CardType::Fire.cost() - other_type.cost()
+        );
+        assert!(app.world().get_entity(fire).is_none());
+        assert!(app.world().get_entity(other).is_none());
+    }
+
+    #[test]
+    fn ending_the_turn_lets_the_monsters_strike() {
+        let mut app = first_fight();
+        let world = app.world_mut();
+        let attacks: f32 = world
+            .query_filtered::<&Behavior, With<Monster>>()
+            .iter(world)
+            .map(|behavior| match behavior.intent() {
+                EnemyMove::Attack(damage) => behavior.attack_damage(*damage),
+                _ => 0.0,
+            })
+            .sum();
+        let player = player(&mut app);
+        let before = health(&app, player);
+
+        let end_turn_button = app.single::<EndTurnButton>();
+        click(&mut app, end_turn_button);
+        app.step_until(20, |world| {
+            world.resource::<FightState>().current_turn == Turn::Player
+        });
+
+        assert!(attacks > 0.0);
+        assert_eq!(before - health(&app, player), attacks);
+        assert_eq!(app.world().resource::<TurnState>().turn_count, 1);
+    }
+
+    #[test]
+    fn killing_every_monster_wins_the_fight() {
+        let mut app = first_fight();
+        let target = target(&app);
+        // Everything but the target is already down, and the target is one hit from it
+        let world = app.world_mut();
+        for (monster, mut health) in world
+            .query_filtered::<(Entity, &mut Health), With<Monster>>()
+            .iter_mut(world)
+        {
+            health.current = if monster == target { 1.0 } else { 0.0 };
+        }
+
+        let gold_before = app.world().resource::<Gold>().amount();
+
+        let fire = card_in_hand(&mut app, CardType::Fire);
+        click(&mut app, fire);
+        assert_eq!(app.count::<VictoryScreen>(), 1);
+        let gold_reward = app.world().resource::<Encounter>().gold_reward;
+        assert!(app.world().resource::<Gold>().amount() >= gold_before + gold_reward);
+
+        let world = app.world_mut();
+        let continue_button = world
+            .query::<(Entity, &VictoryChoice)>()
+            .iter(world)
+            .find(|(_, choice)| **choice == VictoryChoice::Continue)
+            .map(|(entity, _)| entity)
+            .unwrap();
+        app.press(continue_button);
+        app.step_until_state(200, GameState::DeckBuilding);
+
+        assert!(app
+            .world()
+            .resource::<StoryFlags>()
+            .is_set(StoryFlag::ForestCleared));
+        assert_eq!(app.count::<OnCombatScreen>(), 0);
+    }
+
+    #[test]
+    fn a_fight_quit_part_way_leaves_nothing_behind() {
+        let mut app = first_fight();
+        let fire = card_in_hand(&mut app, CardType::Fire);
+        click(&mut app, fire);
+
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Menu);
+        app.step(1);
+        assert_eq!(app.count::<OnCombatScreen>(), 0);
+
+        // A new game starts the fight over rather than picking up the one that was quit
+        app.start_new_game();
+        assert!(fight_state(&app).current_turn == Turn::Mulligan);
+        let turn_state = app.world().resource::<TurnState>();
+        assert!(turn_state.cards_played_this_turn.is_empty());
+        assert_eq!(turn_state.turn_count, 0);
+        assert_eq!(turn_state.energy(), ENERGY_PER_TURN);
+        assert_eq!(app.count::<Card>(), OPENING_HAND_SIZE);
+    }
+}
@@ -0,0 +1,234 @@
+// What happens when the game panics. A crash log with the versions, the state the game was in and
+// the last few combat events is written to `CRASH_LOG`, and on desktop the game starts itself
+// again with `CRASH_ARG` to show a small window saying where the log is, rather than vanishing.
+use std::collections::VecDeque;
+use std::env;
+use std::fs;
+use std::panic;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use bevy::window::WindowResolution;
+
+use super::GameState;
+use crate::combat::FightEvent;
+use crate::platform::data_path;
+use crate::theme::Theme;
+use crate::ui;
+
+const CRASH_LOG: &str = "crash.log";
+
+// Passed to the game with the crash log's path to show the crash window instead of the game
+pub const CRASH_ARG: &str = "--crashed";
+
+// Keep in step with Cargo.toml
+pub const BEVY_VERSION: &str = "0.14.2";
+
+const FIGHT_EVENTS_KEPT: usize = 20;
+
+// What the game was doing, kept up to date for the panic hook, which can't reach the world
+struct CrashContext {
+    state: String,
+    fight_events: VecDeque<String>,
+}
+
+static CRASH_CONTEXT: Mutex<CrashContext> = Mutex::new(CrashContext {
+    state: String::new(),
+    fight_events: VecDeque::new(),
+});
+
+// Panics on other threads, or while handling one, don't write the log again
+static CRASHED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Resource)]
+struct CrashLogPath(String);
+
+#[derive(Component)]
+struct CloseButton;
+
+// Set up before anything else, so a panic while the app is built is caught too
+pub fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        if CRASHED.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let mut log = format!(
+            "Sprited Towards {} crashed\nBevy {BEVY_VERSION}\nTime: {} seconds since the Unix epoch\n",
+            env!("CARGO_PKG_VERSION"),
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs()),
+        );
+        // The panic may have happened while the context was locked
+        match CRASH_CONTEXT.try_lock() {
+            Ok(context) => {
+                log += &format!("State: {}\nLast combat events:\n", context.state);
+                for event in &context.fight_events {
+                    log += &format!("  {event}\n");
+                }
+            }
+            Err(_) => log += "State: unknown\n",
+        }
+        log += &format!(
+            "\n{info}\n\n{}\n",
+            std::backtrace::Backtrace::force_capture()
+        );
+
+        let path = data_path(CRASH_LOG);
+        if let Err(err) = fs::write(&path, log) {
+            eprintln!("Could not write the crash log: {err}");
+            return;
+        }
+        eprintln!("The crash log was written to {}", path.display());
+        show_crash_window(path);
+    }));
+}
+
+// Android has no second window to show, and the log is all there is
+#[cfg(target_os = "android")]
+fn show_crash_window(_path: PathBuf) {}
+
+#[cfg(not(target_os = "android"))]
+fn show_crash_window(path: PathBuf) {
+    let path = fs::canonicalize(&path).unwrap_or(path);
+    if let Err(err) = env::current_exe().and_then(|exe| {
+        std::process::Command::new(exe)
+            .arg(CRASH_ARG)
+            .arg(path)
+            .spawn()
+    }) {
+        eprintln!("Could not show the crash window: {err}");
+    }
+}
+
+// The path the crash window should point at, if the game was started to show one
+pub fn crash_log_arg() -> Option<String> {
+    let mut args = env::args().skip(1);
+    if args.next()? != CRASH_ARG {
+        return None;
+    }
+    args.next()
+}
+
+pub fn crash_plugin(app: &mut App) {
+    app.add_systems(Update, (track_state, track_fight_events));
+}
+
+fn track_state(game_state: Res<State<GameState>>) {
+    if !game_state.is_changed() {
+        return;
+    }
+    if let Ok(mut context) = CRASH_CONTEXT.lock() {
+        context.state = format!("{:?}", game_state.get());
+    }
+}
+
+fn track_fight_events(mut fight_events: EventReader<FightEvent>) {
+    if fight_events.is_empty() {
+        return;
+    }
+    let Ok(mut context) = CRASH_CONTEXT.lock() else {
+        return;
+    };
+    for event in fight_events.read() {
+        context.fight_events.push_back(format!("{event:?}"));
+        if context.fight_events.len() > FIGHT_EVENTS_KEPT {
+            context.fight_events.pop_front();
+        }
+    }
+}
+
+// The combat events the crash log would list, for bug reports
+pub fn recent_fight_events() -> Vec<String> {
+    CRASH_CONTEXT
+        .lock()
+        .map(|context| context.fight_events.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+// A small app of its own, as the game that crashed can't draw anything any more
+pub fn run_crash_window(log_path: String) {
+    App::new()
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                title: "Sprited Towards crashed".to_string(),
+                resolution: WindowResolution::new(720.0, 280.0),
+                resizable: false,
+                ..default()
+            }),
+            ..default()
+        }))
+        .insert_resource(CrashLogPath(log_path))
+        // Without the asset loading of the game, the window keeps the built-in look
+        .init_resource::<Theme>()
+        .add_systems(Startup, crash_window_setup)
+        .add_systems(Update, close_button)
+        .run();
+}
+
+fn crash_window_setup(mut commands: Commands, log_path: Res<CrashLogPath>, theme: Res<Theme>) {
+    let text_style = theme.text_style(20.0);
+
+    commands.spawn(Camera2dBundle::default());
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(20.0),
+                padding: UiRect::all(Val::Px(20.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Sorry, the game ran into a problem and had to close.",
+                TextStyle {
+                    font_size: 28.0,
+                    ..text_style.clone()
+                },
+            ));
+            parent.spawn(
+                TextBundle::from_section(
+                    format!(
+                        "A crash log was written to\n{}\nPlease include it if you report the problem.",
+                        log_path.0
+                    ),
+                    text_style.clone(),
+                )
+                .with_text_justify(JustifyText::Center),
+            );
+            ui::styled_button(parent, &theme, Style {
+                            padding: UiRect::axes(Val::Px(30.0), Val::Px(10.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        }, "Close", text_style, CloseButton);
+        });
+}
+
+fn close_button(
+    mut interaction_query: ui::ChangedButtons<CloseButton>,
+    mut app_exit_events: EventWriter<AppExit>,
+    theme: Res<Theme>,
+) {
+    for (interaction, mut color) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                app_exit_events.send(AppExit::Success);
+            }
+            Interaction::Hovered => *color = theme.button_hovered.into(),
+            Interaction::None => *color = theme.button.into(),
+        }
+    }
+}
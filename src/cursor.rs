@@ -0,0 +1,125 @@
+// The game's own mouse cursor, drawn as a UI image that follows the pointer in place of the
+// system one. It becomes a hand over anything that can be clicked. Screens can ask for another
+// look by setting `CursorStyle` in a system ordered after `pick_cursor`, as the fight does to
+// show a sword over enemies.
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy::ui::UiSystem;
+use bevy::window::PrimaryWindow;
+
+const CURSOR_SIZE: f32 = 32.0;
+
+#[derive(Resource, Default, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum CursorStyle {
+    #[default]
+    Pointer,
+    Hand,
+    Sword,
+}
+
+impl CursorStyle {
+    const ALL: [CursorStyle; 3] = [CursorStyle::Pointer, CursorStyle::Hand, CursorStyle::Sword];
+
+    fn texture(self) -> &'static str {
+        match self {
+            CursorStyle::Pointer => "textures/cursors/pointer.png",
+            CursorStyle::Hand => "textures/cursors/hand.png",
+            CursorStyle::Sword => "textures/cursors/sword.png",
+        }
+    }
+
+    // The point of the image that sits on the pointer, from its top-left corner
+    fn hotspot(self) -> Vec2 {
+        match self {
+            CursorStyle::Pointer | CursorStyle::Sword => Vec2::ZERO,
+            CursorStyle::Hand => Vec2::new(9.0, 0.0),
+        }
+    }
+}
+
+// Every look is loaded up front, so switching never shows an empty cursor
+#[derive(Resource)]
+struct CursorImages(HashMap<CursorStyle, Handle<Image>>);
+
+#[derive(Component)]
+struct CustomCursor;
+
+pub fn cursor_plugin(app: &mut App) {
+    app.init_resource::<CursorStyle>()
+        .add_systems(Startup, spawn_cursor)
+        .add_systems(Update, pick_cursor)
+        .add_systems(PostUpdate, draw_cursor.before(UiSystem::Layout));
+}
+
+fn spawn_cursor(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    for mut window in window_query.iter_mut() {
+        window.cursor.visible = false;
+    }
+
+    let images = CursorImages(
+        CursorStyle::ALL
+            .into_iter()
+            .map(|style| (style, asset_server.load(style.texture())))
+            .collect(),
+    );
+    commands.spawn((
+        ImageBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Px(CURSOR_SIZE),
+                height: Val::Px(CURSOR_SIZE),
+                ..default()
+            },
+            image: UiImage::new(images.0[&CursorStyle::Pointer].clone()),
+            visibility: Visibility::Hidden,
+            // Above everything else, overlays included
+            z_index: ZIndex::Global(100),
+            ..default()
+        },
+        CustomCursor,
+    ));
+    commands.insert_resource(images);
+}
+
+// A hand while anything clickable is under the pointer, otherwise the plain pointer
+pub fn pick_cursor(interaction_query: Query<&Interaction>, mut cursor_style: ResMut<CursorStyle>) {
+    let over_clickable = interaction_query
+        .iter()
+        .any(|interaction| *interaction != Interaction::None);
+    cursor_style.set_if_neq(if over_clickable {
+        CursorStyle::Hand
+    } else {
+        CursorStyle::Pointer
+    });
+}
+
+fn draw_cursor(
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    cursor_style: Res<CursorStyle>,
+    images: Res<CursorImages>,
+    mut cursor_query: Query<(&mut Style, &mut UiImage, &mut Visibility), With<CustomCursor>>,
+) {
+    let position = window_query
+        .get_single()
+        .ok()
+        .and_then(Window::cursor_position);
+    for (mut style, mut image, mut visibility) in cursor_query.iter_mut() {
+        // Hidden while the pointer is outside the window
+        let Some(position) = position else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+        visibility.set_if_neq(Visibility::Visible);
+        let corner = position - cursor_style.hotspot();
+        style.left = Val::Px(corner.x);
+        style.top = Val::Px(corner.y);
+        if cursor_style.is_changed() {
+            image.texture = images.0[&*cursor_style].clone();
+        }
+    }
+}
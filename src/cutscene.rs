@@ -0,0 +1,1054 @@
+// Shared typewriter cutscene used by the `Game`..`Game4` intro states. Each intro module
+// only sets up its own background, then registers these systems for its state. The intros'
+// lines are read from `assets/story/chapterN.ron`, so the script can be changed without
+// rebuilding and there's one place to translate it. The closing cutscenes are read the same way,
+// from `assets/story/ending_*.ron`. They load before the splash with the other assets. A script
+// can also name a folder of frames to play behind the lines in place of the chapter's sprite
+// sheet, so a new background doesn't have to be packed into one huge sheet.
+// Folders are listed from the file system, so they only play where it can be read. Intros that
+// register the progress systems keep the line being shown in the save, so quitting part-way
+// through resumes at the same line.
+use std::sync::Arc;
+use std::time::Duration;
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext, LoadedFolder, UntypedAssetId};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{GameState, Volume};
+use crate::ending::Ending;
+use crate::inventory::{Inventory, KeyItem};
+use crate::rng::RunRng;
+use crate::save::SaveData;
+use crate::sfx::{self, play_sfx};
+use crate::shared_assets::SharedAssets;
+use crate::story::{StoryFlag, StoryFlags};
+
+// Seconds between characters when a line doesn't override its speed
+const DEFAULT_CHAR_DELAY: f32 = 0.08;
+
+// How long a line stays up when it doesn't set its own delay: the time it takes to type, then a
+// short pause plus a little reading time for each character, so short beats don't drag and long
+// lines aren't cut off
+const LINE_PAUSE: f32 = 1.5;
+const READ_TIME_PER_CHAR: f32 = 0.04;
+
+// The chapter intros' scripts, the first chapter's first
+const SCRIPTS: [&str; 4] = [
+    "story/chapter1.ron",
+    "story/chapter2.ron",
+    "story/chapter3.ron",
+    "story/chapter4.ron",
+];
+
+// How far each typewriter click's playback speed, and so its pitch, can stray either way
+const CLICK_PITCH_SPREAD: f32 = 0.12;
+
+// Where an intro's background is drawn, in a window of this size
+const BACKGROUND_SIZE: Vec2 = Vec2::new(1920.0, 1080.0);
+
+// Seconds each sprite sheet frame is shown
+const SHEET_FRAME_TIME: f32 = 0.1;
+const SHEET_LAST_FRAME: usize = 320;
+
+// Size of a reaction prompt's timer ring when it appears, and how long a hit stays on screen
+const REACTION_RING_SIZE: f32 = 160.0;
+const REACTION_HIT_HOLD: f32 = 0.6;
+
+// A typewriter click's audio entity. They are kept once spawned and replayed when their click
+// has finished, so typing doesn't spawn an entity per character.
+#[derive(Component)]
+pub struct TypewriterVoice;
+
+#[derive(Component)]
+pub struct TextContainer;
+
+#[derive(Component)]
+pub struct AnimationTimer(pub Timer);
+
+#[derive(Component)]
+pub struct AnimationIndices {
+    pub first: usize,
+    pub last: usize,
+}
+
+// A quick-time prompt shown with a line: pressing `key` within `seconds` sets `flag`
+#[derive(Clone, Copy)]
+pub struct Reaction {
+    pub key: KeyCode,
+    pub seconds: f32,
+    pub flag: StoryFlag,
+}
+
+// One line of a cutscene script
+#[derive(Clone)]
+pub struct CutsceneLine {
+    pub text: String,
+    // Seconds per character, overriding `DEFAULT_CHAR_DELAY` for slower, dramatic lines
+    pub speed: Option<f32>,
+    // Only shown when the flag is set (`true`) or not set (`false`)
+    pub condition: Option<(StoryFlag, bool)>,
+    // Only shown while the player carries this item
+    pub required_item: Option<KeyItem>,
+    // Prompt the player to react as the line starts
+    pub reaction: Option<Reaction>,
+    // Who says the line, shown before it
+    pub speaker: Option<String>,
+    // Seconds the line stays up from when it starts typing, overriding `hold_time`'s estimate
+    pub delay: Option<f32>,
+    // A sound played as the line starts
+    pub sfx: Option<String>,
+}
+
+impl CutsceneLine {
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            speed: None,
+            condition: None,
+            required_item: None,
+            reaction: None,
+            speaker: None,
+            delay: None,
+            sfx: None,
+        }
+    }
+
+    pub fn with_speed(mut self, seconds_per_char: f32) -> Self {
+        self.speed = Some(seconds_per_char);
+        self
+    }
+
+    pub fn when(mut self, flag: StoryFlag) -> Self {
+        self.condition = Some((flag, true));
+        self
+    }
+
+    pub fn unless(mut self, flag: StoryFlag) -> Self {
+        self.condition = Some((flag, false));
+        self
+    }
+
+    pub fn holding(mut self, item: KeyItem) -> Self {
+        self.required_item = Some(item);
+        self
+    }
+
+    // The text as typed out, after its speaker
+    fn typed_text(&self) -> String {
+        match &self.speaker {
+            Some(speaker) => format!("{speaker}: {}", self.text),
+            None => self.text.clone(),
+        }
+    }
+
+    // Seconds before the next line replaces this one
+    fn hold_time(&self) -> f32 {
+        self.delay.unwrap_or_else(|| {
+            let chars = self.typed_text().trim().chars().count() as f32;
+            chars * (self.speed.unwrap_or(DEFAULT_CHAR_DELAY) + READ_TIME_PER_CHAR) + LINE_PAUSE
+        })
+    }
+
+    fn applies(&self, story_flags: &StoryFlags, inventory: &Inventory) -> bool {
+        self.condition
+            .is_none_or(|(flag, wanted)| story_flags.is_set(flag) == wanted)
+            && self.required_item.is_none_or(|item| inventory.has(item))
+    }
+}
+
+// A line being typed out. The line is kept whole from when it starts typing, and `typed` is how
+// many bytes of it are shown; typing a character moves it from the untyped section to the end
+// of the typed one, so nothing is rebuilt from the whole line as it goes.
+#[derive(Component)]
+pub struct TypingText {
+    line: Option<Arc<str>>,
+    typed: usize,
+    char_delay: f32,
+    timer: Timer,
+    completed: bool,
+}
+
+impl TypingText {
+    pub fn completed(&self) -> bool {
+        self.completed
+    }
+}
+
+#[derive(Component)]
+pub struct TextSequence {
+    sequence_index: usize,
+}
+
+#[derive(Resource)]
+pub struct TextSequenceState {
+    pub current_sequence: usize,
+    pub texts: Vec<CutsceneLine>,
+    pub delay_timer: Timer,
+    pub ready_for_next: bool,
+    // The last line whose reaction prompt was shown, so it is only offered once
+    pub prompted_line: Option<usize>,
+    // Set once the last line has been typed out and held for its time
+    finished: bool,
+}
+
+impl TextSequenceState {
+    // Keeps only the lines whose conditions hold for the current story flags and inventory
+    pub fn new(texts: Vec<CutsceneLine>, story_flags: &StoryFlags, inventory: &Inventory) -> Self {
+        Self {
+            current_sequence: 0,
+            texts: texts
+                .into_iter()
+                .filter(|line| line.applies(story_flags, inventory))
+                .collect(),
+            // Set to each line's hold time as it starts
+            delay_timer: Timer::default(),
+            ready_for_next: true,
+            prompted_line: None,
+            finished: false,
+        }
+    }
+
+    // Keep the current line up for its hold time before the next replaces it
+    fn hold_current_line(&mut self) {
+        let hold_time = self.texts[self.current_sequence].hold_time();
+        self.delay_timer
+            .set_duration(Duration::from_secs_f32(hold_time));
+        self.delay_timer.reset();
+        self.ready_for_next = false;
+    }
+
+    // Whether the script has played out, so the intro can move on to its chapter
+    pub fn finished(&self) -> bool {
+        self.finished
+    }
+}
+
+// Keys a script's reaction prompt can ask for
+#[derive(Deserialize, Clone, Copy, Debug)]
+enum ReactionKey {
+    Space,
+    Enter,
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl ReactionKey {
+    fn key_code(self) -> KeyCode {
+        match self {
+            ReactionKey::Space => KeyCode::Space,
+            ReactionKey::Enter => KeyCode::Enter,
+            ReactionKey::Up => KeyCode::ArrowUp,
+            ReactionKey::Down => KeyCode::ArrowDown,
+            ReactionKey::Left => KeyCode::ArrowLeft,
+            ReactionKey::Right => KeyCode::ArrowRight,
+        }
+    }
+}
+
+// One line as a script file writes it. Everything but the text may be left out.
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+struct ScriptLine {
+    text: String,
+    speaker: Option<String>,
+    speed: Option<f32>,
+    delay: Option<f32>,
+    sfx: Option<String>,
+    when: Option<StoryFlag>,
+    unless: Option<StoryFlag>,
+    holding: Option<KeyItem>,
+    reaction: Option<ScriptReaction>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ScriptReaction {
+    key: ReactionKey,
+    seconds: f32,
+    flag: StoryFlag,
+}
+
+impl ScriptLine {
+    fn to_line(&self) -> CutsceneLine {
+        let mut line = CutsceneLine {
+            speed: self.speed,
+            speaker: self.speaker.clone(),
+            delay: self.delay,
+            sfx: self.sfx.clone(),
+            required_item: self.holding,
+            reaction: self.reaction.as_ref().map(|reaction| Reaction {
+                key: reaction.key.key_code(),
+                seconds: reaction.seconds,
+                flag: reaction.flag,
+            }),
+            ..CutsceneLine::new(&self.text)
+        };
+        if let Some(flag) = self.when {
+            line = line.when(flag);
+        }
+        if let Some(flag) = self.unless {
+            line = line.unless(flag);
+        }
+        line
+    }
+}
+
+// A folder of images played in file name order as an intro's background
+#[derive(Deserialize, Clone, Debug)]
+pub struct FrameFolder {
+    folder: String,
+    fps: f32,
+    // Start over after the last frame, or else stay on it
+    #[serde(default = "loops_by_default")]
+    looping: bool,
+}
+
+fn loops_by_default() -> bool {
+    true
+}
+
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct CutsceneScript {
+    lines: Vec<ScriptLine>,
+    #[serde(default)]
+    frames: Option<FrameFolder>,
+}
+
+// A background playing a frame folder. Its frames are picked up once the whole folder has loaded,
+// and until then the sprite stays hidden.
+#[derive(Component)]
+struct FrameAnimation {
+    folder: Handle<LoadedFolder>,
+    frames: Vec<Handle<Image>>,
+    timer: Timer,
+    looping: bool,
+    index: usize,
+}
+
+#[derive(Default)]
+struct ScriptLoader;
+
+impl AssetLoader for ScriptLoader {
+    type Asset = CutsceneScript;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<CutsceneScript, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+#[derive(Resource)]
+pub struct CutsceneScripts {
+    chapters: Vec<Handle<CutsceneScript>>,
+    endings: Vec<(Ending, Handle<CutsceneScript>)>,
+}
+
+impl CutsceneScripts {
+    pub fn ids(&self) -> impl Iterator<Item = UntypedAssetId> + '_ {
+        self.chapters
+            .iter()
+            .chain(self.endings.iter().map(|(_, handle)| handle))
+            .map(|handle| handle.id().untyped())
+    }
+
+    // The intro lines of a chapter, counted from 1
+    pub fn lines(&self, chapter: usize, scripts: &Assets<CutsceneScript>) -> Vec<CutsceneLine> {
+        script_lines(self.chapters.get(chapter - 1), scripts)
+    }
+
+    pub fn ending_lines(
+        &self,
+        ending: Ending,
+        scripts: &Assets<CutsceneScript>,
+    ) -> Vec<CutsceneLine> {
+        let handle = self
+            .endings
+            .iter()
+            .find(|(script_ending, _)| *script_ending == ending)
+            .map(|(_, handle)| handle);
+        script_lines(handle, scripts)
+    }
+
+    // The frame folder a chapter's script plays instead of its sprite sheet, if any
+    pub fn frames(&self, chapter: usize, scripts: &Assets<CutsceneScript>) -> Option<FrameFolder> {
+        self.chapters
+            .get(chapter - 1)
+            .and_then(|handle| scripts.get(handle))
+            .and_then(|script| script.frames.clone())
+    }
+}
+
+// The scripts as loaded, with the story flags and inventory that decide which of their lines
+// are shown, for the screens that start a cutscene
+#[derive(SystemParam)]
+pub struct Cutscenes<'w> {
+    scripts: Res<'w, CutsceneScripts>,
+    script_assets: Res<'w, Assets<CutsceneScript>>,
+    story_flags: Res<'w, StoryFlags>,
+    inventory: Res<'w, Inventory>,
+}
+
+impl Cutscenes<'_> {
+    pub fn sequence(&self, lines: Vec<CutsceneLine>) -> TextSequenceState {
+        TextSequenceState::new(lines, &self.story_flags, &self.inventory)
+    }
+
+    // A chapter's intro, counted from 1
+    pub fn chapter(&self, chapter: usize) -> TextSequenceState {
+        self.sequence(self.scripts.lines(chapter, &self.script_assets))
+    }
+
+    pub fn ending(&self, ending: Ending) -> TextSequenceState {
+        self.sequence(self.scripts.ending_lines(ending, &self.script_assets))
+    }
+
+    pub fn frames(&self, chapter: usize) -> Option<FrameFolder> {
+        self.scripts.frames(chapter, &self.script_assets)
+    }
+}
+
+// A script that didn't load leaves a blank line, so the cutscene still plays out rather than
+// stopping the game
+fn script_lines(
+    handle: Option<&Handle<CutsceneScript>>,
+    scripts: &Assets<CutsceneScript>,
+) -> Vec<CutsceneLine> {
+    match handle.and_then(|handle| scripts.get(handle)) {
+        Some(script) if !script.lines.is_empty() => {
+            script.lines.iter().map(ScriptLine::to_line).collect()
+        }
+        _ => vec![CutsceneLine::new("  ")],
+    }
+}
+
+pub fn cutscene_plugin(app: &mut App) {
+    app.init_asset::<CutsceneScript>()
+        .init_asset_loader::<ScriptLoader>()
+        .add_systems(PreStartup, load_scripts)
+        .add_systems(Update, animate_frames);
+}
+
+fn load_scripts(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(CutsceneScripts {
+        chapters: SCRIPTS
+            .iter()
+            .map(|path| asset_server.load(*path))
+            .collect(),
+        endings: Ending::ALL
+            .iter()
+            .map(|ending| (*ending, asset_server.load(ending.script())))
+            .collect(),
+    });
+}
+
+// The intro the player quit part-way through, and the line it was on
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct CutsceneProgress {
+    pub state: GameState,
+    pub line: usize,
+}
+
+// An open reaction prompt; the script waits while one is on screen
+#[derive(Component)]
+pub struct ReactionPrompt {
+    reaction: Reaction,
+    timer: Timer,
+    hit: bool,
+}
+
+#[derive(Component)]
+pub struct ReactionRing;
+
+pub fn spawn_initial_text(
+    mut commands: Commands,
+    sequence_state: Res<TextSequenceState>,
+    shared_assets: Res<SharedAssets>,
+) {
+    spawn_text_entity(
+        &mut commands,
+        sequence_state.current_sequence,
+        &shared_assets,
+    );
+}
+
+// Start from the line the player quit on, if they left this intro part-way through. The lines
+// kept depend only on saved flags and items, so the index still points at the same line.
+pub fn resume_cutscene(
+    mut sequence_state: ResMut<TextSequenceState>,
+    save_data: Res<SaveData>,
+    state: Res<State<GameState>>,
+) {
+    if let Some(progress) = save_data
+        .cutscene
+        .filter(|progress| progress.state == *state.get())
+    {
+        sequence_state.current_sequence = progress
+            .line
+            .min(sequence_state.texts.len().saturating_sub(1));
+        // Hold the line as if it had just come up, so resuming on the last line still shows it
+        sequence_state.hold_current_line();
+    }
+}
+
+// Keep the line being shown in the save whenever it moves on
+pub fn record_cutscene_progress(
+    sequence_state: Res<TextSequenceState>,
+    state: Res<State<GameState>>,
+    mut save_data: ResMut<SaveData>,
+) {
+    let progress = CutsceneProgress {
+        state: *state.get(),
+        line: sequence_state.current_sequence,
+    };
+    if save_data.cutscene != Some(progress) {
+        save_data.cutscene = Some(progress);
+        save_data.save();
+    }
+}
+
+// The intro played to its end, so there's nothing to resume
+pub fn finish_cutscene(save_data: &mut SaveData) {
+    if save_data.cutscene.take().is_some() {
+        save_data.save();
+    }
+}
+
+// The line is laid out in full from the start: the typed part goes in the first section and the
+// rest is kept in a transparent second section, so words never jump to the next line half-way
+// through being typed.
+pub fn spawn_text_entity(
+    commands: &mut Commands,
+    sequence_index: usize,
+    shared_assets: &SharedAssets,
+) {
+    let style = TextStyle {
+        font: shared_assets.font.clone(),
+        font_size: 40.0,
+        color: Color::WHITE,
+    };
+
+    commands.spawn((
+        TextBundle::from_sections([
+            TextSection::new("", style.clone()),
+            TextSection::new(
+                "",
+                TextStyle {
+                    color: Color::NONE,
+                    ..style
+                },
+            ),
+        ])
+        .with_style(Style {
+            margin: UiRect::bottom(Val::Px(20.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(80.0),
+            left: Val::Px(20.0),
+            right: Val::Px(20.0),
+            ..default()
+        }),
+        TypingText {
+            line: None,
+            typed: 0,
+            char_delay: DEFAULT_CHAR_DELAY,
+            timer: Timer::from_seconds(DEFAULT_CHAR_DELAY, TimerMode::Repeating),
+            completed: false,
+        },
+        TextSequence { sequence_index },
+    ));
+}
+
+pub fn manage_text_sequence(
+    mut commands: Commands,
+    mut sequence_state: ResMut<TextSequenceState>,
+    time: Res<Time>,
+    typing_query: Query<(Entity, &TypingText, &TextSequence)>,
+    prompt_query: Query<(), With<ReactionPrompt>>,
+    shared_assets: Res<SharedAssets>,
+) {
+    if !prompt_query.is_empty() {
+        return;
+    }
+    if !sequence_state.ready_for_next {
+        sequence_state.delay_timer.tick(time.delta());
+        if sequence_state.delay_timer.finished() {
+            sequence_state.ready_for_next = true;
+            // Immediately despawn all previous text when we're ready for the next one
+            for (entity, _, sequence) in typing_query.iter() {
+                if sequence.sequence_index <= sequence_state.current_sequence {
+                    commands.entity(entity).despawn();
+                }
+            }
+        }
+        return;
+    }
+
+    let mut all_completed = true;
+    for (_, typing_text, sequence) in typing_query.iter() {
+        if sequence.sequence_index == sequence_state.current_sequence && !typing_text.completed {
+            all_completed = false;
+            break;
+        }
+    }
+
+    if !all_completed {
+        return;
+    }
+    if sequence_state.current_sequence + 1 >= sequence_state.texts.len() {
+        sequence_state.finished = true;
+        return;
+    }
+
+    // Clear any existing text before spawning new one
+    for (entity, _, _) in typing_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    sequence_state.current_sequence += 1;
+    sequence_state.hold_current_line();
+
+    spawn_text_entity(
+        &mut commands,
+        sequence_state.current_sequence,
+        &shared_assets,
+    );
+}
+
+// A reaction prompt on screen and the ring shrinking around its key
+#[derive(SystemParam)]
+pub struct ReactionPromptUi<'w, 's> {
+    prompts: Query<'w, 's, (Entity, &'static mut ReactionPrompt)>,
+    rings: Query<'w, 's, (&'static mut Style, &'static mut BorderColor), With<ReactionRing>>,
+}
+
+// Offer the current line's reaction prompt, and shrink its ring until the key is pressed or
+// time runs out
+pub fn run_reaction_prompt(
+    mut commands: Commands,
+    mut sequence_state: ResMut<TextSequenceState>,
+    mut story_flags: ResMut<StoryFlags>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    shared_assets: Res<SharedAssets>,
+    prompt_ui: ReactionPromptUi,
+) {
+    let ReactionPromptUi {
+        prompts: mut prompt_query,
+        rings: mut ring_query,
+    } = prompt_ui;
+    let current = sequence_state.current_sequence;
+    if sequence_state.prompted_line != Some(current) {
+        if let Some(reaction) = sequence_state
+            .texts
+            .get(current)
+            .and_then(|line| line.reaction)
+        {
+            sequence_state.prompted_line = Some(current);
+            spawn_reaction_prompt(&mut commands, reaction, &shared_assets);
+        }
+    }
+
+    for (entity, mut prompt) in prompt_query.iter_mut() {
+        prompt.timer.tick(time.delta());
+        if !prompt.hit && keyboard.just_pressed(prompt.reaction.key) {
+            story_flags.set(prompt.reaction.flag);
+            prompt.hit = true;
+            prompt.timer = Timer::from_seconds(REACTION_HIT_HOLD, TimerMode::Once);
+            for (_, mut border) in ring_query.iter_mut() {
+                *border = Color::srgb(0.2, 0.9, 0.2).into();
+            }
+        }
+        if prompt.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        if !prompt.hit {
+            let size = Val::Px(REACTION_RING_SIZE * prompt.timer.fraction_remaining());
+            for (mut style, _) in ring_query.iter_mut() {
+                style.width = size;
+                style.height = size;
+            }
+        }
+    }
+}
+
+fn spawn_reaction_prompt(
+    commands: &mut Commands,
+    reaction: Reaction,
+    shared_assets: &SharedAssets,
+) {
+    let key_name = match reaction.key {
+        KeyCode::Space => "SPACE".to_string(),
+        key => format!("{:?}", key).to_uppercase(),
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Percent(25.0),
+                    width: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(20.0),
+                    ..default()
+                },
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            ReactionPrompt {
+                reaction,
+                timer: Timer::from_seconds(reaction.seconds, TimerMode::Once),
+                hit: false,
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                format!("Press {}!", key_name),
+                TextStyle {
+                    font: shared_assets.font.clone(),
+                    font_size: 50.0,
+                    color: Color::srgb(1.0, 0.84, 0.0),
+                },
+            ));
+            // The ring shrinks inside a fixed box so the text above it stays put
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(REACTION_RING_SIZE),
+                        height: Val::Px(REACTION_RING_SIZE),
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Px(REACTION_RING_SIZE),
+                                height: Val::Px(REACTION_RING_SIZE),
+                                border: UiRect::all(Val::Px(8.0)),
+                                ..default()
+                            },
+                            border_color: Color::srgb(1.0, 0.84, 0.0).into(),
+                            border_radius: BorderRadius::MAX,
+                            ..default()
+                        },
+                        ReactionRing,
+                    ));
+                });
+        });
+}
+
+// The typewriter's clicks. Each is pitched a little up or down at random and punctuation gets a
+// softer one, so a long line doesn't rattle off the same sample over and over.
+#[derive(SystemParam)]
+pub struct Typewriter<'w, 's> {
+    shared_assets: Res<'w, SharedAssets>,
+    volume: Res<'w, Volume>,
+    voices: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static AudioSink,
+            &'static mut Handle<AudioSource>,
+            &'static mut PlaybackSettings,
+        ),
+        With<TypewriterVoice>,
+    >,
+    // Apart from the run's numbers, so typing never changes what a seeded run draws
+    pitch: Local<'s, RunRng>,
+}
+
+impl Typewriter<'_, '_> {
+    fn click(&mut self, commands: &mut Commands, typed: char) {
+        let sound = if typed.is_ascii_punctuation() {
+            self.shared_assets.typewriter_soft_sound.clone()
+        } else {
+            self.shared_assets.typewriter_sound.clone()
+        };
+        let speed = 1.0 + CLICK_PITCH_SPREAD * (2.0 * self.pitch.fraction() - 1.0);
+        // Without its sink, a voice is queued to play again
+        let idle_voice = self.voices.iter_mut().find(|(_, sink, _, _)| sink.empty());
+        match idle_voice {
+            // The volume may have been changed since the voice was last played
+            Some((voice, _, mut source, mut settings)) => {
+                *source = sound;
+                *settings = sfx::scaled(PlaybackSettings::ONCE.with_speed(speed), &self.volume);
+                commands.entity(voice).remove::<AudioSink>();
+            }
+            None => {
+                let settings = PlaybackSettings::ONCE.with_speed(speed);
+                if let Some(mut voice) = play_sfx(commands, &self.volume, sound, settings) {
+                    voice.insert(TypewriterVoice);
+                }
+            }
+        }
+    }
+}
+
+pub fn type_text(
+    time: Res<Time>,
+    sequence_state: Res<TextSequenceState>,
+    mut query: Query<(&mut TypingText, &mut Text, &TextSequence)>,
+    mut typewriter: Typewriter,
+    asset_server: Res<AssetServer>,
+    volume: Res<Volume>,
+    mut commands: Commands,
+) {
+    for (mut typing_text, mut text, sequence) in query.iter_mut() {
+        if typing_text.completed || sequence.sequence_index != sequence_state.current_sequence {
+            continue;
+        }
+
+        if typing_text.line.is_none() {
+            let line = &sequence_state.texts[sequence.sequence_index];
+            if let Some(sfx) = &line.sfx {
+                play_sfx(
+                    &mut commands,
+                    &volume,
+                    asset_server.load(sfx.clone()),
+                    PlaybackSettings::DESPAWN,
+                );
+            }
+            typing_text.char_delay = line.speed.unwrap_or(DEFAULT_CHAR_DELAY);
+            let char_delay = typing_text.char_delay;
+            typing_text
+                .timer
+                .set_duration(Duration::from_secs_f32(char_delay));
+            let typed_text: Arc<str> = line.typed_text().into();
+            text.sections[0].value.clear();
+            text.sections[1].value = typed_text.to_string();
+            typing_text.line = Some(typed_text);
+        }
+
+        typing_text.timer.tick(time.delta());
+        if !typing_text.timer.just_finished() {
+            continue;
+        }
+        let Some(line) = typing_text.line.clone() else {
+            continue;
+        };
+        let mut rest = line[typing_text.typed..].chars();
+        let Some(next_char) = rest.next() else {
+            typing_text.completed = true;
+            continue;
+        };
+        typing_text.typed += next_char.len_utf8();
+        text.sections[0].value.push(next_char);
+        text.sections[1].value.drain(..next_char.len_utf8());
+
+        // Hold on punctuation before typing the next character
+        let delay = pause_after(next_char, rest.next(), typing_text.char_delay);
+        typing_text
+            .timer
+            .set_duration(Duration::from_secs_f32(delay));
+
+        if next_char != ' ' {
+            typewriter.click(&mut commands, next_char);
+        }
+
+        if typing_text.typed == line.len() {
+            typing_text.completed = true;
+        }
+    }
+}
+
+// Delay before the character following `typed`, so sentences breathe like speech. The dots
+// of an ellipsis trail out slowly, with the long pause only after the last one.
+fn pause_after(typed: char, next: Option<char>, char_delay: f32) -> f32 {
+    match (typed, next) {
+        (_, None) => char_delay,
+        ('.', Some('.')) => char_delay * 3.0,
+        ('.' | '!' | '?', _) => char_delay * 6.0,
+        (',' | ';' | ':', _) => char_delay * 3.0,
+        _ => char_delay,
+    }
+}
+
+pub fn animate_sprite(
+    time: Res<Time>,
+    mut query: Query<(&mut TextureAtlas, &mut AnimationTimer, &AnimationIndices)>,
+) {
+    for (mut atlas, mut timer, indices) in &mut query {
+        timer.0.tick(time.delta());
+        if timer.0.just_finished() {
+            atlas.index = if atlas.index == indices.last {
+                indices.first
+            } else {
+                atlas.index + 1
+            };
+        }
+    }
+}
+
+// Spawn an intro's animated background: the script's frame folder if it has one, or else the
+// chapter's sprite sheet
+pub fn spawn_background(
+    parent: &mut ChildBuilder,
+    frames: Option<FrameFolder>,
+    sheet: Handle<Image>,
+    layout: Handle<TextureAtlasLayout>,
+    asset_server: &AssetServer,
+    window: &Window,
+) {
+    let transform = Transform::from_xyz(-window.width() / 2.0, -window.height() / 2.0 + 60.0, 1.0);
+    let sprite = Sprite {
+        custom_size: Some(BACKGROUND_SIZE),
+        anchor: bevy::sprite::Anchor::Center,
+        ..default()
+    };
+    match frames {
+        Some(frames) => {
+            parent.spawn((
+                SpriteBundle {
+                    transform,
+                    sprite,
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                FrameAnimation {
+                    folder: asset_server.load_folder(frames.folder),
+                    frames: Vec::new(),
+                    timer: Timer::from_seconds(1.0 / frames.fps.max(1.0), TimerMode::Repeating),
+                    looping: frames.looping,
+                    index: 0,
+                },
+            ));
+        }
+        None => {
+            parent.spawn((
+                SpriteBundle {
+                    texture: sheet,
+                    transform,
+                    sprite,
+                    ..default()
+                },
+                TextureAtlas { layout, index: 0 },
+                AnimationTimer(Timer::from_seconds(SHEET_FRAME_TIME, TimerMode::Repeating)),
+                AnimationIndices {
+                    first: 0,
+                    last: SHEET_LAST_FRAME,
+                },
+            ));
+        }
+    }
+}
+
+fn animate_frames(
+    mut commands: Commands,
+    time: Res<Time>,
+    folders: Res<Assets<LoadedFolder>>,
+    mut query: Query<(
+        Entity,
+        &mut FrameAnimation,
+        &mut Handle<Image>,
+        &mut Visibility,
+    )>,
+) {
+    for (entity, mut animation, mut image, mut visibility) in &mut query {
+        if animation.frames.is_empty() {
+            let Some(folder) = folders.get(&animation.folder) else {
+                continue;
+            };
+            let mut frames: Vec<Handle<Image>> = folder
+                .handles
+                .iter()
+                .filter_map(|handle| handle.clone().try_typed::<Image>().ok())
+                .collect();
+            if frames.is_empty() {
+                warn!(
+                    "No images in the cutscene frame folder {:?}",
+                    animation.folder.path()
+                );
+                commands.entity(entity).remove::<FrameAnimation>();
+                continue;
+            }
+            frames.sort_by_key(|frame| frame.path().map(|path| path.path().to_path_buf()));
+            *image = frames[0].clone();
+            *visibility = Visibility::Inherited;
+            animation.frames = frames;
+            continue;
+        }
+
+        animation.timer.tick(time.delta());
+        if !animation.timer.just_finished() {
+            continue;
+        }
+        let last = animation.frames.len() - 1;
+        animation.index = match animation.index {
+            index if index < last => index + 1,
+            _ if animation.looping => 0,
+            index => index,
+        };
+        *image = animation.frames[animation.index].clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::test_app::TestApp;
+
+    #[test]
+    fn lines_type_out_a_whole_character_at_a_time() {
+        let line = "Déjà vu… again";
+        let mut app = TestApp::new();
+        app.insert_resource(TextSequenceState::new(
+            vec![CutsceneLine::new(line).with_speed(0.0)],
+            &StoryFlags::default(),
+            &Inventory::default(),
+        ));
+        app.world_mut().run_system_once(
+            |mut commands: Commands, shared_assets: Res<SharedAssets>| {
+                spawn_text_entity(&mut commands, 0, &shared_assets);
+            },
+        );
+
+        for typed in line.char_indices().map(|(index, c)| index + c.len_utf8()) {
+            app.world_mut().run_system_once(type_text);
+            let world = app.world_mut();
+            let text = world
+                .query_filtered::<&Text, With<TypingText>>()
+                .single(world);
+            let shown: String = text
+                .sections
+                .iter()
+                .filter(|section| section.style.color != Color::NONE)
+                .map(|section| section.value.as_str())
+                .collect();
+            let laid_out: String = text
+                .sections
+                .iter()
+                .map(|section| section.value.as_str())
+                .collect();
+            assert_eq!(shown, line[..typed]);
+            assert_eq!(laid_out, line);
+        }
+        let world = app.world_mut();
+        assert!(world.query::<&TypingText>().single(world).completed());
+    }
+}
@@ -0,0 +1,201 @@
+// The daily run. Everyone playing on the same day gets the same seed and the same one or two
+// modifiers, both worked out from the date. How far each day's run got is kept in the profile's
+// folder, apart from the save, so starting a normal run never loses it, and each day's best goes
+// to the online leaderboard when there is one.
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::GameState;
+use crate::chapters::CHAPTERS;
+use crate::combat::{CardType, FightEvent};
+use crate::deck::Deck;
+use crate::gold::Gold;
+use crate::leaderboard::{Board, Leaderboards};
+use crate::profile::{profile_path, Profiles};
+use crate::rng::RunRng;
+use crate::run_effects::{RunEffect, AIR_START_CARDS, WEALTHY_GOLD};
+use crate::save::SaveData;
+
+const DAILY_FILE: &str = "daily.ron";
+
+// Days of results kept, newest first
+const DAYS_KEPT: usize = 14;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+// Mixed into the day's seed for picking its modifiers, so they don't follow the run's shuffles
+const MODIFIER_SALT: u64 = 0x5EED_DA11;
+
+const MODIFIERS: [RunEffect; 4] = [
+    RunEffect::ToughEnemies,
+    RunEffect::Frail,
+    RunEffect::AirStart,
+    RunEffect::Wealthy,
+];
+
+pub struct DailyChallenge {
+    // The UTC date, like 2026-10-16
+    pub date: String,
+    pub seed: u64,
+    pub modifiers: Vec<RunEffect>,
+}
+
+impl DailyChallenge {
+    pub fn today() -> Self {
+        let days = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs() / SECONDS_PER_DAY);
+        let (year, month, day) = date_from_days(days);
+        // The day number itself, as the run's random numbers scramble their seed anyway
+        let seed = days;
+
+        let mut modifiers = MODIFIERS.to_vec();
+        let mut rng = RunRng::from_seed(seed ^ MODIFIER_SALT);
+        rng.shuffle(&mut modifiers);
+        modifiers.truncate(1 + rng.below(2));
+
+        Self {
+            date: format!("{year:04}-{month:02}-{day:02}"),
+            seed,
+            modifiers,
+        }
+    }
+
+    // Set up a freshly cleared run as the day's run
+    pub fn begin(&self, rng: &mut RunRng, deck: &mut Deck, gold: &mut Gold) {
+        *rng = RunRng::from_seed(self.seed);
+        for modifier in &self.modifiers {
+            match modifier {
+                RunEffect::AirStart => {
+                    for _ in 0..AIR_START_CARDS {
+                        deck.add(CardType::Air);
+                    }
+                }
+                RunEffect::Wealthy => gold.earn(WEALTHY_GOLD),
+                _ => {}
+            }
+        }
+
+        let mut results = DailyResults::load();
+        if !results.days.iter().any(|result| result.date == self.date) {
+            results.days.insert(
+                0,
+                DailyResult {
+                    date: self.date.clone(),
+                    modifiers: self.modifiers.clone(),
+                    chapters_cleared: 0,
+                },
+            );
+            results.days.truncate(DAYS_KEPT);
+            results.save();
+        }
+    }
+}
+
+// The year, month and day `days` after 1970-01-01
+fn date_from_days(days: u64) -> (u64, u64, u64) {
+    // Counted from 0000-03-01, so leap days fall at the end of each year
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct DailyResults {
+    pub days: Vec<DailyResult>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DailyResult {
+    pub date: String,
+    pub modifiers: Vec<RunEffect>,
+    // The most chapters won on any try that day
+    pub chapters_cleared: usize,
+}
+
+impl DailyResult {
+    pub fn summary(&self) -> String {
+        match self.chapters_cleared {
+            0 => "No chapters cleared".to_string(),
+            cleared if cleared == CHAPTERS.len() => "Finished the run".to_string(),
+            cleared => format!("Cleared {cleared} of {} chapters", CHAPTERS.len()),
+        }
+    }
+}
+
+impl DailyResults {
+    // The active profile's results, or none if they can't be read
+    pub fn load() -> Self {
+        fs::read_to_string(profile_path(DAILY_FILE))
+            .ok()
+            .and_then(|contents| ron::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        let contents = match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(contents) => contents,
+            Err(err) => {
+                warn!("Could not serialize the daily results: {err}");
+                return;
+            }
+        };
+        if let Err(err) = fs::write(profile_path(DAILY_FILE), contents) {
+            warn!("Could not write {DAILY_FILE}: {err}");
+        }
+    }
+}
+
+pub fn daily_plugin(app: &mut App) {
+    app.add_systems(Update, record_daily_result);
+}
+
+// Each chapter won on a daily run counts towards that day's result
+fn record_daily_result(
+    mut fight_events: EventReader<FightEvent>,
+    game_state: Res<State<GameState>>,
+    save_data: Res<SaveData>,
+    mut leaderboards: ResMut<Leaderboards>,
+    profiles: Res<Profiles>,
+) {
+    for event in fight_events.read() {
+        let (FightEvent::Won(_), Some(date)) = (event, &save_data.daily) else {
+            continue;
+        };
+        let Some(chapter) = CHAPTERS
+            .iter()
+            .position(|chapter| chapter.fight == *game_state.get())
+        else {
+            continue;
+        };
+        let mut results = DailyResults::load();
+        let Some(result) = results.days.iter_mut().find(|result| result.date == *date) else {
+            continue;
+        };
+        if chapter + 1 > result.chapters_cleared {
+            result.chapters_cleared = chapter + 1;
+            leaderboards.submit(
+                Board::Daily(date.clone()),
+                profiles.active_name(),
+                result.chapters_cleared as f32,
+            );
+            results.save();
+        }
+    }
+}
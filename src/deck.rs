@@ -0,0 +1,121 @@
+// The cards collected over a run. Cards earned from fights wait in `earned` until the player
+// adds them on the deck screen, and the deck is kept in the save.
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::combat::CardType;
+
+// Every run starts with these
+const STARTING_DECK: [CardType; 4] = [
+    CardType::Earth,
+    CardType::Crystal,
+    CardType::Fire,
+    CardType::Ice,
+];
+
+// Removing cards can't shrink the deck below this
+pub const MIN_DECK_SIZE: usize = 3;
+
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Deck {
+    cards: Vec<CardType>,
+    earned: Vec<CardType>,
+    // Card types upgraded at a campfire. Every copy of one is upgraded, including later ones.
+    upgraded: Vec<CardType>,
+    // Cards paid to have removed at the shop this run, which raises the next one's price
+    removals_bought: u32,
+}
+
+impl Default for Deck {
+    fn default() -> Self {
+        Self {
+            cards: STARTING_DECK.to_vec(),
+            earned: Vec::new(),
+            upgraded: Vec::new(),
+            removals_bought: 0,
+        }
+    }
+}
+
+impl Deck {
+    // A deck with just these cards, for fights outside a run
+    pub fn from_cards(cards: Vec<CardType>) -> Self {
+        Self {
+            cards,
+            earned: Vec::new(),
+            upgraded: Vec::new(),
+            removals_bought: 0,
+        }
+    }
+
+    pub fn cards(&self) -> &[CardType] {
+        &self.cards
+    }
+
+    pub fn earned(&self) -> &[CardType] {
+        &self.earned
+    }
+
+    pub fn upgraded(&self) -> &[CardType] {
+        &self.upgraded
+    }
+
+    pub fn is_upgraded(&self, card_type: CardType) -> bool {
+        self.upgraded.contains(&card_type)
+    }
+
+    // Upgrade a card type, unless it already is
+    pub fn upgrade(&mut self, card_type: CardType) -> bool {
+        if self.is_upgraded(card_type) {
+            return false;
+        }
+        self.upgraded.push(card_type);
+        true
+    }
+
+    // Set a newly won card aside for the deck screen
+    pub fn earn(&mut self, card_type: CardType) {
+        self.earned.push(card_type);
+    }
+
+    // Put a card straight into the deck, as a run begins or when a curse is forced on it
+    pub fn add(&mut self, card_type: CardType) {
+        self.cards.push(card_type);
+    }
+
+    // Move an earned card into the deck
+    pub fn add_earned(&mut self, index: usize) {
+        if index < self.earned.len() {
+            let card_type = self.earned.remove(index);
+            self.cards.push(card_type);
+        }
+    }
+
+    // Take a card out of the deck, unless that would leave it too small
+    pub fn remove(&mut self, index: usize) -> bool {
+        if index >= self.cards.len() || self.cards.len() <= MIN_DECK_SIZE {
+            return false;
+        }
+        self.cards.remove(index);
+        true
+    }
+
+    pub fn removals_bought(&self) -> u32 {
+        self.removals_bought
+    }
+
+    // Take a card out that was paid for at the shop
+    pub fn buy_removal(&mut self, index: usize) -> bool {
+        if !self.remove(index) {
+            return false;
+        }
+        self.removals_bought += 1;
+        true
+    }
+
+    // Back to the starting deck, for a new run
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
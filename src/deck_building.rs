@@ -0,0 +1,394 @@
+// Deck screen shown between fights. Drag newly earned cards up into the deck, or drag a deck
+// card onto the fire to get rid of it (a limited number per visit, and never below the
+// minimum deck size), then continue to the shop and campfire before the next part of the story.
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+use bevy::window::PrimaryWindow;
+
+use super::GameState;
+use crate::combat::{CardType, Encounter};
+use crate::deck::{Deck, MIN_DECK_SIZE};
+use crate::shared_assets::SharedAssets;
+use crate::theme::Theme;
+use crate::ui;
+
+const REMOVALS_PER_VISIT: u32 = 1;
+
+const CARD_WIDTH: f32 = 120.0;
+const CARD_HEIGHT: f32 = 167.0;
+
+const ZONE_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.05);
+const REMOVE_ZONE_COLOR: Color = Color::srgba(0.8, 0.2, 0.1, 0.2);
+
+#[derive(Component)]
+struct OnDeckScreen;
+
+// Everything below the background, rebuilt whenever the deck changes
+#[derive(Component)]
+struct DeckScreenContent;
+
+#[derive(Component, Clone, Copy)]
+enum CardSlot {
+    Deck(usize),
+    Earned(usize),
+}
+
+#[derive(Component, Clone, Copy, PartialEq)]
+enum DropZone {
+    Deck,
+    Remove,
+}
+
+// The copy of a card that follows the cursor while it is dragged
+#[derive(Component)]
+struct DragGhost;
+
+#[derive(Component)]
+struct ContinueButton;
+
+#[derive(Resource)]
+struct DeckVisit {
+    removals_left: u32,
+    message: Option<String>,
+    dragging: Option<CardSlot>,
+}
+
+pub fn deck_building_plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::DeckBuilding), deck_building_setup)
+        .add_systems(
+            Update,
+            (
+                rebuild_deck_screen,
+                start_drag,
+                move_drag_ghost,
+                drop_card,
+                continue_button,
+            )
+                .chain()
+                .run_if(in_state(GameState::DeckBuilding)),
+        );
+}
+
+fn deck_building_setup(mut commands: Commands) {
+    commands.insert_resource(DeckVisit {
+        removals_left: REMOVALS_PER_VISIT,
+        message: None,
+        dragging: None,
+    });
+
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            background_color: Color::srgb(0.05, 0.05, 0.08).into(),
+            ..default()
+        },
+        OnDeckScreen,
+        StateScoped(GameState::DeckBuilding),
+    ));
+}
+
+fn rebuild_deck_screen(
+    mut commands: Commands,
+    deck: Res<Deck>,
+    visit: Res<DeckVisit>,
+    root_query: Query<Entity, (With<OnDeckScreen>, Without<DeckScreenContent>)>,
+    content_query: Query<Entity, With<DeckScreenContent>>,
+    shared_assets: Res<SharedAssets>,
+    theme: Res<Theme>,
+) {
+    if !deck.is_changed() && !visit.is_changed() {
+        return;
+    }
+    // Dragging only changes the ghost, not the screen
+    if visit.dragging.is_some() && !deck.is_changed() {
+        return;
+    }
+    let Ok(root) = root_query.get_single() else {
+        return;
+    };
+    for entity in content_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let text_style = theme.text_style(30.0);
+
+    commands.entity(root).with_children(|parent| {
+        parent
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        row_gap: Val::Px(20.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                DeckScreenContent,
+            ))
+            .with_children(|parent| {
+                parent.spawn(ui::title_text(
+                    &theme,
+                    format!("Your Deck ({} cards)", deck.cards().len()),
+                ));
+
+                spawn_card_row(
+                    parent,
+                    DropZone::Deck,
+                    deck.cards()
+                        .iter()
+                        .enumerate()
+                        .map(|(i, card_type)| (CardSlot::Deck(i), *card_type)),
+                    &shared_assets,
+                );
+
+                if !deck.earned().is_empty() {
+                    parent.spawn(TextBundle::from_section(
+                        "New cards - drag them into your deck",
+                        text_style.clone(),
+                    ));
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                column_gap: Val::Px(16.0),
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            for (i, card_type) in deck.earned().iter().enumerate() {
+                                parent.spawn(card_image(
+                                    CardSlot::Earned(i),
+                                    *card_type,
+                                    &shared_assets,
+                                ));
+                            }
+                        });
+                }
+
+                // Drop a deck card here to remove it
+                parent
+                    .spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Px(400.0),
+                                height: Val::Px(80.0),
+                                align_items: AlignItems::Center,
+                                justify_content: JustifyContent::Center,
+                                ..default()
+                            },
+                            background_color: REMOVE_ZONE_COLOR.into(),
+                            ..default()
+                        },
+                        DropZone::Remove,
+                        RelativeCursorPosition::default(),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            format!("Burn a card ({} left)", visit.removals_left),
+                            text_style.clone(),
+                        ));
+                    });
+
+                if let Some(message) = &visit.message {
+                    parent.spawn(TextBundle::from_section(
+                        message.clone(),
+                        TextStyle {
+                            font_size: 24.0,
+                            color: Color::srgb(1.0, 0.4, 0.3),
+                            ..default()
+                        },
+                    ));
+                }
+
+                ui::styled_button(
+                    parent,
+                    &theme,
+                    Style {
+                        width: Val::Px(250.0),
+                        height: Val::Px(65.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    "Continue",
+                    text_style.clone(),
+                    ContinueButton,
+                );
+            });
+    });
+}
+
+fn spawn_card_row(
+    parent: &mut ChildBuilder,
+    zone: DropZone,
+    cards: impl Iterator<Item = (CardSlot, CardType)>,
+    shared_assets: &SharedAssets,
+) {
+    parent
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    min_width: Val::Px(CARD_WIDTH * 4.0),
+                    min_height: Val::Px(CARD_HEIGHT + 20.0),
+                    padding: UiRect::all(Val::Px(10.0)),
+                    column_gap: Val::Px(16.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: ZONE_COLOR.into(),
+                ..default()
+            },
+            zone,
+            RelativeCursorPosition::default(),
+        ))
+        .with_children(|parent| {
+            for (slot, card_type) in cards {
+                parent.spawn(card_image(slot, card_type, shared_assets));
+            }
+        });
+}
+
+fn card_image(slot: CardSlot, card_type: CardType, shared_assets: &SharedAssets) -> impl Bundle {
+    let (image, atlas) = shared_assets.card(card_type);
+    (
+        ImageBundle {
+            style: Style {
+                width: Val::Px(CARD_WIDTH),
+                height: Val::Px(CARD_HEIGHT),
+                ..default()
+            },
+            image,
+            ..default()
+        },
+        atlas,
+        Interaction::None,
+        slot,
+        card_type,
+    )
+}
+
+fn start_drag(
+    mut commands: Commands,
+    mut visit: ResMut<DeckVisit>,
+    mut card_query: Query<(&Interaction, &CardSlot, &CardType, &mut UiImage), Changed<Interaction>>,
+    root_query: Query<Entity, (With<OnDeckScreen>, Without<DeckScreenContent>)>,
+    shared_assets: Res<SharedAssets>,
+) {
+    if visit.dragging.is_some() {
+        return;
+    }
+    let Ok(root) = root_query.get_single() else {
+        return;
+    };
+
+    for (interaction, slot, card_type, mut image) in card_query.iter_mut() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        visit.dragging = Some(*slot);
+        visit.message = None;
+        // Leave a faded card behind while its ghost is carried around
+        image.color = Color::srgba(1.0, 1.0, 1.0, 0.3);
+
+        let (ghost_image, ghost_atlas) = shared_assets.card(*card_type);
+        commands.entity(root).with_children(|parent| {
+            parent.spawn((
+                ImageBundle {
+                    style: Style {
+                        width: Val::Px(CARD_WIDTH),
+                        height: Val::Px(CARD_HEIGHT),
+                        position_type: PositionType::Absolute,
+                        ..default()
+                    },
+                    image: ghost_image,
+                    z_index: ZIndex::Global(5),
+                    ..default()
+                },
+                ghost_atlas,
+                DragGhost,
+            ));
+        });
+        break;
+    }
+}
+
+fn move_drag_ghost(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut ghost_query: Query<&mut Style, With<DragGhost>>,
+) {
+    let Some(cursor) = windows.get_single().ok().and_then(Window::cursor_position) else {
+        return;
+    };
+    for mut style in ghost_query.iter_mut() {
+        style.left = Val::Px(cursor.x - CARD_WIDTH / 2.0);
+        style.top = Val::Px(cursor.y - CARD_HEIGHT / 2.0);
+    }
+}
+
+fn drop_card(
+    mut commands: Commands,
+    mouse: Res<ButtonInput<MouseButton>>,
+    zone_query: Query<(&DropZone, &RelativeCursorPosition)>,
+    ghost_query: Query<Entity, With<DragGhost>>,
+    mut visit: ResMut<DeckVisit>,
+    mut deck: ResMut<Deck>,
+) {
+    if !mouse.just_released(MouseButton::Left) {
+        return;
+    }
+    let Some(slot) = visit.dragging.take() else {
+        return;
+    };
+    for ghost in ghost_query.iter() {
+        commands.entity(ghost).despawn_recursive();
+    }
+
+    let zone = zone_query
+        .iter()
+        .find(|(_, cursor)| cursor.mouse_over())
+        .map(|(zone, _)| *zone);
+
+    match (slot, zone) {
+        (CardSlot::Earned(index), Some(DropZone::Deck)) => deck.add_earned(index),
+        (CardSlot::Deck(index), Some(DropZone::Remove)) => {
+            if visit.removals_left == 0 {
+                visit.message = Some("You can't burn any more cards this time".to_string());
+            } else if deck.remove(index) {
+                visit.removals_left -= 1;
+            } else {
+                visit.message = Some(format!("Your deck needs at least {} cards", MIN_DECK_SIZE));
+            }
+        }
+        _ => {}
+    }
+    // Rebuild even when nothing moved, to restore the faded card
+    visit.set_changed();
+}
+
+fn continue_button(
+    mut interaction_query: ui::ChangedButtons<ContinueButton>,
+    encounter: Res<Encounter>,
+    mut game_state: ResMut<NextState<GameState>>,
+    theme: Res<Theme>,
+) {
+    for (interaction, mut color) in &mut interaction_query {
+        match *interaction {
+            // Endless waves follow each other without a rest
+            Interaction::Pressed => game_state.set(match encounter.next_state {
+                GameState::Endless => GameState::Endless,
+                _ => GameState::Shop,
+            }),
+            Interaction::Hovered => *color = theme.button_hovered.into(),
+            Interaction::None => *color = theme.button.into(),
+        }
+    }
+}
@@ -0,0 +1,216 @@
+// Attract mode. When the main menu sits untouched for a while, the computer plays a fixed demo
+// fight with the on-screen text hidden, until any key, click or mouse movement brings the menu
+// back. The run's deck, random state, effects and health are set aside for the demo and put back
+// afterwards.
+use bevy::input::mouse::MouseMotion;
+use bevy::prelude::*;
+
+use super::GameState;
+use crate::combat::{
+    add_autoplay, add_combat_state, combat_setup, CardType, Encounter, EnemyMove, LootTable,
+    MonsterSpec, PlayerState,
+};
+use crate::deck::Deck;
+use crate::rng::RunRng;
+use crate::run_effects::RunEffects;
+use crate::story::StoryFlag;
+use crate::theme::Theme;
+
+const IDLE_SECONDS: f32 = 30.0;
+
+// The demo plays out the same way every time
+const DEMO_SEED: u64 = 30;
+const DEMO_DECK: [CardType; 6] = [
+    CardType::Fire,
+    CardType::Ice,
+    CardType::Earth,
+    CardType::Crystal,
+    CardType::Fire,
+    CardType::Air,
+];
+
+#[derive(Resource)]
+struct MenuIdle(Timer);
+
+// The run's own deck, random state, effects and health while the demo uses its own
+#[derive(Resource)]
+struct SetAside {
+    deck: Deck,
+    rng: RunRng,
+    run_effects: RunEffects,
+    player_state: PlayerState,
+}
+
+#[derive(Component)]
+struct DemoBanner;
+
+// Text hidden for the demo, with how it was shown before
+#[derive(Component)]
+struct HiddenByDemo(Visibility);
+
+pub fn demo_plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Menu), reset_menu_idle)
+        .add_systems(Update, watch_menu_idle.run_if(in_state(GameState::Menu)))
+        .add_systems(OnEnter(GameState::Demo), demo_setup.before(combat_setup))
+        .add_systems(
+            Update,
+            (hide_ui_text, leave_demo_on_input).run_if(in_state(GameState::Demo)),
+        )
+        .add_systems(OnExit(GameState::Demo), (restore_run, show_ui_text));
+    add_combat_state(app, GameState::Demo);
+    add_autoplay(app, GameState::Demo, GameState::Menu);
+}
+
+fn reset_menu_idle(mut commands: Commands) {
+    commands.insert_resource(MenuIdle(Timer::from_seconds(IDLE_SECONDS, TimerMode::Once)));
+}
+
+fn any_input(
+    keyboard: &ButtonInput<KeyCode>,
+    mouse: &ButtonInput<MouseButton>,
+    motion: &mut EventReader<MouseMotion>,
+) -> bool {
+    // Read every motion event, so none are left over for the next check
+    let moved = motion.read().count() > 0;
+    moved
+        || keyboard.get_just_pressed().next().is_some()
+        || mouse.get_just_pressed().next().is_some()
+}
+
+fn watch_menu_idle(
+    time: Res<Time>,
+    mut idle: ResMut<MenuIdle>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut motion: EventReader<MouseMotion>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    if any_input(&keyboard, &mouse, &mut motion) {
+        idle.0.reset();
+        return;
+    }
+    if idle.0.tick(time.delta()).just_finished() {
+        game_state.set(GameState::Demo);
+    }
+}
+
+fn demo_setup(
+    mut commands: Commands,
+    mut deck: ResMut<Deck>,
+    mut rng: ResMut<RunRng>,
+    mut run_effects: ResMut<RunEffects>,
+    mut player_state: ResMut<PlayerState>,
+    theme: Res<Theme>,
+) {
+    commands.insert_resource(SetAside {
+        deck: std::mem::replace(&mut *deck, Deck::from_cards(DEMO_DECK.to_vec())),
+        rng: std::mem::replace(&mut *rng, RunRng::from_seed(DEMO_SEED)),
+        run_effects: std::mem::take(&mut *run_effects),
+        player_state: std::mem::take(&mut *player_state),
+    });
+
+    // A forest fight like the first chapter's, which rewards nothing
+    commands.insert_resource(Encounter {
+        background: "textures/1.png",
+        monsters: vec![
+            MonsterSpec {
+                texture: "textures/monster.png",
+                health: 30.0,
+                damage: 8.0,
+                size: 250.0,
+                offset: Vec2::new(1.0 / 8.0, 0.0),
+                lift: -75.0,
+                label_height: 120.0,
+                health_bar_depth: -100.0,
+                moves: vec![EnemyMove::Attack(8.0), EnemyMove::Defend(6.0)],
+                loot: LootTable::default(),
+            },
+            MonsterSpec {
+                texture: "textures/monster_2.png",
+                health: 30.0,
+                damage: 6.0,
+                size: 250.0,
+                offset: Vec2::new(-1.0 / 8.0, 0.0),
+                lift: -75.0,
+                label_height: 120.0,
+                health_bar_depth: -100.0,
+                moves: Vec::new(),
+                loot: LootTable::default(),
+            },
+        ],
+        next_state: GameState::Menu,
+        victory_flag: StoryFlag::ForestCleared,
+        wounded_flag: None,
+        reward: None,
+        gold_reward: 0,
+        card_rewards: Vec::new(),
+        enrage_after: None,
+        ritual: None,
+        effect_reward: None,
+        intro: None,
+    });
+
+    commands.spawn((
+        TextBundle::from_section("DEMO - press any key", theme.text_style(30.0)).with_style(
+            Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(20.0),
+                left: Val::Px(20.0),
+                ..default()
+            },
+        ),
+        DemoBanner,
+        StateScoped(GameState::Demo),
+    ));
+}
+
+// The UI text on screen that the demo hasn't hidden yet, apart from its own banner
+type ShownUiText<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static mut Visibility),
+    (
+        With<Text>,
+        With<Node>,
+        Without<DemoBanner>,
+        Without<HiddenByDemo>,
+    ),
+>;
+
+// Keep buttons and HUD text out of the demo; floating numbers and intents stay in the world
+fn hide_ui_text(mut commands: Commands, mut text_query: ShownUiText) {
+    for (entity, mut visibility) in text_query.iter_mut() {
+        commands.entity(entity).insert(HiddenByDemo(*visibility));
+        *visibility = Visibility::Hidden;
+    }
+}
+
+// Text that outlives the demo, like the gold counter, comes back as it was
+fn show_ui_text(
+    mut commands: Commands,
+    mut text_query: Query<(Entity, &mut Visibility, &HiddenByDemo)>,
+) {
+    for (entity, mut visibility, hidden) in text_query.iter_mut() {
+        *visibility = hidden.0;
+        commands.entity(entity).remove::<HiddenByDemo>();
+    }
+}
+
+fn leave_demo_on_input(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut motion: EventReader<MouseMotion>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    if any_input(&keyboard, &mouse, &mut motion) {
+        game_state.set(GameState::Menu);
+    }
+}
+
+fn restore_run(mut commands: Commands, set_aside: Res<SetAside>) {
+    commands.insert_resource(set_aside.deck.clone());
+    commands.insert_resource(set_aside.rng.clone());
+    commands.insert_resource(set_aside.run_effects.clone());
+    commands.insert_resource(set_aside.player_state.clone());
+    commands.remove_resource::<SetAside>();
+}
@@ -0,0 +1,117 @@
+// Adaptive difficulty, off unless the player turns it on from the stats screen. After each fight
+// the enemies' health and damage are nudged up for a flawless win and down for a close call or
+// a loss, within fixed limits. Recent results and the current factor are kept in the save.
+// Merciful mode, also off by default, spares a player who dies at a price, so a lost fight can
+// be tried again instead of the run ending.
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::GameState;
+use crate::combat::FightEvent;
+
+const MIN_FACTOR: f32 = 0.75;
+const MAX_FACTOR: f32 = 1.25;
+
+// Winning with at most this share of health left counts as a close call
+const CLOSE_CALL_HEALTH: f32 = 0.25;
+
+// Results kept for the stats screen
+const HISTORY_LENGTH: usize = 5;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum FightResult {
+    Flawless,
+    Won,
+    CloseCall,
+    Lost,
+}
+
+impl FightResult {
+    pub fn label(self) -> &'static str {
+        match self {
+            FightResult::Flawless => "Flawless",
+            FightResult::Won => "Won",
+            FightResult::CloseCall => "Close call",
+            FightResult::Lost => "Lost",
+        }
+    }
+
+    // How far the factor moves after a fight like this
+    fn nudge(self) -> f32 {
+        match self {
+            FightResult::Flawless => 0.05,
+            FightResult::Won => 0.0,
+            FightResult::CloseCall => -0.05,
+            FightResult::Lost => -0.1,
+        }
+    }
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Difficulty {
+    pub adaptive: bool,
+    pub merciful: bool,
+    factor: f32,
+    // Newest last
+    history: Vec<FightResult>,
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Self {
+            adaptive: false,
+            merciful: false,
+            factor: 1.0,
+            history: Vec::new(),
+        }
+    }
+}
+
+impl Difficulty {
+    // What enemy health and damage are multiplied by
+    pub fn enemy_scale(&self) -> f32 {
+        if self.adaptive {
+            self.factor
+        } else {
+            1.0
+        }
+    }
+
+    pub fn history(&self) -> &[FightResult] {
+        &self.history
+    }
+
+    fn record(&mut self, result: FightResult) {
+        self.history.push(result);
+        if self.history.len() > HISTORY_LENGTH {
+            self.history.remove(0);
+        }
+        if self.adaptive {
+            self.factor = (self.factor + result.nudge()).clamp(MIN_FACTOR, MAX_FACTOR);
+        }
+    }
+}
+
+pub fn difficulty_plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        // The demo's fights say nothing about the player
+        record_fights.run_if(not(in_state(GameState::Demo))),
+    );
+}
+
+fn record_fights(mut fight_events: EventReader<FightEvent>, mut difficulty: ResMut<Difficulty>) {
+    for event in fight_events.read() {
+        let result = match *event {
+            FightEvent::Won(health_left) if health_left >= 1.0 => FightResult::Flawless,
+            FightEvent::Won(health_left) if health_left <= CLOSE_CALL_HEALTH => {
+                FightResult::CloseCall
+            }
+            FightEvent::Won(_) => FightResult::Won,
+            FightEvent::Lost => FightResult::Lost,
+            _ => continue,
+        };
+        difficulty.record(result);
+    }
+}
@@ -0,0 +1,789 @@
+// Online duels, where two players battle with their decks and every card resolves by the same
+// rules as in a fight. One player hosts on `DUEL_PORT` and the other joins by typing the host's
+// address. Play is lockstep: both sides deal the same duel from a seed the host picks, and only
+// the moves are sent, one RON message per line. Each side checks the duel against the other's
+// checksum at the end of every turn, and a mismatch ends it as a desync. A turn left too long
+// ends itself when its timer runs out.
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::ButtonState;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::GameState;
+use crate::combat::{
+    CardType, DrawRules, TriggeredEffect, TurnState, TurnTrigger, BLOCK_DECAY, OPENING_HAND_SIZE,
+    PLAYER_BASE_HEALTH,
+};
+use crate::deck::Deck;
+use crate::rng::RunRng;
+use crate::shared_assets::SharedAssets;
+use crate::theme::Theme;
+use crate::ui;
+
+const DUEL_PORT: u16 = 7878;
+
+// Bumped whenever the messages or the card rules change, so mismatched games don't duel
+const PROTOCOL_VERSION: u32 = 2;
+
+// How long a turn may last before it ends by itself
+const TURN_SECONDS: f32 = 45.0;
+
+// How often a host waiting for a player checks whether they've stopped hosting
+const ACCEPT_POLL: Duration = Duration::from_millis(100);
+
+const MAX_ADDRESS_LENGTH: usize = 64;
+
+// 64-bit FNV-1a, for the turn checksum
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+// The hosting player is side 0 and takes the first turn
+const HOST: usize = 0;
+
+const WAITING_COLOR: Color = Color::srgb(0.5, 0.5, 0.5);
+
+#[derive(Serialize, Deserialize, Debug)]
+enum Message {
+    // Sent by both sides once connected
+    Hello { version: u32, deck: Vec<CardType> },
+    // From the host once both decks are known: the seed both duels are dealt from
+    Start { seed: u64 },
+    // The player whose turn it is played the card at this place in their hand
+    Play { index: usize },
+    // The player whose turn it is ended it, with the checksum of the duel afterwards
+    EndTurn { checksum: u64 },
+    Leave,
+}
+
+enum LinkEvent {
+    Connected,
+    Received(Message),
+    // The connection failed or was lost, and why
+    Closed(String),
+}
+
+// The connection to the other player. It's worked on background threads so a slow network
+// never stalls a frame, and dropping it hangs up.
+struct Link {
+    outgoing: Sender<Message>,
+    incoming: Mutex<Receiver<LinkEvent>>,
+}
+
+enum Connect {
+    Host,
+    Join(String),
+}
+
+impl Link {
+    fn open(connect: Connect) -> Self {
+        let (outgoing, to_send) = mpsc::channel();
+        let (events, incoming) = mpsc::channel();
+        thread::spawn(move || {
+            let stream = match connect {
+                Connect::Host => TcpListener::bind(("0.0.0.0", DUEL_PORT))
+                    .and_then(|listener| accept(listener, &to_send)),
+                Connect::Join(address) => TcpStream::connect(with_port(&address)),
+            };
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(err) => {
+                    let _ = events.send(LinkEvent::Closed(format!("Could not connect: {err}")));
+                    return;
+                }
+            };
+            let reader = match stream.try_clone() {
+                Ok(reader) => reader,
+                Err(err) => {
+                    let _ = events.send(LinkEvent::Closed(format!("Could not connect: {err}")));
+                    return;
+                }
+            };
+            let _ = events.send(LinkEvent::Connected);
+            thread::spawn(move || read_messages(reader, events));
+
+            for message in to_send.iter() {
+                let Ok(line) = ron::to_string(&message) else {
+                    continue;
+                };
+                if writeln!(stream, "{line}").is_err() {
+                    break;
+                }
+            }
+            let _ = stream.shutdown(Shutdown::Both);
+        });
+        Self {
+            outgoing,
+            incoming: Mutex::new(incoming),
+        }
+    }
+
+    fn send(&self, message: Message) {
+        let _ = self.outgoing.send(message);
+    }
+
+    fn poll(&self) -> Vec<LinkEvent> {
+        self.incoming
+            .lock()
+            .map(|incoming| incoming.try_iter().collect())
+            .unwrap_or_default()
+    }
+}
+
+// Wait for a player to join, giving up once the link is dropped
+fn accept(listener: TcpListener, to_send: &Receiver<Message>) -> io::Result<TcpStream> {
+    listener.set_nonblocking(true)?;
+    loop {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                stream.set_nonblocking(false)?;
+                return Ok(stream);
+            }
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                if let Err(TryRecvError::Disconnected) = to_send.try_recv() {
+                    return Err(io::Error::other("stopped hosting"));
+                }
+                thread::sleep(ACCEPT_POLL);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+fn read_messages(stream: TcpStream, events: Sender<LinkEvent>) {
+    let mut reader = BufReader::new(stream);
+    let reason = loop {
+        let mut line = String::new();
+        match reader.read_line(&mut line) {
+            Ok(0) => break "The other player disconnected".to_string(),
+            Ok(_) => match ron::from_str(&line) {
+                Ok(message) => {
+                    if events.send(LinkEvent::Received(message)).is_err() {
+                        return;
+                    }
+                }
+                Err(err) => break format!("Unreadable message from the other player: {err}"),
+            },
+            Err(err) => break format!("Connection lost: {err}"),
+        }
+    };
+    let _ = events.send(LinkEvent::Closed(reason));
+}
+
+// Addresses typed without a port use the duel port
+fn with_port(address: &str) -> String {
+    if address.contains(':') {
+        address.to_string()
+    } else {
+        format!("{address}:{DUEL_PORT}")
+    }
+}
+
+// One player in a duel
+#[derive(Serialize, Clone)]
+struct Duelist {
+    health: f32,
+    block: f32,
+    hand: Vec<CardType>,
+    turn: TurnState,
+}
+
+impl Duelist {
+    // Damage goes through block first
+    fn hit(&mut self, damage: f32) {
+        let absorbed = self.block.min(damage);
+        self.block -= absorbed;
+        self.health = (self.health - (damage - absorbed)).max(0.0);
+    }
+}
+
+// The duel itself, run the same on both sides from the same seed and moves
+struct Duel {
+    sides: [Duelist; 2],
+    rng: RunRng,
+    // The side whose turn it is
+    active: usize,
+    turn_number: u32,
+}
+
+impl Duel {
+    // Decks are given host's first
+    fn new(seed: u64, decks: [Vec<CardType>; 2]) -> Self {
+        let mut rng = RunRng::from_seed(seed);
+        let mut sides = decks.map(|mut draw_pile| {
+            rng.shuffle(&mut draw_pile);
+            let hand = draw_pile.split_off(draw_pile.len().saturating_sub(OPENING_HAND_SIZE));
+            Duelist {
+                health: PLAYER_BASE_HEALTH,
+                block: 0.0,
+                hand,
+                turn: TurnState::new(draw_pile),
+            }
+        });
+        // The opening hand is the first turn's draw
+        sides[HOST].turn.start_turn(0, &mut rng);
+        Self {
+            sides,
+            rng,
+            active: HOST,
+            turn_number: 1,
+        }
+    }
+
+    // Play the card at `index` in the active hand. Damage hits the other player, and healing
+    // goes to the one who played it. Returns `None` if there's no such card.
+    fn play(&mut self, index: usize) -> Option<CardType> {
+        let side = self.active;
+        let cards_in_hand = self.sides[side].hand.len();
+        if index >= cards_in_hand {
+            return None;
+        }
+        let card_type = self.sides[side].hand.remove(index);
+        let opponent_at_full_health = self.sides[1 - side].health >= PLAYER_BASE_HEALTH;
+        let damage = self.sides[side]
+            .turn
+            .play(card_type, cards_in_hand, opponent_at_full_health);
+        if damage < 0.0 {
+            let player = &mut self.sides[side];
+            player.health = (player.health - damage).min(PLAYER_BASE_HEALTH);
+        } else {
+            self.sides[1 - side].hit(damage);
+        }
+        Some(card_type)
+    }
+
+    fn apply(&mut self, side: usize, effects: Vec<TriggeredEffect>) {
+        for effect in effects {
+            match effect {
+                TriggeredEffect::DamageAllEnemies(damage) => self.sides[1 - side].hit(damage),
+                TriggeredEffect::GainBlock(amount) => self.sides[side].block += amount,
+            }
+        }
+    }
+
+    // Hand the turn over: the hand is discarded, any Air cards are added for next time, and the
+    // other player's block decays before they draw
+    fn end_turn(&mut self) {
+        let side = self.active;
+        let effects = self.sides[side].turn.fire(TurnTrigger::EndOfTurn);
+        self.apply(side, effects);
+        let player = &mut self.sides[side];
+        let hand = std::mem::take(&mut player.hand);
+        let air_cards = player.turn.end_turn(hand);
+        player
+            .hand
+            .extend(std::iter::repeat_n(CardType::Air, air_cards as usize));
+
+        self.active = 1 - side;
+        self.turn_number += 1;
+        // The second player's first turn is played from their opening hand
+        let cards = if self.turn_number == 2 {
+            0
+        } else {
+            DrawRules::default().cards_per_turn
+        };
+        let player = &mut self.sides[self.active];
+        player.block = (player.block * BLOCK_DECAY).floor();
+        let (drawn, fatigue_damage) = player.turn.start_turn(cards, &mut self.rng);
+        player.hand.extend(drawn);
+        player.health = (player.health - fatigue_damage).max(0.0);
+        let effects = self.sides[self.active].turn.fire(TurnTrigger::StartOfTurn);
+        self.apply(self.active, effects);
+    }
+
+    fn winner(&self) -> Option<usize> {
+        self.sides
+            .iter()
+            .position(|side| side.health <= 0.0)
+            .map(|loser| 1 - loser)
+    }
+
+    // Both sides' copies of the duel hash the same while they're in step. FNV rather than the
+    // standard library's hasher, whose algorithm may differ between the builds on either side.
+    fn checksum(&self) -> u64 {
+        let sides = ron::to_string(&self.sides).unwrap_or_default();
+        sides
+            .bytes()
+            .chain((self.active as u64).to_le_bytes())
+            .chain(self.turn_number.to_le_bytes())
+            .fold(FNV_OFFSET, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+            })
+    }
+}
+
+#[derive(Default)]
+enum Stage {
+    #[default]
+    Lobby,
+    // Hosting or joining, until both decks are known
+    Connecting,
+    Playing(Box<Duel>),
+    // How it ended
+    Over(String),
+}
+
+#[derive(Resource, Default)]
+struct DuelSession {
+    stage: Stage,
+    // The host's address, as typed by the joining player
+    address: String,
+    status: String,
+    link: Option<Link>,
+    // Which side this player is
+    local: usize,
+    deck: Vec<CardType>,
+    opponent_deck: Option<Vec<CardType>>,
+}
+
+impl DuelSession {
+    fn send(&self, message: Message) {
+        if let Some(link) = &self.link {
+            link.send(message);
+        }
+    }
+
+    fn connect(&mut self, connect: Connect) {
+        self.status = match &connect {
+            Connect::Host => format!("Waiting for a player on port {DUEL_PORT}..."),
+            Connect::Join(address) => format!("Joining {address}..."),
+        };
+        self.local = match connect {
+            Connect::Host => HOST,
+            Connect::Join(_) => 1 - HOST,
+        };
+        self.opponent_deck = None;
+        self.link = Some(Link::open(connect));
+        self.stage = Stage::Connecting;
+    }
+
+    // Back to the lobby, hanging up
+    fn cancel(&mut self, status: String) {
+        self.link = None;
+        self.stage = Stage::Lobby;
+        self.status = status;
+    }
+
+    // End the duel, telling the other player and hanging up
+    fn finish(&mut self, outcome: &str) {
+        self.send(Message::Leave);
+        self.link = None;
+        self.stage = Stage::Over(outcome.to_string());
+    }
+
+    fn begin(&mut self, seed: u64) {
+        let Some(opponent_deck) = self.opponent_deck.take() else {
+            self.finish("The duel went out of step");
+            return;
+        };
+        let mut decks = [self.deck.clone(), opponent_deck];
+        if self.local != HOST {
+            decks.swap(0, 1);
+        }
+        self.stage = Stage::Playing(Box::new(Duel::new(seed, decks)));
+    }
+
+    fn check_winner(&mut self) {
+        let Stage::Playing(duel) = &self.stage else {
+            return;
+        };
+        match duel.winner() {
+            Some(winner) if winner == self.local => self.finish("You won the duel!"),
+            Some(_) => self.finish("You lost the duel"),
+            None => {}
+        }
+    }
+
+    fn play_local(&mut self, index: usize) {
+        let Stage::Playing(duel) = &mut self.stage else {
+            return;
+        };
+        if duel.active != self.local || duel.play(index).is_none() {
+            return;
+        }
+        self.send(Message::Play { index });
+        self.check_winner();
+    }
+
+    fn end_local_turn(&mut self) {
+        let Stage::Playing(duel) = &mut self.stage else {
+            return;
+        };
+        if duel.active != self.local {
+            return;
+        }
+        duel.end_turn();
+        let checksum = duel.checksum();
+        self.send(Message::EndTurn { checksum });
+        self.check_winner();
+    }
+
+    fn receive(&mut self, event: LinkEvent) {
+        match (event, &mut self.stage) {
+            (LinkEvent::Connected, Stage::Connecting) => {
+                self.status = "Connected, trading decks...".to_string();
+                self.send(Message::Hello {
+                    version: PROTOCOL_VERSION,
+                    deck: self.deck.clone(),
+                });
+            }
+            (LinkEvent::Closed(reason), Stage::Connecting) => self.cancel(reason),
+            (LinkEvent::Closed(reason), Stage::Playing(_)) => self.finish(&reason),
+            (LinkEvent::Received(message), _) => self.handle(message),
+            _ => {}
+        }
+    }
+
+    fn handle(&mut self, message: Message) {
+        let local = self.local;
+        match (message, &mut self.stage) {
+            (Message::Hello { version, deck }, Stage::Connecting) => {
+                if version != PROTOCOL_VERSION {
+                    self.cancel("The other player has a different version of the game".to_string());
+                    return;
+                }
+                self.opponent_deck = Some(deck);
+                if local == HOST {
+                    let mut rng = RunRng::default();
+                    rng.reseed();
+                    self.send(Message::Start { seed: rng.seed() });
+                    self.begin(rng.seed());
+                }
+            }
+            (Message::Start { seed }, Stage::Connecting) => self.begin(seed),
+            (Message::Play { index }, Stage::Playing(duel)) => {
+                if duel.active == local || duel.play(index).is_none() {
+                    self.finish("The duel went out of step");
+                    return;
+                }
+                self.check_winner();
+            }
+            (Message::EndTurn { checksum }, Stage::Playing(duel)) => {
+                if duel.active == local {
+                    self.finish("The duel went out of step");
+                    return;
+                }
+                duel.end_turn();
+                if duel.checksum() != checksum {
+                    self.finish("The duel went out of step");
+                    return;
+                }
+                self.check_winner();
+            }
+            (Message::Leave, Stage::Connecting | Stage::Playing(_)) => {
+                self.finish("The other player left");
+            }
+            _ => {}
+        }
+    }
+}
+
+// Counts down the turn under way, started again whenever the turn changes
+#[derive(Resource)]
+struct TurnTimer {
+    timer: Timer,
+    turn_number: u32,
+}
+
+#[derive(Component)]
+struct OnDuelScreen;
+
+#[derive(Component)]
+struct TurnTimerText;
+
+#[derive(Component, Clone, Copy)]
+enum DuelButton {
+    Host,
+    Join,
+    // Stop hosting or joining
+    Cancel,
+    Card(usize),
+    EndTurn,
+    Leave,
+    BackToMenu,
+}
+
+pub fn duel_plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Duel), duel_setup)
+        .add_systems(
+            Update,
+            (
+                poll_link,
+                type_address,
+                duel_buttons,
+                run_turn_timer,
+                show_duel.run_if(resource_exists_and_changed::<DuelSession>),
+            )
+                .chain()
+                .run_if(in_state(GameState::Duel)),
+        )
+        .add_systems(OnExit(GameState::Duel), leave_duel);
+}
+
+fn duel_setup(mut commands: Commands, deck: Res<Deck>) {
+    commands.insert_resource(DuelSession {
+        deck: deck.cards().to_vec(),
+        ..default()
+    });
+    commands.insert_resource(TurnTimer {
+        timer: Timer::from_seconds(TURN_SECONDS, TimerMode::Once),
+        turn_number: 0,
+    });
+}
+
+fn leave_duel(mut commands: Commands, session: Res<DuelSession>) {
+    session.send(Message::Leave);
+    commands.remove_resource::<DuelSession>();
+    commands.remove_resource::<TurnTimer>();
+}
+
+fn poll_link(mut session: ResMut<DuelSession>) {
+    // Only borrowed mutably when something arrived, so the screen isn't rebuilt every frame
+    let events = session.link.as_ref().map(Link::poll).unwrap_or_default();
+    for event in events {
+        session.receive(event);
+    }
+}
+
+fn type_address(mut key_events: EventReader<KeyboardInput>, mut session: ResMut<DuelSession>) {
+    if !matches!(session.stage, Stage::Lobby) {
+        key_events.clear();
+        return;
+    }
+    for event in key_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Enter if !session.address.trim().is_empty() => {
+                let address = session.address.trim().to_string();
+                session.connect(Connect::Join(address));
+                return;
+            }
+            Key::Backspace => {
+                session.address.pop();
+            }
+            Key::Character(typed) => {
+                for character in typed.chars().filter(|character| !character.is_control()) {
+                    if session.address.chars().count() < MAX_ADDRESS_LENGTH {
+                        session.address.push(character);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn duel_buttons(
+    mut interaction_query: ui::ChangedActionButtons<DuelButton>,
+    mut session: ResMut<DuelSession>,
+    mut game_state: ResMut<NextState<GameState>>,
+    theme: Res<Theme>,
+) {
+    for (interaction, button, mut color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => match *button {
+                DuelButton::Host => session.connect(Connect::Host),
+                DuelButton::Join => {
+                    let address = session.address.trim().to_string();
+                    if address.is_empty() {
+                        session.status = "Type the host's address first".to_string();
+                    } else {
+                        session.connect(Connect::Join(address));
+                    }
+                }
+                DuelButton::Cancel => session.cancel(String::new()),
+                DuelButton::Card(index) => session.play_local(index),
+                DuelButton::EndTurn => session.end_local_turn(),
+                DuelButton::Leave => session.finish("You left the duel"),
+                DuelButton::BackToMenu => game_state.set(GameState::Menu),
+            },
+            Interaction::Hovered => *color = theme.button_hovered.into(),
+            Interaction::None => *color = theme.button.into(),
+        }
+    }
+}
+
+// Ends this player's turn for them when its time runs out
+fn run_turn_timer(
+    time: Res<Time>,
+    mut turn_timer: ResMut<TurnTimer>,
+    mut session: ResMut<DuelSession>,
+    mut text_query: Query<&mut Text, With<TurnTimerText>>,
+) {
+    let Stage::Playing(duel) = &session.stage else {
+        return;
+    };
+    if duel.turn_number != turn_timer.turn_number {
+        turn_timer.turn_number = duel.turn_number;
+        turn_timer.timer.reset();
+    }
+    turn_timer.timer.tick(time.delta());
+    for mut text in &mut text_query {
+        text.sections[0].value = format!("{:.0}s left", turn_timer.timer.remaining_secs().ceil());
+    }
+    if turn_timer.timer.finished() && duel.active == session.local {
+        session.end_local_turn();
+    }
+}
+
+fn show_duel(
+    mut commands: Commands,
+    session: Res<DuelSession>,
+    screen_query: Query<Entity, With<OnDuelScreen>>,
+    shared_assets: Res<SharedAssets>,
+    theme: Res<Theme>,
+) {
+    for entity in &screen_query {
+        commands.entity(entity).despawn_recursive();
+    }
+    let text_style = theme.text_style(30.0);
+    let button = |parent: &mut ChildBuilder, label: &str, action: DuelButton| {
+        ui::styled_button(
+            parent,
+            &theme,
+            Style {
+                width: Val::Px(250.0),
+                height: Val::Px(65.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            label,
+            text_style.clone(),
+            action,
+        );
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(20.0),
+                    ..default()
+                },
+                background_color: Color::srgb(0.05, 0.05, 0.08).into(),
+                ..default()
+            },
+            OnDuelScreen,
+            StateScoped(GameState::Duel),
+        ))
+        .with_children(|parent| match &session.stage {
+            Stage::Lobby => {
+                parent.spawn(ui::title_text(&theme, "Duel"));
+                button(parent, "Host", DuelButton::Host);
+                let address = if session.address.is_empty() {
+                    "Type the host's address to join".to_string()
+                } else {
+                    format!("{}_", session.address)
+                };
+                parent.spawn(TextBundle::from_section(address, text_style.clone()));
+                button(parent, "Join", DuelButton::Join);
+                parent.spawn(TextBundle::from_section(
+                    session.status.clone(),
+                    text_style.clone(),
+                ));
+                button(parent, "Back", DuelButton::BackToMenu);
+            }
+            Stage::Connecting => {
+                parent.spawn(ui::title_text(&theme, "Duel"));
+                parent.spawn(TextBundle::from_section(
+                    session.status.clone(),
+                    text_style.clone(),
+                ));
+                button(parent, "Cancel", DuelButton::Cancel);
+            }
+            Stage::Playing(duel) => {
+                let local = session.local;
+                let opponent = &duel.sides[1 - local];
+                let player = &duel.sides[local];
+                parent.spawn(TextBundle::from_section(
+                    format!(
+                        "Opponent: {} health, {} block, {} cards in hand",
+                        opponent.health,
+                        opponent.block,
+                        opponent.hand.len()
+                    ),
+                    text_style.clone(),
+                ));
+                let turn = if duel.active == local {
+                    "Your turn"
+                } else {
+                    "Their turn"
+                };
+                parent.spawn(ui::title_text(
+                    &theme,
+                    format!("Turn {}: {turn}", duel.turn_number),
+                ));
+                parent.spawn((
+                    TextBundle::from_section("", text_style.clone()),
+                    TurnTimerText,
+                ));
+                parent.spawn(TextBundle::from_section(
+                    format!("You: {} health, {} block", player.health, player.block),
+                    text_style.clone(),
+                ));
+                // The hand, greyed out while the other player takes their turn
+                let tint = if duel.active == local {
+                    Color::WHITE
+                } else {
+                    WAITING_COLOR
+                };
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            column_gap: Val::Px(12.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        for (index, card_type) in player.hand.iter().enumerate() {
+                            let (image, atlas) = shared_assets.card(*card_type);
+                            parent.spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        width: Val::Px(120.0),
+                                        height: Val::Px(167.0),
+                                        ..default()
+                                    },
+                                    image: image.with_color(tint),
+                                    ..default()
+                                },
+                                atlas,
+                                DuelButton::Card(index),
+                            ));
+                        }
+                    });
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            column_gap: Val::Px(24.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        button(parent, "End Turn", DuelButton::EndTurn);
+                        button(parent, "Leave", DuelButton::Leave);
+                    });
+            }
+            Stage::Over(outcome) => {
+                parent.spawn(ui::title_text(&theme, outcome.clone()));
+                button(parent, "Back to Menu", DuelButton::BackToMenu);
+            }
+        });
+}
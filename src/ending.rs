@@ -0,0 +1,251 @@
+// Closing cutscene after Chapter 4. Which one plays depends on how the run went: the creatures
+// the player chose to spare in the chapter intros and the health they finished on. Every ending
+// reached is remembered in the save. The endings' lines are read from `assets/story`, like the
+// chapter intros'.
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{despawn_screen, DisplayQuality, GameState};
+use crate::cutscene::{
+    manage_text_sequence, spawn_initial_text, type_text, Cutscenes, TextContainer,
+    TextSequenceState, TypingText,
+};
+use crate::game_window::GameWindow;
+use crate::save::SaveData;
+use crate::story::{StoryFlag, StoryFlags};
+use crate::texture_quality::texture_path;
+use crate::theme::Theme;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Ending {
+    Dawn,
+    Embers,
+    Ashes,
+}
+
+// How the final fight went, written by Chapter 4 when its victory screen finishes
+#[derive(Resource, Default)]
+pub struct RunRecord {
+    pub health_remaining: f32,
+    pub health_maximum: f32,
+}
+
+// The creatures a chapter intro offers to spare, each remembered as the flag its reaction sets
+const SPARES: [StoryFlag; 2] = [StoryFlag::FreedFortGoblin, StoryFlag::FreedPoolSprite];
+
+impl Ending {
+    pub const ALL: [Ending; 3] = [Ending::Dawn, Ending::Embers, Ending::Ashes];
+
+    // Dawn for sparing every creature and walking out of the ritual on your feet, Ashes for
+    // sparing none and barely surviving it, and Embers for anything in between
+    fn choose(record: &RunRecord, story_flags: &StoryFlags) -> Self {
+        let health_fraction = if record.health_maximum > 0.0 {
+            record.health_remaining / record.health_maximum
+        } else {
+            0.0
+        };
+        let spared = SPARES
+            .iter()
+            .filter(|flag| story_flags.is_set(**flag))
+            .count();
+
+        if spared == SPARES.len() && health_fraction >= 0.25 {
+            Ending::Dawn
+        } else if spared > 0 || health_fraction >= 0.6 {
+            Ending::Embers
+        } else {
+            Ending::Ashes
+        }
+    }
+
+    fn background(self) -> &'static str {
+        match self {
+            Ending::Dawn => "textures/waterfall.png",
+            Ending::Embers => "textures/2.png",
+            Ending::Ashes => "textures/Summon.png",
+        }
+    }
+
+    pub fn script(self) -> &'static str {
+        match self {
+            Ending::Dawn => "story/ending_dawn.ron",
+            Ending::Embers => "story/ending_embers.ron",
+            Ending::Ashes => "story/ending_ashes.ron",
+        }
+    }
+}
+
+pub fn ending_plugin(app: &mut App) {
+    app.init_resource::<RunRecord>()
+        .add_systems(OnEnter(GameState::Ending), ending_setup)
+        .add_systems(
+            OnEnter(GameState::Ending),
+            spawn_initial_text.after(ending_setup),
+        )
+        .add_systems(
+            Update,
+            (ending, manage_text_sequence, type_text).run_if(in_state(GameState::Ending)),
+        )
+        .add_systems(OnExit(GameState::Ending), despawn_screen::<TypingText>);
+}
+
+// How the run went, which picks the ending, and the save the ending is remembered in
+#[derive(SystemParam)]
+struct RunOutcome<'w> {
+    record: Res<'w, RunRecord>,
+    story_flags: Res<'w, StoryFlags>,
+    save_data: ResMut<'w, SaveData>,
+}
+
+fn ending_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut outcome: RunOutcome,
+    cutscenes: Cutscenes,
+    display_quality: Res<DisplayQuality>,
+    game_window: GameWindow,
+    theme: Res<Theme>,
+) {
+    let Some(window) = game_window.get() else {
+        return;
+    };
+
+    let chosen = Ending::choose(&outcome.record, &outcome.story_flags);
+    outcome.save_data.record_ending(chosen);
+
+    commands.insert_resource(cutscenes.ending(chosen));
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::SpaceBetween,
+                    ..default()
+                },
+                ..default()
+            },
+            StateScoped(GameState::Ending),
+        ))
+        .with_children(|parent| {
+            // Text container at the bottom
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(20.0),
+                        position_type: PositionType::Absolute,
+                        bottom: Val::Px(0.0),
+                        ..default()
+                    },
+                    background_color: theme.dialogue_backdrop.into(),
+                    ..default()
+                },
+                TextContainer,
+            ));
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Vw(100.0),
+                        height: Val::Vh(100.0),
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(SpriteBundle {
+                        texture: asset_server
+                            .load(texture_path(chosen.background(), *display_quality)),
+                        transform: Transform::from_xyz(
+                            -window.width() / 2.0,
+                            -window.height() / 2.0,
+                            1.0,
+                        ),
+                        sprite: Sprite {
+                            custom_size: Some(Vec2::new(1920.0, 1080.0)),
+                            anchor: bevy::sprite::Anchor::Center,
+                            ..default()
+                        },
+                        ..default()
+                    });
+                });
+        });
+}
+
+// Back to the menu once the ending's script has played out, however long it runs
+fn ending(sequence_state: Res<TextSequenceState>, mut game_state: ResMut<NextState<GameState>>) {
+    if sequence_state.finished() {
+        game_state.set(GameState::Menu);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cutscene::CutsceneLine;
+    use crate::inventory::Inventory;
+    use crate::test_app::TestApp;
+
+    fn record(health_remaining: f32) -> RunRecord {
+        RunRecord {
+            health_remaining,
+            health_maximum: 100.0,
+        }
+    }
+
+    fn spared(flags: &[StoryFlag]) -> StoryFlags {
+        let mut story_flags = StoryFlags::default();
+        for flag in flags {
+            story_flags.set(*flag);
+        }
+        story_flags
+    }
+
+    #[test]
+    fn sparing_every_creature_reaches_dawn() {
+        let story_flags = spared(&SPARES);
+        assert_eq!(Ending::choose(&record(30.0), &story_flags), Ending::Dawn);
+        // Unless the ritual all but killed the player
+        assert_eq!(Ending::choose(&record(10.0), &story_flags), Ending::Embers);
+    }
+
+    #[test]
+    fn health_alone_reaches_no_further_than_embers() {
+        let story_flags = spared(&[]);
+        assert_eq!(Ending::choose(&record(100.0), &story_flags), Ending::Embers);
+        assert_eq!(Ending::choose(&record(50.0), &story_flags), Ending::Ashes);
+        let story_flags = spared(&[StoryFlag::FreedPoolSprite]);
+        assert_eq!(Ending::choose(&record(10.0), &story_flags), Ending::Embers);
+    }
+
+    #[test]
+    fn the_ending_stays_up_until_its_script_plays_out() {
+        let mut app = TestApp::new();
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Ending);
+        app.step(1);
+        // A last line held for longer than the ending used to give the whole script
+        let closing_line = CutsceneLine {
+            delay: Some(30.0),
+            ..CutsceneLine::new("And the forest was quiet again.")
+        };
+        app.insert_resource(TextSequenceState::new(
+            vec![CutsceneLine::new(" "), closing_line],
+            &StoryFlags::default(),
+            &Inventory::default(),
+        ));
+
+        // 20 seconds in
+        app.step(400);
+        assert_eq!(app.state(), GameState::Ending);
+        app.step_until_state(300, GameState::Menu);
+    }
+}
@@ -0,0 +1,194 @@
+// Endless mode, unlocked by finishing Chapter 4. Fights are made up wave by wave from a few
+// monster templates, growing in number and strength as the waves go on, with the deck screen
+// between each. The wave under way is kept in the save so a quit fight resumes as the same fight,
+// and the furthest wave ever reached is kept for the stats screen.
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::GameState;
+use crate::combat::{
+    add_combat_state, combat_setup, CardType, Encounter, EnemyMove, FightEvent, LootTable,
+    MonsterSpec,
+};
+use crate::rng::RunRng;
+use crate::save::SaveData;
+use crate::story::{StoryFlag, StoryFlags};
+
+// How much stronger the monsters get with each wave
+const WAVE_GROWTH: f32 = 0.15;
+
+// Another monster joins every few waves, up to the most that fit on screen
+const WAVES_PER_EXTRA_MONSTER: u32 = 3;
+const MAX_MONSTERS: u32 = 3;
+
+// Space between monsters, as a share of the window's width
+const MONSTER_SPACING: f32 = 1.0 / 4.0;
+
+const BASE_GOLD_REWARD: u32 = 5;
+const GOLD_PER_WAVE: u32 = 2;
+
+const BACKGROUNDS: [&str; 4] = [
+    "textures/1.png",
+    "textures/2.png",
+    "textures/waterfall.png",
+    "textures/Summon.png",
+];
+
+// A monster the generator can put in a wave, at its first wave's strength
+struct MonsterTemplate {
+    texture: &'static str,
+    health: f32,
+    damage: f32,
+    size: f32,
+    rise: f32,
+    lift: f32,
+    label_height: f32,
+    health_bar_depth: f32,
+    // The moves it cycles through, for the damage it deals
+    moves: fn(f32) -> Vec<EnemyMove>,
+}
+
+const TEMPLATES: [MonsterTemplate; 4] = [
+    MonsterTemplate {
+        texture: "textures/monster.png",
+        health: 30.0,
+        damage: 8.0,
+        size: 250.0,
+        rise: 0.0,
+        lift: -75.0,
+        label_height: 120.0,
+        health_bar_depth: -100.0,
+        moves: |damage| vec![EnemyMove::Attack(damage), EnemyMove::Defend(damage)],
+    },
+    MonsterTemplate {
+        texture: "textures/monster_2.png",
+        health: 24.0,
+        damage: 7.0,
+        size: 250.0,
+        rise: 0.0,
+        lift: -75.0,
+        label_height: 120.0,
+        health_bar_depth: -100.0,
+        moves: |_| Vec::new(),
+    },
+    MonsterTemplate {
+        texture: "textures/knight.png",
+        health: 22.0,
+        damage: 10.0,
+        size: 300.0,
+        rise: 0.0,
+        lift: 0.0,
+        label_height: 120.0,
+        health_bar_depth: -140.0,
+        moves: |damage| vec![EnemyMove::Attack(damage), EnemyMove::Buff(damage / 4.0)],
+    },
+    MonsterTemplate {
+        texture: "textures/angle.png",
+        health: 34.0,
+        damage: 12.0,
+        size: 300.0,
+        rise: 1.0 / 16.0,
+        lift: 0.0,
+        label_height: 150.0,
+        health_bar_depth: -165.0,
+        moves: |damage| vec![EnemyMove::Attack(damage), EnemyMove::Heal(damage)],
+    },
+];
+
+// The endless run under way: which wave it's on, and the seed its waves are made from
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct EndlessRun {
+    pub wave: u32,
+    seed: u64,
+}
+
+impl EndlessRun {
+    fn new(rng: &RunRng) -> Self {
+        Self {
+            wave: 1,
+            seed: rng.seed(),
+        }
+    }
+}
+
+// Finishing the campaign opens endless mode
+pub fn is_unlocked(save_data: &SaveData, story_flags: &StoryFlags) -> bool {
+    save_data.progress.is_set(StoryFlag::RitualBroken)
+        || story_flags.is_set(StoryFlag::RitualBroken)
+}
+
+pub fn endless_plugin(app: &mut App) {
+    app.add_systems(
+        OnEnter(GameState::Endless),
+        endless_setup.before(combat_setup),
+    )
+    .add_systems(Update, next_wave.run_if(in_state(GameState::Endless)));
+    add_combat_state(app, GameState::Endless);
+}
+
+// The fight for a wave. The same wave of the same run always makes the same fight.
+pub fn generate_encounter(wave: u32, seed: u64) -> Encounter {
+    let mut rng = RunRng::from_seed(seed ^ u64::from(wave));
+    let strength = 1.0 + WAVE_GROWTH * (wave - 1) as f32;
+    let count = (1 + (wave - 1) / WAVES_PER_EXTRA_MONSTER).min(MAX_MONSTERS);
+
+    let monsters = (0..count)
+        .map(|i| {
+            let template = &TEMPLATES[rng.below(TEMPLATES.len())];
+            let damage = (template.damage * strength).round();
+            MonsterSpec {
+                texture: template.texture,
+                health: (template.health * strength).round(),
+                damage,
+                size: template.size,
+                // Lined up across the middle, the first on the right
+                offset: Vec2::new(
+                    ((count - 1) as f32 / 2.0 - i as f32) * MONSTER_SPACING,
+                    template.rise,
+                ),
+                lift: template.lift,
+                label_height: template.label_height,
+                health_bar_depth: template.health_bar_depth,
+                moves: (template.moves)(damage),
+                loot: LootTable {
+                    gold: (1, 2 + wave / 2),
+                    ..default()
+                },
+            }
+        })
+        .collect();
+
+    Encounter {
+        background: BACKGROUNDS[(wave as usize - 1) % BACKGROUNDS.len()],
+        monsters,
+        next_state: GameState::Endless,
+        victory_flag: StoryFlag::WaveCleared,
+        wounded_flag: None,
+        reward: None,
+        gold_reward: BASE_GOLD_REWARD + GOLD_PER_WAVE * wave,
+        card_rewards: vec![CardType::ALL[rng.below(CardType::ALL.len())]],
+        enrage_after: None,
+        ritual: None,
+        effect_reward: None,
+        intro: None,
+    }
+}
+
+fn endless_setup(mut commands: Commands, rng: Res<RunRng>, mut save_data: ResMut<SaveData>) {
+    let run = *save_data
+        .endless
+        .get_or_insert_with(|| EndlessRun::new(&rng));
+    save_data.best_wave = save_data.best_wave.max(run.wave);
+    save_data.save();
+    commands.insert_resource(generate_encounter(run.wave, run.seed));
+}
+
+// Winning a wave moves the run on to the next
+fn next_wave(mut fight_events: EventReader<FightEvent>, mut save_data: ResMut<SaveData>) {
+    for event in fight_events.read() {
+        if let (FightEvent::Won(_), Some(run)) = (event, &mut save_data.endless) {
+            run.wave += 1;
+            save_data.save();
+        }
+    }
+}
@@ -0,0 +1,59 @@
+// The window each screen is laid out against. It isn't always there to ask: a minimized window
+// has no size, Android takes the window away while the app is in the background and makes a new
+// one on return, and there can be more than one window. `GameWindow` finds the right one without
+// panicking, and the game waits to change state until there is one to set the next screen up in.
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use super::GameState;
+
+#[derive(SystemParam)]
+pub struct GameWindow<'w, 's> {
+    windows: Query<'w, 's, (&'static Window, Has<PrimaryWindow>)>,
+}
+
+impl GameWindow<'_, '_> {
+    // The primary window, or the first window while none is primary. One with no size can't
+    // have anything laid out in it, so it counts as no window.
+    pub fn get(&self) -> Option<&Window> {
+        let (window, _) = self
+            .windows
+            .iter()
+            .find(|(_, primary)| *primary)
+            .or_else(|| self.windows.iter().next())?;
+        (window.width() > 0.0 && window.height() > 0.0).then_some(window)
+    }
+}
+
+// A state change asked for while there was no window, made once there is one again
+#[derive(Resource)]
+struct HeldState(GameState);
+
+pub fn game_window_plugin(app: &mut App) {
+    // Before the state changes, so no screen's setup runs without a window
+    app.add_systems(PreUpdate, hold_state_until_window);
+}
+
+fn hold_state_until_window(
+    mut commands: Commands,
+    game_window: GameWindow,
+    mut next_state: ResMut<NextState<GameState>>,
+    held: Option<Res<HeldState>>,
+) {
+    if game_window.get().is_none() {
+        if let NextState::Pending(state) = *next_state {
+            info!("Waiting for the window before going to {state:?}");
+            commands.insert_resource(HeldState(state));
+            next_state.reset();
+        }
+        return;
+    }
+    if let Some(held) = held {
+        // Anything asked for since is newer, so it wins
+        if matches!(*next_state, NextState::Unchanged) {
+            next_state.set(held.0);
+        }
+        commands.remove_resource::<HeldState>();
+    }
+}
@@ -0,0 +1,127 @@
+// Gold won in fights. It is shown in the corner of the screen during a run, kept in the save,
+// and can be spent by shops and events.
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::GameState;
+use crate::platform::SafeAnchor;
+
+pub const GOLD_COLOR: Color = Color::srgb(1.0, 0.84, 0.0);
+
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, Default, Debug)]
+pub struct Gold(u32);
+
+impl Gold {
+    pub fn amount(self) -> u32 {
+        self.0
+    }
+
+    pub fn earn(&mut self, amount: u32) {
+        self.0 += amount;
+    }
+
+    // Takes the gold only if there is enough of it
+    pub fn spend(&mut self, amount: u32) -> bool {
+        if self.0 < amount {
+            return false;
+        }
+        self.0 -= amount;
+        true
+    }
+
+    // Empty the purse, for a new run
+    pub fn clear(&mut self) {
+        self.0 = 0;
+    }
+}
+
+#[derive(Component)]
+struct GoldHud;
+
+#[derive(Component)]
+struct GoldHudText;
+
+pub fn gold_plugin(app: &mut App) {
+    app.add_systems(Startup, spawn_gold_hud)
+        .add_systems(Update, (update_gold_hud, show_gold_hud));
+}
+
+fn spawn_gold_hud(mut commands: Commands, asset_server: Res<AssetServer>, gold: Res<Gold>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(16.0),
+                    left: Val::Px(16.0),
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(8.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.6).into(),
+                visibility: Visibility::Hidden,
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            GoldHud,
+            SafeAnchor::top_left(16.0, 16.0),
+        ))
+        .with_children(|parent| {
+            parent.spawn(ImageBundle {
+                style: Style {
+                    width: Val::Px(28.0),
+                    height: Val::Px(28.0),
+                    ..default()
+                },
+                image: UiImage::new(asset_server.load("textures/Game Icons/crystal.png"))
+                    .with_color(GOLD_COLOR),
+                ..default()
+            });
+            parent.spawn((
+                TextBundle::from_section(
+                    gold.amount().to_string(),
+                    TextStyle {
+                        font_size: 28.0,
+                        color: GOLD_COLOR,
+                        ..default()
+                    },
+                ),
+                GoldHudText,
+            ));
+        });
+}
+
+fn update_gold_hud(gold: Res<Gold>, mut text_query: Query<&mut Text, With<GoldHudText>>) {
+    if !gold.is_changed() {
+        return;
+    }
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = gold.amount().to_string();
+    }
+}
+
+// The purse is only shown once a run has started
+fn show_gold_hud(
+    game_state: Res<State<GameState>>,
+    mut hud_query: Query<&mut Visibility, With<GoldHud>>,
+) {
+    if !game_state.is_changed() {
+        return;
+    }
+    let shown = !matches!(
+        game_state.get(),
+        GameState::Loading
+            | GameState::Profiles
+            | GameState::Splash
+            | GameState::Menu
+            | GameState::Demo
+    );
+    for mut visibility in hud_query.iter_mut() {
+        *visibility = if shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
@@ -0,0 +1,236 @@
+// Key items picked up during a run. They show up on the inventory screen (toggled with I),
+// are announced when found, are kept in the save, and can unlock alternate cutscene lines.
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::theme::Theme;
+use crate::ui;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum KeyItem {
+    // Found in the fort in Chapter 2
+    Rune,
+}
+
+impl KeyItem {
+    pub fn name(self) -> &'static str {
+        match self {
+            KeyItem::Rune => "Fort Rune",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            KeyItem::Rune => "A cold stone etched with shifting patterns",
+        }
+    }
+
+    fn icon(self) -> &'static str {
+        match self {
+            KeyItem::Rune => "textures/Game Icons/crystal.png",
+        }
+    }
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone, Default, Debug)]
+pub struct Inventory(Vec<KeyItem>);
+
+impl Inventory {
+    pub fn has(&self, item: KeyItem) -> bool {
+        self.0.contains(&item)
+    }
+
+    pub fn items(&self) -> &[KeyItem] {
+        &self.0
+    }
+
+    // Empty the inventory, for a new run
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    fn add(&mut self, item: KeyItem) -> bool {
+        if self.has(item) {
+            return false;
+        }
+        self.0.push(item);
+        true
+    }
+}
+
+// Send this to give the player an item
+#[derive(Event)]
+pub struct ItemPickedUp(pub KeyItem);
+
+#[derive(Component)]
+struct PickupNotification {
+    timer: Timer,
+}
+
+#[derive(Component)]
+struct InventoryScreen;
+
+pub fn inventory_plugin(app: &mut App) {
+    // Pickups land in PostUpdate so an item won at the end of a fight is already held when
+    // the next state's cutscene is set up
+    app.add_event::<ItemPickedUp>()
+        .add_systems(PostUpdate, pick_up_items)
+        .add_systems(Update, (fade_pickup_notifications, toggle_inventory_screen));
+}
+
+fn pick_up_items(
+    mut commands: Commands,
+    mut pickups: EventReader<ItemPickedUp>,
+    mut inventory: ResMut<Inventory>,
+    asset_server: Res<AssetServer>,
+    theme: Res<Theme>,
+) {
+    for ItemPickedUp(item) in pickups.read() {
+        if !inventory.add(*item) {
+            continue;
+        }
+
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        top: Val::Px(100.0),
+                        width: Val::Percent(100.0),
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    z_index: ZIndex::Global(20),
+                    ..default()
+                },
+                PickupNotification {
+                    timer: Timer::from_seconds(3.0, TimerMode::Once),
+                },
+            ))
+            .with_children(|parent| {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(12.0),
+                            padding: UiRect::all(Val::Px(12.0)),
+                            ..default()
+                        },
+                        background_color: Color::srgba(0.0, 0.0, 0.0, 0.8).into(),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(ImageBundle {
+                            style: Style {
+                                width: Val::Px(40.0),
+                                height: Val::Px(40.0),
+                                ..default()
+                            },
+                            image: UiImage::new(asset_server.load(item.icon())),
+                            ..default()
+                        });
+                        parent.spawn(TextBundle::from_section(
+                            format!("Found: {}", item.name()),
+                            theme.text_style(30.0),
+                        ));
+                    });
+            });
+    }
+}
+
+fn fade_pickup_notifications(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut PickupNotification)>,
+) {
+    for (entity, mut notification) in query.iter_mut() {
+        if notification.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn toggle_inventory_screen(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    inventory: Res<Inventory>,
+    screen_query: Query<Entity, With<InventoryScreen>>,
+    asset_server: Res<AssetServer>,
+    theme: Res<Theme>,
+) {
+    if !keyboard.just_pressed(KeyCode::KeyI) {
+        return;
+    }
+
+    if let Ok(screen) = screen_query.get_single() {
+        commands.entity(screen).despawn_recursive();
+        return;
+    }
+
+    let text_style = theme.text_style(30.0);
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(16.0),
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.85).into(),
+                z_index: ZIndex::Global(15),
+                ..default()
+            },
+            InventoryScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(ui::title_text(&theme, "Inventory"));
+
+            if inventory.items().is_empty() {
+                parent.spawn(TextBundle::from_section(
+                    "Your pockets are empty",
+                    text_style.clone(),
+                ));
+            }
+
+            for item in inventory.items() {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(16.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(ImageBundle {
+                            style: Style {
+                                width: Val::Px(48.0),
+                                height: Val::Px(48.0),
+                                ..default()
+                            },
+                            image: UiImage::new(asset_server.load(item.icon())),
+                            ..default()
+                        });
+                        parent.spawn(TextBundle::from_section(
+                            format!("{} - {}", item.name(), item.description()),
+                            text_style.clone(),
+                        ));
+                    });
+            }
+
+            parent.spawn(TextBundle::from_section(
+                "Press I to close",
+                TextStyle {
+                    font_size: 20.0,
+                    ..text_style.clone()
+                },
+            ));
+        });
+}
@@ -0,0 +1,313 @@
+// Optional online leaderboards. Setting `LEADERBOARD_URL_VAR` to an `http://host[:port]/path`
+// address submits each finished speedrun's time, each arena run's waves and each daily run's
+// chapters under the profile's name, and the leaderboards screen shows the top of each board.
+// The server keeps each board at `path/<board>`, and only has to take an entry with POST and
+// answer GET with the board's entries, both in RON. Entries carry an id, so one sent twice can be
+// counted once. Scores the server hasn't taken yet, and the boards as last fetched, are kept in
+// the profile's folder, so nothing is lost offline and the screen still has something to show.
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::daily::DailyChallenge;
+use crate::profile::profile_path;
+use crate::save_backend::Address;
+use crate::speedrun::format_time;
+
+const LEADERBOARD_FILE: &str = "leaderboards.ron";
+
+pub const LEADERBOARD_URL_VAR: &str = "SPRITED_TOWARDS_LEADERBOARD_URL";
+
+// Entries shown on each board
+const TABLE_SIZE: usize = 10;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub enum Board {
+    Speedrun,
+    Arena,
+    // One board for each day's run, by its date
+    Daily(String),
+}
+
+impl Board {
+    // The boards on the leaderboards screen
+    pub fn shown() -> [Board; 3] {
+        [
+            Board::Speedrun,
+            Board::Arena,
+            Board::Daily(DailyChallenge::today().date),
+        ]
+    }
+
+    // Where the board is kept on the server, under the configured path
+    fn path(&self) -> String {
+        match self {
+            Board::Speedrun => "speedrun".to_string(),
+            Board::Arena => "arena".to_string(),
+            Board::Daily(date) => format!("daily/{date}"),
+        }
+    }
+
+    pub fn title(&self) -> String {
+        match self {
+            Board::Speedrun => "Fastest runs".to_string(),
+            Board::Arena => "Arena waves".to_string(),
+            Board::Daily(date) => format!("Daily run {date}"),
+        }
+    }
+
+    // Times rank lowest first, everything else highest first
+    fn rank(&self, score: f32, other: f32) -> Ordering {
+        match self {
+            Board::Speedrun => score.total_cmp(&other),
+            _ => other.total_cmp(&score),
+        }
+    }
+
+    fn score_text(&self, score: f32) -> String {
+        match self {
+            Board::Speedrun => format_time(score),
+            Board::Arena => format!("Wave {}", score as u32),
+            Board::Daily(_) => format!("{} chapters", score as u32),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct Entry {
+    id: u64,
+    name: String,
+    score: f32,
+}
+
+#[derive(Resource, Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct Leaderboards {
+    // Scores not taken by the server yet, sent again whenever the boards are fetched
+    pending: Vec<(Board, Entry)>,
+    // Each board as last fetched, by its path
+    tables: BTreeMap<String, Vec<Entry>>,
+    // Whether the last request reached the server, or `None` before any this session
+    #[serde(skip)]
+    online: Option<bool>,
+}
+
+enum Request {
+    Submit(Board, Entry),
+    Fetch(Board),
+}
+
+enum Reply {
+    Submitted(u64),
+    Fetched(Board, Vec<Entry>),
+    Unreachable,
+}
+
+struct Client {
+    requests: Mutex<Sender<Request>>,
+    replies: Mutex<Receiver<Reply>>,
+}
+
+// The server's client, if one is configured. Requests go out on a background thread so a slow
+// server never stalls a frame, and their replies are picked up each frame.
+fn client() -> Option<&'static Client> {
+    static CLIENT: OnceLock<Option<Client>> = OnceLock::new();
+    CLIENT
+        .get_or_init(|| {
+            let url = env::var(LEADERBOARD_URL_VAR).ok()?;
+            let Some(address) = Address::from_url(&url) else {
+                warn!("Ignoring {LEADERBOARD_URL_VAR}: {url} is not an http:// address");
+                return None;
+            };
+            let (requests, request_receiver) = mpsc::channel::<Request>();
+            let (reply_sender, replies) = mpsc::channel::<Reply>();
+            thread::spawn(move || {
+                while let Ok(request) = request_receiver.recv() {
+                    if reply_sender.send(send_request(&address, request)).is_err() {
+                        return;
+                    }
+                }
+            });
+            Some(Client {
+                requests: Mutex::new(requests),
+                replies: Mutex::new(replies),
+            })
+        })
+        .as_ref()
+}
+
+fn send_request(address: &Address, request: Request) -> Reply {
+    let board_path =
+        |board: &Board| format!("{}/{}", address.path().trim_end_matches('/'), board.path());
+    match request {
+        Request::Submit(board, entry) => {
+            let Ok(body) = ron::to_string(&entry) else {
+                return Reply::Unreachable;
+            };
+            match address.request("POST", &board_path(&board), &body) {
+                Ok((200..=299, _)) => Reply::Submitted(entry.id),
+                Ok((status, _)) => {
+                    warn!("Leaderboard submission failed with status {status}");
+                    Reply::Unreachable
+                }
+                Err(err) => {
+                    warn!("Leaderboard submission failed: {err}");
+                    Reply::Unreachable
+                }
+            }
+        }
+        Request::Fetch(board) => match address.request("GET", &board_path(&board), "") {
+            Ok((200, body)) => match ron::from_str(&body) {
+                Ok(entries) => Reply::Fetched(board, entries),
+                Err(err) => {
+                    warn!("Unreadable leaderboard from the server: {err}");
+                    Reply::Unreachable
+                }
+            },
+            // No one has a score on it yet
+            Ok((404, _)) => Reply::Fetched(board, Vec::new()),
+            Ok((status, _)) => {
+                warn!("Leaderboard fetch failed with status {status}");
+                Reply::Unreachable
+            }
+            Err(err) => {
+                warn!("Leaderboard fetch failed: {err}");
+                Reply::Unreachable
+            }
+        },
+    }
+}
+
+fn send(request: Request) {
+    if let Some(Ok(requests)) = client().map(|client| client.requests.lock()) {
+        let _ = requests.send(request);
+    }
+}
+
+impl Leaderboards {
+    // The active profile's cache, or an empty one if it can't be read
+    pub fn load() -> Self {
+        fs::read_to_string(profile_path(LEADERBOARD_FILE))
+            .ok()
+            .and_then(|contents| ron::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        let contents = match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(contents) => contents,
+            Err(err) => {
+                warn!("Could not serialize the leaderboards: {err}");
+                return;
+            }
+        };
+        if let Err(err) = fs::write(profile_path(LEADERBOARD_FILE), contents) {
+            warn!("Could not write {LEADERBOARD_FILE}: {err}");
+        }
+    }
+
+    // Send a score to a board under the player's name. Nothing is kept without a server.
+    pub fn submit(&mut self, board: Board, name: &str, score: f32) {
+        if client().is_none() {
+            return;
+        }
+        let entry = Entry {
+            id: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_nanos() as u64),
+            name: name.to_string(),
+            score,
+        };
+        self.pending.push((board.clone(), entry.clone()));
+        self.save();
+        send(Request::Submit(board, entry));
+    }
+
+    // Send whatever is still pending, then fetch the boards shown
+    pub fn refresh(&self) {
+        for (board, entry) in &self.pending {
+            send(Request::Submit(board.clone(), entry.clone()));
+        }
+        for board in Board::shown() {
+            send(Request::Fetch(board));
+        }
+    }
+
+    // How the boards on the screen came to be
+    pub fn status(&self) -> String {
+        if client().is_none() {
+            return format!("Set {LEADERBOARD_URL_VAR} to take part in the leaderboards");
+        }
+        let waiting = match self.pending.len() {
+            0 => String::new(),
+            1 => "\n1 score waiting to be sent".to_string(),
+            count => format!("\n{count} scores waiting to be sent"),
+        };
+        let status = match self.online {
+            None => "Fetching the leaderboards...",
+            Some(true) => "Online",
+            Some(false) => "Offline, showing the leaderboards as last fetched",
+        };
+        format!("{status}{waiting}")
+    }
+
+    // A board's lines, best first
+    pub fn rows(&self, board: &Board) -> Vec<String> {
+        self.tables
+            .get(&board.path())
+            .map(|entries| {
+                entries
+                    .iter()
+                    .enumerate()
+                    .map(|(i, entry)| {
+                        format!(
+                            "{}. {}  {}",
+                            i + 1,
+                            entry.name,
+                            board.score_text(entry.score)
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+pub fn leaderboard_plugin(app: &mut App) {
+    app.init_resource::<Leaderboards>()
+        .add_systems(Update, receive_replies);
+}
+
+fn receive_replies(mut leaderboards: ResMut<Leaderboards>) {
+    let replies: Vec<Reply> = match client().map(|client| client.replies.lock()) {
+        Some(Ok(replies)) => replies.try_iter().collect(),
+        _ => return,
+    };
+    if replies.is_empty() {
+        return;
+    }
+    for reply in replies {
+        match reply {
+            Reply::Submitted(id) => {
+                leaderboards.pending.retain(|(_, entry)| entry.id != id);
+                leaderboards.online = Some(true);
+            }
+            Reply::Fetched(board, mut entries) => {
+                entries.sort_by(|a, b| board.rank(a.score, b.score));
+                entries.truncate(TABLE_SIZE);
+                leaderboards.tables.insert(board.path(), entries);
+                leaderboards.online = Some(true);
+            }
+            Reply::Unreachable => leaderboards.online = Some(false),
+        }
+    }
+    leaderboards.save();
+}
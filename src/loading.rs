@@ -0,0 +1,130 @@
+// Loading screen shown before the splash. It starts loading the shared assets, the cutscene
+// scripts, the theme and every large texture the cutscenes and fights use, and only moves on once
+// they are all ready, so no screen opens with a missing font, script or a blank sprite sheet. The handles
+// are kept for the whole game so screens that load the same files later get them straight away.
+use bevy::asset::{LoadState, UntypedAssetId};
+use bevy::prelude::*;
+
+use super::{DisplayQuality, GameState};
+use crate::cutscene::CutsceneScripts;
+use crate::shared_assets::SharedAssets;
+use crate::texture_quality::texture_path;
+use crate::theme::{Theme, ThemeHandle};
+
+const PRELOADED_IMAGES: [&str; 17] = [
+    "branding/icon.png",
+    "textures/logo.png",
+    "textures/Game Icons/1.png",
+    // Cutscene sprite sheets
+    "textures/intro_game_sprite.png",
+    "textures/forest_fort.png",
+    "textures/pool.png",
+    "textures/summoning.png",
+    // Fight backgrounds, the player and the monsters
+    "textures/1.png",
+    "textures/2.png",
+    "textures/waterfall.png",
+    "textures/Summon.png",
+    "textures/character.png",
+    "textures/monster.png",
+    "textures/monster_2.png",
+    "textures/knight.png",
+    "textures/angle.png",
+    "textures/mage.png",
+];
+
+// The background music is streamed while it plays, so it isn't loaded here
+const PRELOADED_SOUNDS: [&str; 1] = ["sounds/breakout_collision.ogg"];
+
+#[derive(Resource)]
+struct Preloaded(Vec<UntypedHandle>);
+
+#[derive(Component)]
+struct LoadingText;
+
+pub fn loading_plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Loading), loading_setup)
+        .add_systems(Update, wait_for_assets.run_if(in_state(GameState::Loading)));
+}
+
+fn loading_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    display_quality: Res<DisplayQuality>,
+    theme: Res<Theme>,
+) {
+    let images = PRELOADED_IMAGES.iter().map(|path| {
+        asset_server
+            .load::<Image>(texture_path(path, *display_quality))
+            .untyped()
+    });
+    let sounds = PRELOADED_SOUNDS
+        .iter()
+        .map(|path| asset_server.load::<AudioSource>(*path).untyped());
+    commands.insert_resource(Preloaded(images.chain(sounds).collect()));
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            StateScoped(GameState::Loading),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section("Loading...", theme.text_style(40.0)),
+                LoadingText,
+            ));
+        });
+}
+
+// Failed loads count as finished, so a missing file doesn't hang the game here. The asset
+// server has already logged what went wrong.
+fn finished(asset_server: &AssetServer, id: UntypedAssetId) -> bool {
+    matches!(
+        asset_server.get_load_state(id),
+        Some(LoadState::Loaded | LoadState::Failed(_))
+    )
+}
+
+fn wait_for_assets(
+    asset_server: Res<AssetServer>,
+    preloaded: Res<Preloaded>,
+    shared_assets: Res<SharedAssets>,
+    scripts: Res<CutsceneScripts>,
+    theme: Res<ThemeHandle>,
+    mut text_query: Query<&mut Text, With<LoadingText>>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    let ids: Vec<UntypedAssetId> = preloaded
+        .0
+        .iter()
+        .map(UntypedHandle::id)
+        .chain([
+            shared_assets.font.id().untyped(),
+            shared_assets.typewriter_sound.id().untyped(),
+            shared_assets.typewriter_soft_sound.id().untyped(),
+        ])
+        .chain(scripts.ids())
+        .chain(theme.ids())
+        .collect();
+    let done = ids
+        .iter()
+        .filter(|id| finished(&asset_server, **id))
+        .count();
+
+    if done == ids.len() && shared_assets.icons_ready() {
+        game_state.set(GameState::Profiles);
+        return;
+    }
+    if let Ok(mut text) = text_query.get_single_mut() {
+        text.sections[0].value = format!("Loading... {done}/{}", ids.len());
+    }
+}
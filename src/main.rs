@@ -1,45 +1,83 @@
 //! This example will display a simple menu using Bevy UI where you can start a new game,
 //! change some settings or quit. There is no actual game, it will just display the current
 //! settings for 5 seconds before going back to the menu.
-// Add these imports at the top level
-use bevy::audio::*;
-
-// Create a component to mark the background music entity
-#[derive(Component)]
-struct BackgroundMusic;
 
 // Create a plugin for the audio system
 pub struct AudioPlugin;
 
 impl Plugin for AudioPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup_background_music);
+        app.add_plugins(music::music_plugin);
     }
 }
 
-// System to set up the background music
-fn setup_background_music(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.spawn((
-        AudioBundle {
-            source: asset_server.load("sounds/Shadowy Whispers.ogg"), // Replace with your music file
-            settings: PlaybackSettings {
-                // volume: Volume::0.5, // Volume between 0.0 and 1.0
-                mode: PlaybackMode::Loop,
-                ..default()
-            },
-            ..default()
-        },
-        BackgroundMusic,
-    ));
-}
-
+mod ambience;
+mod arena;
+mod bench;
+mod boss_intro;
+mod bug_report;
+mod campfire;
+mod chapters;
+#[cfg(feature = "dev-tools")]
+mod cheats;
+mod combat;
+mod crash;
+mod cursor;
+mod cutscene;
+mod daily;
+mod deck;
+mod deck_building;
+mod demo;
+mod difficulty;
+mod duel;
+mod ending;
+mod endless;
+mod game_window;
+mod gold;
+mod inventory;
+mod leaderboard;
+mod loading;
+mod music;
+mod pause;
+mod perf_overlay;
+mod platform;
+mod profile;
+mod reveal;
+mod rng;
+mod rules;
+mod rumble;
+mod run_effects;
+mod save;
+mod save_backend;
+mod sfx;
+mod shared_assets;
+mod shop;
+mod speedrun;
+mod story;
+mod telemetry;
+#[cfg(test)]
+mod test_app;
+mod texture_quality;
+mod theme;
+mod tutorial;
+mod tween;
+mod ui;
+mod window_config;
+
+use bevy::log::LogPlugin;
 use bevy::prelude::*;
-const TEXT_COLOR: Color = Color::srgb(0.9, 0.9, 0.9);
+use profile::Profiles;
+use serde::{Deserialize, Serialize};
+use window_config::WindowConfig;
 
 // Enum that will be used as a global state for the game
-#[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States, Serialize, Deserialize)]
 enum GameState {
+    // Waits for assets before anything is shown
     #[default]
+    Loading,
+    // Picking who's playing, which decides the save and settings used from here on
+    Profiles,
     Splash,
     Menu,
     Game,
@@ -50,6 +88,25 @@ enum GameState {
     Chapter2,
     Chapter3,
     Chapter4,
+    // Passed through on the way back into a lost fight, so it's set up again from the start
+    RetryFight,
+    DeckBuilding,
+    // The merchant between chapters, who takes cards out of the deck for gold
+    Shop,
+    // Resting between chapters: heal, upgrade a card or burn one
+    Campfire,
+    Ending,
+    // Generated fights one after another, once the campaign is finished
+    Endless,
+    // Waves fought one after another from a chosen starting deck, with upgrade picks between
+    Arena,
+    ArenaUpgrade,
+    // Two players battling each other's decks over the network
+    Duel,
+    // Attract-mode fight played by the computer after the menu sits idle
+    Demo,
+    // The benchmark scene, only reached by starting the game with `bench::BENCH_ARG`
+    Bench,
 }
 
 // One of the two settings that can be set through the menu. It will be a resource in the app
@@ -64,21 +121,63 @@ enum DisplayQuality {
 #[derive(Resource, Debug, Component, PartialEq, Eq, Clone, Copy)]
 struct Volume(u32);
 
-#[derive(Resource, Default)]
-struct PendingAirCards {
-    to_add: i32,
+// How large the text that follows this setting is drawn, set with the display settings
+#[derive(Resource, Debug, Component, PartialEq, Eq, Clone, Copy)]
+enum TextSize {
+    Small,
+    Normal,
+    Large,
+}
+
+impl TextSize {
+    // What font sizes are multiplied by
+    fn scale(self) -> f32 {
+        match self {
+            TextSize::Small => 0.8,
+            TextSize::Normal => 1.0,
+            TextSize::Large => 1.3,
+        }
+    }
 }
 
+#[bevy_main]
 fn main() {
+    if let Some(log_path) = crash::crash_log_arg() {
+        crash::run_crash_window(log_path);
+        return;
+    }
+    crash::install_panic_hook();
+
+    // The last played profile's settings decide how the window opens
+    let profiles = Profiles::load();
+    let window_config = WindowConfig::load();
+    // Every line logged is kept for bug reports too
+    let default_plugins = DefaultPlugins
+        .set(window_config.window_plugin())
+        .set(LogPlugin {
+            custom_layer: bug_report::log_layer,
+            ..default()
+        });
     App::new()
-        .add_plugins(DefaultPlugins)
-        .add_plugins(AudioPlugin) // Add this line
+        .add_plugins(default_plugins)
+        .insert_resource(profiles)
+        .insert_resource(window_config)
+        .add_plugins(game_plugins)
+        .run();
+}
+
+// Everything the game adds on top of Bevy's own plugins, shared with the headless app the tests
+// build in `test_app`
+fn game_plugins(app: &mut App) {
+    app.add_plugins(AudioPlugin) // Add this line
         // Insert as resource the initial value for the settings resources
         .insert_resource(DisplayQuality::Medium)
         .insert_resource(Volume(7))
-        .insert_resource(PendingAirCards::default()) // Add this line
+        .insert_resource(TextSize::Normal)
         // Declare the game state, whose starting value is determined by the `Default` trait
         .init_state::<GameState>()
+        // Entities spawned with `StateScoped` are despawned when their state is left
+        .enable_state_scoped_entities::<GameState>()
         .add_systems(Startup, setup)
         // Adds the plugins for each state
         .add_plugins((
@@ -88,12 +187,54 @@ fn main() {
             game2::game_plugin_2,
             game3::game_plugin_3,
             game4::game_plugin_3,
+            combat::combat_plugin,
             chapter1::chapter1_plugin,
             chapter2::chapter2_plugin,
             chapter3::chapter3_plugin,
             chapter4::chapter3_plugin,
+            ending::ending_plugin,
+            save::save_plugin,
+            inventory::inventory_plugin,
+            gold::gold_plugin,
         ))
-        .run();
+        .add_plugins((
+            tween::tween_plugin,
+            reveal::reveal_plugin,
+            deck_building::deck_building_plugin,
+            demo::demo_plugin,
+            tutorial::tutorial_plugin,
+            difficulty::difficulty_plugin,
+            perf_overlay::perf_overlay_plugin,
+            shared_assets::shared_assets_plugin,
+            loading::loading_plugin,
+            window_config::window_config_plugin,
+            cursor::cursor_plugin,
+            rumble::rumble_plugin,
+            platform::platform_plugin,
+            shop::shop_plugin,
+            bug_report::bug_report_plugin,
+        ))
+        .add_plugins((
+            profile::profile_plugin,
+            telemetry::telemetry_plugin,
+            crash::crash_plugin,
+            game_window::game_window_plugin,
+            pause::pause_plugin,
+            chapters::chapters_plugin,
+            speedrun::speedrun_plugin,
+            daily::daily_plugin,
+            endless::endless_plugin,
+            arena::arena_plugin,
+            cutscene::cutscene_plugin,
+            duel::duel_plugin,
+            leaderboard::leaderboard_plugin,
+            campfire::campfire_plugin,
+            ambience::ambience_plugin,
+        ))
+        .add_plugins(bench::bench_plugin)
+        .add_plugins(theme::theme_plugin);
+    #[cfg(feature = "dev-tools")]
+    app.add_plugins(cheats::cheats_plugin);
 }
 
 fn setup(mut commands: Commands) {
@@ -103,7 +244,9 @@ fn setup(mut commands: Commands) {
 mod splash {
     use bevy::prelude::*;
 
-    use super::{despawn_screen, GameState};
+    use super::GameState;
+    use crate::save::SaveConflict;
+    use crate::telemetry::Telemetry;
 
     // This plugin will display a splash screen with Bevy logo for 1 second before switching to the menu
     pub fn splash_plugin(app: &mut App) {
@@ -112,15 +255,9 @@ mod splash {
             // When entering the state, spawn everything needed for this screen
             .add_systems(OnEnter(GameState::Splash), splash_setup)
             // While in this state, run the `countdown` system
-            .add_systems(Update, countdown.run_if(in_state(GameState::Splash)))
-            // When exiting the state, despawn everything that was spawned for this screen
-            .add_systems(OnExit(GameState::Splash), despawn_screen::<OnSplashScreen>);
+            .add_systems(Update, countdown.run_if(in_state(GameState::Splash)));
     }
 
-    // Tag component used to tag entities added on the splash screen
-    #[derive(Component)]
-    struct OnSplashScreen;
-
     // Newtype to use a `Timer` for this screen as a resource
     #[derive(Resource, Deref, DerefMut)]
     struct SplashTimer(Timer);
@@ -140,7 +277,7 @@ mod splash {
                     },
                     ..default()
                 },
-                OnSplashScreen,
+                StateScoped(GameState::Splash),
             ))
             .with_children(|parent| {
                 parent.spawn(ImageBundle {
@@ -157,104 +294,58 @@ mod splash {
         commands.insert_resource(SplashTimer(Timer::from_seconds(1.0, TimerMode::Once)));
     }
 
-    // Tick the timer, and change state when finished
+    // Tick the timer, and change state when finished, once any save conflict is settled and the
+    // player has answered the telemetry prompt
     fn countdown(
         mut game_state: ResMut<NextState<GameState>>,
         time: Res<Time>,
         mut timer: ResMut<SplashTimer>,
+        conflict: Option<Res<SaveConflict>>,
+        telemetry: Res<Telemetry>,
     ) {
-        if timer.tick(time.delta()).finished() {
+        let answered = conflict.is_none() && telemetry.consent.is_some();
+        if timer.tick(time.delta()).finished() && answered {
             game_state.set(GameState::Menu);
         }
     }
 }
 
 mod game {
-    use super::{despawn_screen, DisplayQuality, GameState, Volume, TEXT_COLOR};
+    use super::{despawn_screen, DisplayQuality, GameState};
+    use crate::cutscene::{
+        animate_sprite, finish_cutscene, manage_text_sequence, record_cutscene_progress,
+        resume_cutscene, run_reaction_prompt, spawn_background, spawn_initial_text, type_text,
+        Cutscenes, ReactionPrompt, TextContainer, TextSequenceState, TypingText,
+    };
+    use crate::game_window::GameWindow;
+    use crate::save::SaveData;
+    use crate::texture_quality::{sheet_layout, texture_path};
+    use crate::theme::Theme;
     use bevy::prelude::*;
 
-    // Add this new resource to handle the custom font
-    #[derive(Resource)]
-    struct GameFont(Handle<Font>);
-
-    #[derive(Component)]
-    struct OnGameScreen;
-
-    #[derive(Component)]
-    struct AnimationTimer(Timer);
-
-    #[derive(Component)]
-    struct AnimationIndices {
-        first: usize,
-        last: usize,
-    }
-
-    #[derive(Component)]
-    struct TextContainer;
-
-    #[derive(Component)]
-    struct TypingText {
-        full_text: String,
-        current_index: usize,
-        timer: Timer,
-        completed: bool,
-    }
-
-    #[derive(Component)]
-    struct TextSequence {
-        sequence_index: usize,
-    }
-
-    #[derive(Resource)]
-    struct TextSequenceState {
-        current_sequence: usize,
-        texts: Vec<String>,
-        delay_timer: Timer,
-        ready_for_next: bool,
-    }
-
-    #[derive(Resource)]
-    struct TypewriterSound(Handle<AudioSource>);
-
-    #[derive(Resource, Deref, DerefMut)]
-    struct GameTimer(Timer);
-
     fn game_setup(
         mut commands: Commands,
         display_quality: Res<DisplayQuality>,
-        volume: Res<Volume>,
+        cutscenes: Cutscenes,
         asset_server: Res<AssetServer>,
         mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
-        windows: Query<&Window>,
+        game_window: GameWindow,
+        theme: Res<Theme>,
     ) {
-        let window = windows.single();
-
-        // Load custom font and create resource
-        let custom_font = asset_server.load("joystix monospace.otf");
-        commands.insert_resource(GameFont(custom_font));
-        commands.init_resource::<GameSetupComplete>();
-
-        // Load typewriter sound
-        let typewriter_sound = asset_server.load("sounds/typewriter.ogg");
-        commands.insert_resource(TypewriterSound(typewriter_sound));
+        let Some(window) = game_window.get() else {
+            return;
+        };
 
         // Initialize text sequence
-        commands.insert_resource(TextSequenceState {
-            current_sequence: 0,
-            texts: vec![
-                "  ".to_string(),
-                "Strange... the patterns are shifting...".to_string(),
-                "You awake in a magic forest".to_string(),
-                "Something breaks a twig nearby...".to_string(),
-            ],
-            delay_timer: Timer::from_seconds(4.0, TimerMode::Once), // 4 second delay between texts
-            ready_for_next: true,
-        });
+        commands.insert_resource(cutscenes.chapter(1));
 
         // Load the sprite sheet
-        let texture_handle = asset_server.load("textures/intro_game_sprite.png");
+        let texture_handle = asset_server.load(texture_path(
+            "textures/intro_game_sprite.png",
+            *display_quality,
+        ));
 
-        let layout = TextureAtlasLayout::from_grid(UVec2::new(576, 324), 5, 64, None, None);
+        let layout = sheet_layout(*display_quality);
 
         let atlas_layout = atlas_layouts.add(layout);
 
@@ -271,7 +362,7 @@ mod game {
                     },
                     ..default()
                 },
-                OnGameScreen,
+                StateScoped(GameState::Game),
             ))
             .with_children(|parent| {
                 // Text container at the bottom
@@ -288,7 +379,7 @@ mod game {
                             bottom: Val::Px(0.0),
                             ..default()
                         },
-                        background_color: Color::srgba(0.0, 0.0, 0.0, 0.7).into(),
+                        background_color: theme.dialogue_backdrop.into(),
                         ..default()
                     },
                     TextContainer,
@@ -307,321 +398,101 @@ mod game {
                         ..default()
                     })
                     .with_children(|parent| {
-                        parent.spawn((
-                            SpriteSheetBundle {
-                                texture: texture_handle,
-                                atlas: TextureAtlas {
-                                    layout: atlas_layout,
-                                    index: 0,
-                                },
-                                transform: Transform::from_xyz(
-                                    -window.width() / 2.0,
-                                    -window.height() / 2.0 + 60.0,
-                                    1.0,
-                                ),
-                                sprite: Sprite {
-                                    custom_size: Some(Vec2::new(1920.0, 1080.0)),
-                                    anchor: bevy::sprite::Anchor::Center,
-                                    ..default()
-                                },
-                                ..default()
-                            },
-                            AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)), // for (entity, _, sequence) in typing_query.iter() {
-                            //     if sequence.sequence_index <= sequence_state.current_sequence {
-                            //         commands.entity(entity).despawn();
-                            //     }
-                            // }
-                            AnimationIndices {
-                                first: 0,
-                                last: 320,
-                            },
-                        ));
+                        spawn_background(
+                            parent,
+                            cutscenes.frames(1),
+                            texture_handle,
+                            atlas_layout,
+                            &asset_server,
+                            window,
+                        );
                     });
             });
 
-        commands.insert_resource(GameTimer(Timer::from_seconds(14.0, TimerMode::Once)));
         // Immediately despawn all previous text when we're ready for the next one
         // for (entity, _, sequence) in typing_query.iter() {
         //     if sequence.sequence_index <= sequence_state.current_sequence {
         //         commands.entity(entity).despawn();
         //     }
         // }
-        // make liek 20 s when proper
-
-        // We can't spawn the text in game_setup because we need to wait for the GameFont resource to be available
-        // Instead, we'll create a new system to handle the initial text spawn
-    }
-
-    // Add marker resource to ensure proper setup order
-    #[derive(Resource, Default)]
-    struct GameSetupComplete;
-
-    // Modify the spawn_initial_text system to run after setup is complete
-    fn spawn_initial_text(
-        mut commands: Commands,
-        game_font: Res<GameFont>,
-        setup_complete: Res<GameSetupComplete>,
-    ) {
-        spawn_text_entity(&mut commands, 0, &game_font);
     }
 
     // Update the game plugin with proper system ordering
     pub fn game_plugin(app: &mut App) {
-        app.init_resource::<GameSetupComplete>() // Initialize the marker resource
-            .add_systems(OnEnter(GameState::Game), game_setup)
+        app.add_systems(OnEnter(GameState::Game), game_setup)
             .add_systems(
                 OnEnter(GameState::Game),
-                spawn_initial_text.after(game_setup),
+                (resume_cutscene, spawn_initial_text)
+                    .chain()
+                    .after(game_setup),
             )
             .add_systems(
                 Update,
-                (game, animate_sprite, manage_text_sequence, type_text)
+                (
+                    game,
+                    animate_sprite,
+                    manage_text_sequence,
+                    run_reaction_prompt,
+                    type_text,
+                    record_cutscene_progress,
+                )
                     .run_if(in_state(GameState::Game)),
             )
-            .add_systems(OnExit(GameState::Game), despawn_screen::<OnGameScreen>);
-    }
-
-    // Make sure spawn_text_entity uses the correct font parameter
-    fn spawn_text_entity(
-        commands: &mut Commands,
-        sequence_index: usize,
-        game_font: &Res<GameFont>,
-    ) {
-        commands.spawn((
-            TextBundle::from_section(
-                "",
-                TextStyle {
-                    font: game_font.0.clone(),
-                    font_size: 40.0,
-                    color: Color::WHITE,
-                    ..default()
-                },
-            )
-            .with_style(Style {
-                margin: UiRect::bottom(Val::Px(20.0)),
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
-                position_type: PositionType::Absolute,
-                bottom: Val::Px(80.0),
-                left: Val::Px(20.0),
-                right: Val::Px(20.0),
-                ..default()
-            }),
-            TypingText {
-                full_text: String::new(),
-                current_index: 0,
-                timer: Timer::from_seconds(0.08, TimerMode::Repeating),
-                completed: false,
-            },
-            TextSequence { sequence_index },
-        ));
-    }
-
-    fn manage_text_sequence(
-        mut commands: Commands,
-        mut sequence_state: ResMut<TextSequenceState>,
-        time: Res<Time>,
-        typing_query: Query<(Entity, &TypingText, &TextSequence)>,
-        game_font: Res<GameFont>,
-    ) {
-        if !sequence_state.ready_for_next {
-            sequence_state.delay_timer.tick(time.delta());
-            if sequence_state.delay_timer.finished() {
-                sequence_state.ready_for_next = true;
-                // Immediately despawn all previous text when we're ready for the next one
-                for (entity, _, sequence) in typing_query.iter() {
-                    if sequence.sequence_index <= sequence_state.current_sequence {
-                        commands.entity(entity).despawn();
-                    }
-                }
-            }
-            return;
-        }
-
-        let mut all_completed = true;
-        for (_, typing_text, sequence) in typing_query.iter() {
-            if sequence.sequence_index == sequence_state.current_sequence {
-                if !typing_text.completed {
-                    all_completed = false;
-                    break;
-                }
-            }
-        }
-
-        if all_completed && sequence_state.current_sequence < sequence_state.texts.len() - 1 {
-            // Clear any existing text before spawning new one
-            for (entity, _, _) in typing_query.iter() {
-                commands.entity(entity).despawn();
-            }
-
-            sequence_state.current_sequence += 1;
-            sequence_state.ready_for_next = false;
-            sequence_state.delay_timer.reset();
-
-            spawn_text_entity(&mut commands, sequence_state.current_sequence, &game_font);
-        }
-    }
-
-    fn type_text(
-        time: Res<Time>,
-        sequence_state: Res<TextSequenceState>,
-        mut query: Query<(&mut TypingText, &mut Text, &TextSequence)>,
-        typewriter_sound: Res<TypewriterSound>,
-        mut commands: Commands,
-    ) {
-        for (mut typing_text, mut text, sequence) in query.iter_mut() {
-            if typing_text.completed || sequence.sequence_index != sequence_state.current_sequence {
-                continue;
-            }
-
-            if typing_text.full_text.is_empty() {
-                typing_text.full_text = sequence_state.texts[sequence.sequence_index].clone();
-            }
-
-            typing_text.timer.tick(time.delta());
-
-            if typing_text.timer.just_finished()
-                && typing_text.current_index < typing_text.full_text.len()
-            {
-                let next_char = typing_text
-                    .full_text
-                    .chars()
-                    .nth(typing_text.current_index)
-                    .unwrap();
-                typing_text.current_index += 1;
-
-                if let Some(section) = text.sections.first_mut() {
-                    section.value = typing_text.full_text[..typing_text.current_index].to_string();
-                }
-
-                if next_char != ' ' {
-                    commands.spawn(AudioBundle {
-                        source: typewriter_sound.0.clone(),
-                        settings: PlaybackSettings::DESPAWN,
-                        ..default()
-                    });
-                }
-
-                if typing_text.current_index == typing_text.full_text.len() {
-                    typing_text.completed = true;
-                }
-            }
-        }
+            .add_systems(
+                OnExit(GameState::Game),
+                (
+                    despawn_screen::<TypingText>,
+                    despawn_screen::<ReactionPrompt>,
+                ),
+            );
     }
 
     fn game(
-        time: Res<Time>,
+        sequence_state: Res<TextSequenceState>,
         mut game_state: ResMut<NextState<GameState>>,
-        mut timer: ResMut<GameTimer>,
+        mut save_data: ResMut<SaveData>,
     ) {
-        if timer.tick(time.delta()).finished() {
+        if sequence_state.finished() {
+            finish_cutscene(&mut save_data);
             game_state.set(GameState::Chapter1);
         }
     }
-
-    fn animate_sprite(
-        time: Res<Time>,
-        mut query: Query<(&mut TextureAtlas, &mut AnimationTimer, &AnimationIndices)>,
-    ) {
-        for (mut atlas, mut timer, indices) in &mut query {
-            timer.0.tick(time.delta());
-            if timer.0.just_finished() {
-                atlas.index = if atlas.index == indices.last {
-                    indices.first
-                } else {
-                    atlas.index + 1
-                };
-            }
-        }
-    }
 }
 
 mod game2 {
-    use super::{despawn_screen, DisplayQuality, GameState, Volume, TEXT_COLOR};
+    use super::{despawn_screen, DisplayQuality, GameState};
+    use crate::cutscene::{
+        animate_sprite, finish_cutscene, manage_text_sequence, record_cutscene_progress,
+        resume_cutscene, run_reaction_prompt, spawn_background, spawn_initial_text, type_text,
+        Cutscenes, ReactionPrompt, TextContainer, TextSequenceState, TypingText,
+    };
+    use crate::game_window::GameWindow;
+    use crate::save::SaveData;
+    use crate::texture_quality::{sheet_layout, texture_path};
+    use crate::theme::Theme;
     use bevy::prelude::*;
 
-    // Add this new resource to handle the custom font
-    #[derive(Resource)]
-    struct GameFont(Handle<Font>);
-
-    #[derive(Component)]
-    struct OnGameScreen;
-
-    #[derive(Component)]
-    struct AnimationTimer(Timer);
-
-    #[derive(Component)]
-    struct AnimationIndices {
-        first: usize,
-        last: usize,
-    }
-
-    #[derive(Component)]
-    struct TextContainer;
-
-    #[derive(Component)]
-    struct TypingText {
-        full_text: String,
-        current_index: usize,
-        timer: Timer,
-        completed: bool,
-    }
-
-    #[derive(Component)]
-    struct TextSequence {
-        sequence_index: usize,
-    }
-
-    #[derive(Resource)]
-    struct TextSequenceState {
-        current_sequence: usize,
-        texts: Vec<String>,
-        delay_timer: Timer,
-        ready_for_next: bool,
-    }
-
-    #[derive(Resource)]
-    struct TypewriterSound(Handle<AudioSource>);
-
-    #[derive(Resource, Deref, DerefMut)]
-    struct GameTimer(Timer);
-
     fn game_setup2(
         mut commands: Commands,
         display_quality: Res<DisplayQuality>,
-        volume: Res<Volume>,
+        cutscenes: Cutscenes,
         asset_server: Res<AssetServer>,
         mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
-        windows: Query<&Window>,
+        game_window: GameWindow,
+        theme: Res<Theme>,
     ) {
-        let window = windows.single();
-
-        // Load custom font and create resource
-        let custom_font = asset_server.load("joystix monospace.otf");
-        commands.insert_resource(GameFont(custom_font));
-        commands.init_resource::<GameSetupComplete>();
-
-        // Load typewriter sound
-        let typewriter_sound = asset_server.load("sounds/typewriter.ogg");
-        commands.insert_resource(TypewriterSound(typewriter_sound));
+        let Some(window) = game_window.get() else {
+            return;
+        };
 
         // Initialize text sequence
-        commands.insert_resource(TextSequenceState {
-            current_sequence: 0,
-            texts: vec![
-                "  ".to_string(),
-                "As you walk you come across a fort...".to_string(),
-                "The door shudders in the wind".to_string(),
-                "Suddenly the door swings open...".to_string(),
-            ],
-            delay_timer: Timer::from_seconds(4.0, TimerMode::Once), // 4 second delay between texts
-            ready_for_next: true,
-        });
+        commands.insert_resource(cutscenes.chapter(2));
 
         // Load the sprite sheet
-        let texture_handle = asset_server.load("textures/forest_fort.png");
+        let texture_handle =
+            asset_server.load(texture_path("textures/forest_fort.png", *display_quality));
 
-        let layout = TextureAtlasLayout::from_grid(UVec2::new(576, 324), 5, 64, None, None);
+        let layout = sheet_layout(*display_quality);
 
         let atlas_layout = atlas_layouts.add(layout);
 
@@ -638,7 +509,7 @@ mod game2 {
                     },
                     ..default()
                 },
-                OnGameScreen,
+                StateScoped(GameState::Game2),
             ))
             .with_children(|parent| {
                 // Text container at the bottom
@@ -655,7 +526,7 @@ mod game2 {
                             bottom: Val::Px(0.0),
                             ..default()
                         },
-                        background_color: Color::srgba(0.0, 0.0, 0.0, 0.7).into(),
+                        background_color: theme.dialogue_backdrop.into(),
                         ..default()
                     },
                     TextContainer,
@@ -674,323 +545,101 @@ mod game2 {
                         ..default()
                     })
                     .with_children(|parent| {
-                        parent.spawn((
-                            SpriteSheetBundle {
-                                texture: texture_handle,
-                                atlas: TextureAtlas {
-                                    layout: atlas_layout,
-                                    index: 0,
-                                },
-                                transform: Transform::from_xyz(
-                                    -window.width() / 2.0,
-                                    -window.height() / 2.0 + 60.0,
-                                    1.0,
-                                ),
-                                sprite: Sprite {
-                                    custom_size: Some(Vec2::new(1920.0, 1080.0)),
-                                    anchor: bevy::sprite::Anchor::Center,
-                                    ..default()
-                                },
-                                ..default()
-                            },
-                            AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)), // for (entity, _, sequence) in typing_query.iter() {
-                            //     if sequence.sequence_index <= sequence_state.current_sequence {
-                            //         commands.entity(entity).despawn();
-                            //     }
-                            // }
-                            AnimationIndices {
-                                first: 0,
-                                last: 320,
-                            },
-                        ));
+                        spawn_background(
+                            parent,
+                            cutscenes.frames(2),
+                            texture_handle,
+                            atlas_layout,
+                            &asset_server,
+                            window,
+                        );
                     });
             });
 
-        commands.insert_resource(GameTimer(Timer::from_seconds(14.0, TimerMode::Once)));
         // Immediately despawn all previous text when we're ready for the next one
         // for (entity, _, sequence) in typing_query.iter() {
         //     if sequence.sequence_index <= sequence_state.current_sequence {
         //         commands.entity(entity).despawn();
         //     }
         // }
-        // make liek 20 s when proper
-
-        // We can't spawn the text in game_setup because we need to wait for the GameFont resource to be available
-        // Instead, we'll create a new system to handle the initial text spawn
-    }
-
-    // Add marker resource to ensure proper setup order
-    #[derive(Resource, Default)]
-    struct GameSetupComplete;
-
-    // Modify the spawn_initial_text system to run after setup is complete
-    fn spawn_initial_text(
-        mut commands: Commands,
-        game_font: Res<GameFont>,
-        setup_complete: Res<GameSetupComplete>,
-    ) {
-        spawn_text_entity(&mut commands, 0, &game_font);
     }
 
     // Update the game plugin with proper system ordering
     pub fn game_plugin_2(app: &mut App) {
-        app.init_resource::<GameSetupComplete>() // Initialize the marker resource
-            .add_systems(OnEnter(GameState::Game2), game_setup2)
+        app.add_systems(OnEnter(GameState::Game2), game_setup2)
             .add_systems(
                 OnEnter(GameState::Game2),
-                spawn_initial_text.after(game_setup2),
+                (resume_cutscene, spawn_initial_text)
+                    .chain()
+                    .after(game_setup2),
             )
             .add_systems(
                 Update,
-                (game2, animate_sprite, manage_text_sequence, type_text)
+                (
+                    game2,
+                    animate_sprite,
+                    manage_text_sequence,
+                    run_reaction_prompt,
+                    type_text,
+                    record_cutscene_progress,
+                )
                     .run_if(in_state(GameState::Game2)),
             )
-            .add_systems(OnExit(GameState::Game2), despawn_screen::<OnGameScreen>);
-    }
-
-    // Make sure spawn_text_entity uses the correct font parameter
-    fn spawn_text_entity(
-        commands: &mut Commands,
-        sequence_index: usize,
-        game_font: &Res<GameFont>,
-    ) {
-        commands.spawn((
-            TextBundle::from_section(
-                "",
-                TextStyle {
-                    font: game_font.0.clone(),
-                    font_size: 40.0,
-                    color: Color::WHITE,
-                    ..default()
-                },
-            )
-            .with_style(Style {
-                margin: UiRect::bottom(Val::Px(20.0)),
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
-                position_type: PositionType::Absolute,
-                bottom: Val::Px(80.0),
-                left: Val::Px(20.0),
-                right: Val::Px(20.0),
-                ..default()
-            }),
-            TypingText {
-                full_text: String::new(),
-                current_index: 0,
-                timer: Timer::from_seconds(0.08, TimerMode::Repeating),
-                completed: false,
-            },
-            TextSequence { sequence_index },
-        ));
-    }
-
-    fn manage_text_sequence(
-        mut commands: Commands,
-        mut sequence_state: ResMut<TextSequenceState>,
-        time: Res<Time>,
-        typing_query: Query<(Entity, &TypingText, &TextSequence)>,
-        game_font: Res<GameFont>,
-    ) {
-        if !sequence_state.ready_for_next {
-            sequence_state.delay_timer.tick(time.delta());
-            if sequence_state.delay_timer.finished() {
-                sequence_state.ready_for_next = true;
-                // Immediately despawn all previous text when we're ready for the next one
-                for (entity, _, sequence) in typing_query.iter() {
-                    if sequence.sequence_index <= sequence_state.current_sequence {
-                        commands.entity(entity).despawn();
-                    }
-                }
-            }
-            return;
-        }
-
-        let mut all_completed = true;
-        for (_, typing_text, sequence) in typing_query.iter() {
-            if sequence.sequence_index == sequence_state.current_sequence {
-                if !typing_text.completed {
-                    all_completed = false;
-                    break;
-                }
-            }
-        }
-
-        if all_completed && sequence_state.current_sequence < sequence_state.texts.len() - 1 {
-            // Clear any existing text before spawning new one
-            for (entity, _, _) in typing_query.iter() {
-                commands.entity(entity).despawn();
-            }
-
-            sequence_state.current_sequence += 1;
-            sequence_state.ready_for_next = false;
-            sequence_state.delay_timer.reset();
-
-            spawn_text_entity(&mut commands, sequence_state.current_sequence, &game_font);
-        }
-    }
-
-    fn type_text(
-        time: Res<Time>,
-        sequence_state: Res<TextSequenceState>,
-        mut query: Query<(&mut TypingText, &mut Text, &TextSequence)>,
-        typewriter_sound: Res<TypewriterSound>,
-        mut commands: Commands,
-    ) {
-        for (mut typing_text, mut text, sequence) in query.iter_mut() {
-            if typing_text.completed || sequence.sequence_index != sequence_state.current_sequence {
-                continue;
-            }
-
-            if typing_text.full_text.is_empty() {
-                typing_text.full_text = sequence_state.texts[sequence.sequence_index].clone();
-            }
-
-            typing_text.timer.tick(time.delta());
-
-            if typing_text.timer.just_finished()
-                && typing_text.current_index < typing_text.full_text.len()
-            {
-                let next_char = typing_text
-                    .full_text
-                    .chars()
-                    .nth(typing_text.current_index)
-                    .unwrap();
-                typing_text.current_index += 1;
-
-                if let Some(section) = text.sections.first_mut() {
-                    section.value = typing_text.full_text[..typing_text.current_index].to_string();
-                }
-
-                if next_char != ' ' {
-                    commands.spawn(AudioBundle {
-                        source: typewriter_sound.0.clone(),
-                        settings: PlaybackSettings::DESPAWN,
-                        ..default()
-                    });
-                }
-
-                if typing_text.current_index == typing_text.full_text.len() {
-                    typing_text.completed = true;
-                }
-            }
-        }
+            .add_systems(
+                OnExit(GameState::Game2),
+                (
+                    despawn_screen::<TypingText>,
+                    despawn_screen::<ReactionPrompt>,
+                ),
+            );
     }
 
     fn game2(
-        time: Res<Time>,
+        sequence_state: Res<TextSequenceState>,
         mut game_state: ResMut<NextState<GameState>>,
-        mut timer: ResMut<GameTimer>,
+        mut save_data: ResMut<SaveData>,
     ) {
-        if timer.tick(time.delta()).finished() {
+        if sequence_state.finished() {
+            finish_cutscene(&mut save_data);
             game_state.set(GameState::Chapter2);
         }
     }
-
-    fn animate_sprite(
-        time: Res<Time>,
-        mut query: Query<(&mut TextureAtlas, &mut AnimationTimer, &AnimationIndices)>,
-    ) {
-        for (mut atlas, mut timer, indices) in &mut query {
-            timer.0.tick(time.delta());
-            if timer.0.just_finished() {
-                atlas.index = if atlas.index == indices.last {
-                    indices.first
-                } else {
-                    atlas.index + 1
-                };
-            }
-        }
-    }
 }
 
 mod game3 {
-    use super::{despawn_screen, DisplayQuality, GameState, Volume, TEXT_COLOR};
+    use super::{despawn_screen, DisplayQuality, GameState};
+    use crate::cutscene::{
+        animate_sprite, manage_text_sequence, run_reaction_prompt, spawn_background,
+        spawn_initial_text, type_text, Cutscenes, ReactionPrompt, TextContainer, TextSequenceState,
+        TypingText,
+    };
+    use crate::game_window::GameWindow;
+    use crate::texture_quality::{sheet_layout, texture_path};
+    use crate::theme::Theme;
     use bevy::prelude::*;
 
-    // Add this new resource to handle the custom font
-    #[derive(Resource)]
-    struct GameFont(Handle<Font>);
-
-    #[derive(Component)]
-    struct OnGameScreen;
+    fn game_setup3(
+        mut commands: Commands,
+        display_quality: Res<DisplayQuality>,
+        cutscenes: Cutscenes,
+        asset_server: Res<AssetServer>,
+        mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+        game_window: GameWindow,
+        theme: Res<Theme>,
+    ) {
+        let Some(window) = game_window.get() else {
+            return;
+        };
 
-    #[derive(Component)]
-    struct AnimationTimer(Timer);
+        // Initialize text sequence
+        commands.insert_resource(cutscenes.chapter(3));
 
-    #[derive(Component)]
-    struct AnimationIndices {
-        first: usize,
-        last: usize,
-    }
+        // Load the sprite sheet
+        let texture_handle = asset_server.load(texture_path("textures/pool.png", *display_quality));
 
-    #[derive(Component)]
-    struct TextContainer;
+        let layout = sheet_layout(*display_quality);
 
-    #[derive(Component)]
-    struct TypingText {
-        full_text: String,
-        current_index: usize,
-        timer: Timer,
-        completed: bool,
-    }
-
-    #[derive(Component)]
-    struct TextSequence {
-        sequence_index: usize,
-    }
-
-    #[derive(Resource)]
-    struct TextSequenceState {
-        current_sequence: usize,
-        texts: Vec<String>,
-        delay_timer: Timer,
-        ready_for_next: bool,
-    }
-
-    #[derive(Resource)]
-    struct TypewriterSound(Handle<AudioSource>);
-
-    #[derive(Resource, Deref, DerefMut)]
-    struct GameTimer(Timer);
-
-    fn game_setup3(
-        mut commands: Commands,
-        display_quality: Res<DisplayQuality>,
-        volume: Res<Volume>,
-        asset_server: Res<AssetServer>,
-        mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
-        windows: Query<&Window>,
-    ) {
-        let window = windows.single();
-
-        // Load custom font and create resource
-        let custom_font = asset_server.load("joystix monospace.otf");
-        commands.insert_resource(GameFont(custom_font));
-        commands.init_resource::<GameSetupComplete>();
-
-        // Load typewriter sound
-        let typewriter_sound = asset_server.load("sounds/typewriter.ogg");
-        commands.insert_resource(TypewriterSound(typewriter_sound));
-
-        // Initialize text sequence
-        commands.insert_resource(TextSequenceState {
-            current_sequence: 0,
-            texts: vec![
-                "  ".to_string(),
-                "Clearing the fort you hear running water".to_string(),
-                "Did the statue rotate...".to_string(),
-                "It's probably an illusion...".to_string(),
-            ],
-            delay_timer: Timer::from_seconds(4.0, TimerMode::Once), // 4 second delay between texts
-            ready_for_next: true,
-        });
-
-        // Load the sprite sheet
-        let texture_handle = asset_server.load("textures/pool.png");
-
-        let layout = TextureAtlasLayout::from_grid(UVec2::new(576, 324), 5, 64, None, None);
-
-        let atlas_layout = atlas_layouts.add(layout);
+        let atlas_layout = atlas_layouts.add(layout);
 
         commands
             .spawn((
@@ -1005,7 +654,7 @@ mod game3 {
                     },
                     ..default()
                 },
-                OnGameScreen,
+                StateScoped(GameState::Game3),
             ))
             .with_children(|parent| {
                 // Text container at the bottom
@@ -1022,7 +671,7 @@ mod game3 {
                             bottom: Val::Px(0.0),
                             ..default()
                         },
-                        background_color: Color::srgba(0.0, 0.0, 0.0, 0.7).into(),
+                        background_color: theme.dialogue_backdrop.into(),
                         ..default()
                     },
                     TextContainer,
@@ -1041,321 +690,92 @@ mod game3 {
                         ..default()
                     })
                     .with_children(|parent| {
-                        parent.spawn((
-                            SpriteSheetBundle {
-                                texture: texture_handle,
-                                atlas: TextureAtlas {
-                                    layout: atlas_layout,
-                                    index: 0,
-                                },
-                                transform: Transform::from_xyz(
-                                    -window.width() / 2.0,
-                                    -window.height() / 2.0 + 60.0,
-                                    1.0,
-                                ),
-                                sprite: Sprite {
-                                    custom_size: Some(Vec2::new(1920.0, 1080.0)),
-                                    anchor: bevy::sprite::Anchor::Center,
-                                    ..default()
-                                },
-                                ..default()
-                            },
-                            AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)), // for (entity, _, sequence) in typing_query.iter() {
-                            //     if sequence.sequence_index <= sequence_state.current_sequence {
-                            //         commands.entity(entity).despawn();
-                            //     }
-                            // }
-                            AnimationIndices {
-                                first: 0,
-                                last: 320,
-                            },
-                        ));
+                        spawn_background(
+                            parent,
+                            cutscenes.frames(3),
+                            texture_handle,
+                            atlas_layout,
+                            &asset_server,
+                            window,
+                        );
                     });
             });
 
-        commands.insert_resource(GameTimer(Timer::from_seconds(14.0, TimerMode::Once)));
         // Immediately despawn all previous text when we're ready for the next one
         // for (entity, _, sequence) in typing_query.iter() {
         //     if sequence.sequence_index <= sequence_state.current_sequence {
         //         commands.entity(entity).despawn();
         //     }
         // }
-        // make liek 20 s when proper
-
-        // We can't spawn the text in game_setup because we need to wait for the GameFont resource to be available
-        // Instead, we'll create a new system to handle the initial text spawn
-    }
-
-    // Add marker resource to ensure proper setup order
-    #[derive(Resource, Default)]
-    struct GameSetupComplete;
-
-    // Modify the spawn_initial_text system to run after setup is complete
-    fn spawn_initial_text(
-        mut commands: Commands,
-        game_font: Res<GameFont>,
-        setup_complete: Res<GameSetupComplete>,
-    ) {
-        spawn_text_entity(&mut commands, 0, &game_font);
     }
 
     // Update the game plugin with proper system ordering
     pub fn game_plugin_3(app: &mut App) {
-        app.init_resource::<GameSetupComplete>() // Initialize the marker resource
-            .add_systems(OnEnter(GameState::Game3), game_setup3)
+        app.add_systems(OnEnter(GameState::Game3), game_setup3)
             .add_systems(
                 OnEnter(GameState::Game3),
                 spawn_initial_text.after(game_setup3),
             )
             .add_systems(
                 Update,
-                (game3, animate_sprite, manage_text_sequence, type_text)
+                (
+                    game3,
+                    animate_sprite,
+                    manage_text_sequence,
+                    run_reaction_prompt,
+                    type_text,
+                )
                     .run_if(in_state(GameState::Game3)),
             )
-            .add_systems(OnExit(GameState::Game3), despawn_screen::<OnGameScreen>);
-    }
-
-    // Make sure spawn_text_entity uses the correct font parameter
-    fn spawn_text_entity(
-        commands: &mut Commands,
-        sequence_index: usize,
-        game_font: &Res<GameFont>,
-    ) {
-        commands.spawn((
-            TextBundle::from_section(
-                "",
-                TextStyle {
-                    font: game_font.0.clone(),
-                    font_size: 40.0,
-                    color: Color::WHITE,
-                    ..default()
-                },
-            )
-            .with_style(Style {
-                margin: UiRect::bottom(Val::Px(20.0)),
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
-                position_type: PositionType::Absolute,
-                bottom: Val::Px(80.0),
-                left: Val::Px(20.0),
-                right: Val::Px(20.0),
-                ..default()
-            }),
-            TypingText {
-                full_text: String::new(),
-                current_index: 0,
-                timer: Timer::from_seconds(0.08, TimerMode::Repeating),
-                completed: false,
-            },
-            TextSequence { sequence_index },
-        ));
-    }
-
-    fn manage_text_sequence(
-        mut commands: Commands,
-        mut sequence_state: ResMut<TextSequenceState>,
-        time: Res<Time>,
-        typing_query: Query<(Entity, &TypingText, &TextSequence)>,
-        game_font: Res<GameFont>,
-    ) {
-        if !sequence_state.ready_for_next {
-            sequence_state.delay_timer.tick(time.delta());
-            if sequence_state.delay_timer.finished() {
-                sequence_state.ready_for_next = true;
-                // Immediately despawn all previous text when we're ready for the next one
-                for (entity, _, sequence) in typing_query.iter() {
-                    if sequence.sequence_index <= sequence_state.current_sequence {
-                        commands.entity(entity).despawn();
-                    }
-                }
-            }
-            return;
-        }
-
-        let mut all_completed = true;
-        for (_, typing_text, sequence) in typing_query.iter() {
-            if sequence.sequence_index == sequence_state.current_sequence {
-                if !typing_text.completed {
-                    all_completed = false;
-                    break;
-                }
-            }
-        }
-
-        if all_completed && sequence_state.current_sequence < sequence_state.texts.len() - 1 {
-            // Clear any existing text before spawning new one
-            for (entity, _, _) in typing_query.iter() {
-                commands.entity(entity).despawn();
-            }
-
-            sequence_state.current_sequence += 1;
-            sequence_state.ready_for_next = false;
-            sequence_state.delay_timer.reset();
-
-            spawn_text_entity(&mut commands, sequence_state.current_sequence, &game_font);
-        }
-    }
-
-    fn type_text(
-        time: Res<Time>,
-        sequence_state: Res<TextSequenceState>,
-        mut query: Query<(&mut TypingText, &mut Text, &TextSequence)>,
-        typewriter_sound: Res<TypewriterSound>,
-        mut commands: Commands,
-    ) {
-        for (mut typing_text, mut text, sequence) in query.iter_mut() {
-            if typing_text.completed || sequence.sequence_index != sequence_state.current_sequence {
-                continue;
-            }
-
-            if typing_text.full_text.is_empty() {
-                typing_text.full_text = sequence_state.texts[sequence.sequence_index].clone();
-            }
-
-            typing_text.timer.tick(time.delta());
-
-            if typing_text.timer.just_finished()
-                && typing_text.current_index < typing_text.full_text.len()
-            {
-                let next_char = typing_text
-                    .full_text
-                    .chars()
-                    .nth(typing_text.current_index)
-                    .unwrap();
-                typing_text.current_index += 1;
-
-                if let Some(section) = text.sections.first_mut() {
-                    section.value = typing_text.full_text[..typing_text.current_index].to_string();
-                }
-
-                if next_char != ' ' {
-                    commands.spawn(AudioBundle {
-                        source: typewriter_sound.0.clone(),
-                        settings: PlaybackSettings::DESPAWN,
-                        ..default()
-                    });
-                }
-
-                if typing_text.current_index == typing_text.full_text.len() {
-                    typing_text.completed = true;
-                }
-            }
-        }
+            .add_systems(
+                OnExit(GameState::Game3),
+                (
+                    despawn_screen::<TypingText>,
+                    despawn_screen::<ReactionPrompt>,
+                ),
+            );
     }
 
-    fn game3(
-        time: Res<Time>,
-        mut game_state: ResMut<NextState<GameState>>,
-        mut timer: ResMut<GameTimer>,
-    ) {
-        if timer.tick(time.delta()).finished() {
+    fn game3(sequence_state: Res<TextSequenceState>, mut game_state: ResMut<NextState<GameState>>) {
+        if sequence_state.finished() {
             game_state.set(GameState::Chapter3);
         }
     }
-
-    fn animate_sprite(
-        time: Res<Time>,
-        mut query: Query<(&mut TextureAtlas, &mut AnimationTimer, &AnimationIndices)>,
-    ) {
-        for (mut atlas, mut timer, indices) in &mut query {
-            timer.0.tick(time.delta());
-            if timer.0.just_finished() {
-                atlas.index = if atlas.index == indices.last {
-                    indices.first
-                } else {
-                    atlas.index + 1
-                };
-            }
-        }
-    }
 }
 
 mod game4 {
-    use super::{despawn_screen, DisplayQuality, GameState, Volume, TEXT_COLOR};
+    use super::{despawn_screen, DisplayQuality, GameState};
+    use crate::cutscene::{
+        animate_sprite, manage_text_sequence, run_reaction_prompt, spawn_background,
+        spawn_initial_text, type_text, Cutscenes, ReactionPrompt, TextContainer, TextSequenceState,
+        TypingText,
+    };
+    use crate::game_window::GameWindow;
+    use crate::texture_quality::{sheet_layout, texture_path};
+    use crate::theme::Theme;
     use bevy::prelude::*;
 
-    // Add this new resource to handle the custom font
-    #[derive(Resource)]
-    struct GameFont(Handle<Font>);
-
-    #[derive(Component)]
-    struct OnGameScreen;
-
-    #[derive(Component)]
-    struct AnimationTimer(Timer);
-
-    #[derive(Component)]
-    struct AnimationIndices {
-        first: usize,
-        last: usize,
-    }
-
-    #[derive(Component)]
-    struct TextContainer;
-
-    #[derive(Component)]
-    struct TypingText {
-        full_text: String,
-        current_index: usize,
-        timer: Timer,
-        completed: bool,
-    }
-
-    #[derive(Component)]
-    struct TextSequence {
-        sequence_index: usize,
-    }
-
-    #[derive(Resource)]
-    struct TextSequenceState {
-        current_sequence: usize,
-        texts: Vec<String>,
-        delay_timer: Timer,
-        ready_for_next: bool,
-    }
-
-    #[derive(Resource)]
-    struct TypewriterSound(Handle<AudioSource>);
-
-    #[derive(Resource, Deref, DerefMut)]
-    struct GameTimer(Timer);
-
     fn game_setup3(
         mut commands: Commands,
         display_quality: Res<DisplayQuality>,
-        volume: Res<Volume>,
+        cutscenes: Cutscenes,
         asset_server: Res<AssetServer>,
         mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
-        windows: Query<&Window>,
+        game_window: GameWindow,
+        theme: Res<Theme>,
     ) {
-        let window = windows.single();
-
-        // Load custom font and create resource
-        let custom_font = asset_server.load("joystix monospace.otf");
-        commands.insert_resource(GameFont(custom_font));
-        commands.init_resource::<GameSetupComplete>();
-
-        // Load typewriter sound
-        let typewriter_sound = asset_server.load("sounds/typewriter.ogg");
-        commands.insert_resource(TypewriterSound(typewriter_sound));
+        let Some(window) = game_window.get() else {
+            return;
+        };
 
         // Initialize text sequence
-        commands.insert_resource(TextSequenceState {
-            current_sequence: 0,
-            texts: vec![
-                "  ".to_string(),
-                "A pile of rubble lies at your feet".to_string(),
-                "You hear voices chanting...".to_string(),
-                "Stella luminara, verita serena...".to_string(),
-            ],
-            delay_timer: Timer::from_seconds(4.0, TimerMode::Once), // 4 second delay between texts
-            ready_for_next: true,
-        });
+        commands.insert_resource(cutscenes.chapter(4));
 
         // Load the sprite sheet
-        let texture_handle = asset_server.load("textures/summoning.png");
+        let texture_handle =
+            asset_server.load(texture_path("textures/summoning.png", *display_quality));
 
-        let layout = TextureAtlasLayout::from_grid(UVec2::new(576, 324), 5, 64, None, None);
+        let layout = sheet_layout(*display_quality);
 
         let atlas_layout = atlas_layouts.add(layout);
 
@@ -1372,7 +792,7 @@ mod game4 {
                     },
                     ..default()
                 },
-                OnGameScreen,
+                StateScoped(GameState::Game4),
             ))
             .with_children(|parent| {
                 // Text container at the bottom
@@ -1389,7 +809,7 @@ mod game4 {
                             bottom: Val::Px(0.0),
                             ..default()
                         },
-                        background_color: Color::srgba(0.0, 0.0, 0.0, 0.7).into(),
+                        background_color: theme.dialogue_backdrop.into(),
                         ..default()
                     },
                     TextContainer,
@@ -1408,264 +828,105 @@ mod game4 {
                         ..default()
                     })
                     .with_children(|parent| {
-                        parent.spawn((
-                            SpriteSheetBundle {
-                                texture: texture_handle,
-                                atlas: TextureAtlas {
-                                    layout: atlas_layout,
-                                    index: 0,
-                                },
-                                transform: Transform::from_xyz(
-                                    -window.width() / 2.0,
-                                    -window.height() / 2.0 + 60.0,
-                                    1.0,
-                                ),
-                                sprite: Sprite {
-                                    custom_size: Some(Vec2::new(1920.0, 1080.0)),
-                                    anchor: bevy::sprite::Anchor::Center,
-                                    ..default()
-                                },
-                                ..default()
-                            },
-                            AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)), // for (entity, _, sequence) in typing_query.iter() {
-                            //     if sequence.sequence_index <= sequence_state.current_sequence {
-                            //         commands.entity(entity).despawn();
-                            //     }
-                            // }
-                            AnimationIndices {
-                                first: 0,
-                                last: 320,
-                            },
-                        ));
+                        spawn_background(
+                            parent,
+                            cutscenes.frames(4),
+                            texture_handle,
+                            atlas_layout,
+                            &asset_server,
+                            window,
+                        );
                     });
             });
 
-        commands.insert_resource(GameTimer(Timer::from_seconds(14.0, TimerMode::Once)));
         // Immediately despawn all previous text when we're ready for the next one
         // for (entity, _, sequence) in typing_query.iter() {
         //     if sequence.sequence_index <= sequence_state.current_sequence {
         //         commands.entity(entity).despawn();
         //     }
         // }
-        // make liek 20 s when proper
-
-        // We can't spawn the text in game_setup because we need to wait for the GameFont resource to be available
-        // Instead, we'll create a new system to handle the initial text spawn
-    }
-
-    // Add marker resource to ensure proper setup order
-    #[derive(Resource, Default)]
-    struct GameSetupComplete;
-
-    // Modify the spawn_initial_text system to run after setup is complete
-    fn spawn_initial_text(
-        mut commands: Commands,
-        game_font: Res<GameFont>,
-        setup_complete: Res<GameSetupComplete>,
-    ) {
-        spawn_text_entity(&mut commands, 0, &game_font);
     }
 
     // Update the game plugin with proper system ordering
     pub fn game_plugin_3(app: &mut App) {
-        app.init_resource::<GameSetupComplete>() // Initialize the marker resource
-            .add_systems(OnEnter(GameState::Game4), game_setup3)
+        app.add_systems(OnEnter(GameState::Game4), game_setup3)
             .add_systems(
                 OnEnter(GameState::Game4),
                 spawn_initial_text.after(game_setup3),
             )
             .add_systems(
                 Update,
-                (game3, animate_sprite, manage_text_sequence, type_text)
+                (
+                    game3,
+                    animate_sprite,
+                    manage_text_sequence,
+                    run_reaction_prompt,
+                    type_text,
+                )
                     .run_if(in_state(GameState::Game4)),
             )
-            .add_systems(OnExit(GameState::Game4), despawn_screen::<OnGameScreen>);
-    }
-
-    // Make sure spawn_text_entity uses the correct font parameter
-    fn spawn_text_entity(
-        commands: &mut Commands,
-        sequence_index: usize,
-        game_font: &Res<GameFont>,
-    ) {
-        commands.spawn((
-            TextBundle::from_section(
-                "",
-                TextStyle {
-                    font: game_font.0.clone(),
-                    font_size: 40.0,
-                    color: Color::WHITE,
-                    ..default()
-                },
-            )
-            .with_style(Style {
-                margin: UiRect::bottom(Val::Px(20.0)),
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
-                position_type: PositionType::Absolute,
-                bottom: Val::Px(80.0),
-                left: Val::Px(20.0),
-                right: Val::Px(20.0),
-                ..default()
-            }),
-            TypingText {
-                full_text: String::new(),
-                current_index: 0,
-                timer: Timer::from_seconds(0.08, TimerMode::Repeating),
-                completed: false,
-            },
-            TextSequence { sequence_index },
-        ));
-    }
-
-    fn manage_text_sequence(
-        mut commands: Commands,
-        mut sequence_state: ResMut<TextSequenceState>,
-        time: Res<Time>,
-        typing_query: Query<(Entity, &TypingText, &TextSequence)>,
-        game_font: Res<GameFont>,
-    ) {
-        if !sequence_state.ready_for_next {
-            sequence_state.delay_timer.tick(time.delta());
-            if sequence_state.delay_timer.finished() {
-                sequence_state.ready_for_next = true;
-                // Immediately despawn all previous text when we're ready for the next one
-                for (entity, _, sequence) in typing_query.iter() {
-                    if sequence.sequence_index <= sequence_state.current_sequence {
-                        commands.entity(entity).despawn();
-                    }
-                }
-            }
-            return;
-        }
-
-        let mut all_completed = true;
-        for (_, typing_text, sequence) in typing_query.iter() {
-            if sequence.sequence_index == sequence_state.current_sequence {
-                if !typing_text.completed {
-                    all_completed = false;
-                    break;
-                }
-            }
-        }
-
-        if all_completed && sequence_state.current_sequence < sequence_state.texts.len() - 1 {
-            // Clear any existing text before spawning new one
-            for (entity, _, _) in typing_query.iter() {
-                commands.entity(entity).despawn();
-            }
-
-            sequence_state.current_sequence += 1;
-            sequence_state.ready_for_next = false;
-            sequence_state.delay_timer.reset();
-
-            spawn_text_entity(&mut commands, sequence_state.current_sequence, &game_font);
-        }
-    }
-
-    fn type_text(
-        time: Res<Time>,
-        sequence_state: Res<TextSequenceState>,
-        mut query: Query<(&mut TypingText, &mut Text, &TextSequence)>,
-        typewriter_sound: Res<TypewriterSound>,
-        mut commands: Commands,
-    ) {
-        for (mut typing_text, mut text, sequence) in query.iter_mut() {
-            if typing_text.completed || sequence.sequence_index != sequence_state.current_sequence {
-                continue;
-            }
-
-            if typing_text.full_text.is_empty() {
-                typing_text.full_text = sequence_state.texts[sequence.sequence_index].clone();
-            }
-
-            typing_text.timer.tick(time.delta());
-
-            if typing_text.timer.just_finished()
-                && typing_text.current_index < typing_text.full_text.len()
-            {
-                let next_char = typing_text
-                    .full_text
-                    .chars()
-                    .nth(typing_text.current_index)
-                    .unwrap();
-                typing_text.current_index += 1;
-
-                if let Some(section) = text.sections.first_mut() {
-                    section.value = typing_text.full_text[..typing_text.current_index].to_string();
-                }
-
-                if next_char != ' ' {
-                    commands.spawn(AudioBundle {
-                        source: typewriter_sound.0.clone(),
-                        settings: PlaybackSettings::DESPAWN,
-                        ..default()
-                    });
-                }
-
-                if typing_text.current_index == typing_text.full_text.len() {
-                    typing_text.completed = true;
-                }
-            }
-        }
+            .add_systems(
+                OnExit(GameState::Game4),
+                (
+                    despawn_screen::<TypingText>,
+                    despawn_screen::<ReactionPrompt>,
+                ),
+            );
     }
 
-    fn game3(
-        time: Res<Time>,
-        mut game_state: ResMut<NextState<GameState>>,
-        mut timer: ResMut<GameTimer>,
-    ) {
-        if timer.tick(time.delta()).finished() {
+    fn game3(sequence_state: Res<TextSequenceState>, mut game_state: ResMut<NextState<GameState>>) {
+        if sequence_state.finished() {
             game_state.set(GameState::Chapter4);
         }
     }
-
-    fn animate_sprite(
-        time: Res<Time>,
-        mut query: Query<(&mut TextureAtlas, &mut AnimationTimer, &AnimationIndices)>,
-    ) {
-        for (mut atlas, mut timer, indices) in &mut query {
-            timer.0.tick(time.delta());
-            if timer.0.just_finished() {
-                atlas.index = if atlas.index == indices.last {
-                    indices.first
-                } else {
-                    atlas.index + 1
-                };
-            }
-        }
-    }
 }
 
 mod menu {
-    use bevy::{
-        app::AppExit,
-        color::palettes::css::{CRIMSON, GREEN},
-        prelude::*,
-    };
-
-    use super::{despawn_screen, DisplayQuality, GameState, Volume, TEXT_COLOR};
-
-    // This plugin manages the menu, with 5 different screens:
-    // - a main menu with "New Game", "Settings", "Quit"
-    // - a settings menu with two submenus and a back button
-    // - two settings screen with a setting that can be set and a back button
+    use bevy::{app::AppExit, color::palettes::css::GREEN, ecs::system::RunSystemOnce, prelude::*};
+
+    use super::{DisplayQuality, GameState, TextSize, Volume};
+    use crate::arena::{ArenaWaves, ARENA_DECKS};
+    use crate::chapters::{spawn_padlock, CHAPTERS};
+    use crate::combat::HotSeat;
+    use crate::daily::{DailyChallenge, DailyResults};
+    use crate::deck::Deck;
+    use crate::difficulty::Difficulty;
+    use crate::endless;
+    use crate::leaderboard::{Board, Leaderboards};
+    use crate::rumble::RumbleIntensity;
+    use crate::save::{SaveData, SavedRun};
+    use crate::speedrun::{BestTimes, SpeedrunTimer};
+    use crate::story::StoryFlags;
+    use crate::theme::{apply_theme, Contrast, Theme};
+    use crate::ui;
+    use crate::window_config::ScreenMode;
+
+    // This plugin manages the menu, with 10 different screens:
+    // - a main menu with "Continue" (when a fight or an intro was left unfinished), "New Game",
+    //   "Co-op", "Daily Run", "Chapters", "Arena", "Duel", "Stats", "Quit"
+    // - a settings menu with three submenus and a back button
+    // - three settings screens, for display quality, screen mode, text size and contrast, for
+    //   volume and for gamepad rumble, each with a back button
+    // - a daily run screen, with today's modifiers and the results of recent days
+    // - a chapter select screen, with the chapters not reached yet locked, and endless mode
+    // - an arena screen, to pick the starting deck for a run of arena waves
+    // - a stats screen with recent fights, and the adaptive difficulty, merciful mode and
+    //   speedrun timer switches
+    // - a leaderboards screen, reached from the stats screen, with the online top scores
     pub fn menu_plugin(app: &mut App) {
         app
             // At start, the menu is not enabled. This will be changed in `menu_setup` when
             // entering the `GameState::Menu` state.
             // Current screen in the menu is handled by an independent state from `GameState`
             .init_state::<MenuState>()
+            .enable_state_scoped_entities::<MenuState>()
             .add_systems(OnEnter(GameState::Menu), menu_setup)
+            // Whichever way the menu is left, close its current screen
+            .add_systems(OnExit(GameState::Menu), menu_cleanup)
             // Systems to handle the main menu screen
             .add_systems(OnEnter(MenuState::Main), main_menu_setup)
-            .add_systems(OnExit(MenuState::Main), despawn_screen::<OnMainMenuScreen>)
             // Systems to handle the settings menu screen
             .add_systems(OnEnter(MenuState::Settings), settings_menu_setup)
-            .add_systems(
-                OnExit(MenuState::Settings),
-                despawn_screen::<OnSettingsMenuScreen>,
-            )
             // Systems to handle the display settings screen
             .add_systems(
                 OnEnter(MenuState::SettingsDisplay),
@@ -1673,11 +934,18 @@ mod menu {
             )
             .add_systems(
                 Update,
-                (setting_button::<DisplayQuality>.run_if(in_state(MenuState::SettingsDisplay)),),
-            )
-            .add_systems(
-                OnExit(MenuState::SettingsDisplay),
-                despawn_screen::<OnDisplaySettingsMenuScreen>,
+                (
+                    setting_button::<DisplayQuality>,
+                    setting_button::<ScreenMode>,
+                    setting_button::<TextSize>,
+                    // The theme follows the contrast setting, then the screen is built again in it
+                    (
+                        setting_button::<Contrast>.before(apply_theme),
+                        rebuild_display_settings.after(apply_theme),
+                    )
+                        .chain(),
+                )
+                    .run_if(in_state(MenuState::SettingsDisplay)),
             )
             // Systems to handle the sound settings screen
             .add_systems(OnEnter(MenuState::SettingsSound), sound_settings_menu_setup)
@@ -1685,9 +953,32 @@ mod menu {
                 Update,
                 setting_button::<Volume>.run_if(in_state(MenuState::SettingsSound)),
             )
+            // Systems to handle the controls settings screen
+            .add_systems(
+                OnEnter(MenuState::SettingsControls),
+                controls_settings_menu_setup,
+            )
+            .add_systems(
+                Update,
+                setting_button::<RumbleIntensity>.run_if(in_state(MenuState::SettingsControls)),
+            )
+            // Systems to handle the daily run screen
+            .add_systems(OnEnter(MenuState::Daily), daily_menu_setup)
+            // Systems to handle the arena deck screen
+            .add_systems(OnEnter(MenuState::Arena), arena_menu_setup)
+            // Systems to handle the chapter select screen
+            .add_systems(OnEnter(MenuState::Chapters), chapters_menu_setup)
+            // Systems to handle the stats screen
+            .add_systems(OnEnter(MenuState::Stats), stats_menu_setup)
+            .add_systems(
+                Update,
+                update_stats_screen.run_if(in_state(MenuState::Stats)),
+            )
+            // Systems to handle the leaderboards screen
+            .add_systems(OnEnter(MenuState::Leaderboards), leaderboards_menu_setup)
             .add_systems(
-                OnExit(MenuState::SettingsSound),
-                despawn_screen::<OnSoundSettingsMenuScreen>,
+                Update,
+                update_leaderboards_screen.run_if(in_state(MenuState::Leaderboards)),
             )
             // Common systems to all screens that handles buttons behavior
             .add_systems(
@@ -1703,30 +994,30 @@ mod menu {
         Settings,
         SettingsDisplay,
         SettingsSound,
+        SettingsControls,
+        Daily,
+        Chapters,
+        Arena,
+        Stats,
+        Leaderboards,
         #[default]
         Disabled,
     }
 
-    // Tag component used to tag entities added on the main menu screen
-    #[derive(Component)]
-    struct OnMainMenuScreen;
-
-    // Tag component used to tag entities added on the settings menu screen
-    #[derive(Component)]
-    struct OnSettingsMenuScreen;
-
-    // Tag component used to tag entities added on the display settings menu screen
+    // Holds the boards and their status, rebuilt as replies come in from the server
     #[derive(Component)]
-    struct OnDisplaySettingsMenuScreen;
+    struct LeaderboardTables;
 
-    // Tag component used to tag entities added on the sound settings menu screen
+    // Text on the stats screen that follows the difficulty as it changes
     #[derive(Component)]
-    struct OnSoundSettingsMenuScreen;
+    enum StatsText {
+        Summary,
+        AdaptiveToggle,
+        MercifulToggle,
+        SpeedrunToggle,
+    }
 
-    const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
-    const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
-    const HOVERED_PRESSED_BUTTON: Color = Color::srgb(0.25, 0.65, 0.25);
-    const PRESSED_BUTTON: Color = Color::srgb(0.35, 0.75, 0.35);
+    const LOCKED_BUTTON: Color = Color::srgb(0.08, 0.08, 0.08);
 
     // Tag component used to mark which setting is currently selected
     #[derive(Component)]
@@ -1735,10 +1026,27 @@ mod menu {
     // All actions that can be triggered from a button click
     #[derive(Component)]
     enum MenuButtonAction {
+        Continue,
         Play,
+        PlayCoop,
         Settings,
         SettingsDisplay,
         SettingsSound,
+        SettingsControls,
+        SwitchProfile,
+        Daily,
+        StartDaily,
+        Arena,
+        StartArena(usize),
+        Duel,
+        Chapters,
+        // Start a new run from the chapter opening with this state
+        StartChapter(GameState),
+        Stats,
+        Leaderboards,
+        ToggleAdaptive,
+        ToggleMerciful,
+        ToggleSpeedrunTimer,
         BackToMainMenu,
         BackToSettings,
         Quit,
@@ -1750,13 +1058,14 @@ mod menu {
             (&Interaction, &mut UiImage, Option<&SelectedOption>),
             (Changed<Interaction>, With<Button>),
         >,
+        theme: Res<Theme>,
     ) {
         for (interaction, mut image, selected) in &mut interaction_query {
             image.color = match (*interaction, selected) {
-                (Interaction::Pressed, _) | (Interaction::None, Some(_)) => PRESSED_BUTTON,
-                (Interaction::Hovered, Some(_)) => HOVERED_PRESSED_BUTTON,
-                (Interaction::Hovered, None) => HOVERED_BUTTON,
-                (Interaction::None, None) => NORMAL_BUTTON,
+                (Interaction::Pressed, _) | (Interaction::None, Some(_)) => theme.button_selected,
+                (Interaction::Hovered, Some(_)) => theme.button_selected_hovered,
+                (Interaction::Hovered, None) => theme.button_hovered,
+                (Interaction::None, None) => theme.button,
             }
         }
     }
@@ -1765,14 +1074,16 @@ mod menu {
     // the button as the one currently selected
     fn setting_button<T: Resource + Component + PartialEq + Copy>(
         interaction_query: Query<(&Interaction, &T, Entity), (Changed<Interaction>, With<Button>)>,
-        mut selected_query: Query<(Entity, &mut UiImage), With<SelectedOption>>,
+        // Only the selected button of this setting, as a screen can show more than one
+        mut selected_query: Query<(Entity, &mut UiImage), (With<SelectedOption>, With<T>)>,
         mut commands: Commands,
         mut setting: ResMut<T>,
+        theme: Res<Theme>,
     ) {
         for (interaction, button_setting, entity) in &interaction_query {
             if *interaction == Interaction::Pressed && *setting != *button_setting {
                 let (previous_button, mut previous_image) = selected_query.single_mut();
-                previous_image.color = NORMAL_BUTTON;
+                previous_image.color = theme.button;
                 commands.entity(previous_button).remove::<SelectedOption>();
                 commands.entity(entity).insert(SelectedOption);
                 *setting = *button_setting;
@@ -1780,11 +1091,42 @@ mod menu {
         }
     }
 
+    // A screen is only built on entering it, so the display settings are built again by hand
+    // for a new contrast to show on them straight away
+    fn rebuild_display_settings(
+        mut commands: Commands,
+        contrast: Res<Contrast>,
+        interaction_query: Query<&Interaction, (Changed<Interaction>, With<Contrast>)>,
+        screen_query: Query<(Entity, &StateScoped<MenuState>)>,
+    ) {
+        let pressed = interaction_query
+            .iter()
+            .any(|interaction| *interaction == Interaction::Pressed);
+        if !pressed || !contrast.is_changed() {
+            return;
+        }
+        for (entity, scoped) in &screen_query {
+            if scoped.0 == MenuState::SettingsDisplay {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+        commands.add(|world: &mut World| world.run_system_once(display_settings_menu_setup));
+    }
+
     fn menu_setup(mut menu_state: ResMut<NextState<MenuState>>) {
         menu_state.set(MenuState::Main);
     }
 
-    fn main_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    fn menu_cleanup(mut menu_state: ResMut<NextState<MenuState>>) {
+        menu_state.set(MenuState::Disabled);
+    }
+
+    fn main_menu_setup(
+        mut commands: Commands,
+        asset_server: Res<AssetServer>,
+        save_data: Res<SaveData>,
+        theme: Res<Theme>,
+    ) {
         // Common style for all buttons on the screen
         let button_style = Style {
             width: Val::Px(250.0),
@@ -1800,11 +1142,7 @@ mod menu {
             left: Val::Px(10.0),
             ..default()
         };
-        let button_text_style = TextStyle {
-            font_size: 40.0,
-            color: TEXT_COLOR,
-            ..default()
-        };
+        let button_text_style = theme.text_style(40.0);
 
         commands
             .spawn((
@@ -1818,7 +1156,7 @@ mod menu {
                     },
                     ..default()
                 },
-                OnMainMenuScreen,
+                StateScoped(MenuState::Main),
             ))
             .with_children(|parent| {
                 // Background image
@@ -1856,53 +1194,189 @@ mod menu {
                             ..default()
                         });
 
-                        // New Game button
-                        parent
-                            .spawn((
-                                ButtonBundle {
-                                    style: button_style.clone(),
-                                    background_color: NORMAL_BUTTON.into(),
-                                    ..default()
-                                },
-                                MenuButtonAction::Play,
-                            ))
-                            .with_children(|parent| {
-                                let icon = asset_server.load("textures/Game Icons/right.png");
-                                parent.spawn(ImageBundle {
-                                    style: button_icon_style.clone(),
-                                    image: UiImage::new(icon),
+                        // Continue button, back into a fight or an intro that was quit part-way
+                        // through
+                        if save_data.fight.is_some() || save_data.cutscene.is_some() {
+                            parent
+                                .spawn((
+                                    ui::button(&theme, button_style.clone()),
+                                    MenuButtonAction::Continue,
+                                ))
+                                .with_children(|parent| {
+                                    let icon = asset_server.load("textures/Game Icons/right.png");
+                                    parent.spawn(ImageBundle {
+                                        style: button_icon_style.clone(),
+                                        image: UiImage::new(icon),
+                                        ..default()
+                                    });
+                                    parent.spawn(TextBundle::from_section(
+                                        "Continue",
+                                        button_text_style.clone(),
+                                    ));
+                                });
+                        }
+
+                        // New Game button, beside one for two players sharing the keyboard
+                        parent.spawn(NodeBundle::default()).with_children(|parent| {
+                            parent
+                                .spawn((
+                                    ui::button(&theme, button_style.clone()),
+                                    MenuButtonAction::Play,
+                                ))
+                                .with_children(|parent| {
+                                    let icon = asset_server.load("textures/Game Icons/right.png");
+                                    parent.spawn(ImageBundle {
+                                        style: button_icon_style.clone(),
+                                        image: UiImage::new(icon),
+                                        ..default()
+                                    });
+                                    parent.spawn(TextBundle::from_section(
+                                        "New Game",
+                                        button_text_style.clone(),
+                                    ));
+                                });
+                            ui::styled_button(
+                                parent,
+                                &theme,
+                                Style {
+                                    width: Val::Px(150.0),
+                                    ..button_style.clone()
+                                },
+                                "Co-op",
+                                button_text_style.clone(),
+                                MenuButtonAction::PlayCoop,
+                            );
+                        });
+
+                        // Daily Run button, for the run everyone gets today
+                        parent
+                            .spawn((
+                                ui::button(&theme, button_style.clone()),
+                                MenuButtonAction::Daily,
+                            ))
+                            .with_children(|parent| {
+                                let icon = asset_server.load("textures/Game Icons/right.png");
+                                parent.spawn(ImageBundle {
+                                    style: button_icon_style.clone(),
+                                    image: UiImage::new(icon),
                                     ..default()
                                 });
                                 parent.spawn(TextBundle::from_section(
-                                    "New Game",
+                                    "Daily Run",
                                     button_text_style.clone(),
                                 ));
                             });
 
-                        // Quit button
+                        // Chapters button, to start a run from any chapter reached before
                         parent
                             .spawn((
-                                ButtonBundle {
-                                    style: button_style,
-                                    background_color: NORMAL_BUTTON.into(),
+                                ui::button(&theme, button_style.clone()),
+                                MenuButtonAction::Chapters,
+                            ))
+                            .with_children(|parent| {
+                                let icon = asset_server.load("textures/Game Icons/right.png");
+                                parent.spawn(ImageBundle {
+                                    style: button_icon_style.clone(),
+                                    image: UiImage::new(icon),
                                     ..default()
+                                });
+                                parent.spawn(TextBundle::from_section(
+                                    "Chapters",
+                                    button_text_style.clone(),
+                                ));
+                            });
+
+                        // Arena button, for waves of fights from a chosen starting deck, beside
+                        // one for a duel against another player online
+                        parent.spawn(NodeBundle::default()).with_children(|parent| {
+                            parent
+                                .spawn((
+                                    ui::button(&theme, button_style.clone()),
+                                    MenuButtonAction::Arena,
+                                ))
+                                .with_children(|parent| {
+                                    let icon = asset_server.load("textures/Game Icons/right.png");
+                                    parent.spawn(ImageBundle {
+                                        style: button_icon_style.clone(),
+                                        image: UiImage::new(icon),
+                                        ..default()
+                                    });
+                                    parent.spawn(TextBundle::from_section(
+                                        "Arena",
+                                        button_text_style.clone(),
+                                    ));
+                                });
+                            ui::styled_button(
+                                parent,
+                                &theme,
+                                Style {
+                                    width: Val::Px(150.0),
+                                    ..button_style.clone()
                                 },
-                                MenuButtonAction::Quit,
+                                "Duel",
+                                button_text_style.clone(),
+                                MenuButtonAction::Duel,
+                            );
+                        });
+
+                        // Stats button
+                        parent
+                            .spawn((
+                                ui::button(&theme, button_style.clone()),
+                                MenuButtonAction::Stats,
                             ))
                             .with_children(|parent| {
-                                let icon = asset_server.load("textures/Game Icons/exitRight.png");
+                                let icon = asset_server.load("textures/Game Icons/card.png");
                                 parent.spawn(ImageBundle {
-                                    style: button_icon_style,
+                                    style: button_icon_style.clone(),
                                     image: UiImage::new(icon),
                                     ..default()
                                 });
-                                parent.spawn(TextBundle::from_section("Quit", button_text_style));
+                                parent.spawn(TextBundle::from_section(
+                                    "Stats",
+                                    button_text_style.clone(),
+                                ));
                             });
+
+                        // Settings and Quit buttons, sharing the last row so the column still
+                        // fits under the logo
+                        parent.spawn(NodeBundle::default()).with_children(|parent| {
+                            parent
+                                .spawn((
+                                    ui::button(&theme, button_style.clone()),
+                                    MenuButtonAction::Settings,
+                                ))
+                                .with_children(|parent| {
+                                    let icon = asset_server.load("textures/Game Icons/wrench.png");
+                                    parent.spawn(ImageBundle {
+                                        style: button_icon_style.clone(),
+                                        image: UiImage::new(icon),
+                                        ..default()
+                                    });
+                                    parent.spawn(TextBundle::from_section(
+                                        "Settings",
+                                        button_text_style.clone(),
+                                    ));
+                                });
+                            parent
+                                .spawn((ui::button(&theme, button_style), MenuButtonAction::Quit))
+                                .with_children(|parent| {
+                                    let icon =
+                                        asset_server.load("textures/Game Icons/exitRight.png");
+                                    parent.spawn(ImageBundle {
+                                        style: button_icon_style,
+                                        image: UiImage::new(icon),
+                                        ..default()
+                                    });
+                                    parent
+                                        .spawn(TextBundle::from_section("Quit", button_text_style));
+                                });
+                        });
                     });
             });
     }
 
-    fn settings_menu_setup(mut commands: Commands) {
+    fn settings_menu_setup(mut commands: Commands, theme: Res<Theme>) {
         let button_style = Style {
             width: Val::Px(200.0),
             height: Val::Px(65.0),
@@ -1912,11 +1386,7 @@ mod menu {
             ..default()
         };
 
-        let button_text_style = TextStyle {
-            font_size: 40.0,
-            color: TEXT_COLOR,
-            ..default()
-        };
+        let button_text_style = theme.text_style(40.0);
 
         commands
             .spawn((
@@ -1930,7 +1400,7 @@ mod menu {
                     },
                     ..default()
                 },
-                OnSettingsMenuScreen,
+                StateScoped(MenuState::Settings),
             ))
             .with_children(|parent| {
                 parent
@@ -1940,36 +1410,38 @@ mod menu {
                             align_items: AlignItems::Center,
                             ..default()
                         },
-                        background_color: CRIMSON.into(),
+                        background_color: theme.panel.into(),
                         ..default()
                     })
                     .with_children(|parent| {
                         for (action, text) in [
                             (MenuButtonAction::SettingsDisplay, "Display"),
                             (MenuButtonAction::SettingsSound, "Sound"),
+                            (MenuButtonAction::SettingsControls, "Controls"),
+                            (MenuButtonAction::SwitchProfile, "Profile"),
                             (MenuButtonAction::BackToMainMenu, "Back"),
                         ] {
-                            parent
-                                .spawn((
-                                    ButtonBundle {
-                                        style: button_style.clone(),
-                                        background_color: NORMAL_BUTTON.into(),
-                                        ..default()
-                                    },
-                                    action,
-                                ))
-                                .with_children(|parent| {
-                                    parent.spawn(TextBundle::from_section(
-                                        text,
-                                        button_text_style.clone(),
-                                    ));
-                                });
+                            ui::styled_button(
+                                parent,
+                                &theme,
+                                button_style.clone(),
+                                text,
+                                button_text_style.clone(),
+                                action,
+                            );
                         }
                     });
             });
     }
 
-    fn display_settings_menu_setup(mut commands: Commands, display_quality: Res<DisplayQuality>) {
+    fn display_settings_menu_setup(
+        mut commands: Commands,
+        display_quality: Res<DisplayQuality>,
+        screen_mode: Res<ScreenMode>,
+        text_size: Res<TextSize>,
+        contrast: Res<Contrast>,
+        theme: Res<Theme>,
+    ) {
         let button_style = Style {
             width: Val::Px(200.0),
             height: Val::Px(65.0),
@@ -1978,11 +1450,7 @@ mod menu {
             align_items: AlignItems::Center,
             ..default()
         };
-        let button_text_style = TextStyle {
-            font_size: 40.0,
-            color: TEXT_COLOR,
-            ..default()
-        };
+        let button_text_style = theme.text_style(40.0);
 
         commands
             .spawn((
@@ -1996,7 +1464,7 @@ mod menu {
                     },
                     ..default()
                 },
-                OnDisplaySettingsMenuScreen,
+                StateScoped(MenuState::SettingsDisplay),
             ))
             .with_children(|parent| {
                 parent
@@ -2006,7 +1474,7 @@ mod menu {
                             align_items: AlignItems::Center,
                             ..default()
                         },
-                        background_color: CRIMSON.into(),
+                        background_color: theme.panel.into(),
                         ..default()
                     })
                     .with_children(|parent| {
@@ -2018,7 +1486,7 @@ mod menu {
                                     align_items: AlignItems::Center,
                                     ..default()
                                 },
-                                background_color: CRIMSON.into(),
+                                background_color: theme.panel.into(),
                                 ..default()
                             })
                             .with_children(|parent| {
@@ -2033,47 +1501,139 @@ mod menu {
                                     DisplayQuality::Medium,
                                     DisplayQuality::High,
                                 ] {
-                                    let mut entity = parent.spawn((
-                                        ButtonBundle {
-                                            style: Style {
-                                                width: Val::Px(150.0),
-                                                height: Val::Px(65.0),
-                                                ..button_style.clone()
-                                            },
-                                            background_color: NORMAL_BUTTON.into(),
-                                            ..default()
+                                    let mut entity = ui::styled_button(
+                                        parent,
+                                        &theme,
+                                        Style {
+                                            width: Val::Px(150.0),
+                                            height: Val::Px(65.0),
+                                            ..button_style.clone()
                                         },
+                                        format!("{quality_setting:?}"),
+                                        button_text_style.clone(),
                                         quality_setting,
-                                    ));
-                                    entity.with_children(|parent| {
-                                        parent.spawn(TextBundle::from_section(
-                                            format!("{quality_setting:?}"),
-                                            button_text_style.clone(),
-                                        ));
-                                    });
+                                    );
                                     if *display_quality == quality_setting {
                                         entity.insert(SelectedOption);
                                     }
                                 }
                             });
-                        // Display the back button to return to the settings screen
                         parent
-                            .spawn((
-                                ButtonBundle {
-                                    style: button_style,
-                                    background_color: NORMAL_BUTTON.into(),
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    align_items: AlignItems::Center,
                                     ..default()
                                 },
-                                MenuButtonAction::BackToSettings,
-                            ))
+                                background_color: theme.panel.into(),
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(
+                                    "Screen",
+                                    button_text_style.clone(),
+                                ));
+                                for mode_setting in [
+                                    ScreenMode::Windowed,
+                                    ScreenMode::Borderless,
+                                    ScreenMode::Fullscreen,
+                                ] {
+                                    let mut entity = ui::styled_button(
+                                        parent,
+                                        &theme,
+                                        Style {
+                                            width: Val::Px(260.0),
+                                            height: Val::Px(65.0),
+                                            ..button_style.clone()
+                                        },
+                                        mode_setting.label(),
+                                        button_text_style.clone(),
+                                        mode_setting,
+                                    );
+                                    if *screen_mode == mode_setting {
+                                        entity.insert(SelectedOption);
+                                    }
+                                }
+                            });
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                },
+                                background_color: theme.panel.into(),
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(
+                                    "Text Size",
+                                    button_text_style.clone(),
+                                ));
+                                for size_setting in
+                                    [TextSize::Small, TextSize::Normal, TextSize::Large]
+                                {
+                                    let mut entity = ui::styled_button(
+                                        parent,
+                                        &theme,
+                                        Style {
+                                            width: Val::Px(150.0),
+                                            height: Val::Px(65.0),
+                                            ..button_style.clone()
+                                        },
+                                        format!("{size_setting:?}"),
+                                        button_text_style.clone(),
+                                        size_setting,
+                                    );
+                                    if *text_size == size_setting {
+                                        entity.insert(SelectedOption);
+                                    }
+                                }
+                            });
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                },
+                                background_color: theme.panel.into(),
+                                ..default()
+                            })
                             .with_children(|parent| {
-                                parent.spawn(TextBundle::from_section("Back", button_text_style));
+                                parent.spawn(TextBundle::from_section(
+                                    "Contrast",
+                                    button_text_style.clone(),
+                                ));
+                                for contrast_setting in [Contrast::Normal, Contrast::High] {
+                                    let mut entity = ui::styled_button(
+                                        parent,
+                                        &theme,
+                                        Style {
+                                            width: Val::Px(180.0),
+                                            height: Val::Px(65.0),
+                                            ..button_style.clone()
+                                        },
+                                        format!("{contrast_setting:?}"),
+                                        button_text_style.clone(),
+                                        contrast_setting,
+                                    );
+                                    if *contrast == contrast_setting {
+                                        entity.insert(SelectedOption);
+                                    }
+                                }
                             });
+                        // Display the back button to return to the settings screen
+                        ui::styled_button(
+                            parent,
+                            &theme,
+                            button_style,
+                            "Back",
+                            button_text_style,
+                            MenuButtonAction::BackToSettings,
+                        );
                     });
             });
     }
 
-    fn sound_settings_menu_setup(mut commands: Commands, volume: Res<Volume>) {
+    fn sound_settings_menu_setup(mut commands: Commands, volume: Res<Volume>, theme: Res<Theme>) {
         let button_style = Style {
             width: Val::Px(200.0),
             height: Val::Px(65.0),
@@ -2082,11 +1642,7 @@ mod menu {
             align_items: AlignItems::Center,
             ..default()
         };
-        let button_text_style = TextStyle {
-            font_size: 40.0,
-            color: TEXT_COLOR,
-            ..default()
-        };
+        let button_text_style = theme.text_style(40.0);
 
         commands
             .spawn((
@@ -2100,7 +1656,7 @@ mod menu {
                     },
                     ..default()
                 },
-                OnSoundSettingsMenuScreen,
+                StateScoped(MenuState::SettingsSound),
             ))
             .with_children(|parent| {
                 parent
@@ -2110,7 +1666,7 @@ mod menu {
                             align_items: AlignItems::Center,
                             ..default()
                         },
-                        background_color: CRIMSON.into(),
+                        background_color: theme.panel.into(),
                         ..default()
                     })
                     .with_children(|parent| {
@@ -2120,7 +1676,7 @@ mod menu {
                                     align_items: AlignItems::Center,
                                     ..default()
                                 },
-                                background_color: CRIMSON.into(),
+                                background_color: theme.panel.into(),
                                 ..default()
                             })
                             .with_children(|parent| {
@@ -2130,15 +1686,14 @@ mod menu {
                                 ));
                                 for volume_setting in [0, 1, 2, 3, 4, 5, 6, 7, 8, 9] {
                                     let mut entity = parent.spawn((
-                                        ButtonBundle {
-                                            style: Style {
+                                        ui::button(
+                                            &theme,
+                                            Style {
                                                 width: Val::Px(30.0),
                                                 height: Val::Px(65.0),
                                                 ..button_style.clone()
                                             },
-                                            background_color: NORMAL_BUTTON.into(),
-                                            ..default()
-                                        },
+                                        ),
                                         Volume(volume_setting),
                                     ));
                                     if *volume == Volume(volume_setting) {
@@ -2146,5008 +1701,1201 @@ mod menu {
                                     }
                                 }
                             });
+                        ui::styled_button(
+                            parent,
+                            &theme,
+                            button_style,
+                            "Back",
+                            button_text_style,
+                            MenuButtonAction::BackToSettings,
+                        );
+                    });
+            });
+    }
+
+    fn controls_settings_menu_setup(
+        mut commands: Commands,
+        rumble: Res<RumbleIntensity>,
+        theme: Res<Theme>,
+    ) {
+        let button_style = Style {
+            width: Val::Px(200.0),
+            height: Val::Px(65.0),
+            margin: UiRect::all(Val::Px(20.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        };
+        let button_text_style = theme.text_style(40.0);
+
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    ..default()
+                },
+                StateScoped(MenuState::SettingsControls),
+            ))
+            .with_children(|parent| {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        background_color: theme.panel.into(),
+                        ..default()
+                    })
+                    .with_children(|parent| {
                         parent
-                            .spawn((
-                                ButtonBundle {
-                                    style: button_style,
-                                    background_color: NORMAL_BUTTON.into(),
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    align_items: AlignItems::Center,
                                     ..default()
                                 },
-                                MenuButtonAction::BackToSettings,
-                            ))
+                                background_color: theme.panel.into(),
+                                ..default()
+                            })
                             .with_children(|parent| {
-                                parent.spawn(TextBundle::from_section("Back", button_text_style));
+                                parent.spawn(TextBundle::from_section(
+                                    "Rumble",
+                                    button_text_style.clone(),
+                                ));
+                                for rumble_setting in RumbleIntensity::ALL {
+                                    let mut entity = ui::styled_button(
+                                        parent,
+                                        &theme,
+                                        Style {
+                                            width: Val::Px(180.0),
+                                            height: Val::Px(65.0),
+                                            ..button_style.clone()
+                                        },
+                                        format!("{rumble_setting:?}"),
+                                        button_text_style.clone(),
+                                        rumble_setting,
+                                    );
+                                    if *rumble == rumble_setting {
+                                        entity.insert(SelectedOption);
+                                    }
+                                }
                             });
+                        ui::styled_button(
+                            parent,
+                            &theme,
+                            button_style,
+                            "Back",
+                            button_text_style,
+                            MenuButtonAction::BackToSettings,
+                        );
                     });
             });
     }
 
-    fn menu_action(
-        interaction_query: Query<
-            (&Interaction, &MenuButtonAction),
-            (Changed<Interaction>, With<Button>),
-        >,
-        mut app_exit_events: EventWriter<AppExit>,
-        mut menu_state: ResMut<NextState<MenuState>>,
-        mut game_state: ResMut<NextState<GameState>>,
-    ) {
-        for (interaction, menu_button_action) in &interaction_query {
-            if *interaction == Interaction::Pressed {
-                match menu_button_action {
-                    MenuButtonAction::Quit => {
-                        app_exit_events.send(AppExit::Success);
-                    }
-                    MenuButtonAction::Play => {
-                        // game_state.set(GameState::Chapter3);
-                        game_state.set(GameState::Game);
+    fn stats_summary(difficulty: &Difficulty, save_data: &SaveData) -> String {
+        let recent = if difficulty.history().is_empty() {
+            "none yet".to_string()
+        } else {
+            difficulty
+                .history()
+                .iter()
+                .map(|result| result.label())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let mut summary = format!(
+            "Enemy health and damage: {:.0}%\nRecent fights: {recent}",
+            difficulty.enemy_scale() * 100.0
+        );
+        if save_data.best_wave > 0 {
+            summary += &format!("\nFurthest endless wave: {}", save_data.best_wave);
+        }
+        summary
+    }
 
-                        menu_state.set(MenuState::Disabled);
-                    }
-                    MenuButtonAction::Settings => menu_state.set(MenuState::Settings),
-                    MenuButtonAction::SettingsDisplay => {
-                        menu_state.set(MenuState::SettingsDisplay);
-                    }
-                    MenuButtonAction::SettingsSound => {
-                        menu_state.set(MenuState::SettingsSound);
-                    }
-                    MenuButtonAction::BackToMainMenu => menu_state.set(MenuState::Main),
-                    MenuButtonAction::BackToSettings => {
-                        menu_state.set(MenuState::Settings);
-                    }
-                }
-            }
-        }
-    }
-}
-
-// Generic system that takes a component as a parameter, and will despawn all entities with that component
-fn despawn_screen<T: Component>(to_despawn: Query<Entity, With<T>>, mut commands: Commands) {
-    for entity in &to_despawn {
-        commands.entity(entity).despawn_recursive();
-    }
-}
-
-mod chapter1 {
-    use crate::game2;
-
-    use super::GameState;
-    use bevy::app::AppExit;
-    use bevy::ecs::system::ParamSet;
-    use bevy::prelude::*;
-
-    #[derive(Component, Copy, Clone, Debug, PartialEq)]
-    enum CardType {
-        Fire,
-        Ice,
-        Air,
-        Earth,
-        Crystal,
-        // Add other types as needed
-    }
-    // Components
-    #[derive(Component)]
-    struct AnimationTimer(Timer);
-
-    #[derive(Component)]
-    struct AnimationIndices {
-        first: usize,
-        last: usize,
-    }
-
-    #[derive(Component)]
-    struct Card;
-
-    #[derive(Component)]
-    struct OriginalPosition(Vec2);
-
-    #[derive(Component)]
-    struct OnChapterOneScreen;
-
-    #[derive(Component)]
-    struct SideCharacter;
-
-    #[derive(Component)]
-    struct Monster;
-
-    #[derive(Component)]
-    struct Health {
-        current: f32,
-        maximum: f32,
-    }
-
-    // Add this to your existing components if not already present
-    #[derive(Component)]
-    struct HealthBarContainer;
-
-    #[derive(Component)]
-    struct HealthBar;
-
-    // Add these new components in the chapter1 mod
-    #[derive(Component)]
-    struct EndTurnButton;
-
-    #[derive(Component)]
-    struct ButtonText;
-
-    #[derive(Component)]
-    struct Damage(f32);
-
-    #[derive(Component)]
-    struct DeathScreen;
-
-    #[derive(Component)]
-    struct DeathText;
-
-    #[derive(Component)]
-    struct FadeInEffect {
-        timer: Timer,
-    }
-
-    #[derive(Component)]
-    struct ReturnToMenuTimer {
-        timer: Timer,
-    }
-
-    #[derive(Component)]
-    struct DamageDisplay;
-
-    #[derive(Resource, Default)]
-    struct PendingAirCards(i32);
-
-    //CHANGE
-    #[derive(Component)]
-    struct CardPlayAnimation {
-        elapsed_time: f32,
-        duration: f32,
-    }
-
-    fn animate_card_play(
-        mut commands: Commands,
-        time: Res<Time>,
-        windows: Query<&Window>,
-        mut animation_query: Query<(Entity, &mut Style, &mut CardPlayAnimation)>,
-        monster_query: Query<&Transform, With<Monster>>,
-    ) {
-        let window = windows.single();
-
-        // Get monster position once
-        if let Ok(monster_transform) = monster_query.get_single() {
-            for (entity, mut style, mut animation) in animation_query.iter_mut() {
-                animation.elapsed_time += time.delta_seconds();
-                let progress = (animation.elapsed_time / animation.duration).min(1.0);
-
-                // Scale down the card as it moves
-                let scale = 1.0 - (progress * 0.9); // Scale to 10% of original size
-                style.width = Val::Px(180.0 * scale);
-                style.height = Val::Px(250.0 * scale);
-
-                // Move card towards monster
-                style.top = Val::Px(monster_transform.translation.y);
-
-                // Remove card when animation is done
-                if progress >= 1.0 {
-                    commands.entity(entity).despawn_recursive();
-                }
-            }
-        }
-    }
-    //END CAHNGE
-
-    fn spawn_death_screen(commands: &mut Commands, asset_server: &AssetServer) {
-        // Main container
-        commands
-            .spawn((
-                NodeBundle {
-                    style: Style {
-                        width: Val::Percent(100.0),
-                        height: Val::Percent(100.0),
-                        position_type: PositionType::Absolute,
-                        align_items: AlignItems::Center,
-                        justify_content: JustifyContent::Center,
-                        flex_direction: FlexDirection::Column,
-                        ..default()
-                    },
-                    background_color: BackgroundColor(Color::rgba(0.0, 0.0, 0.0, 0.0)),
-                    ..default()
-                },
-                DeathScreen,
-                FadeInEffect {
-                    timer: Timer::from_seconds(2.0, TimerMode::Once),
-                },
-                // Add a timer to return to menu after 5 seconds
-                ReturnToMenuTimer {
-                    timer: Timer::from_seconds(5.0, TimerMode::Once),
-                },
-            ))
-            .with_children(|parent| {
-                // "YOU DIED" text
-                parent.spawn((
-                    TextBundle::from_section(
-                        "YOU DIED",
-                        TextStyle {
-                            font_size: 120.0,
-                            color: Color::rgba(0.8, 0.0, 0.0, 0.0), // Start transparent
-                            ..default()
-                        },
-                    ),
-                    DeathText,
-                    FadeInEffect {
-                        timer: Timer::from_seconds(2.0, TimerMode::Once),
-                    },
-                ));
-            });
-    }
-
-    // Update the death screen system to handle both fade-in and menu transition
-    fn update_death_screen(
-        mut commands: Commands,
-        time: Res<Time>,
-        mut query: Query<
-            (
-                Entity,
-                &mut BackgroundColor,
-                &mut FadeInEffect,
-                &mut ReturnToMenuTimer,
-            ),
-            With<DeathScreen>,
-        >,
-        mut text_query: Query<&mut Text, With<DeathText>>,
-        mut game_state: ResMut<NextState<GameState>>,
-        mut app_exit_events: EventWriter<AppExit>,
-    ) {
-        for (entity, mut bg_color, mut fade, mut return_timer) in query.iter_mut() {
-            // Update fade effect
-            fade.timer.tick(time.delta());
-            let alpha = fade.timer.fraction();
-            bg_color.0 = Color::rgba(0.0, 0.0, 0.0, alpha * 0.7);
-
-            // Update text color
-            if let Ok(mut text) = text_query.get_single_mut() {
-                text.sections[0].style.color = Color::rgba(0.8, 0.0, 0.0, alpha);
-            }
-
-            // Update return timer
-            return_timer.timer.tick(time.delta());
-            if return_timer.timer.finished() {
-                app_exit_events.send(AppExit::Success);
-            }
-        }
-    }
-    // Modify TurnState to include pending air cards
-    #[derive(Resource)]
-    struct TurnState {
-        first_card_played: bool,
-        cards_played_this_turn: Vec<CardType>,
-        crystal_power: i32,
-        turn_count: i32,
-        pending_air_cards: i32,
-    }
-
-    impl Default for TurnState {
-        fn default() -> Self {
-            Self {
-                first_card_played: true,
-                cards_played_this_turn: Vec::new(),
-                crystal_power: 0,
-                turn_count: 0,
-                pending_air_cards: 0, // Initialize pending_air_cards to 0
-            }
-        }
-    }
-
-    // Constants for base damage values
-    const FIRE_BASE_DAMAGE: f32 = 8.0;
-    const FIRE_FIRST_CARD_BONUS: f32 = 7.0;
-    const ICE_BASE_DAMAGE: f32 = 6.0;
-    const CRYSTAL_BASE_DAMAGE: f32 = 4.0;
-    const AIR_BASE_DAMAGE: f32 = 2.0;
-    const EARTH_BASE_DAMAGE: f32 = 5.0;
-    const HEAL_BASE_DAMAGE: f32 = 5.0;
-
-    fn update_health_bars(
-        query: Query<(&Health, &Children), Without<HealthBar>>,
-        mut health_bar_query: Query<&mut Sprite, With<HealthBar>>,
-    ) {
-        for (health, children) in query.iter() {
-            for child in children.iter() {
-                if let Ok(mut bar_sprite) = health_bar_query.get_mut(*child) {
-                    // Update health bar width based on current health
-                    let bar_width = 100.0;
-                    let health_percentage = health.current / health.maximum;
-
-                    bar_sprite.custom_size = Some(Vec2::new(
-                        bar_width * health_percentage,
-                        bar_sprite.custom_size.unwrap_or(Vec2::ZERO).y,
-                    ));
-
-                    // Update color based on health percentage
-                    bar_sprite.color = if health_percentage > 0.5 {
-                        Color::srgb(0.0, 1.0, 0.0) // Green: rgb(0, 255, 0)
-                    } else if health_percentage > 0.25 {
-                        Color::srgb(1.0, 0.65, 0.0) // Orange: rgb(255, 165, 0)
-                    } else {
-                        Color::srgb(1.0, 0.0, 0.0) // Red: rgb(255, 0, 0)
-                    };
-                }
-            }
-        }
-    }
-    #[derive(Resource)]
-    struct FightState {
-        current_turn: Turn,
-        selected_card: Option<usize>,
-    }
-
-    #[derive(PartialEq)]
-    enum Turn {
-        Player,
-        Enemy,
-    }
-
-    impl Default for FightState {
-        fn default() -> Self {
-            Self {
-                current_turn: Turn::Player,
-                selected_card: None,
-            }
-        }
-    }
-
-    // Update the card hover system to use FightState
-    fn update_card_hover(
-        mut card_query: Query<
-            (
-                &Interaction,
-                &mut Transform,
-                &OriginalPosition,
-                &mut Style,
-                Entity,
-            ),
-            (With<Card>, Changed<Interaction>),
-        >,
-        mut commands: Commands,
-        fight_state: Res<FightState>,
-    ) {
-        for (interaction, mut transform, original_pos, mut style, entity) in card_query.iter_mut() {
-            match *interaction {
-                Interaction::Hovered => {
-                    if fight_state.current_turn == Turn::Player {
-                        transform.translation.y = original_pos.0.y + 30.0;
-                        style.width = Val::Px(200.0);
-                        style.height = Val::Px(280.0);
-                    }
-                }
-                _ => {
-                    transform.translation.y = original_pos.0.y;
-                    style.width = Val::Px(180.0);
-                    style.height = Val::Px(250.0);
-                }
-            }
-        }
-    }
-
-    fn handle_card_click(
-        mut commands: Commands,
-        mut card_query: Query<
-            (&Interaction, Entity, &CardType),
-            (Changed<Interaction>, With<Card>),
-        >,
-        cards_in_hand: Query<Entity, With<Card>>, // Query to count cards
-        mut fight_state: ResMut<FightState>,
-        mut turn_state: ResMut<TurnState>,
-        mut monster_query: Query<(Entity, &mut Health, &Children), With<Monster>>,
-        health_container_query: Query<&Children, With<HealthBarContainer>>,
-        mut health_bar_query: Query<&mut Sprite, With<HealthBar>>,
-        asset_server: Res<AssetServer>,
-    ) {
-        if fight_state.current_turn != Turn::Player {
-            return;
-        }
-
-        for (interaction, card_entity, card_type) in card_query.iter() {
-            if *interaction == Interaction::Pressed {
-                println!("First card played status: {}", turn_state.first_card_played);
-                // Add animation component
-                commands
-                    .entity(card_entity)
-                    .insert(CardPlayAnimation {
-                        elapsed_time: 0.0,
-                        duration: 3.0, // Animation duration in seconds
-                    })
-                    .remove::<Interaction>();
-                // Calculate damage based on whether this is the first card
-                let is_first = turn_state.first_card_played;
-                let cards_in_hand_count = cards_in_hand.iter().count() as f32; // Get count here
-
-                let damage = if *card_type == CardType::Fire && is_first {
-                    println!("Fire card played as first card! Enhanced damage!");
-                    FIRE_BASE_DAMAGE + FIRE_FIRST_CARD_BONUS
-                } else {
-                    match card_type {
-                        CardType::Fire => {
-                            println!("Fire card played but not first");
-                            FIRE_BASE_DAMAGE
-                        }
-                        CardType::Ice => {
-                            let mut damage = ICE_BASE_DAMAGE;
-
-                            if let Some(last_card) = turn_state.cards_played_this_turn.last() {
-                                if matches!(last_card, CardType::Fire) {
-                                    damage *= 2.0;
-                                }
-                            }
-
-                            if turn_state
-                                .cards_played_this_turn
-                                .iter()
-                                .any(|c| matches!(c, CardType::Earth))
-                            {
-                                damage = 0.0;
-                            }
-
-                            damage
-                        }
-                        CardType::Crystal => {
-                            let effects_bonus =
-                                (turn_state.cards_played_this_turn.len() as f32) * 2.0;
-                            let turn_bonus = turn_state.crystal_power as f32;
-                            CRYSTAL_BASE_DAMAGE + effects_bonus + turn_bonus
-                        }
-                        CardType::Air => AIR_BASE_DAMAGE,
-                        CardType::Earth => {
-                            let turn_bonus = turn_state.turn_count as f32;
-                            EARTH_BASE_DAMAGE + cards_in_hand_count + turn_bonus
-                            // Use the count here
-                        }
-                    }
-                };
-
-                // Deal damage
-                for (entity, mut monster_health, children) in monster_query.iter_mut() {
-                    monster_health.current = (monster_health.current - damage).max(0.0);
-                    println!("Dealing {} damage. First card: {}", damage, is_first);
-                    spawn_damage_text(&mut commands, damage, &asset_server);
-                    // Update monster's health bar
-                    for child in children.iter() {
-                        if let Ok(container_children) = health_container_query.get(*child) {
-                            for health_bar_entity in container_children.iter() {
-                                if let Ok(mut bar_sprite) =
-                                    health_bar_query.get_mut(*health_bar_entity)
-                                {
-                                    let bar_width = 150.0; // Match the width set in chapter1_setup
-                                    let health_percentage =
-                                        monster_health.current / monster_health.maximum;
-
-                                    bar_sprite.custom_size = Some(Vec2::new(
-                                        bar_width * health_percentage,
-                                        bar_sprite.custom_size.unwrap_or(Vec2::ZERO).y,
-                                    ));
-
-                                    // Update color based on health percentage
-                                    bar_sprite.color = if health_percentage > 0.5 {
-                                        Color::srgb(0.0, 1.0, 0.0) // Green
-                                    } else if health_percentage > 0.25 {
-                                        Color::srgb(1.0, 0.65, 0.0) // Orange
-                                    } else {
-                                        Color::srgb(1.0, 0.0, 0.0) // Red
-                                    };
-                                }
-                            }
-                        }
-                    }
-
-                    // If monster dies, despawn it
-                    if monster_health.current <= 0.0 {
-                        commands.entity(entity).despawn_recursive();
-                    }
-                }
-
-                // Handle special card effects and cleanup
-                if matches!(card_type, CardType::Air) {
-                    turn_state.pending_air_cards += 2;
-                }
-
-                // Update turn state BEFORE destroying the card
-                turn_state.cards_played_this_turn.push(*card_type);
-                turn_state.first_card_played = false;
-                println!("Set first_card_played to false");
-
-                // Destroy the played card
-                commands.entity(card_entity).despawn_recursive();
-
-                break;
-            }
-        }
-    }
-
-    // Add this system to help debug the turn state
-    // Add this system to help debug the turn state
-    fn debug_turn_state(turn_state: Res<TurnState>, fight_state: Res<FightState>) {
-        println!(
-            "Turn State - First card: {}, Cards played: {}",
-            turn_state.first_card_played,
-            turn_state.cards_played_this_turn.len(),
-        );
-    }
-
-    // Add this new component for the jump animation
-    #[derive(Component)]
-    struct JumpAnimation {
-        start_y: f32,
-        elapsed_time: f32,
-        duration: f32,
-        height: f32,
-    }
-
-    fn process_turn(
-        mut fight_state: ResMut<FightState>,
-        mut query_set: ParamSet<(
-            Query<(&mut Health, &Children), With<SideCharacter>>,
-            Query<(&Health, &Damage), With<Monster>>,
-        )>,
-        health_container_query: Query<&Children, With<HealthBarContainer>>,
-        mut health_bar_query: Query<&mut Sprite, With<HealthBar>>,
-        asset_server: Res<AssetServer>,
-        mut commands: Commands,
-    ) {
-        if fight_state.current_turn == Turn::Enemy {
-            // First, collect all living monsters and their damage
-            let monster_attacks: Vec<f32> = query_set
-                .p1()
-                .iter()
-                .filter(|(health, _)| health.current > 0.0)
-                .map(|(_, damage)| damage.0)
-                .collect();
-
-            // Then apply damage to the player
-            if let Ok((mut character_health, children)) = query_set.p0().get_single_mut() {
-                for damage in monster_attacks {
-                    character_health.current = (character_health.current - damage).max(0.0);
-                    println!(
-                        "Player health: {}/{}",
-                        character_health.current, character_health.maximum
-                    );
-
-                    // Health bar update logic using nested queries
-                    for child in children.iter() {
-                        if let Ok(container_children) = health_container_query.get(*child) {
-                            for health_bar_entity in container_children.iter() {
-                                if let Ok(mut bar_sprite) =
-                                    health_bar_query.get_mut(*health_bar_entity)
-                                {
-                                    let bar_width = 150.0;
-                                    let health_percentage =
-                                        character_health.current / character_health.maximum;
-
-                                    bar_sprite.custom_size = Some(Vec2::new(
-                                        bar_width * health_percentage,
-                                        bar_sprite.custom_size.unwrap_or(Vec2::ZERO).y,
-                                    ));
-
-                                    bar_sprite.color = if health_percentage > 0.5 {
-                                        Color::srgb(0.0, 1.0, 0.0)
-                                    } else if health_percentage > 0.25 {
-                                        Color::srgb(1.0, 0.65, 0.0)
-                                    } else {
-                                        Color::srgb(1.0, 0.0, 0.0)
-                                    };
-                                }
-                            }
-                        }
-                    }
-
-                    spawn_damage_text(&mut commands, damage, &asset_server);
-
-                    // Check for player death
-                    if character_health.current <= 0.0 {
-                        spawn_death_screen(&mut commands, &asset_server);
-                    }
-                }
-
-                // Switch back to player turn
-                fight_state.current_turn = Turn::Player;
-            }
-        }
-    }
-
-    // Add a component for the damage text effect
-    #[derive(Component)]
-    struct DamageText {
-        timer: Timer,
-    }
-
-    // The spawn_damage_text and animate_damage_text functions remain the same
-    fn spawn_damage_text(commands: &mut Commands, damage: f32, asset_server: &Res<AssetServer>) {
-        let mut color = Color::srgb(1.0, 0.0, 0.0);
-
-        commands.spawn((
-            Text2dBundle {
-                text: Text::from_section(
-                    format!("-{}", damage),
-                    TextStyle {
-                        font_size: 30.0,
-                        color,
-                        ..default()
-                    },
-                ),
-                transform: Transform::from_xyz(0.0, 0.0, 10.0),
-                ..default()
-            },
-            DamageText {
-                timer: Timer::from_seconds(1.0, TimerMode::Once),
-            },
-        ));
-    }
-
-    fn animate_damage_text(
-        mut commands: Commands,
-        time: Res<Time>,
-        mut query: Query<(Entity, &mut Transform, &mut Text, &mut DamageText)>,
-    ) {
-        for (entity, mut transform, mut text, mut damage_text) in query.iter_mut() {
-            damage_text.timer.tick(time.delta());
-
-            // Move the text upward
-            transform.translation.y += 100.0 * time.delta_seconds();
-
-            // Fade out the text
-            let alpha =
-                1.0 - damage_text.timer.elapsed_secs() / damage_text.timer.duration().as_secs_f32();
-
-            // Remove the text when the timer is finished
-            if damage_text.timer.finished() {
-                commands.entity(entity).despawn();
-            }
-        }
-    }
-    fn handle_end_turn_button(
-        mut interaction_query: Query<
-            (&Interaction, &mut BackgroundColor),
-            (Changed<Interaction>, With<EndTurnButton>),
-        >,
-        mut fight_state: ResMut<FightState>,
-        mut commands: Commands,
-        asset_server: Res<AssetServer>,
-        turn_state: Res<TurnState>,
-    ) {
-        for (interaction, mut color) in &mut interaction_query {
-            match *interaction {
-                Interaction::Pressed => {
-                    if fight_state.current_turn == Turn::Player {
-                        // Add air cards before changing turn
-                        for _ in 0..turn_state.pending_air_cards {
-                            spawn_card(&mut commands, CardType::Air, &asset_server);
-                        }
-
-                        fight_state.current_turn = Turn::Enemy;
-                        *color = Color::srgb(0.35, 0.35, 0.35).into();
-                    }
-                }
-                Interaction::Hovered => {
-                    if fight_state.current_turn == Turn::Player {
-                        *color = Color::srgb(0.25, 0.25, 0.25).into();
-                    }
-                }
-                Interaction::None => {
-                    *color = Color::srgb(0.15, 0.15, 0.15).into();
-                }
-            }
-        }
-    }
-
-    // Add this system to update the button's appearance based on turn state
-    fn update_end_turn_button(
-        fight_state: Res<FightState>,
-        mut button_query: Query<&mut BackgroundColor, With<EndTurnButton>>,
-        mut text_query: Query<&mut Text, With<ButtonText>>,
-    ) {
-        if let Ok(mut color) = button_query.get_single_mut() {
-            if fight_state.current_turn == Turn::Player {
-                *color = Color::srgb(0.15, 0.15, 0.15).into();
-            } else {
-                *color = Color::srgb(0.5, 0.5, 0.5).into();
-            }
-        }
-
-        if let Ok(mut text) = text_query.get_single_mut() {
-            if fight_state.current_turn == Turn::Player {
-                text.sections[0].value = "End Turn".to_string();
-            } else {
-                text.sections[0].value = "Enemy Turn".to_string();
-            }
-        }
-    }
-    // Update the chapter1_plugin to include debug system
-    pub fn chapter1_plugin(app: &mut App) {
-        app.init_resource::<FightState>()
-            .init_resource::<TurnState>() // This line was already correct
-            .add_systems(OnEnter(GameState::Chapter1), (chapter1_setup,))
-            .add_systems(
-                Update,
-                (
-                    animate_sprite,
-                    update_card_hover,
-                    handle_card_click,
-                    process_turn,
-                    update_health_bars,
-                    handle_end_turn_button,
-                    update_end_turn_button,
-                    animate_damage_text,
-                    update_death_screen,
-                    process_pending_cards,
-                    update_turn_state,
-                    check_victory_condition, // Add this
-                    update_victory_screen,
-                    animate_card_play,
-                    //debug_turn_state,
-                )
-                    .chain()
-                    .run_if(in_state(GameState::Chapter1)),
-            )
-            .add_systems(
-                OnExit(GameState::Chapter1),
-                super::despawn_screen::<OnChapterOneScreen>,
-            );
-    }
-
-    #[derive(Component)]
-    struct PendingCards {
-        card_type: CardType,
-        amount: i32,
-    }
-
-    fn process_pending_cards(
-        mut commands: Commands,
-        pending_query: Query<(Entity, &PendingCards)>,
-        mut turn_state: ResMut<TurnState>,
-        asset_server: Res<AssetServer>,
-    ) {
-        for (entity, pending) in pending_query.iter() {
-            for _ in 0..pending.amount {
-                spawn_card(&mut commands, pending.card_type, &asset_server);
-            }
-            commands.entity(entity).despawn();
-        }
-    }
-
-    fn spawn_card(commands: &mut Commands, card_type: CardType, asset_server: &Res<AssetServer>) {
-        let texture = match card_type {
-            CardType::Fire => asset_server.load("textures/Game Icons/Fire.png"),
-            CardType::Ice => asset_server.load("textures/Game Icons/Frost.png"),
-            CardType::Air => asset_server.load("textures/Game Icons/air.png"),
-            CardType::Earth => asset_server.load("textures/Game Icons/Earth.png"),
-            CardType::Crystal => asset_server.load("textures/Game Icons/Crystal.png"),
-        };
-
-        commands.spawn((
-            ImageBundle {
-                style: Style {
-                    width: Val::Px(180.0),
-                    height: Val::Px(250.0),
-                    margin: UiRect::horizontal(Val::Px(10.0)),
-                    ..default()
-                },
-                image: UiImage::new(texture),
-                background_color: Color::WHITE.into(),
-                ..default()
-            },
-            Interaction::None,
-            Card,
-            card_type,
-            OriginalPosition(Vec2::new(0.0, 0.0)), // Position will need to be adjusted
-            OnChapterOneScreen,
-        ));
-    }
-
-    fn update_turn_state(mut fight_state: ResMut<FightState>, mut turn_state: ResMut<TurnState>) {
-        // if fight_state.current_turn == Turn::Player {
-        //     turn_state.cards_played_this_turn.clear();
-        //     turn_state.crystal_power += 1;
-        //     turn_state.turn_count += 1;
-        //     turn_state.pending_air_cards = 0; // Reset pending air cards
-        // }
-    }
-
-    fn chapter1_setup(
-        mut commands: Commands,
-        asset_server: Res<AssetServer>,
-        mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
-        windows: Query<&Window>,
-    ) {
-        commands.insert_resource(TurnState {
-            first_card_played: true,
-            cards_played_this_turn: Vec::new(),
-            crystal_power: 0,
-            turn_count: 0,
-            pending_air_cards: 0,
-        });
-        let window = windows.single();
-
-        // Calculate positions
-        let char_x = window.width() * -0.25;
-        let char_y = window.height() * -0.75;
-
-        // Load textures
-        let texture_handle: Handle<Image> = asset_server.load("textures/intro_game_sprite.png");
-        let fire_card_texture: Handle<Image> = asset_server.load("textures/Game Icons/Fire.png");
-        let ice_card_texture: Handle<Image> = asset_server.load("textures/Game Icons/Frost.png");
-        let air_card_texture: Handle<Image> = asset_server.load("textures/Game Icons/air.png");
-        let earth_card_texture: Handle<Image> = asset_server.load("textures/Game Icons/Earth.png");
-        let crystal_card_texture: Handle<Image> =
-            asset_server.load("textures/Game Icons/Crystal.png");
-        let forest: Handle<Image> = asset_server.load("textures/1.png");
-
-        let side_character_texture = asset_server.load("textures/character.png");
-        let monster_texture: Handle<Image> = asset_server.load("textures/monster.png");
-        let monster_texture_2 = asset_server.load("textures/monster_2.png");
-
-        let layout = TextureAtlasLayout::from_grid(UVec2::new(576, 324), 5, 64, None, None);
-        let atlas_layout = atlas_layouts.add(layout);
-
-        // Spawn main container
-        commands
-            .spawn((
-                NodeBundle {
-                    style: Style {
-                        width: Val::Percent(100.0),
-                        height: Val::Percent(100.0),
-                        flex_direction: FlexDirection::Column,
-                        align_items: AlignItems::Center,
-                        justify_content: JustifyContent::SpaceBetween,
-                        ..default()
-                    },
-                    ..default()
-                },
-                OnChapterOneScreen,
-            ))
-            // .with_children(|parent| {
-            //     // Background animation (same as before)
-            //     parent
-            //         .spawn(NodeBundle {
-            //             style: Style {
-            //                 width: Val::Vw(100.0),
-            //                 height: Val::Vh(100.0),
-            //                 align_items: AlignItems::Center,
-            //                 justify_content: JustifyContent::Center,
-            //                 ..default()
-            //             },
-            //             ..default()
-            //         })
-            //         .with_children(|parent| {
-            //             parent.spawn((
-            //                 SpriteSheetBundle {
-            //                     texture: texture_handle,
-            //                     atlas: TextureAtlas {
-            //                         layout: atlas_layout,
-            //                         index: 0,
-            //                     },
-            //                     transform: Transform::from_xyz(
-            //                         -window.width() / 2.0,
-            //                         -window.height() / 2.0,
-            //                         1.0,
-            //                     ),
-            //                     sprite: Sprite {
-            //                         custom_size: Some(Vec2::new(1920.0, 1080.0)),
-            //                         anchor: bevy::sprite::Anchor::Center,
-            //                         ..default()
-            //                     },
-            //                     ..default()
-            //                 },
-            //                 AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)),
-            //                 AnimationIndices {
-            //                     first: 0,
-            //                     last: 320,
-            //                 },
-            //             ));
-            //         });
-            .with_children(|parent| {
-                parent
-                    .spawn(NodeBundle {
-                        style: Style {
-                            width: Val::Vw(100.0),
-                            height: Val::Vh(100.0),
-                            align_items: AlignItems::Center,
-                            justify_content: JustifyContent::Center,
-                            ..default()
-                        },
-                        ..default()
-                    })
-                    .with_children(|parent| {
-                        parent.spawn(SpriteBundle {
-                            texture: forest,
-                            transform: Transform::from_xyz(
-                                -window.width() / 2.0,
-                                -window.height() / 2.0,
-                                1.0,
-                            ),
-                            sprite: Sprite {
-                                custom_size: Some(Vec2::new(1920.0, 1080.0)),
-                                anchor: bevy::sprite::Anchor::Center,
-                                ..default()
-                            },
-                            ..default()
-                        });
-                    });
-
-                // Side character with health
-                parent
-                    .spawn((
-                        SpriteBundle {
-                            texture: side_character_texture,
-                            transform: Transform::from_xyz(
-                                -window.width() / 2.0 + char_x,
-                                char_y,
-                                2.0,
-                            ),
-                            sprite: Sprite {
-                                custom_size: Some(Vec2::new(400.0, 400.0)),
-                                anchor: bevy::sprite::Anchor::Center,
-                                ..default()
-                            },
-                            ..default()
-                        },
-                        SideCharacter,
-                        Health {
-                            current: 100.0,
-                            maximum: 100.0,
-                        },
-                    ))
-                    .with_children(|monster| {
-                        // Health bar background
-                        monster
-                            .spawn((
-                                SpriteBundle {
-                                    sprite: Sprite {
-                                        color: Color::srgb(0.2, 0.2, 0.2),
-                                        custom_size: Some(Vec2::new(150.0, 10.0)),
-                                        ..default()
-                                    },
-                                    transform: Transform::from_xyz(
-                                        0.0,    // Centered horizontally relative to parent
-                                        -175.0, // Below sprite with 20px padding
-                                        0.1,
-                                    ),
-                                    ..default()
-                                },
-                                HealthBarContainer,
-                                OnChapterOneScreen,
-                            ))
-                            .with_children(|container| {
-                                // Actual health bar
-                                container.spawn((
-                                    SpriteBundle {
-                                        sprite: Sprite {
-                                            color: Color::srgb(0.0, 1.0, 0.0),
-                                            custom_size: Some(Vec2::new(150.0, 10.0)),
-                                            anchor: bevy::sprite::Anchor::CenterLeft,
-                                            ..default()
-                                        },
-                                        transform: Transform::from_xyz(-75.0, 0.0, 0.2),
-                                        ..default()
-                                    },
-                                    HealthBar,
-                                ));
-                            });
-                    });
-                let monster1_damage = 15.0;
-                let monster2_damage = 10.0;
-                // Monster 1 with health
-                parent
-                    .spawn((
-                        SpriteBundle {
-                            texture: monster_texture,
-                            transform: Transform::from_xyz(
-                                char_x + window.width() / 8.0,
-                                char_y - 75.0,
-                                2.0,
-                            ),
-                            sprite: Sprite {
-                                custom_size: Some(Vec2::new(250.0, 250.0)),
-                                anchor: bevy::sprite::Anchor::Center,
-                                ..default()
-                            },
-                            ..default()
-                        },
-                        Monster,
-                        Health {
-                            current: 40.0,
-                            maximum: 40.0,
-                        },
-                        Damage(monster1_damage), // This monster deals 15 damage
-                    ))
-                    .with_children(|monster| {
-                        // Spawn the black background sprite
-                        monster.spawn(SpriteBundle {
-                            sprite: Sprite {
-                                color: Color::BLACK,
-                                custom_size: Some(Vec2::new(50.0, 30.0)), // Adjust size as needed
-                                ..default()
-                            },
-                            transform: Transform::from_xyz(0.0, 120.0, 0.0),
-                            ..default()
-                        });
-                        // Spawn damage text above monster
-                        monster.spawn((
-                            Text2dBundle {
-                                text: Text::from_section(
-                                    format!("{}", monster1_damage),
-                                    TextStyle {
-                                        font_size: 24.0,
-                                        color: Color::rgb(1.0, 0.0, 0.0),
-                                        ..default()
-                                    },
-                                ),
-                                transform: Transform::from_xyz(0.0, 120.0, 0.1), // Position above monster
-                                ..default()
-                            },
-                            DamageDisplay,
-                        ));
-                        // Health bar background
-                        monster
-                            .spawn((
-                                SpriteBundle {
-                                    sprite: Sprite {
-                                        color: Color::srgb(0.2, 0.2, 0.2),
-                                        custom_size: Some(Vec2::new(150.0, 10.0)),
-                                        ..default()
-                                    },
-                                    transform: Transform::from_xyz(
-                                        0.0,    // Centered horizontally relative to parent
-                                        -100.0, // Below sprite with 20px padding
-                                        0.1,
-                                    ),
-                                    ..default()
-                                },
-                                HealthBarContainer,
-                                OnChapterOneScreen,
-                            ))
-                            .with_children(|container| {
-                                // Actual health bar
-                                container.spawn((
-                                    SpriteBundle {
-                                        sprite: Sprite {
-                                            color: Color::srgb(0.0, 1.0, 0.0),
-                                            custom_size: Some(Vec2::new(150.0, 10.0)),
-                                            anchor: bevy::sprite::Anchor::CenterLeft,
-                                            ..default()
-                                        },
-                                        transform: Transform::from_xyz(-75.0, 0.0, 0.2),
-                                        ..default()
-                                    },
-                                    HealthBar,
-                                ));
-                            });
-                    });
-                // Monster 2 with healthcurrent:
-                parent
-                    .spawn((
-                        SpriteBundle {
-                            texture: monster_texture_2,
-                            transform: Transform::from_xyz(
-                                char_x - window.width() / 8.0,
-                                char_y - 75.0,
-                                2.0,
-                            ),
-                            sprite: Sprite {
-                                custom_size: Some(Vec2::new(250.0, 250.0)),
-                                anchor: bevy::sprite::Anchor::Center,
-                                ..default()
-                            },
-                            ..default()
-                        },
-                        Monster,
-                        Health {
-                            current: 40.0,
-                            maximum: 40.0,
-                        },
-                        Damage(monster2_damage), // This monster deals 10 damage
-                    ))
-                    .with_children(|monster| {
-                        // Spawn the black background sprite
-                        monster.spawn(SpriteBundle {
-                            sprite: Sprite {
-                                color: Color::BLACK,
-                                custom_size: Some(Vec2::new(50.0, 30.0)), // Adjust size as needed
-                                ..default()
-                            },
-                            transform: Transform::from_xyz(0.0, 120.0, 0.0),
-                            ..default()
-                        });
-                        // Spawn damage text above monster
-                        monster.spawn((
-                            Text2dBundle {
-                                text: Text::from_section(
-                                    format!("{}", monster2_damage),
-                                    TextStyle {
-                                        font_size: 24.0,
-                                        color: Color::srgb(1.0, 0.0, 0.0),
-                                        ..default()
-                                    },
-                                ),
-                                transform: Transform::from_xyz(0.0, 120.0, 0.1), // Position above monster
-                                ..default()
-                            },
-                            DamageDisplay,
-                        ));
-                        // Health bar background
-                        monster
-                            .spawn((
-                                SpriteBundle {
-                                    sprite: Sprite {
-                                        color: Color::srgb(0.2, 0.2, 0.2),
-                                        custom_size: Some(Vec2::new(150.0, 10.0)),
-                                        ..default()
-                                    },
-                                    transform: Transform::from_xyz(
-                                        0.0,    // Centered horizontally relative to parent
-                                        -100.0, // Below sprite with 20px padding
-                                        0.1,
-                                    ),
-                                    ..default()
-                                },
-                                HealthBarContainer,
-                                OnChapterOneScreen,
-                            ))
-                            .with_children(|container| {
-                                // Actual health bar
-                                container.spawn((
-                                    SpriteBundle {
-                                        sprite: Sprite {
-                                            color: Color::srgb(0.0, 1.0, 0.0),
-                                            custom_size: Some(Vec2::new(150.0, 10.0)),
-                                            anchor: bevy::sprite::Anchor::CenterLeft,
-                                            ..default()
-                                        },
-                                        transform: Transform::from_xyz(-75.0, 0.0, 0.2),
-                                        ..default()
-                                    },
-                                    HealthBar,
-                                ));
-                            });
-                    });
-
-                // Add this to the chapter1_setup function after spawning the cards container
-                parent
-                    .spawn(NodeBundle {
-                        style: Style {
-                            position_type: PositionType::Absolute,
-                            right: Val::Px(20.0),
-                            top: Val::Px(20.0), // Changed from top to bottom
-                            width: Val::Px(150.0),
-                            height: Val::Px(50.0),
-                            align_items: AlignItems::Center,
-                            justify_content: JustifyContent::Center,
-                            ..default()
-                        },
-                        background_color: Color::srgb(0.15, 0.15, 0.15).into(),
-                        ..default()
-                    })
-                    .with_children(|parent| {
-                        parent
-                            .spawn((
-                                ButtonBundle {
-                                    style: Style {
-                                        width: Val::Percent(100.0),
-                                        height: Val::Percent(100.0),
-                                        align_items: AlignItems::Center,
-                                        justify_content: JustifyContent::Center,
-                                        ..default()
-                                    },
-                                    background_color: Color::srgb(0.15, 0.15, 0.15).into(),
-                                    ..default()
-                                },
-                                EndTurnButton,
-                            ))
-                            .with_children(|parent| {
-                                parent.spawn((
-                                    TextBundle::from_section(
-                                        "End Turn",
-                                        TextStyle {
-                                            font_size: 20.0,
-                                            color: Color::WHITE,
-                                            ..default()
-                                        },
-                                    ),
-                                    ButtonText,
-                                ));
-                            });
-                    });
-                // Cards container
-                parent
-                    .spawn(NodeBundle {
-                        style: Style {
-                            width: Val::Percent(100.0),
-                            height: Val::Px(200.0),
-                            position_type: PositionType::Absolute,
-                            top: Val::Px(20.0),
-                            justify_content: JustifyContent::Center,
-                            align_items: AlignItems::Center,
-                            column_gap: Val::Px(20.0),
-                            ..default()
-                        },
-                        ..default()
-                    })
-                    .with_children(|parent| {
-                        // Define card types and their corresponding textures
-                        let cards = vec![
-                            //(CardType::Air, air_card_texture.clone()),
-                            (CardType::Earth, earth_card_texture.clone()),
-                            (CardType::Crystal, crystal_card_texture.clone()),
-                            (CardType::Fire, fire_card_texture.clone()),
-                            (CardType::Ice, ice_card_texture.clone()),
-                        ];
-
-                        // Spawn three cards
-                        for (i, (card_type, card_texture)) in cards.into_iter().enumerate() {
-                            // Changed to into_iter()
-                            let x_position = (i as f32 - 1.0) * 220.0;
-
-                            parent.spawn((
-                                ImageBundle {
-                                    style: Style {
-                                        width: Val::Px(180.0),
-                                        height: Val::Px(250.0),
-                                        margin: UiRect::horizontal(Val::Px(10.0)),
-                                        ..default()
-                                    },
-                                    image: UiImage::new(card_texture),
-                                    background_color: Color::WHITE.into(),
-                                    transform: Transform::from_xyz(x_position, 0.0, 0.0),
-                                    ..default()
-                                },
-                                Interaction::None,
-                                Card,
-                                card_type, // No longer a reference
-                                OriginalPosition(Vec2::new(x_position, 0.0)),
-                            ));
-                        }
-                    });
-            });
-    }
-
-    fn animate_sprite(
-        time: Res<Time>,
-        mut query: Query<(&mut TextureAtlas, &mut AnimationTimer, &AnimationIndices)>,
-    ) {
-        for (mut atlas, mut timer, indices) in &mut query {
-            timer.0.tick(time.delta());
-            if timer.0.just_finished() {
-                atlas.index = if atlas.index == indices.last {
-                    indices.first
-                } else {
-                    atlas.index + 1
-                };
-            }
-        }
-    }
-
-    // Add these new components and structs in the chapter1 mod
-    #[derive(Component)]
-    struct VictoryScreen;
-
-    #[derive(Component)]
-    struct VictoryText;
-
-    #[derive(Component)]
-    struct ReturnToMenuVictoryTimer {
-        timer: Timer,
-    }
-
-    fn spawn_victory_screen(commands: &mut Commands, asset_server: &AssetServer) {
-        commands
-            .spawn((
-                NodeBundle {
-                    style: Style {
-                        width: Val::Percent(100.0),
-                        height: Val::Percent(100.0),
-                        position_type: PositionType::Absolute,
-                        align_items: AlignItems::Center,
-                        justify_content: JustifyContent::Center,
-                        flex_direction: FlexDirection::Column,
-                        ..default()
-                    },
-                    background_color: BackgroundColor(Color::rgba(0.0, 0.0, 0.0, 0.0)),
-                    ..default()
-                },
-                VictoryScreen,
-                FadeInEffect {
-                    timer: Timer::from_seconds(2.0, TimerMode::Once),
-                },
-                ReturnToMenuVictoryTimer {
-                    timer: Timer::from_seconds(5.0, TimerMode::Once),
-                },
-            ))
-            .with_children(|parent| {
-                parent.spawn((
-                    TextBundle::from_section(
-                        "VICTORY!",
-                        TextStyle {
-                            font_size: 120.0,
-                            color: Color::rgba(0.0, 0.8, 0.0, 0.0), // Start transparent, but green
-                            ..default()
-                        },
-                    ),
-                    VictoryText,
-                    FadeInEffect {
-                        timer: Timer::from_seconds(2.0, TimerMode::Once),
-                    },
-                ));
-            });
-    }
-
-    fn update_victory_screen(
-        mut commands: Commands,
-        time: Res<Time>,
-        mut query: Query<
-            (
-                Entity,
-                &mut BackgroundColor,
-                &mut FadeInEffect,
-                &mut ReturnToMenuVictoryTimer,
-            ),
-            With<VictoryScreen>,
-        >,
-        mut text_query: Query<&mut Text, With<VictoryText>>,
-        mut game_state: ResMut<NextState<GameState>>,
-        mut app_exit_events: EventWriter<AppExit>,
-    ) {
-        for (entity, mut bg_color, mut fade, mut return_timer) in query.iter_mut() {
-            fade.timer.tick(time.delta());
-            let alpha = fade.timer.fraction();
-            bg_color.0 = Color::rgba(0.0, 0.0, 0.0, alpha * 0.7);
-
-            if let Ok(mut text) = text_query.get_single_mut() {
-                text.sections[0].style.color = Color::rgba(0.0, 0.8, 0.0, alpha);
-            }
-
-            return_timer.timer.tick(time.delta());
-            if return_timer.timer.finished() {
-                game_state.set(GameState::Game2); // Change this line to transition to Chapter2
-                commands.entity(entity).despawn_recursive(); // Clean up victory screen
-                                                             //app_exit_events.send(AppExit::Success);
-            }
-        }
-    }
-
-    fn check_victory_condition(
-        monster_query: Query<&Health, With<Monster>>,
-        victory_screen_query: Query<(), With<VictoryScreen>>,
-        mut commands: Commands,
-        asset_server: Res<AssetServer>,
-    ) {
-        if victory_screen_query.is_empty() {
-            // Only check if victory screen isn't already shown
-            let all_monsters_dead = monster_query.iter().all(|health| health.current <= 0.0);
-
-            if all_monsters_dead {
-                spawn_victory_screen(&mut commands, &asset_server);
-            }
-        }
-    }
-}
-
-mod chapter2 {
-    use super::GameState;
-    use bevy::app::AppExit;
-    use bevy::ecs::system::ParamSet;
-    use bevy::prelude::*;
-
-    #[derive(Component, Copy, Clone, Debug, PartialEq)]
-    enum CardType {
-        Fire,
-        Ice,
-        Air,
-        Earth,
-        Crystal,
-        // Add other types as needed
-    }
-    // Components
-    #[derive(Component)]
-    struct AnimationTimer(Timer);
-
-    #[derive(Component)]
-    struct AnimationIndices {
-        first: usize,
-        last: usize,
-    }
-
-    #[derive(Component)]
-    struct Card;
-
-    #[derive(Component)]
-    struct OriginalPosition(Vec2);
-
-    #[derive(Component)]
-    struct OnChapterOneScreen;
-
-    #[derive(Component)]
-    struct SideCharacter;
-
-    #[derive(Component)]
-    struct Monster;
-
-    #[derive(Component)]
-    struct Health {
-        current: f32,
-        maximum: f32,
-    }
-
-    // Add this to your existing components if not already present
-    #[derive(Component)]
-    struct HealthBarContainer;
-
-    #[derive(Component)]
-    struct HealthBar;
-
-    // Add these new components in the chapter1 mod
-    #[derive(Component)]
-    struct EndTurnButton;
-
-    #[derive(Component)]
-    struct ButtonText;
-
-    #[derive(Component)]
-    struct Damage(f32);
-
-    #[derive(Component)]
-    struct DeathScreen;
-
-    #[derive(Component)]
-    struct DeathText;
-
-    #[derive(Component)]
-    struct FadeInEffect {
-        timer: Timer,
-    }
-
-    #[derive(Component)]
-    struct ReturnToMenuTimer {
-        timer: Timer,
-    }
-
-    #[derive(Component)]
-    struct DamageDisplay;
-
-    #[derive(Resource, Default)]
-    struct PendingAirCards(i32);
-
-    fn spawn_death_screen(commands: &mut Commands, asset_server: &AssetServer) {
-        // Main container
-        commands
-            .spawn((
-                NodeBundle {
-                    style: Style {
-                        width: Val::Percent(100.0),
-                        height: Val::Percent(100.0),
-                        position_type: PositionType::Absolute,
-                        align_items: AlignItems::Center,
-                        justify_content: JustifyContent::Center,
-                        flex_direction: FlexDirection::Column,
-                        ..default()
-                    },
-                    background_color: BackgroundColor(Color::rgba(0.0, 0.0, 0.0, 0.0)),
-                    ..default()
-                },
-                DeathScreen,
-                FadeInEffect {
-                    timer: Timer::from_seconds(2.0, TimerMode::Once),
-                },
-                // Add a timer to return to menu after 5 seconds
-                ReturnToMenuTimer {
-                    timer: Timer::from_seconds(5.0, TimerMode::Once),
-                },
-            ))
-            .with_children(|parent| {
-                // "YOU DIED" text
-                parent.spawn((
-                    TextBundle::from_section(
-                        "YOU DIED",
-                        TextStyle {
-                            font_size: 120.0,
-                            color: Color::rgba(0.8, 0.0, 0.0, 0.0), // Start transparent
-                            ..default()
-                        },
-                    ),
-                    DeathText,
-                    FadeInEffect {
-                        timer: Timer::from_seconds(2.0, TimerMode::Once),
-                    },
-                ));
-            });
-    }
-
-    // Update the death screen system to handle both fade-in and menu transition
-    fn update_death_screen(
-        mut commands: Commands,
-        time: Res<Time>,
-        mut query: Query<
-            (
-                Entity,
-                &mut BackgroundColor,
-                &mut FadeInEffect,
-                &mut ReturnToMenuTimer,
-            ),
-            With<DeathScreen>,
-        >,
-        mut text_query: Query<&mut Text, With<DeathText>>,
-        mut game_state: ResMut<NextState<GameState>>,
-        mut app_exit_events: EventWriter<AppExit>,
-    ) {
-        for (entity, mut bg_color, mut fade, mut return_timer) in query.iter_mut() {
-            // Update fade effect
-            fade.timer.tick(time.delta());
-            let alpha = fade.timer.fraction();
-            bg_color.0 = Color::rgba(0.0, 0.0, 0.0, alpha * 0.7);
-
-            // Update text color
-            if let Ok(mut text) = text_query.get_single_mut() {
-                text.sections[0].style.color = Color::rgba(0.8, 0.0, 0.0, alpha);
-            }
-
-            // Update return timer
-            return_timer.timer.tick(time.delta());
-            if return_timer.timer.finished() {
-                app_exit_events.send(AppExit::Success);
-            }
-        }
-    }
-    // Modify TurnState to include pending air cards
-    #[derive(Resource)]
-    struct TurnState {
-        first_card_played: bool,
-        cards_played_this_turn: Vec<CardType>,
-        crystal_power: i32,
-        turn_count: i32,
-        pending_air_cards: i32,
-    }
-
-    impl Default for TurnState {
-        fn default() -> Self {
-            Self {
-                first_card_played: true,
-                cards_played_this_turn: Vec::new(),
-                crystal_power: 0,
-                turn_count: 0,
-                pending_air_cards: 0, // Initialize pending_air_cards to 0
-            }
-        }
-    }
-
-    // Constants for base damage values
-    const FIRE_BASE_DAMAGE: f32 = 8.0;
-    const FIRE_FIRST_CARD_BONUS: f32 = 7.0;
-    const ICE_BASE_DAMAGE: f32 = 6.0;
-    const CRYSTAL_BASE_DAMAGE: f32 = 4.0;
-    const AIR_BASE_DAMAGE: f32 = 2.0;
-    const EARTH_BASE_DAMAGE: f32 = 5.0;
-
-    fn update_health_bars(
-        query: Query<(&Health, &Children), Without<HealthBar>>,
-        mut health_bar_query: Query<&mut Sprite, With<HealthBar>>,
-    ) {
-        for (health, children) in query.iter() {
-            for child in children.iter() {
-                if let Ok(mut bar_sprite) = health_bar_query.get_mut(*child) {
-                    // Update health bar width based on current health
-                    let bar_width = 100.0;
-                    let health_percentage = health.current / health.maximum;
-
-                    bar_sprite.custom_size = Some(Vec2::new(
-                        bar_width * health_percentage,
-                        bar_sprite.custom_size.unwrap_or(Vec2::ZERO).y,
-                    ));
-
-                    // Update color based on health percentage
-                    bar_sprite.color = if health_percentage > 0.5 {
-                        Color::srgb(0.0, 1.0, 0.0) // Green: rgb(0, 255, 0)
-                    } else if health_percentage > 0.25 {
-                        Color::srgb(1.0, 0.65, 0.0) // Orange: rgb(255, 165, 0)
-                    } else {
-                        Color::srgb(1.0, 0.0, 0.0) // Red: rgb(255, 0, 0)
-                    };
-                }
-            }
-        }
-    }
-    #[derive(Resource)]
-    struct FightState {
-        current_turn: Turn,
-        selected_card: Option<usize>,
-    }
-
-    #[derive(PartialEq)]
-    enum Turn {
-        Player,
-        Enemy,
-    }
-
-    impl Default for FightState {
-        fn default() -> Self {
-            Self {
-                current_turn: Turn::Player,
-                selected_card: None,
-            }
-        }
-    }
-
-    // Update the card hover system to use FightState
-    fn update_card_hover(
-        mut card_query: Query<
-            (
-                &Interaction,
-                &mut Transform,
-                &OriginalPosition,
-                &mut Style,
-                Entity,
-            ),
-            (With<Card>, Changed<Interaction>),
-        >,
-        mut commands: Commands,
-        fight_state: Res<FightState>,
-    ) {
-        for (interaction, mut transform, original_pos, mut style, entity) in card_query.iter_mut() {
-            match *interaction {
-                Interaction::Hovered => {
-                    if fight_state.current_turn == Turn::Player {
-                        transform.translation.y = original_pos.0.y + 30.0;
-                        style.width = Val::Px(200.0);
-                        style.height = Val::Px(280.0);
-                    }
-                }
-                _ => {
-                    transform.translation.y = original_pos.0.y;
-                    style.width = Val::Px(180.0);
-                    style.height = Val::Px(250.0);
-                }
-            }
-        }
-    }
-
-    fn handle_card_click(
-        mut commands: Commands,
-        mut card_query: Query<
-            (&Interaction, Entity, &CardType),
-            (Changed<Interaction>, With<Card>),
-        >,
-        cards_in_hand: Query<Entity, With<Card>>, // Query to count cards
-        mut fight_state: ResMut<FightState>,
-        mut turn_state: ResMut<TurnState>,
-        mut monster_query: Query<(Entity, &mut Health, &Children), With<Monster>>,
-        health_container_query: Query<&Children, With<HealthBarContainer>>,
-        mut health_bar_query: Query<&mut Sprite, With<HealthBar>>,
-        asset_server: Res<AssetServer>,
-    ) {
-        if fight_state.current_turn != Turn::Player {
-            return;
-        }
-
-        for (interaction, card_entity, card_type) in card_query.iter() {
-            if *interaction == Interaction::Pressed {
-                println!("First card played status: {}", turn_state.first_card_played);
-
-                // Calculate damage based on whether this is the first card
-                let is_first = turn_state.first_card_played;
-                let cards_in_hand_count = cards_in_hand.iter().count() as f32; // Get count here
-
-                let damage = if *card_type == CardType::Fire && is_first {
-                    println!("Fire card played as first card! Enhanced damage!");
-                    FIRE_BASE_DAMAGE + FIRE_FIRST_CARD_BONUS
-                } else {
-                    match card_type {
-                        CardType::Fire => {
-                            println!("Fire card played but not first");
-                            FIRE_BASE_DAMAGE
-                        }
-                        CardType::Ice => {
-                            let mut damage = ICE_BASE_DAMAGE;
-
-                            if let Some(last_card) = turn_state.cards_played_this_turn.last() {
-                                if matches!(last_card, CardType::Fire) {
-                                    damage *= 2.0;
-                                }
-                            }
-
-                            if turn_state
-                                .cards_played_this_turn
-                                .iter()
-                                .any(|c| matches!(c, CardType::Earth))
-                            {
-                                damage = 0.0;
-                            }
-
-                            damage
-                        }
-                        CardType::Crystal => {
-                            let effects_bonus =
-                                (turn_state.cards_played_this_turn.len() as f32) * 2.0;
-                            let turn_bonus = turn_state.crystal_power as f32;
-                            CRYSTAL_BASE_DAMAGE + effects_bonus + turn_bonus
-                        }
-                        CardType::Air => AIR_BASE_DAMAGE,
-                        CardType::Earth => {
-                            let turn_bonus = turn_state.turn_count as f32;
-                            EARTH_BASE_DAMAGE + cards_in_hand_count + turn_bonus
-                            // Use the count here
-                        }
-                    }
-                };
-
-                // Deal damage
-                for (entity, mut monster_health, children) in monster_query.iter_mut() {
-                    monster_health.current = (monster_health.current - damage).max(0.0);
-                    println!("Dealing {} damage. First card: {}", damage, is_first);
-                    spawn_damage_text(&mut commands, damage, &asset_server);
-                    // Update monster's health bar
-                    for child in children.iter() {
-                        if let Ok(container_children) = health_container_query.get(*child) {
-                            for health_bar_entity in container_children.iter() {
-                                if let Ok(mut bar_sprite) =
-                                    health_bar_query.get_mut(*health_bar_entity)
-                                {
-                                    let bar_width = 150.0; // Match the width set in chapter1_setup
-                                    let health_percentage =
-                                        monster_health.current / monster_health.maximum;
-
-                                    bar_sprite.custom_size = Some(Vec2::new(
-                                        bar_width * health_percentage,
-                                        bar_sprite.custom_size.unwrap_or(Vec2::ZERO).y,
-                                    ));
-
-                                    // Update color based on health percentage
-                                    bar_sprite.color = if health_percentage > 0.5 {
-                                        Color::srgb(0.0, 1.0, 0.0) // Green
-                                    } else if health_percentage > 0.25 {
-                                        Color::srgb(1.0, 0.65, 0.0) // Orange
-                                    } else {
-                                        Color::srgb(1.0, 0.0, 0.0) // Red
-                                    };
-                                }
-                            }
-                        }
-                    }
-
-                    // If monster dies, despawn it
-                    if monster_health.current <= 0.0 {
-                        commands.entity(entity).despawn_recursive();
-                    }
-                }
-
-                // Handle special card effects and cleanup
-                if matches!(card_type, CardType::Air) {
-                    turn_state.pending_air_cards += 2;
-                }
-
-                // Update turn state BEFORE destroying the card
-                turn_state.cards_played_this_turn.push(*card_type);
-                turn_state.first_card_played = false;
-                println!("Set first_card_played to false");
-
-                // Destroy the played card
-                commands.entity(card_entity).despawn_recursive();
-
-                break;
-            }
-        }
-    }
-
-    // Add this system to help debug the turn state
-    // Add this system to help debug the turn state
-    fn debug_turn_state(turn_state: Res<TurnState>, fight_state: Res<FightState>) {
-        println!(
-            "Turn State - First card: {}, Cards played: {}",
-            turn_state.first_card_played,
-            turn_state.cards_played_this_turn.len(),
-        );
-    }
-
-    fn process_turn(
-        mut fight_state: ResMut<FightState>,
-        mut query_set: ParamSet<(
-            Query<(&mut Health, &Children), With<SideCharacter>>,
-            Query<(&Health, &Damage), With<Monster>>,
-        )>,
-        health_container_query: Query<&Children, With<HealthBarContainer>>,
-        mut health_bar_query: Query<&mut Sprite, With<HealthBar>>,
-        asset_server: Res<AssetServer>,
-        mut commands: Commands,
-    ) {
-        if fight_state.current_turn == Turn::Enemy {
-            // First, collect all living monsters and their damage
-            let monster_attacks: Vec<f32> = query_set
-                .p1()
-                .iter()
-                .filter(|(health, _)| health.current > 0.0)
-                .map(|(_, damage)| damage.0)
-                .collect();
-
-            // Then apply damage to the player
-            if let Ok((mut character_health, children)) = query_set.p0().get_single_mut() {
-                for damage in monster_attacks {
-                    character_health.current = (character_health.current - damage).max(0.0);
-                    println!(
-                        "Player health: {}/{}",
-                        character_health.current, character_health.maximum
-                    );
-
-                    // Health bar update logic using nested queries
-                    for child in children.iter() {
-                        if let Ok(container_children) = health_container_query.get(*child) {
-                            for health_bar_entity in container_children.iter() {
-                                if let Ok(mut bar_sprite) =
-                                    health_bar_query.get_mut(*health_bar_entity)
-                                {
-                                    let bar_width = 150.0;
-                                    let health_percentage =
-                                        character_health.current / character_health.maximum;
-
-                                    bar_sprite.custom_size = Some(Vec2::new(
-                                        bar_width * health_percentage,
-                                        bar_sprite.custom_size.unwrap_or(Vec2::ZERO).y,
-                                    ));
-
-                                    bar_sprite.color = if health_percentage > 0.5 {
-                                        Color::srgb(0.0, 1.0, 0.0)
-                                    } else if health_percentage > 0.25 {
-                                        Color::srgb(1.0, 0.65, 0.0)
-                                    } else {
-                                        Color::srgb(1.0, 0.0, 0.0)
-                                    };
-                                }
-                            }
-                        }
-                    }
-
-                    spawn_damage_text(&mut commands, damage, &asset_server);
-
-                    // Check for player death
-                    if character_health.current <= 0.0 {
-                        spawn_death_screen(&mut commands, &asset_server);
-                    }
-                }
-
-                // Switch back to player turn
-                fight_state.current_turn = Turn::Player;
-            }
-        }
-    }
-
-    // Add a component for the damage text effect
-    #[derive(Component)]
-    struct DamageText {
-        timer: Timer,
-    }
-
-    // The spawn_damage_text and animate_damage_text functions remain the same
-    fn spawn_damage_text(commands: &mut Commands, damage: f32, asset_server: &Res<AssetServer>) {
-        let mut color = Color::srgb(1.0, 0.0, 0.0);
-
-        commands.spawn((
-            Text2dBundle {
-                text: Text::from_section(
-                    format!("-{}", damage),
-                    TextStyle {
-                        font_size: 30.0,
-                        color,
-                        ..default()
-                    },
-                ),
-                transform: Transform::from_xyz(0.0, 0.0, 10.0),
-                ..default()
-            },
-            DamageText {
-                timer: Timer::from_seconds(1.0, TimerMode::Once),
-            },
-        ));
-    }
-
-    fn animate_damage_text(
-        mut commands: Commands,
-        time: Res<Time>,
-        mut query: Query<(Entity, &mut Transform, &mut Text, &mut DamageText)>,
-    ) {
-        for (entity, mut transform, mut text, mut damage_text) in query.iter_mut() {
-            damage_text.timer.tick(time.delta());
-
-            // Move the text upward
-            transform.translation.y += 100.0 * time.delta_seconds();
-
-            // Fade out the text
-            let alpha =
-                1.0 - damage_text.timer.elapsed_secs() / damage_text.timer.duration().as_secs_f32();
-
-            // Remove the text when the timer is finished
-            if damage_text.timer.finished() {
-                commands.entity(entity).despawn();
-            }
-        }
-    }
-    fn handle_end_turn_button(
-        mut interaction_query: Query<
-            (&Interaction, &mut BackgroundColor),
-            (Changed<Interaction>, With<EndTurnButton>),
-        >,
-        mut fight_state: ResMut<FightState>,
-        mut commands: Commands,
-        asset_server: Res<AssetServer>,
-        turn_state: Res<TurnState>,
-    ) {
-        for (interaction, mut color) in &mut interaction_query {
-            match *interaction {
-                Interaction::Pressed => {
-                    if fight_state.current_turn == Turn::Player {
-                        // Add air cards before changing turn
-                        for _ in 0..turn_state.pending_air_cards {
-                            spawn_card(&mut commands, CardType::Air, &asset_server);
-                        }
-
-                        fight_state.current_turn = Turn::Enemy;
-                        *color = Color::srgb(0.35, 0.35, 0.35).into();
-                    }
-                }
-                Interaction::Hovered => {
-                    if fight_state.current_turn == Turn::Player {
-                        *color = Color::srgb(0.25, 0.25, 0.25).into();
-                    }
-                }
-                Interaction::None => {
-                    *color = Color::srgb(0.15, 0.15, 0.15).into();
-                }
-            }
-        }
-    }
-
-    // Add this system to update the button's appearance based on turn state
-    fn update_end_turn_button(
-        fight_state: Res<FightState>,
-        mut button_query: Query<&mut BackgroundColor, With<EndTurnButton>>,
-        mut text_query: Query<&mut Text, With<ButtonText>>,
-    ) {
-        if let Ok(mut color) = button_query.get_single_mut() {
-            if fight_state.current_turn == Turn::Player {
-                *color = Color::srgb(0.15, 0.15, 0.15).into();
-            } else {
-                *color = Color::srgb(0.5, 0.5, 0.5).into();
-            }
-        }
-
-        if let Ok(mut text) = text_query.get_single_mut() {
-            if fight_state.current_turn == Turn::Player {
-                text.sections[0].value = "End Turn".to_string();
-            } else {
-                text.sections[0].value = "Enemy Turn".to_string();
-            }
-        }
-    }
-    // Update the chapter1_plugin to include debug system
-    pub fn chapter2_plugin(app: &mut App) {
-        app.init_resource::<FightState>()
-            .init_resource::<TurnState>() // This line was already correct
-            .add_systems(OnEnter(GameState::Chapter2), (chapter1_setup,))
-            .add_systems(
-                Update,
-                (
-                    animate_sprite,
-                    update_card_hover,
-                    handle_card_click,
-                    process_turn,
-                    update_health_bars,
-                    handle_end_turn_button,
-                    update_end_turn_button,
-                    animate_damage_text,
-                    update_death_screen,
-                    process_pending_cards,
-                    update_turn_state,
-                    check_victory_condition, // Add this
-                    update_victory_screen,
-                    //debug_turn_state,
-                )
-                    .chain()
-                    .run_if(in_state(GameState::Chapter2)),
-            )
-            .add_systems(
-                OnExit(GameState::Chapter2),
-                super::despawn_screen::<OnChapterOneScreen>,
-            );
-    }
-
-    #[derive(Component)]
-    struct PendingCards {
-        card_type: CardType,
-        amount: i32,
-    }
-
-    fn process_pending_cards(
-        mut commands: Commands,
-        pending_query: Query<(Entity, &PendingCards)>,
-        mut turn_state: ResMut<TurnState>,
-        asset_server: Res<AssetServer>,
-    ) {
-        for (entity, pending) in pending_query.iter() {
-            for _ in 0..pending.amount {
-                spawn_card(&mut commands, pending.card_type, &asset_server);
-            }
-            commands.entity(entity).despawn();
-        }
-    }
-
-    fn spawn_card(commands: &mut Commands, card_type: CardType, asset_server: &Res<AssetServer>) {
-        let texture = match card_type {
-            CardType::Fire => asset_server.load("textures/Game Icons/Fire.png"),
-            CardType::Ice => asset_server.load("textures/Game Icons/Frost.png"),
-            CardType::Air => asset_server.load("textures/Game Icons/air.png"),
-            CardType::Earth => asset_server.load("textures/Game Icons/Earth.png"),
-            CardType::Crystal => asset_server.load("textures/Game Icons/Crystal.png"),
-        };
-
-        commands.spawn((
-            ImageBundle {
-                style: Style {
-                    width: Val::Px(180.0),
-                    height: Val::Px(250.0),
-                    margin: UiRect::horizontal(Val::Px(10.0)),
-                    ..default()
-                },
-                image: UiImage::new(texture),
-                background_color: Color::WHITE.into(),
-                ..default()
-            },
-            Interaction::None,
-            Card,
-            card_type,
-            OriginalPosition(Vec2::new(0.0, 0.0)), // Position will need to be adjusted
-            OnChapterOneScreen,
-        ));
-    }
-
-    fn update_turn_state(mut fight_state: ResMut<FightState>, mut turn_state: ResMut<TurnState>) {
-        // if fight_state.current_turn == Turn::Player {
-        //     turn_state.cards_played_this_turn.clear();
-        //     turn_state.crystal_power += 1;
-        //     turn_state.turn_count += 1;
-        //     turn_state.pending_air_cards = 0; // Reset pending air cards
-        // }
-    }
-
-    fn chapter1_setup(
-        mut commands: Commands,
-        asset_server: Res<AssetServer>,
-        mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
-        windows: Query<&Window>,
-    ) {
-        commands.insert_resource(TurnState {
-            first_card_played: true,
-            cards_played_this_turn: Vec::new(),
-            crystal_power: 0,
-            turn_count: 0,
-            pending_air_cards: 0,
-        });
-        let window = windows.single();
-
-        // Calculate positions
-        let char_x = window.width() * -0.25;
-        let char_y = window.height() * -0.75;
-
-        // Load textures
-        let texture_handle: Handle<Image> = asset_server.load("textures/intro_game_sprite.png");
-        let fire_card_texture: Handle<Image> = asset_server.load("textures/Game Icons/Fire.png");
-        let ice_card_texture: Handle<Image> = asset_server.load("textures/Game Icons/Frost.png");
-        let air_card_texture: Handle<Image> = asset_server.load("textures/Game Icons/air.png");
-        let earth_card_texture: Handle<Image> = asset_server.load("textures/Game Icons/Earth.png");
-        let crystal_card_texture: Handle<Image> =
-            asset_server.load("textures/Game Icons/Crystal.png");
-        let forest: Handle<Image> = asset_server.load("textures/2.png");
-
-        let side_character_texture = asset_server.load("textures/character.png");
-        let monster_texture: Handle<Image> = asset_server.load("textures/knight.png");
-        let monster_texture_2 = asset_server.load("textures/knight.png");
-
-        let layout = TextureAtlasLayout::from_grid(UVec2::new(576, 324), 5, 64, None, None);
-        let atlas_layout = atlas_layouts.add(layout);
-
-        // Spawn main container
-        commands
-            .spawn((
-                NodeBundle {
-                    style: Style {
-                        width: Val::Percent(100.0),
-                        height: Val::Percent(100.0),
-                        flex_direction: FlexDirection::Column,
-                        align_items: AlignItems::Center,
-                        justify_content: JustifyContent::SpaceBetween,
-                        ..default()
-                    },
-                    ..default()
-                },
-                OnChapterOneScreen,
-            ))
-            // .with_children(|parent| {
-            //     // Background animation (same as before)
-            //     parent
-            //         .spawn(NodeBundle {
-            //             style: Style {
-            //                 width: Val::Vw(100.0),
-            //                 height: Val::Vh(100.0),
-            //                 align_items: AlignItems::Center,
-            //                 justify_content: JustifyContent::Center,
-            //                 ..default()
-            //             },
-            //             ..default()
-            //         })
-            //         .with_children(|parent| {
-            //             parent.spawn((
-            //                 SpriteSheetBundle {
-            //                     texture: texture_handle,
-            //                     atlas: TextureAtlas {
-            //                         layout: atlas_layout,
-            //                         index: 0,
-            //                     },
-            //                     transform: Transform::from_xyz(
-            //                         -window.width() / 2.0,
-            //                         -window.height() / 2.0,
-            //                         1.0,
-            //                     ),
-            //                     sprite: Sprite {
-            //                         custom_size: Some(Vec2::new(1920.0, 1080.0)),
-            //                         anchor: bevy::sprite::Anchor::Center,
-            //                         ..default()
-            //                     },
-            //                     ..default()
-            //                 },
-            //                 AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)),
-            //                 AnimationIndices {
-            //                     first: 0,
-            //                     last: 320,
-            //                 },
-            //             ));
-            //         });
-            .with_children(|parent| {
-                parent
-                    .spawn(NodeBundle {
-                        style: Style {
-                            width: Val::Vw(100.0),
-                            height: Val::Vh(100.0),
-                            align_items: AlignItems::Center,
-                            justify_content: JustifyContent::Center,
-                            ..default()
-                        },
-                        ..default()
-                    })
-                    .with_children(|parent| {
-                        parent.spawn(SpriteBundle {
-                            texture: forest,
-                            transform: Transform::from_xyz(
-                                -window.width() / 2.0,
-                                -window.height() / 2.0,
-                                1.0,
-                            ),
-                            sprite: Sprite {
-                                custom_size: Some(Vec2::new(1920.0, 1080.0)),
-                                anchor: bevy::sprite::Anchor::Center,
-                                ..default()
-                            },
-                            ..default()
-                        });
-                    });
-
-                // Side character with health
-                parent
-                    .spawn((
-                        SpriteBundle {
-                            texture: side_character_texture,
-                            transform: Transform::from_xyz(
-                                -window.width() / 2.0 + char_x,
-                                char_y,
-                                2.0,
-                            ),
-                            sprite: Sprite {
-                                custom_size: Some(Vec2::new(400.0, 400.0)),
-                                anchor: bevy::sprite::Anchor::Center,
-                                ..default()
-                            },
-                            ..default()
-                        },
-                        SideCharacter,
-                        Health {
-                            current: 100.0,
-                            maximum: 100.0,
-                        },
-                    ))
-                    .with_children(|monster| {
-                        // Health bar background
-                        monster
-                            .spawn((
-                                SpriteBundle {
-                                    sprite: Sprite {
-                                        color: Color::srgb(0.2, 0.2, 0.2),
-                                        custom_size: Some(Vec2::new(150.0, 10.0)),
-                                        ..default()
-                                    },
-                                    transform: Transform::from_xyz(
-                                        0.0,    // Centered horizontally relative to parent
-                                        -175.0, // Below sprite with 20px padding
-                                        0.1,
-                                    ),
-                                    ..default()
-                                },
-                                HealthBarContainer,
-                                OnChapterOneScreen,
-                            ))
-                            .with_children(|container| {
-                                // Actual health bar
-                                container.spawn((
-                                    SpriteBundle {
-                                        sprite: Sprite {
-                                            color: Color::srgb(0.0, 1.0, 0.0),
-                                            custom_size: Some(Vec2::new(150.0, 10.0)),
-                                            anchor: bevy::sprite::Anchor::CenterLeft,
-                                            ..default()
-                                        },
-                                        transform: Transform::from_xyz(-75.0, 0.0, 0.2),
-                                        ..default()
-                                    },
-                                    HealthBar,
-                                ));
-                            });
-                    });
-                let monster1_damage = 25.0;
-                let monster2_damage = 10.0;
-                // Monster 1 with health
-                parent
-                    .spawn((
-                        SpriteBundle {
-                            texture: monster_texture,
-                            transform: Transform::from_xyz(
-                                char_x + window.width() / 8.0,
-                                char_y,
-                                2.0,
-                            ),
-                            sprite: Sprite {
-                                custom_size: Some(Vec2::new(400.0, 400.0)),
-                                anchor: bevy::sprite::Anchor::Center,
-                                ..default()
-                            },
-                            ..default()
-                        },
-                        Monster,
-                        Health {
-                            current: 21.0,
-                            maximum: 21.0,
-                        },
-                        Damage(monster1_damage), // This monster deals 15 damage
-                    ))
-                    .with_children(|monster| {
-                        // Spawn the black background sprite
-                        monster.spawn(SpriteBundle {
-                            sprite: Sprite {
-                                color: Color::BLACK,
-                                custom_size: Some(Vec2::new(50.0, 30.0)), // Adjust size as needed
-                                ..default()
-                            },
-                            transform: Transform::from_xyz(0.0, 120.0, 0.0),
-                            ..default()
-                        });
-                        // Spawn damage text above monster
-                        monster.spawn((
-                            Text2dBundle {
-                                text: Text::from_section(
-                                    format!("{}", monster1_damage),
-                                    TextStyle {
-                                        font_size: 24.0,
-                                        color: Color::rgb(1.0, 0.0, 0.0),
-                                        ..default()
-                                    },
-                                ),
-                                transform: Transform::from_xyz(0.0, 120.0, 0.1), // Position above monster
-                                ..default()
-                            },
-                            DamageDisplay,
-                        ));
-                        // Health bar background
-                        monster
-                            .spawn((
-                                SpriteBundle {
-                                    sprite: Sprite {
-                                        color: Color::srgb(0.2, 0.2, 0.2),
-                                        custom_size: Some(Vec2::new(150.0, 10.0)),
-                                        ..default()
-                                    },
-                                    transform: Transform::from_xyz(
-                                        0.0,    // Centered horizontally relative to parent
-                                        -170.0, // Below sprite with 20px padding
-                                        0.1,
-                                    ),
-                                    ..default()
-                                },
-                                HealthBarContainer,
-                                OnChapterOneScreen,
-                            ))
-                            .with_children(|container| {
-                                // Actual health bar
-                                container.spawn((
-                                    SpriteBundle {
-                                        sprite: Sprite {
-                                            color: Color::srgb(0.0, 1.0, 0.0),
-                                            custom_size: Some(Vec2::new(150.0, 10.0)),
-                                            anchor: bevy::sprite::Anchor::CenterLeft,
-                                            ..default()
-                                        },
-                                        transform: Transform::from_xyz(-75.0, 0.0, 0.2),
-                                        ..default()
-                                    },
-                                    HealthBar,
-                                ));
-                            });
-                    });
-                // Monster 2 with healthcurrent:
-                parent
-                    .spawn((
-                        SpriteBundle {
-                            texture: monster_texture_2,
-                            transform: Transform::from_xyz(
-                                char_x - window.width() / 8.0,
-                                char_y,
-                                2.0,
-                            ),
-                            sprite: Sprite {
-                                custom_size: Some(Vec2::new(400.0, 400.0)),
-                                anchor: bevy::sprite::Anchor::Center,
-                                ..default()
-                            },
-                            ..default()
-                        },
-                        Monster,
-                        Health {
-                            current: 21.0,
-                            maximum: 21.0,
-                        },
-                        Damage(monster2_damage), // This monster deals 10 damage
-                    ))
-                    .with_children(|monster| {
-                        // Spawn the black background sprite
-                        monster.spawn(SpriteBundle {
-                            sprite: Sprite {
-                                color: Color::BLACK,
-                                custom_size: Some(Vec2::new(50.0, 30.0)), // Adjust size as needed
-                                ..default()
-                            },
-                            transform: Transform::from_xyz(0.0, 120.0, 0.0),
-                            ..default()
-                        });
-                        // Spawn damage text above monster
-                        monster.spawn((
-                            Text2dBundle {
-                                text: Text::from_section(
-                                    format!("{}", monster2_damage),
-                                    TextStyle {
-                                        font_size: 24.0,
-                                        color: Color::srgb(1.0, 0.0, 0.0),
-                                        ..default()
-                                    },
-                                ),
-                                transform: Transform::from_xyz(0.0, 120.0, 0.1), // Position above monster
-                                ..default()
-                            },
-                            DamageDisplay,
-                        ));
-                        // Health bar background
-                        monster
-                            .spawn((
-                                SpriteBundle {
-                                    sprite: Sprite {
-                                        color: Color::srgb(0.2, 0.2, 0.2),
-                                        custom_size: Some(Vec2::new(150.0, 10.0)),
-                                        ..default()
-                                    },
-                                    transform: Transform::from_xyz(
-                                        0.0,    // Centered horizontally relative to parent
-                                        -170.0, // Below sprite with 20px padding
-                                        0.1,
-                                    ),
-                                    ..default()
-                                },
-                                HealthBarContainer,
-                                OnChapterOneScreen,
-                            ))
-                            .with_children(|container| {
-                                // Actual health bar
-                                container.spawn((
-                                    SpriteBundle {
-                                        sprite: Sprite {
-                                            color: Color::srgb(0.0, 1.0, 0.0),
-                                            custom_size: Some(Vec2::new(150.0, 10.0)),
-                                            anchor: bevy::sprite::Anchor::CenterLeft,
-                                            ..default()
-                                        },
-                                        transform: Transform::from_xyz(-75.0, 0.0, 0.2),
-                                        ..default()
-                                    },
-                                    HealthBar,
-                                ));
-                            });
-                    });
-
-                // Add this to the chapter1_setup function after spawning the cards container
-                parent
-                    .spawn(NodeBundle {
-                        style: Style {
-                            position_type: PositionType::Absolute,
-                            right: Val::Px(20.0),
-                            top: Val::Px(20.0), // Changed from top to bottom
-                            width: Val::Px(150.0),
-                            height: Val::Px(50.0),
-                            align_items: AlignItems::Center,
-                            justify_content: JustifyContent::Center,
-                            ..default()
-                        },
-                        background_color: Color::srgb(0.15, 0.15, 0.15).into(),
-                        ..default()
-                    })
-                    .with_children(|parent| {
-                        parent
-                            .spawn((
-                                ButtonBundle {
-                                    style: Style {
-                                        width: Val::Percent(100.0),
-                                        height: Val::Percent(100.0),
-                                        align_items: AlignItems::Center,
-                                        justify_content: JustifyContent::Center,
-                                        ..default()
-                                    },
-                                    background_color: Color::srgb(0.15, 0.15, 0.15).into(),
-                                    ..default()
-                                },
-                                EndTurnButton,
-                            ))
-                            .with_children(|parent| {
-                                parent.spawn((
-                                    TextBundle::from_section(
-                                        "End Turn",
-                                        TextStyle {
-                                            font_size: 20.0,
-                                            color: Color::WHITE,
-                                            ..default()
-                                        },
-                                    ),
-                                    ButtonText,
-                                ));
-                            });
-                    });
-                // Cards container
-                parent
-                    .spawn(NodeBundle {
-                        style: Style {
-                            width: Val::Percent(100.0),
-                            height: Val::Px(200.0),
-                            position_type: PositionType::Absolute,
-                            top: Val::Px(20.0),
-                            justify_content: JustifyContent::Center,
-                            align_items: AlignItems::Center,
-                            column_gap: Val::Px(20.0),
-                            ..default()
-                        },
-                        ..default()
-                    })
-                    .with_children(|parent| {
-                        // Define card types and their corresponding textures
-                        let cards = vec![
-                            (CardType::Ice, ice_card_texture.clone()),
-                            //(CardType::Air, air_card_texture.clone()),
-                            (CardType::Earth, earth_card_texture.clone()),
-                            (CardType::Crystal, crystal_card_texture.clone()),
-                        ];
-
-                        // Spawn three cards
-                        for (i, (card_type, card_texture)) in cards.into_iter().enumerate() {
-                            // Changed to into_iter()
-                            let x_position = (i as f32 - 1.0) * 220.0;
-
-                            parent.spawn((
-                                ImageBundle {
-                                    style: Style {
-                                        width: Val::Px(180.0),
-                                        height: Val::Px(250.0),
-                                        margin: UiRect::horizontal(Val::Px(10.0)),
-                                        ..default()
-                                    },
-                                    image: UiImage::new(card_texture),
-                                    background_color: Color::WHITE.into(),
-                                    transform: Transform::from_xyz(x_position, 0.0, 0.0),
-                                    ..default()
-                                },
-                                Interaction::None,
-                                Card,
-                                card_type, // No longer a reference
-                                OriginalPosition(Vec2::new(x_position, 0.0)),
-                            ));
-                        }
-                    });
-            });
-    }
-
-    fn animate_sprite(
-        time: Res<Time>,
-        mut query: Query<(&mut TextureAtlas, &mut AnimationTimer, &AnimationIndices)>,
-    ) {
-        for (mut atlas, mut timer, indices) in &mut query {
-            timer.0.tick(time.delta());
-            if timer.0.just_finished() {
-                atlas.index = if atlas.index == indices.last {
-                    indices.first
-                } else {
-                    atlas.index + 1
-                };
-            }
-        }
-    }
-
-    // Add these new components and structs in the chapter1 mod
-    #[derive(Component)]
-    struct VictoryScreen;
-
-    #[derive(Component)]
-    struct VictoryText;
-
-    #[derive(Component)]
-    struct ReturnToMenuVictoryTimer {
-        timer: Timer,
-    }
-
-    fn spawn_victory_screen(commands: &mut Commands, asset_server: &AssetServer) {
-        commands
-            .spawn((
-                NodeBundle {
-                    style: Style {
-                        width: Val::Percent(100.0),
-                        height: Val::Percent(100.0),
-                        position_type: PositionType::Absolute,
-                        align_items: AlignItems::Center,
-                        justify_content: JustifyContent::Center,
-                        flex_direction: FlexDirection::Column,
-                        ..default()
-                    },
-                    background_color: BackgroundColor(Color::rgba(0.0, 0.0, 0.0, 0.0)),
-                    ..default()
-                },
-                VictoryScreen,
-                FadeInEffect {
-                    timer: Timer::from_seconds(2.0, TimerMode::Once),
-                },
-                ReturnToMenuVictoryTimer {
-                    timer: Timer::from_seconds(5.0, TimerMode::Once),
-                },
-            ))
-            .with_children(|parent| {
-                parent.spawn((
-                    TextBundle::from_section(
-                        "VICTORY!",
-                        TextStyle {
-                            font_size: 120.0,
-                            color: Color::rgba(0.0, 0.8, 0.0, 0.0), // Start transparent, but green
-                            ..default()
-                        },
-                    ),
-                    VictoryText,
-                    FadeInEffect {
-                        timer: Timer::from_seconds(2.0, TimerMode::Once),
-                    },
-                ));
-            });
-    }
-
-    fn update_victory_screen(
-        mut commands: Commands,
-        time: Res<Time>,
-        mut query: Query<
-            (
-                Entity,
-                &mut BackgroundColor,
-                &mut FadeInEffect,
-                &mut ReturnToMenuVictoryTimer,
-            ),
-            With<VictoryScreen>,
-        >,
-        mut text_query: Query<&mut Text, With<VictoryText>>,
-        mut game_state: ResMut<NextState<GameState>>,
-        mut app_exit_events: EventWriter<AppExit>,
-    ) {
-        for (entity, mut bg_color, mut fade, mut return_timer) in query.iter_mut() {
-            fade.timer.tick(time.delta());
-            let alpha = fade.timer.fraction();
-            bg_color.0 = Color::rgba(0.0, 0.0, 0.0, alpha * 0.7);
-
-            if let Ok(mut text) = text_query.get_single_mut() {
-                text.sections[0].style.color = Color::rgba(0.0, 0.8, 0.0, alpha);
-            }
-
-            return_timer.timer.tick(time.delta());
-            if return_timer.timer.finished() {
-                game_state.set(GameState::Game3);
-                commands.entity(entity).despawn_recursive(); // Clean up victory screen
-                                                             //app_exit_events.send(AppExit::Success);
-            }
-        }
-    }
-
-    fn check_victory_condition(
-        monster_query: Query<&Health, With<Monster>>,
-        victory_screen_query: Query<(), With<VictoryScreen>>,
-        mut commands: Commands,
-        asset_server: Res<AssetServer>,
-    ) {
-        if victory_screen_query.is_empty() {
-            // Only check if victory screen isn't already shown
-            let all_monsters_dead = monster_query.iter().all(|health| health.current <= 0.0);
-
-            if all_monsters_dead {
-                spawn_victory_screen(&mut commands, &asset_server);
-            }
-        }
-    }
-}
-
-mod chapter3 {
-    use super::GameState;
-    use bevy::app::AppExit;
-    use bevy::ecs::system::ParamSet;
-    use bevy::prelude::*;
-
-    #[derive(Component, Copy, Clone, Debug, PartialEq)]
-    enum CardType {
-        Fire,
-        Ice,
-        Air,
-        Earth,
-        Crystal,
-        // Add other types as needed
-    }
-    // Components
-    #[derive(Component)]
-    struct AnimationTimer(Timer);
-
-    #[derive(Component)]
-    struct AnimationIndices {
-        first: usize,
-        last: usize,
-    }
-
-    #[derive(Component)]
-    struct Card;
-
-    #[derive(Component)]
-    struct OriginalPosition(Vec2);
-
-    #[derive(Component)]
-    struct OnChapterOneScreen;
-
-    #[derive(Component)]
-    struct SideCharacter;
-
-    #[derive(Component)]
-    struct Monster;
-
-    #[derive(Component)]
-    struct Health {
-        current: f32,
-        maximum: f32,
-    }
-
-    // Add this to your existing components if not already present
-    #[derive(Component)]
-    struct HealthBarContainer;
-
-    #[derive(Component)]
-    struct HealthBar;
-
-    // Add these new components in the chapter1 mod
-    #[derive(Component)]
-    struct EndTurnButton;
-
-    #[derive(Component)]
-    struct ButtonText;
-
-    #[derive(Component)]
-    struct Damage(f32);
-
-    #[derive(Component)]
-    struct DeathScreen;
-
-    #[derive(Component)]
-    struct DeathText;
-
-    #[derive(Component)]
-    struct FadeInEffect {
-        timer: Timer,
-    }
-
-    #[derive(Component)]
-    struct ReturnToMenuTimer {
-        timer: Timer,
-    }
-
-    #[derive(Component)]
-    struct DamageDisplay;
-
-    #[derive(Resource, Default)]
-    struct PendingAirCards(i32);
-
-    fn spawn_death_screen(commands: &mut Commands, asset_server: &AssetServer) {
-        // Main container
-        commands
-            .spawn((
-                NodeBundle {
-                    style: Style {
-                        width: Val::Percent(100.0),
-                        height: Val::Percent(100.0),
-                        position_type: PositionType::Absolute,
-                        align_items: AlignItems::Center,
-                        justify_content: JustifyContent::Center,
-                        flex_direction: FlexDirection::Column,
-                        ..default()
-                    },
-                    background_color: BackgroundColor(Color::rgba(0.0, 0.0, 0.0, 0.0)),
-                    ..default()
-                },
-                DeathScreen,
-                FadeInEffect {
-                    timer: Timer::from_seconds(2.0, TimerMode::Once),
-                },
-                // Add a timer to return to menu after 5 seconds
-                ReturnToMenuTimer {
-                    timer: Timer::from_seconds(5.0, TimerMode::Once),
-                },
-            ))
-            .with_children(|parent| {
-                // "YOU DIED" text
-                parent.spawn((
-                    TextBundle::from_section(
-                        "YOU DIED",
-                        TextStyle {
-                            font_size: 120.0,
-                            color: Color::rgba(0.8, 0.0, 0.0, 0.0), // Start transparent
-                            ..default()
-                        },
-                    ),
-                    DeathText,
-                    FadeInEffect {
-                        timer: Timer::from_seconds(2.0, TimerMode::Once),
-                    },
-                ));
-            });
-    }
-
-    // Update the death screen system to handle both fade-in and menu transition
-    fn update_death_screen(
-        mut commands: Commands,
-        time: Res<Time>,
-        mut query: Query<
-            (
-                Entity,
-                &mut BackgroundColor,
-                &mut FadeInEffect,
-                &mut ReturnToMenuTimer,
-            ),
-            With<DeathScreen>,
-        >,
-        mut text_query: Query<&mut Text, With<DeathText>>,
-        mut game_state: ResMut<NextState<GameState>>,
-        mut app_exit_events: EventWriter<AppExit>,
-    ) {
-        for (entity, mut bg_color, mut fade, mut return_timer) in query.iter_mut() {
-            // Update fade effect
-            fade.timer.tick(time.delta());
-            let alpha = fade.timer.fraction();
-            bg_color.0 = Color::rgba(0.0, 0.0, 0.0, alpha * 0.7);
-
-            // Update text color
-            if let Ok(mut text) = text_query.get_single_mut() {
-                text.sections[0].style.color = Color::rgba(0.8, 0.0, 0.0, alpha);
-            }
-
-            // Update return timer
-            return_timer.timer.tick(time.delta());
-            if return_timer.timer.finished() {
-                app_exit_events.send(AppExit::Success);
-            }
-        }
-    }
-    // Modify TurnState to include pending air cards
-    #[derive(Resource)]
-    struct TurnState {
-        first_card_played: bool,
-        cards_played_this_turn: Vec<CardType>,
-        crystal_power: i32,
-        turn_count: i32,
-        pending_air_cards: i32,
-    }
-
-    impl Default for TurnState {
-        fn default() -> Self {
-            Self {
-                first_card_played: true,
-                cards_played_this_turn: Vec::new(),
-                crystal_power: 0,
-                turn_count: 0,
-                pending_air_cards: 0, // Initialize pending_air_cards to 0
-            }
-        }
-    }
-
-    // Constants for base damage values
-    const FIRE_BASE_DAMAGE: f32 = 8.0;
-    const FIRE_FIRST_CARD_BONUS: f32 = 7.0;
-    const ICE_BASE_DAMAGE: f32 = 6.0;
-    const CRYSTAL_BASE_DAMAGE: f32 = 4.0;
-    const AIR_BASE_DAMAGE: f32 = 2.0;
-    const EARTH_BASE_DAMAGE: f32 = 5.0;
-
-    fn update_health_bars(
-        query: Query<(&Health, &Children), Without<HealthBar>>,
-        mut health_bar_query: Query<&mut Sprite, With<HealthBar>>,
-    ) {
-        for (health, children) in query.iter() {
-            for child in children.iter() {
-                if let Ok(mut bar_sprite) = health_bar_query.get_mut(*child) {
-                    // Update health bar width based on current health
-                    let bar_width = 100.0;
-                    let health_percentage = health.current / health.maximum;
-
-                    bar_sprite.custom_size = Some(Vec2::new(
-                        bar_width * health_percentage,
-                        bar_sprite.custom_size.unwrap_or(Vec2::ZERO).y,
-                    ));
-
-                    // Update color based on health percentage
-                    bar_sprite.color = if health_percentage > 0.5 {
-                        Color::srgb(0.0, 1.0, 0.0) // Green: rgb(0, 255, 0)
-                    } else if health_percentage > 0.25 {
-                        Color::srgb(1.0, 0.65, 0.0) // Orange: rgb(255, 165, 0)
-                    } else {
-                        Color::srgb(1.0, 0.0, 0.0) // Red: rgb(255, 0, 0)
-                    };
-                }
-            }
-        }
-    }
-    #[derive(Resource)]
-    struct FightState {
-        current_turn: Turn,
-        selected_card: Option<usize>,
-    }
-
-    #[derive(PartialEq)]
-    enum Turn {
-        Player,
-        Enemy,
-    }
-
-    impl Default for FightState {
-        fn default() -> Self {
-            Self {
-                current_turn: Turn::Player,
-                selected_card: None,
-            }
-        }
-    }
-
-    // Update the card hover system to use FightState
-    fn update_card_hover(
-        mut card_query: Query<
-            (
-                &Interaction,
-                &mut Transform,
-                &OriginalPosition,
-                &mut Style,
-                Entity,
-            ),
-            (With<Card>, Changed<Interaction>),
-        >,
-        mut commands: Commands,
-        fight_state: Res<FightState>,
-    ) {
-        for (interaction, mut transform, original_pos, mut style, entity) in card_query.iter_mut() {
-            match *interaction {
-                Interaction::Hovered => {
-                    if fight_state.current_turn == Turn::Player {
-                        transform.translation.y = original_pos.0.y + 30.0;
-                        style.width = Val::Px(200.0);
-                        style.height = Val::Px(280.0);
-                    }
-                }
-                _ => {
-                    transform.translation.y = original_pos.0.y;
-                    style.width = Val::Px(180.0);
-                    style.height = Val::Px(250.0);
-                }
-            }
-        }
-    }
-
-    fn handle_card_click(
-        mut commands: Commands,
-        mut card_query: Query<
-            (&Interaction, Entity, &CardType),
-            (Changed<Interaction>, With<Card>),
-        >,
-        cards_in_hand: Query<Entity, With<Card>>, // Query to count cards
-        mut fight_state: ResMut<FightState>,
-        mut turn_state: ResMut<TurnState>,
-        mut monster_query: Query<(Entity, &mut Health, &Children), With<Monster>>,
-        health_container_query: Query<&Children, With<HealthBarContainer>>,
-        mut health_bar_query: Query<&mut Sprite, With<HealthBar>>,
-        asset_server: Res<AssetServer>,
-    ) {
-        if fight_state.current_turn != Turn::Player {
-            return;
-        }
-
-        for (interaction, card_entity, card_type) in card_query.iter() {
-            if *interaction == Interaction::Pressed {
-                println!("First card played status: {}", turn_state.first_card_played);
-
-                // Calculate damage based on whether this is the first card
-                let is_first = turn_state.first_card_played;
-                let cards_in_hand_count = cards_in_hand.iter().count() as f32; // Get count here
-
-                let damage = if *card_type == CardType::Fire && is_first {
-                    println!("Fire card played as first card! Enhanced damage!");
-                    FIRE_BASE_DAMAGE + FIRE_FIRST_CARD_BONUS
-                } else {
-                    match card_type {
-                        CardType::Fire => {
-                            println!("Fire card played but not first");
-                            FIRE_BASE_DAMAGE
-                        }
-                        CardType::Ice => {
-                            let mut damage = ICE_BASE_DAMAGE;
-
-                            if let Some(last_card) = turn_state.cards_played_this_turn.last() {
-                                if matches!(last_card, CardType::Fire) {
-                                    damage *= 2.0;
-                                }
-                            }
-
-                            if turn_state
-                                .cards_played_this_turn
-                                .iter()
-                                .any(|c| matches!(c, CardType::Earth))
-                            {
-                                damage = 0.0;
-                            }
-
-                            damage
-                        }
-                        CardType::Crystal => {
-                            let effects_bonus =
-                                (turn_state.cards_played_this_turn.len() as f32) * 2.0;
-                            let turn_bonus = turn_state.crystal_power as f32;
-                            CRYSTAL_BASE_DAMAGE + effects_bonus + turn_bonus
-                        }
-                        CardType::Air => AIR_BASE_DAMAGE,
-                        CardType::Earth => {
-                            let turn_bonus = turn_state.turn_count as f32;
-                            EARTH_BASE_DAMAGE + cards_in_hand_count + turn_bonus
-                            // Use the count here
-                        }
-                    }
-                };
-
-                // Deal damage
-                for (entity, mut monster_health, children) in monster_query.iter_mut() {
-                    monster_health.current = (monster_health.current - damage).max(0.0);
-                    println!("Dealing {} damage. First card: {}", damage, is_first);
-                    spawn_damage_text(&mut commands, damage, &asset_server);
-                    // Update monster's health bar
-                    for child in children.iter() {
-                        if let Ok(container_children) = health_container_query.get(*child) {
-                            for health_bar_entity in container_children.iter() {
-                                if let Ok(mut bar_sprite) =
-                                    health_bar_query.get_mut(*health_bar_entity)
-                                {
-                                    let bar_width = 150.0; // Match the width set in chapter1_setup
-                                    let health_percentage =
-                                        monster_health.current / monster_health.maximum;
-
-                                    bar_sprite.custom_size = Some(Vec2::new(
-                                        bar_width * health_percentage,
-                                        bar_sprite.custom_size.unwrap_or(Vec2::ZERO).y,
-                                    ));
-
-                                    // Update color based on health percentage
-                                    bar_sprite.color = if health_percentage > 0.5 {
-                                        Color::srgb(0.0, 1.0, 0.0) // Green
-                                    } else if health_percentage > 0.25 {
-                                        Color::srgb(1.0, 0.65, 0.0) // Orange
-                                    } else {
-                                        Color::srgb(1.0, 0.0, 0.0) // Red
-                                    };
-                                }
-                            }
-                        }
-                    }
-
-                    // If monster dies, despawn it
-                    if monster_health.current <= 0.0 {
-                        commands.entity(entity).despawn_recursive();
-                    }
-                }
-
-                // Handle special card effects and cleanup
-                if matches!(card_type, CardType::Air) {
-                    turn_state.pending_air_cards += 2;
-                }
-
-                // Update turn state BEFORE destroying the card
-                turn_state.cards_played_this_turn.push(*card_type);
-                turn_state.first_card_played = false;
-                println!("Set first_card_played to false");
-
-                // Destroy the played card
-                commands.entity(card_entity).despawn_recursive();
-
-                break;
-            }
-        }
-    }
-
-    // Add this system to help debug the turn state
-    // Add this system to help debug the turn state
-    fn debug_turn_state(turn_state: Res<TurnState>, fight_state: Res<FightState>) {
-        println!(
-            "Turn State - First card: {}, Cards played: {}",
-            turn_state.first_card_played,
-            turn_state.cards_played_this_turn.len(),
-        );
-    }
-
-    fn process_turn(
-        mut fight_state: ResMut<FightState>,
-        mut query_set: ParamSet<(
-            Query<(&mut Health, &Children), With<SideCharacter>>,
-            Query<(&Health, &Damage), With<Monster>>,
-        )>,
-        health_container_query: Query<&Children, With<HealthBarContainer>>,
-        mut health_bar_query: Query<&mut Sprite, With<HealthBar>>,
-        asset_server: Res<AssetServer>,
-        mut commands: Commands,
-    ) {
-        if fight_state.current_turn == Turn::Enemy {
-            // First, collect all living monsters and their damage
-            let monster_attacks: Vec<f32> = query_set
-                .p1()
-                .iter()
-                .filter(|(health, _)| health.current > 0.0)
-                .map(|(_, damage)| damage.0)
-                .collect();
-
-            // Then apply damage to the player
-            if let Ok((mut character_health, children)) = query_set.p0().get_single_mut() {
-                for damage in monster_attacks {
-                    character_health.current = (character_health.current - damage).max(0.0);
-                    println!(
-                        "Player health: {}/{}",
-                        character_health.current, character_health.maximum
-                    );
-
-                    // Health bar update logic using nested queries
-                    for child in children.iter() {
-                        if let Ok(container_children) = health_container_query.get(*child) {
-                            for health_bar_entity in container_children.iter() {
-                                if let Ok(mut bar_sprite) =
-                                    health_bar_query.get_mut(*health_bar_entity)
-                                {
-                                    let bar_width = 150.0;
-                                    let health_percentage =
-                                        character_health.current / character_health.maximum;
-
-                                    bar_sprite.custom_size = Some(Vec2::new(
-                                        bar_width * health_percentage,
-                                        bar_sprite.custom_size.unwrap_or(Vec2::ZERO).y,
-                                    ));
-
-                                    bar_sprite.color = if health_percentage > 0.5 {
-                                        Color::srgb(0.0, 1.0, 0.0)
-                                    } else if health_percentage > 0.25 {
-                                        Color::srgb(1.0, 0.65, 0.0)
-                                    } else {
-                                        Color::srgb(1.0, 0.0, 0.0)
-                                    };
-                                }
-                            }
-                        }
-                    }
-
-                    spawn_damage_text(&mut commands, damage, &asset_server);
-
-                    // Check for player death
-                    if character_health.current <= 0.0 {
-                        spawn_death_screen(&mut commands, &asset_server);
-                    }
-                }
-
-                // Switch back to player turn
-                fight_state.current_turn = Turn::Player;
-            }
-        }
-    }
-
-    // Add a component for the damage text effect
-    #[derive(Component)]
-    struct DamageText {
-        timer: Timer,
-    }
-
-    // The spawn_damage_text and animate_damage_text functions remain the same
-    fn spawn_damage_text(commands: &mut Commands, damage: f32, asset_server: &Res<AssetServer>) {
-        let mut color = Color::srgb(1.0, 0.0, 0.0);
-
-        commands.spawn((
-            Text2dBundle {
-                text: Text::from_section(
-                    format!("-{}", damage),
-                    TextStyle {
-                        font_size: 30.0,
-                        color,
-                        ..default()
-                    },
-                ),
-                transform: Transform::from_xyz(0.0, 0.0, 10.0),
-                ..default()
-            },
-            DamageText {
-                timer: Timer::from_seconds(1.0, TimerMode::Once),
-            },
-        ));
-    }
-
-    fn animate_damage_text(
-        mut commands: Commands,
-        time: Res<Time>,
-        mut query: Query<(Entity, &mut Transform, &mut Text, &mut DamageText)>,
-    ) {
-        for (entity, mut transform, mut text, mut damage_text) in query.iter_mut() {
-            damage_text.timer.tick(time.delta());
-
-            // Move the text upward
-            transform.translation.y += 100.0 * time.delta_seconds();
-
-            // Fade out the text
-            let alpha =
-                1.0 - damage_text.timer.elapsed_secs() / damage_text.timer.duration().as_secs_f32();
-
-            // Remove the text when the timer is finished
-            if damage_text.timer.finished() {
-                commands.entity(entity).despawn();
-            }
-        }
-    }
-    fn handle_end_turn_button(
-        mut interaction_query: Query<
-            (&Interaction, &mut BackgroundColor),
-            (Changed<Interaction>, With<EndTurnButton>),
-        >,
-        mut fight_state: ResMut<FightState>,
-        mut commands: Commands,
-        asset_server: Res<AssetServer>,
-        turn_state: Res<TurnState>,
-    ) {
-        for (interaction, mut color) in &mut interaction_query {
-            match *interaction {
-                Interaction::Pressed => {
-                    if fight_state.current_turn == Turn::Player {
-                        // Add air cards before changing turn
-                        for _ in 0..turn_state.pending_air_cards {
-                            spawn_card(&mut commands, CardType::Air, &asset_server);
-                        }
-
-                        fight_state.current_turn = Turn::Enemy;
-                        *color = Color::srgb(0.35, 0.35, 0.35).into();
-                    }
-                }
-                Interaction::Hovered => {
-                    if fight_state.current_turn == Turn::Player {
-                        *color = Color::srgb(0.25, 0.25, 0.25).into();
-                    }
-                }
-                Interaction::None => {
-                    *color = Color::srgb(0.15, 0.15, 0.15).into();
-                }
-            }
-        }
-    }
-
-    // Add this system to update the button's appearance based on turn state
-    fn update_end_turn_button(
-        fight_state: Res<FightState>,
-        mut button_query: Query<&mut BackgroundColor, With<EndTurnButton>>,
-        mut text_query: Query<&mut Text, With<ButtonText>>,
-    ) {
-        if let Ok(mut color) = button_query.get_single_mut() {
-            if fight_state.current_turn == Turn::Player {
-                *color = Color::srgb(0.15, 0.15, 0.15).into();
-            } else {
-                *color = Color::srgb(0.5, 0.5, 0.5).into();
-            }
-        }
-
-        if let Ok(mut text) = text_query.get_single_mut() {
-            if fight_state.current_turn == Turn::Player {
-                text.sections[0].value = "End Turn".to_string();
-            } else {
-                text.sections[0].value = "Enemy Turn".to_string();
-            }
-        }
-    }
-    // Update the chapter1_plugin to include debug system
-    pub fn chapter3_plugin(app: &mut App) {
-        app.init_resource::<FightState>()
-            .init_resource::<TurnState>() // This line was already correct
-            .add_systems(OnEnter(GameState::Chapter3), (chapter1_setup,))
-            .add_systems(
-                Update,
-                (
-                    animate_sprite,
-                    update_card_hover,
-                    handle_card_click,
-                    process_turn,
-                    update_health_bars,
-                    handle_end_turn_button,
-                    update_end_turn_button,
-                    animate_damage_text,
-                    update_death_screen,
-                    process_pending_cards,
-                    update_turn_state,
-                    check_victory_condition, // Add this
-                    update_victory_screen,
-                    //debug_turn_state,
-                )
-                    .chain()
-                    .run_if(in_state(GameState::Chapter3)),
-            )
-            .add_systems(
-                OnExit(GameState::Chapter3),
-                super::despawn_screen::<OnChapterOneScreen>,
-            );
-    }
-
-    #[derive(Component)]
-    struct PendingCards {
-        card_type: CardType,
-        amount: i32,
-    }
-
-    fn process_pending_cards(
-        mut commands: Commands,
-        pending_query: Query<(Entity, &PendingCards)>,
-        mut turn_state: ResMut<TurnState>,
-        asset_server: Res<AssetServer>,
-    ) {
-        for (entity, pending) in pending_query.iter() {
-            for _ in 0..pending.amount {
-                spawn_card(&mut commands, pending.card_type, &asset_server);
-            }
-            commands.entity(entity).despawn();
-        }
-    }
-
-    fn spawn_card(commands: &mut Commands, card_type: CardType, asset_server: &Res<AssetServer>) {
-        let texture = match card_type {
-            CardType::Fire => asset_server.load("textures/Game Icons/Fire.png"),
-            CardType::Ice => asset_server.load("textures/Game Icons/Frost.png"),
-            CardType::Air => asset_server.load("textures/Game Icons/air.png"),
-            CardType::Earth => asset_server.load("textures/Game Icons/Earth.png"),
-            CardType::Crystal => asset_server.load("textures/Game Icons/Crystal.png"),
-        };
-
-        commands.spawn((
-            ImageBundle {
-                style: Style {
-                    width: Val::Px(180.0),
-                    height: Val::Px(250.0),
-                    margin: UiRect::horizontal(Val::Px(10.0)),
-                    ..default()
-                },
-                image: UiImage::new(texture),
-                background_color: Color::WHITE.into(),
-                ..default()
-            },
-            Interaction::None,
-            Card,
-            card_type,
-            OriginalPosition(Vec2::new(0.0, 0.0)), // Position will need to be adjusted
-            OnChapterOneScreen,
-        ));
-    }
-
-    fn update_turn_state(mut fight_state: ResMut<FightState>, mut turn_state: ResMut<TurnState>) {
-        // if fight_state.current_turn == Turn::Player {
-        //     turn_state.cards_played_this_turn.clear();
-        //     turn_state.crystal_power += 1;
-        //     turn_state.turn_count += 1;
-        //     turn_state.pending_air_cards = 0; // Reset pending air cards
-        // }
-    }
-
-    fn chapter1_setup(
-        mut commands: Commands,
-        asset_server: Res<AssetServer>,
-        mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
-        windows: Query<&Window>,
-    ) {
-        commands.insert_resource(TurnState {
-            first_card_played: true,
-            cards_played_this_turn: Vec::new(),
-            crystal_power: 0,
-            turn_count: 0,
-            pending_air_cards: 0,
-        });
-        let window = windows.single();
-
-        // Calculate positions
-        let char_x = window.width() * -0.25;
-        let char_y = window.height() * -0.75;
-
-        // Load textures
-        let texture_handle: Handle<Image> = asset_server.load("textures/intro_game_sprite.png");
-        let fire_card_texture: Handle<Image> = asset_server.load("textures/Game Icons/Fire.png");
-        let ice_card_texture: Handle<Image> = asset_server.load("textures/Game Icons/Frost.png");
-        let air_card_texture: Handle<Image> = asset_server.load("textures/Game Icons/air.png");
-        let earth_card_texture: Handle<Image> = asset_server.load("textures/Game Icons/Earth.png");
-        let crystal_card_texture: Handle<Image> =
-            asset_server.load("textures/Game Icons/Crystal.png");
-        let forest: Handle<Image> = asset_server.load("textures/waterfall.png");
-
-        let side_character_texture = asset_server.load("textures/character.png");
-        let monster_texture: Handle<Image> = asset_server.load("textures/angle.png");
-
-        let layout = TextureAtlasLayout::from_grid(UVec2::new(576, 324), 5, 64, None, None);
-        let atlas_layout = atlas_layouts.add(layout);
-
-        // Spawn main container
-        commands
-            .spawn((
-                NodeBundle {
-                    style: Style {
-                        width: Val::Percent(100.0),
-                        height: Val::Percent(100.0),
-                        flex_direction: FlexDirection::Column,
-                        align_items: AlignItems::Center,
-                        justify_content: JustifyContent::SpaceBetween,
-                        ..default()
-                    },
-                    ..default()
-                },
-                OnChapterOneScreen,
-            ))
-            // .with_children(|parent| {
-            //     // Background animation (same as before)
-            //     parent
-            //         .spawn(NodeBundle {
-            //             style: Style {
-            //                 width: Val::Vw(100.0),
-            //                 height: Val::Vh(100.0),
-            //                 align_items: AlignItems::Center,
-            //                 justify_content: JustifyContent::Center,
-            //                 ..default()
-            //             },
-            //             ..default()
-            //         })
-            //         .with_children(|parent| {
-            //             parent.spawn((
-            //                 SpriteSheetBundle {
-            //                     texture: texture_handle,
-            //                     atlas: TextureAtlas {
-            //                         layout: atlas_layout,
-            //                         index: 0,
-            //                     },
-            //                     transform: Transform::from_xyz(
-            //                         -window.width() / 2.0,
-            //                         -window.height() / 2.0,
-            //                         1.0,
-            //                     ),
-            //                     sprite: Sprite {
-            //                         custom_size: Some(Vec2::new(1920.0, 1080.0)),
-            //                         anchor: bevy::sprite::Anchor::Center,
-            //                         ..default()
-            //                     },
-            //                     ..default()
-            //                 },
-            //                 AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)),
-            //                 AnimationIndices {
-            //                     first: 0,
-            //                     last: 320,
-            //                 },
-            //             ));
-            //         });
-            .with_children(|parent| {
-                parent
-                    .spawn(NodeBundle {
-                        style: Style {
-                            width: Val::Vw(100.0),
-                            height: Val::Vh(100.0),
-                            align_items: AlignItems::Center,
-                            justify_content: JustifyContent::Center,
-                            ..default()
-                        },
-                        ..default()
-                    })
-                    .with_children(|parent| {
-                        parent.spawn(SpriteBundle {
-                            texture: forest,
-                            transform: Transform::from_xyz(
-                                -window.width() / 2.0,
-                                -window.height() / 2.0,
-                                1.0,
-                            ),
-                            sprite: Sprite {
-                                custom_size: Some(Vec2::new(1920.0, 1080.0)),
-                                anchor: bevy::sprite::Anchor::Center,
-                                ..default()
-                            },
-                            ..default()
-                        });
-                    });
-
-                // Side character with health
-                parent
-                    .spawn((
-                        SpriteBundle {
-                            texture: side_character_texture,
-                            transform: Transform::from_xyz(
-                                -window.width() / 2.0 + char_x,
-                                char_y,
-                                2.0,
-                            ),
-                            sprite: Sprite {
-                                custom_size: Some(Vec2::new(400.0, 400.0)),
-                                anchor: bevy::sprite::Anchor::Center,
-                                ..default()
-                            },
-                            ..default()
-                        },
-                        SideCharacter,
-                        Health {
-                            current: 100.0,
-                            maximum: 100.0,
-                        },
-                    ))
-                    .with_children(|monster| {
-                        // Health bar background
-                        monster
-                            .spawn((
-                                SpriteBundle {
-                                    sprite: Sprite {
-                                        color: Color::srgb(0.2, 0.2, 0.2),
-                                        custom_size: Some(Vec2::new(150.0, 10.0)),
-                                        ..default()
-                                    },
-                                    transform: Transform::from_xyz(
-                                        0.0,    // Centered horizontally relative to parent
-                                        -175.0, // Below sprite with 20px padding
-                                        0.1,
-                                    ),
-                                    ..default()
-                                },
-                                HealthBarContainer,
-                                OnChapterOneScreen,
-                            ))
-                            .with_children(|container| {
-                                // Actual health bar
-                                container.spawn((
-                                    SpriteBundle {
-                                        sprite: Sprite {
-                                            color: Color::srgb(0.0, 1.0, 0.0),
-                                            custom_size: Some(Vec2::new(150.0, 10.0)),
-                                            anchor: bevy::sprite::Anchor::CenterLeft,
-                                            ..default()
-                                        },
-                                        transform: Transform::from_xyz(-75.0, 0.0, 0.2),
-                                        ..default()
-                                    },
-                                    HealthBar,
-                                ));
-                            });
-                    });
-                let monster1_damage = 50.0;
-                let monster2_damage = 10.0;
-                // Monster 1 with health
-                parent
-                    .spawn((
-                        SpriteBundle {
-                            texture: monster_texture,
-                            transform: Transform::from_xyz(
-                                char_x,
-                                char_y + window.height() / 16.0,
-                                2.0,
-                            ),
-                            sprite: Sprite {
-                                custom_size: Some(Vec2::new(400.0, 400.0)),
-                                anchor: bevy::sprite::Anchor::Center,
-                                ..default()
-                            },
-                            ..default()
-                        },
-                        Monster,
-                        Health {
-                            current: 44.0,
-                            maximum: 44.0,
-                        },
-                        Damage(monster1_damage), // This monster deals 15 damage
-                    ))
-                    .with_children(|monster| {
-                        // Spawn the black background sprite
-                        monster.spawn(SpriteBundle {
-                            sprite: Sprite {
-                                color: Color::BLACK,
-                                custom_size: Some(Vec2::new(50.0, 30.0)), // Adjust size as needed
-                                ..default()
-                            },
-                            transform: Transform::from_xyz(0.0, 180.0, 0.0),
-                            ..default()
-                        });
-                        // Spawn damage text above monster
-                        monster.spawn((
-                            Text2dBundle {
-                                text: Text::from_section(
-                                    format!("{}", monster1_damage),
-                                    TextStyle {
-                                        font_size: 24.0,
-                                        color: Color::rgb(1.0, 0.0, 0.0),
-                                        ..default()
-                                    },
-                                ),
-                                transform: Transform::from_xyz(0.0, 180.0, 0.1), // Position above monster
-                                ..default()
-                            },
-                            DamageDisplay,
-                        ));
-                        // Health bar background
-                        monster
-                            .spawn((
-                                SpriteBundle {
-                                    sprite: Sprite {
-                                        color: Color::srgb(0.2, 0.2, 0.2),
-                                        custom_size: Some(Vec2::new(150.0, 10.0)),
-                                        ..default()
-                                    },
-                                    transform: Transform::from_xyz(
-                                        0.0,    // Centered horizontally relative to parent
-                                        -215.0, // Below sprite with 20px padding
-                                        0.1,
-                                    ),
-                                    ..default()
-                                },
-                                HealthBarContainer,
-                                OnChapterOneScreen,
-                            ))
-                            .with_children(|container| {
-                                // Actual health bar
-                                container.spawn((
-                                    SpriteBundle {
-                                        sprite: Sprite {
-                                            color: Color::srgb(0.0, 1.0, 0.0),
-                                            custom_size: Some(Vec2::new(150.0, 10.0)),
-                                            anchor: bevy::sprite::Anchor::CenterLeft,
-                                            ..default()
-                                        },
-                                        transform: Transform::from_xyz(-75.0, 0.0, 0.2),
-                                        ..default()
-                                    },
-                                    HealthBar,
-                                ));
-                            });
-                    });
-
-                // Add this to the chapter1_setup function after spawning the cards container
-                parent
-                    .spawn(NodeBundle {
-                        style: Style {
-                            position_type: PositionType::Absolute,
-                            right: Val::Px(20.0),
-                            top: Val::Px(20.0), // Changed from top to bottom
-                            width: Val::Px(150.0),
-                            height: Val::Px(50.0),
-                            align_items: AlignItems::Center,
-                            justify_content: JustifyContent::Center,
-                            ..default()
-                        },
-                        background_color: Color::srgb(0.15, 0.15, 0.15).into(),
-                        ..default()
-                    })
-                    .with_children(|parent| {
-                        parent
-                            .spawn((
-                                ButtonBundle {
-                                    style: Style {
-                                        width: Val::Percent(100.0),
-                                        height: Val::Percent(100.0),
-                                        align_items: AlignItems::Center,
-                                        justify_content: JustifyContent::Center,
-                                        ..default()
-                                    },
-                                    background_color: Color::srgb(0.15, 0.15, 0.15).into(),
-                                    ..default()
-                                },
-                                EndTurnButton,
-                            ))
-                            .with_children(|parent| {
-                                parent.spawn((
-                                    TextBundle::from_section(
-                                        "End Turn",
-                                        TextStyle {
-                                            font_size: 20.0,
-                                            color: Color::WHITE,
-                                            ..default()
-                                        },
-                                    ),
-                                    ButtonText,
-                                ));
-                            });
-                    });
-                // Cards container
-                parent
-                    .spawn(NodeBundle {
-                        style: Style {
-                            width: Val::Percent(100.0),
-                            height: Val::Px(200.0),
-                            position_type: PositionType::Absolute,
-                            top: Val::Px(20.0),
-                            justify_content: JustifyContent::Center,
-                            align_items: AlignItems::Center,
-                            column_gap: Val::Px(20.0),
-                            ..default()
-                        },
-                        ..default()
-                    })
-                    .with_children(|parent| {
-                        // Define card types and their corresponding textures
-                        let cards = vec![
-                            (CardType::Earth, earth_card_texture.clone()),
-                            (CardType::Crystal, crystal_card_texture.clone()),
-                            (CardType::Fire, fire_card_texture.clone()),
-                            (CardType::Ice, ice_card_texture.clone()),
-                            //(CardType::Air, air_card_texture.clone()),
-                        ];
-
-                        // Spawn three cards
-                        for (i, (card_type, card_texture)) in cards.into_iter().enumerate() {
-                            // Changed to into_iter()
-                            let x_position = (i as f32 - 1.0) * 220.0;
-
-                            parent.spawn((
-                                ImageBundle {
-                                    style: Style {
-                                        width: Val::Px(180.0),
-                                        height: Val::Px(250.0),
-                                        margin: UiRect::horizontal(Val::Px(10.0)),
-                                        ..default()
-                                    },
-                                    image: UiImage::new(card_texture),
-                                    background_color: Color::WHITE.into(),
-                                    transform: Transform::from_xyz(x_position, 0.0, 0.0),
-                                    ..default()
-                                },
-                                Interaction::None,
-                                Card,
-                                card_type, // No longer a reference
-                                OriginalPosition(Vec2::new(x_position, 0.0)),
-                            ));
-                        }
-                    });
-            });
-    }
-
-    fn animate_sprite(
-        time: Res<Time>,
-        mut query: Query<(&mut TextureAtlas, &mut AnimationTimer, &AnimationIndices)>,
-    ) {
-        for (mut atlas, mut timer, indices) in &mut query {
-            timer.0.tick(time.delta());
-            if timer.0.just_finished() {
-                atlas.index = if atlas.index == indices.last {
-                    indices.first
-                } else {
-                    atlas.index + 1
-                };
-            }
-        }
-    }
-
-    // Add these new components and structs in the chapter1 mod
-    #[derive(Component)]
-    struct VictoryScreen;
-
-    #[derive(Component)]
-    struct VictoryText;
-
-    #[derive(Component)]
-    struct ReturnToMenuVictoryTimer {
-        timer: Timer,
-    }
-
-    fn spawn_victory_screen(commands: &mut Commands, asset_server: &AssetServer) {
-        commands
-            .spawn((
-                NodeBundle {
-                    style: Style {
-                        width: Val::Percent(100.0),
-                        height: Val::Percent(100.0),
-                        position_type: PositionType::Absolute,
-                        align_items: AlignItems::Center,
-                        justify_content: JustifyContent::Center,
-                        flex_direction: FlexDirection::Column,
-                        ..default()
-                    },
-                    background_color: BackgroundColor(Color::rgba(0.0, 0.0, 0.0, 0.0)),
-                    ..default()
-                },
-                VictoryScreen,
-                FadeInEffect {
-                    timer: Timer::from_seconds(2.0, TimerMode::Once),
-                },
-                ReturnToMenuVictoryTimer {
-                    timer: Timer::from_seconds(5.0, TimerMode::Once),
-                },
-            ))
-            .with_children(|parent| {
-                parent.spawn((
-                    TextBundle::from_section(
-                        "VICTORY!",
-                        TextStyle {
-                            font_size: 120.0,
-                            color: Color::rgba(0.0, 0.8, 0.0, 0.0), // Start transparent, but green
-                            ..default()
-                        },
-                    ),
-                    VictoryText,
-                    FadeInEffect {
-                        timer: Timer::from_seconds(2.0, TimerMode::Once),
-                    },
-                ));
-            });
-    }
-
-    fn update_victory_screen(
-        mut commands: Commands,
-        time: Res<Time>,
-        mut query: Query<
-            (
-                Entity,
-                &mut BackgroundColor,
-                &mut FadeInEffect,
-                &mut ReturnToMenuVictoryTimer,
-            ),
-            With<VictoryScreen>,
-        >,
-        mut text_query: Query<&mut Text, With<VictoryText>>,
-        mut game_state: ResMut<NextState<GameState>>,
-        mut app_exit_events: EventWriter<AppExit>,
-    ) {
-        for (entity, mut bg_color, mut fade, mut return_timer) in query.iter_mut() {
-            fade.timer.tick(time.delta());
-            let alpha = fade.timer.fraction();
-            bg_color.0 = Color::rgba(0.0, 0.0, 0.0, alpha * 0.7);
-
-            if let Ok(mut text) = text_query.get_single_mut() {
-                text.sections[0].style.color = Color::rgba(0.0, 0.8, 0.0, alpha);
-            }
-
-            return_timer.timer.tick(time.delta());
-            if return_timer.timer.finished() {
-                commands.entity(entity).despawn_recursive(); // Clean up victory screen
-                game_state.set(GameState::Game4);
-                // commands.entity(entity).despawn_recursive(); // Clean up victory screen
-                //app_exit_events.send(AppExit::Success);
-            }
-        }
-    }
-
-    fn check_victory_condition(
-        monster_query: Query<&Health, With<Monster>>,
-        victory_screen_query: Query<(), With<VictoryScreen>>,
-        mut commands: Commands,
-        asset_server: Res<AssetServer>,
-    ) {
-        if victory_screen_query.is_empty() {
-            // Only check if victory screen isn't already shown
-            let all_monsters_dead = monster_query.iter().all(|health| health.current <= 0.0);
-
-            if all_monsters_dead {
-                spawn_victory_screen(&mut commands, &asset_server);
-            }
-        }
-    }
-}
-
-mod chapter4 {
-    use super::GameState;
-    use bevy::app::AppExit;
-    use bevy::ecs::system::ParamSet;
-    use bevy::prelude::*;
-
-    #[derive(Component, Copy, Clone, Debug, PartialEq)]
-    enum CardType {
-        Fire,
-        Ice,
-        Air,
-        Earth,
-        Crystal,
-        Heal,
-        // Add other types as needed
-    }
-    // Components
-    #[derive(Component)]
-    struct AnimationTimer(Timer);
-
-    #[derive(Component)]
-    struct AnimationIndices {
-        first: usize,
-        last: usize,
-    }
-
-    #[derive(Component)]
-    struct Card;
-
-    #[derive(Component)]
-    struct OriginalPosition(Vec2);
-
-    #[derive(Component)]
-    struct OnChapterOneScreen;
-
-    #[derive(Component)]
-    struct SideCharacter;
-
-    #[derive(Component)]
-    struct Monster;
-
-    #[derive(Component)]
-    struct Health {
-        current: f32,
-        maximum: f32,
-    }
-
-    // Add this to your existing components if not already present
-    #[derive(Component)]
-    struct HealthBarContainer;
-
-    #[derive(Component)]
-    struct HealthBar;
-
-    // Add these new components in the chapter1 mod
-    #[derive(Component)]
-    struct EndTurnButton;
-
-    #[derive(Component)]
-    struct ButtonText;
-
-    #[derive(Component)]
-    struct Damage(f32);
-
-    #[derive(Component)]
-    struct DeathScreen;
-
-    #[derive(Component)]
-    struct DeathText;
-
-    #[derive(Component)]
-    struct FadeInEffect {
-        timer: Timer,
-    }
-
-    #[derive(Component)]
-    struct ReturnToMenuTimer {
-        timer: Timer,
-    }
-
-    #[derive(Component)]
-    struct DamageDisplay;
-
-    #[derive(Resource, Default)]
-    struct PendingAirCards(i32);
-
-    fn spawn_death_screen(commands: &mut Commands, asset_server: &AssetServer) {
-        // Main container
-        commands
-            .spawn((
-                NodeBundle {
-                    style: Style {
-                        width: Val::Percent(100.0),
-                        height: Val::Percent(100.0),
-                        position_type: PositionType::Absolute,
-                        align_items: AlignItems::Center,
-                        justify_content: JustifyContent::Center,
-                        flex_direction: FlexDirection::Column,
-                        ..default()
-                    },
-                    background_color: BackgroundColor(Color::rgba(0.0, 0.0, 0.0, 0.0)),
-                    ..default()
-                },
-                DeathScreen,
-                FadeInEffect {
-                    timer: Timer::from_seconds(2.0, TimerMode::Once),
-                },
-                // Add a timer to return to menu after 5 seconds
-                ReturnToMenuTimer {
-                    timer: Timer::from_seconds(5.0, TimerMode::Once),
-                },
-            ))
-            .with_children(|parent| {
-                // "YOU DIED" text
-                parent.spawn((
-                    TextBundle::from_section(
-                        "YOU DIED",
-                        TextStyle {
-                            font_size: 120.0,
-                            color: Color::rgba(0.8, 0.0, 0.0, 0.0), // Start transparent
-                            ..default()
-                        },
-                    ),
-                    DeathText,
-                    FadeInEffect {
-                        timer: Timer::from_seconds(2.0, TimerMode::Once),
-                    },
-                ));
-            });
-    }
-
-    // Update the death screen system to handle both fade-in and menu transition
-    fn update_death_screen(
-        mut commands: Commands,
-        time: Res<Time>,
-        mut query: Query<
-            (
-                Entity,
-                &mut BackgroundColor,
-                &mut FadeInEffect,
-                &mut ReturnToMenuTimer,
-            ),
-            With<DeathScreen>,
-        >,
-        mut text_query: Query<&mut Text, With<DeathText>>,
-        mut game_state: ResMut<NextState<GameState>>,
-        mut app_exit_events: EventWriter<AppExit>,
-    ) {
-        for (entity, mut bg_color, mut fade, mut return_timer) in query.iter_mut() {
-            // Update fade effect
-            fade.timer.tick(time.delta());
-            let alpha = fade.timer.fraction();
-            bg_color.0 = Color::rgba(0.0, 0.0, 0.0, alpha * 0.7);
-
-            // Update text color
-            if let Ok(mut text) = text_query.get_single_mut() {
-                text.sections[0].style.color = Color::rgba(0.8, 0.0, 0.0, alpha);
-            }
-
-            // Update return timer
-            return_timer.timer.tick(time.delta());
-            if return_timer.timer.finished() {
-                app_exit_events.send(AppExit::Success);
-            }
-        }
-    }
-    // Modify TurnState to include pending air cards
-    #[derive(Resource)]
-    struct TurnState {
-        first_card_played: bool,
-        cards_played_this_turn: Vec<CardType>,
-        crystal_power: i32,
-        turn_count: i32,
-        pending_air_cards: i32,
-    }
-
-    impl Default for TurnState {
-        fn default() -> Self {
-            Self {
-                first_card_played: true,
-                cards_played_this_turn: Vec::new(),
-                crystal_power: 0,
-                turn_count: 0,
-                pending_air_cards: 0, // Initialize pending_air_cards to 0
-            }
-        }
-    }
-
-    // Constants for base damage values
-    const FIRE_BASE_DAMAGE: f32 = 8.0;
-    const FIRE_FIRST_CARD_BONUS: f32 = 7.0;
-    const ICE_BASE_DAMAGE: f32 = 6.0;
-    const CRYSTAL_BASE_DAMAGE: f32 = 4.0;
-    const AIR_BASE_DAMAGE: f32 = 2.0;
-    const EARTH_BASE_DAMAGE: f32 = 5.0;
-    const HEAL_BASE_DAMAGE: f32 = 8.0;
-
-    fn update_health_bars(
-        query: Query<(&Health, &Children), Without<HealthBar>>,
-        mut health_bar_query: Query<&mut Sprite, With<HealthBar>>,
-    ) {
-        for (health, children) in query.iter() {
-            for child in children.iter() {
-                if let Ok(mut bar_sprite) = health_bar_query.get_mut(*child) {
-                    // Update health bar width based on current health
-                    let bar_width = 100.0;
-                    let health_percentage = health.current / health.maximum;
-
-                    bar_sprite.custom_size = Some(Vec2::new(
-                        bar_width * health_percentage,
-                        bar_sprite.custom_size.unwrap_or(Vec2::ZERO).y,
-                    ));
-
-                    // Update color based on health percentage
-                    bar_sprite.color = if health_percentage > 0.5 {
-                        Color::srgb(0.0, 1.0, 0.0) // Green: rgb(0, 255, 0)
-                    } else if health_percentage > 0.25 {
-                        Color::srgb(1.0, 0.65, 0.0) // Orange: rgb(255, 165, 0)
-                    } else {
-                        Color::srgb(1.0, 0.0, 0.0) // Red: rgb(255, 0, 0)
-                    };
-                }
-            }
-        }
-    }
-    #[derive(Resource)]
-    struct FightState {
-        current_turn: Turn,
-        selected_card: Option<usize>,
-    }
-
-    #[derive(PartialEq)]
-    enum Turn {
-        Player,
-        Enemy,
-    }
-
-    impl Default for FightState {
-        fn default() -> Self {
-            Self {
-                current_turn: Turn::Player,
-                selected_card: None,
-            }
-        }
-    }
-
-    // Update the card hover system to use FightState
-    fn update_card_hover(
-        mut card_query: Query<
-            (
-                &Interaction,
-                &mut Transform,
-                &OriginalPosition,
-                &mut Style,
-                Entity,
-            ),
-            (With<Card>, Changed<Interaction>),
-        >,
-        mut commands: Commands,
-        fight_state: Res<FightState>,
-    ) {
-        for (interaction, mut transform, original_pos, mut style, entity) in card_query.iter_mut() {
-            match *interaction {
-                Interaction::Hovered => {
-                    if fight_state.current_turn == Turn::Player {
-                        transform.translation.y = original_pos.0.y + 30.0;
-                        style.width = Val::Px(200.0);
-                        style.height = Val::Px(280.0);
-                    }
-                }
-                _ => {
-                    transform.translation.y = original_pos.0.y;
-                    style.width = Val::Px(180.0);
-                    style.height = Val::Px(250.0);
-                }
-            }
-        }
-    }
-
-    fn handle_card_click(
-        mut commands: Commands,
-        mut card_query: Query<
-            (&Interaction, Entity, &CardType),
-            (Changed<Interaction>, With<Card>),
-        >,
-        cards_in_hand: Query<Entity, With<Card>>, // Query to count cards
-        mut fight_state: ResMut<FightState>,
-        mut turn_state: ResMut<TurnState>,
-        mut monster_query: Query<(Entity, &mut Health, &Children), With<Monster>>,
-        health_container_query: Query<&Children, With<HealthBarContainer>>,
-        mut health_bar_query: Query<&mut Sprite, With<HealthBar>>,
-        asset_server: Res<AssetServer>,
-    ) {
-        if fight_state.current_turn != Turn::Player {
-            return;
-        }
-
-        for (interaction, card_entity, card_type) in card_query.iter() {
-            if *interaction == Interaction::Pressed {
-                println!("First card played status: {}", turn_state.first_card_played);
-
-                // Calculate damage based on whether this is the first card
-                let is_first = turn_state.first_card_played;
-                let cards_in_hand_count = cards_in_hand.iter().count() as f32; // Get count here
-
-                let damage = if *card_type == CardType::Fire && is_first {
-                    println!("Fire card played as first card! Enhanced damage!");
-                    FIRE_BASE_DAMAGE + FIRE_FIRST_CARD_BONUS
-                } else {
-                    match card_type {
-                        CardType::Fire => {
-                            println!("Fire card played but not first");
-                            FIRE_BASE_DAMAGE
-                        }
-                        CardType::Ice => {
-                            let mut damage = ICE_BASE_DAMAGE;
-
-                            if let Some(last_card) = turn_state.cards_played_this_turn.last() {
-                                if matches!(last_card, CardType::Fire) {
-                                    damage *= 2.0;
-                                }
-                            }
-
-                            if turn_state
-                                .cards_played_this_turn
-                                .iter()
-                                .any(|c| matches!(c, CardType::Earth))
-                            {
-                                damage = 0.0;
-                            }
-
-                            damage
-                        }
-                        CardType::Crystal => {
-                            let effects_bonus =
-                                (turn_state.cards_played_this_turn.len() as f32) * 2.0;
-                            let turn_bonus = turn_state.crystal_power as f32;
-                            CRYSTAL_BASE_DAMAGE + effects_bonus + turn_bonus
-                        }
-                        CardType::Air => AIR_BASE_DAMAGE,
-                        CardType::Heal => {
-                            // Check if any monster is at full health
-                            let mut is_any_monster_full_hp = false;
-                            for (_, monster_health, _) in monster_query.iter() {
-                                if (monster_health.current - monster_health.maximum).abs()
-                                    < f32::EPSILON
-                                {
-                                    is_any_monster_full_hp = true;
-                                    break;
-                                }
-                            }
-
-                            if is_any_monster_full_hp {
-                                HEAL_BASE_DAMAGE // Heal will deal -8 damage (healing) if enemy is at full HP
-                            } else {
-                                -HEAL_BASE_DAMAGE // Otherwise deal 8 damage
-                            }
-                        }
-                        CardType::Earth => {
-                            let turn_bonus = turn_state.turn_count as f32;
-                            EARTH_BASE_DAMAGE + cards_in_hand_count + turn_bonus
-                            // Use the count here
-                        }
-                    }
-                };
-
-                // Deal damage
-                for (entity, mut monster_health, children) in monster_query.iter_mut() {
-                    monster_health.current = (monster_health.current - damage).max(0.0);
-                    println!("Dealing {} damage. First card: {}", damage, is_first);
-                    spawn_damage_text(&mut commands, damage, &asset_server);
-                    // Update monster's health bar
-                    for child in children.iter() {
-                        if let Ok(container_children) = health_container_query.get(*child) {
-                            for health_bar_entity in container_children.iter() {
-                                if let Ok(mut bar_sprite) =
-                                    health_bar_query.get_mut(*health_bar_entity)
-                                {
-                                    let bar_width = 150.0; // Match the width set in chapter1_setup
-                                    let health_percentage =
-                                        monster_health.current / monster_health.maximum;
-
-                                    bar_sprite.custom_size = Some(Vec2::new(
-                                        bar_width * health_percentage,
-                                        bar_sprite.custom_size.unwrap_or(Vec2::ZERO).y,
-                                    ));
-
-                                    // Update color based on health percentage
-                                    bar_sprite.color = if health_percentage > 0.5 {
-                                        Color::srgb(0.0, 1.0, 0.0) // Green
-                                    } else if health_percentage > 0.25 {
-                                        Color::srgb(1.0, 0.65, 0.0) // Orange
-                                    } else {
-                                        Color::srgb(1.0, 0.0, 0.0) // Red
-                                    };
-                                }
-                            }
-                        }
-                    }
-
-                    // If monster dies, despawn it
-                    if monster_health.current <= 0.0 {
-                        commands.entity(entity).despawn_recursive();
-                    }
-                }
-
-                // Handle special card effects and cleanup
-                if matches!(card_type, CardType::Air) {
-                    turn_state.pending_air_cards += 2;
-                }
-
-                // Update turn state BEFORE destroying the card
-                turn_state.cards_played_this_turn.push(*card_type);
-                turn_state.first_card_played = false;
-                println!("Set first_card_played to false");
-
-                // Destroy the played card
-                commands.entity(card_entity).despawn_recursive();
-
-                break;
-            }
-        }
-    }
-
-    // Add this system to help debug the turn state
-    // Add this system to help debug the turn state
-    fn debug_turn_state(turn_state: Res<TurnState>, fight_state: Res<FightState>) {
-        println!(
-            "Turn State - First card: {}, Cards played: {}",
-            turn_state.first_card_played,
-            turn_state.cards_played_this_turn.len(),
-        );
-    }
-
-    fn process_turn(
-        mut fight_state: ResMut<FightState>,
-        mut query_set: ParamSet<(
-            Query<(&mut Health, &Children), With<SideCharacter>>,
-            Query<(&Health, &Damage), With<Monster>>,
-        )>,
-        health_container_query: Query<&Children, With<HealthBarContainer>>,
-        mut health_bar_query: Query<&mut Sprite, With<HealthBar>>,
-        asset_server: Res<AssetServer>,
-        mut commands: Commands,
-    ) {
-        if fight_state.current_turn == Turn::Enemy {
-            // First, collect all living monsters and their damage
-            let monster_attacks: Vec<f32> = query_set
-                .p1()
-                .iter()
-                .filter(|(health, _)| health.current > 0.0)
-                .map(|(_, damage)| damage.0)
-                .collect();
-
-            // Then apply damage to the player
-            if let Ok((mut character_health, children)) = query_set.p0().get_single_mut() {
-                for damage in monster_attacks {
-                    character_health.current = (character_health.current - damage).max(0.0);
-                    println!(
-                        "Player health: {}/{}",
-                        character_health.current, character_health.maximum
-                    );
-
-                    // Health bar update logic using nested queries
-                    for child in children.iter() {
-                        if let Ok(container_children) = health_container_query.get(*child) {
-                            for health_bar_entity in container_children.iter() {
-                                if let Ok(mut bar_sprite) =
-                                    health_bar_query.get_mut(*health_bar_entity)
-                                {
-                                    let bar_width = 150.0;
-                                    let health_percentage =
-                                        character_health.current / character_health.maximum;
-
-                                    bar_sprite.custom_size = Some(Vec2::new(
-                                        bar_width * health_percentage,
-                                        bar_sprite.custom_size.unwrap_or(Vec2::ZERO).y,
-                                    ));
-
-                                    bar_sprite.color = if health_percentage > 0.5 {
-                                        Color::srgb(0.0, 1.0, 0.0)
-                                    } else if health_percentage > 0.25 {
-                                        Color::srgb(1.0, 0.65, 0.0)
-                                    } else {
-                                        Color::srgb(1.0, 0.0, 0.0)
-                                    };
-                                }
-                            }
-                        }
-                    }
-
-                    spawn_damage_text(&mut commands, damage, &asset_server);
-
-                    // Check for player death
-                    if character_health.current <= 0.0 {
-                        spawn_death_screen(&mut commands, &asset_server);
-                    }
-                }
-
-                // Switch back to player turn
-                fight_state.current_turn = Turn::Player;
-            }
-        }
-    }
-
-    // Add a component for the damage text effect
-    #[derive(Component)]
-    struct DamageText {
-        timer: Timer,
-    }
-
-    // The spawn_damage_text and animate_damage_text functions remain the same
-    fn spawn_damage_text(commands: &mut Commands, damage: f32, asset_server: &Res<AssetServer>) {
-        let mut color = Color::srgb(1.0, 0.0, 0.0);
-
-        commands.spawn((
-            Text2dBundle {
-                text: Text::from_section(
-                    format!("-{}", damage),
-                    TextStyle {
-                        font_size: 30.0,
-                        color,
-                        ..default()
-                    },
-                ),
-                transform: Transform::from_xyz(0.0, 0.0, 10.0),
-                ..default()
-            },
-            DamageText {
-                timer: Timer::from_seconds(1.0, TimerMode::Once),
-            },
-        ));
+    fn adaptive_label(difficulty: &Difficulty) -> String {
+        let setting = if difficulty.adaptive { "On" } else { "Off" };
+        format!("Adaptive difficulty: {setting}")
     }
 
-    fn animate_damage_text(
-        mut commands: Commands,
-        time: Res<Time>,
-        mut query: Query<(Entity, &mut Transform, &mut Text, &mut DamageText)>,
-    ) {
-        for (entity, mut transform, mut text, mut damage_text) in query.iter_mut() {
-            damage_text.timer.tick(time.delta());
-
-            // Move the text upward
-            transform.translation.y += 100.0 * time.delta_seconds();
-
-            // Fade out the text
-            let alpha =
-                1.0 - damage_text.timer.elapsed_secs() / damage_text.timer.duration().as_secs_f32();
-
-            // Remove the text when the timer is finished
-            if damage_text.timer.finished() {
-                commands.entity(entity).despawn();
-            }
-        }
-    }
-    fn handle_end_turn_button(
-        mut interaction_query: Query<
-            (&Interaction, &mut BackgroundColor),
-            (Changed<Interaction>, With<EndTurnButton>),
-        >,
-        mut fight_state: ResMut<FightState>,
-        mut commands: Commands,
-        asset_server: Res<AssetServer>,
-        turn_state: Res<TurnState>,
-    ) {
-        for (interaction, mut color) in &mut interaction_query {
-            match *interaction {
-                Interaction::Pressed => {
-                    if fight_state.current_turn == Turn::Player {
-                        // Add air cards before changing turn
-                        for _ in 0..turn_state.pending_air_cards {
-                            spawn_card(&mut commands, CardType::Air, &asset_server);
-                        }
-
-                        fight_state.current_turn = Turn::Enemy;
-                        *color = Color::srgb(0.35, 0.35, 0.35).into();
-                    }
-                }
-                Interaction::Hovered => {
-                    if fight_state.current_turn == Turn::Player {
-                        *color = Color::srgb(0.25, 0.25, 0.25).into();
-                    }
-                }
-                Interaction::None => {
-                    *color = Color::srgb(0.15, 0.15, 0.15).into();
-                }
-            }
-        }
-    }
-
-    // Add this system to update the button's appearance based on turn state
-    fn update_end_turn_button(
-        fight_state: Res<FightState>,
-        mut button_query: Query<&mut BackgroundColor, With<EndTurnButton>>,
-        mut text_query: Query<&mut Text, With<ButtonText>>,
-    ) {
-        if let Ok(mut color) = button_query.get_single_mut() {
-            if fight_state.current_turn == Turn::Player {
-                *color = Color::srgb(0.15, 0.15, 0.15).into();
-            } else {
-                *color = Color::srgb(0.5, 0.5, 0.5).into();
-            }
-        }
-
-        if let Ok(mut text) = text_query.get_single_mut() {
-            if fight_state.current_turn == Turn::Player {
-                text.sections[0].value = "End Turn".to_string();
-            } else {
-                text.sections[0].value = "Enemy Turn".to_string();
-            }
-        }
-    }
-    // Update the chapter1_plugin to include debug system
-    pub fn chapter3_plugin(app: &mut App) {
-        app.init_resource::<FightState>()
-            .init_resource::<TurnState>() // This line was already correct
-            .add_systems(OnEnter(GameState::Chapter4), (chapter1_setup,))
-            .add_systems(
-                Update,
-                (
-                    animate_sprite,
-                    update_card_hover,
-                    handle_card_click,
-                    process_turn,
-                    update_health_bars,
-                    handle_end_turn_button,
-                    update_end_turn_button,
-                    animate_damage_text,
-                    update_death_screen,
-                    process_pending_cards,
-                    update_turn_state,
-                    check_victory_condition, // Add this
-                    update_victory_screen,
-                    //debug_turn_state,
-                )
-                    .chain()
-                    .run_if(in_state(GameState::Chapter4)),
-            )
-            .add_systems(
-                OnExit(GameState::Chapter3),
-                super::despawn_screen::<OnChapterOneScreen>,
-            );
-    }
-
-    #[derive(Component)]
-    struct PendingCards {
-        card_type: CardType,
-        amount: i32,
+    fn merciful_label(difficulty: &Difficulty) -> String {
+        let setting = if difficulty.merciful { "On" } else { "Off" };
+        format!("Merciful mode: {setting}")
     }
 
-    fn process_pending_cards(
-        mut commands: Commands,
-        pending_query: Query<(Entity, &PendingCards)>,
-        mut turn_state: ResMut<TurnState>,
-        asset_server: Res<AssetServer>,
-    ) {
-        for (entity, pending) in pending_query.iter() {
-            for _ in 0..pending.amount {
-                spawn_card(&mut commands, pending.card_type, &asset_server);
-            }
-            commands.entity(entity).despawn();
-        }
+    fn speedrun_label(best_times: &BestTimes) -> String {
+        let setting = if best_times.timer_enabled {
+            "On"
+        } else {
+            "Off"
+        };
+        format!("Speedrun timer: {setting}")
     }
 
-    fn spawn_card(commands: &mut Commands, card_type: CardType, asset_server: &Res<AssetServer>) {
-        let texture = match card_type {
-            CardType::Fire => asset_server.load("textures/Game Icons/Fire.png"),
-            CardType::Ice => asset_server.load("textures/Game Icons/Frost.png"),
-            CardType::Air => asset_server.load("textures/Game Icons/air.png"),
-            CardType::Earth => asset_server.load("textures/Game Icons/Earth.png"),
-            CardType::Crystal => asset_server.load("textures/Game Icons/Crystal.png"),
-            CardType::Heal => asset_server.load("textures/Game Icons/Heal.png"),
+    fn daily_menu_setup(mut commands: Commands, theme: Res<Theme>) {
+        let daily = DailyChallenge::today();
+        let results = DailyResults::load();
+        let button_style = Style {
+            width: Val::Px(300.0),
+            height: Val::Px(65.0),
+            margin: UiRect::all(Val::Px(12.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
         };
+        let button_text_style = theme.text_style(40.0);
+        let text_style = theme.text_style(26.0);
 
-        commands.spawn((
-            ImageBundle {
-                style: Style {
-                    width: Val::Px(180.0),
-                    height: Val::Px(250.0),
-                    margin: UiRect::horizontal(Val::Px(10.0)),
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
                     ..default()
                 },
-                image: UiImage::new(texture),
-                background_color: Color::WHITE.into(),
-                ..default()
-            },
-            Interaction::None,
-            Card,
-            card_type,
-            OriginalPosition(Vec2::new(0.0, 0.0)), // Position will need to be adjusted
-            OnChapterOneScreen,
-        ));
-    }
+                StateScoped(MenuState::Daily),
+            ))
+            .with_children(|parent| {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            row_gap: Val::Px(6.0),
+                            padding: UiRect::all(Val::Px(20.0)),
+                            ..default()
+                        },
+                        background_color: theme.panel.into(),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            format!("Daily Run {}", daily.date),
+                            button_text_style.clone(),
+                        ));
+                        for modifier in &daily.modifiers {
+                            parent.spawn(TextBundle::from_section(
+                                format!("{}: {}", modifier.name(), modifier.description()),
+                                text_style.clone(),
+                            ));
+                        }
+                        ui::styled_button(
+                            parent,
+                            &theme,
+                            button_style.clone(),
+                            "Start",
+                            button_text_style.clone(),
+                            MenuButtonAction::StartDaily,
+                        );
+
+                        // How the last few days went, today's included
+                        parent.spawn(TextBundle::from_section(
+                            "Recent days",
+                            TextStyle {
+                                font_size: 30.0,
+                                ..text_style.clone()
+                            },
+                        ));
+                        if results.days.is_empty() {
+                            parent.spawn(TextBundle::from_section(
+                                "No daily runs yet",
+                                text_style.clone(),
+                            ));
+                        }
+                        for result in results.days.iter().take(5) {
+                            parent.spawn(TextBundle::from_section(
+                                format!("{}  {}", result.date, result.summary()),
+                                text_style.clone(),
+                            ));
+                        }
 
-    fn update_turn_state(mut fight_state: ResMut<FightState>, mut turn_state: ResMut<TurnState>) {
-        // if fight_state.current_turn == Turn::Player {
-        //     turn_state.cards_played_this_turn.clear();
-        //     turn_state.crystal_power += 1;
-        //     turn_state.turn_count += 1;
-        //     turn_state.pending_air_cards = 0; // Reset pending air cards
-        // }
+                        ui::styled_button(
+                            parent,
+                            &theme,
+                            button_style,
+                            "Back",
+                            button_text_style,
+                            MenuButtonAction::BackToMainMenu,
+                        );
+                    });
+            });
     }
 
-    fn chapter1_setup(
-        mut commands: Commands,
-        asset_server: Res<AssetServer>,
-        mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
-        windows: Query<&Window>,
-    ) {
-        commands.insert_resource(TurnState {
-            first_card_played: true,
-            cards_played_this_turn: Vec::new(),
-            crystal_power: 0,
-            turn_count: 0,
-            pending_air_cards: 0,
-        });
-        let window = windows.single();
-
-        // Calculate positions
-        let char_x = window.width() * -0.25;
-        let char_y = window.height() * -0.75;
-
-        // Load textures
-        let texture_handle: Handle<Image> = asset_server.load("textures/intro_game_sprite.png");
-        let fire_card_texture: Handle<Image> = asset_server.load("textures/Game Icons/Fire.png");
-        let ice_card_texture: Handle<Image> = asset_server.load("textures/Game Icons/Frost.png");
-        let air_card_texture: Handle<Image> = asset_server.load("textures/Game Icons/air.png");
-        let earth_card_texture: Handle<Image> = asset_server.load("textures/Game Icons/Earth.png");
-        let heal_card_texture: Handle<Image> = asset_server.load("textures/Game Icons/Heal.png");
-        let crystal_card_texture: Handle<Image> =
-            asset_server.load("textures/Game Icons/Crystal.png");
-        let forest: Handle<Image> = asset_server.load("textures/Summon.png");
-
-        let side_character_texture = asset_server.load("textures/character.png");
-        let monster_texture: Handle<Image> = asset_server.load("textures/mage.png");
-
-        let layout = TextureAtlasLayout::from_grid(UVec2::new(576, 324), 5, 64, None, None);
-        let atlas_layout = atlas_layouts.add(layout);
+    fn arena_menu_setup(mut commands: Commands, theme: Res<Theme>) {
+        let button_style = Style {
+            width: Val::Px(520.0),
+            height: Val::Px(75.0),
+            margin: UiRect::all(Val::Px(12.0)),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        };
+        let button_text_style = theme.text_style(36.0);
 
-        // Spawn main container
         commands
             .spawn((
                 NodeBundle {
                     style: Style {
                         width: Val::Percent(100.0),
                         height: Val::Percent(100.0),
-                        flex_direction: FlexDirection::Column,
                         align_items: AlignItems::Center,
-                        justify_content: JustifyContent::SpaceBetween,
+                        justify_content: JustifyContent::Center,
                         ..default()
                     },
                     ..default()
                 },
-                OnChapterOneScreen,
+                StateScoped(MenuState::Arena),
             ))
-            // .with_children(|parent| {
-            //     // Background animation (same as before)
-            //     parent
-            //         .spawn(NodeBundle {
-            //             style: Style {
-            //                 width: Val::Vw(100.0),
-            //                 height: Val::Vh(100.0),
-            //                 align_items: AlignItems::Center,
-            //                 justify_content: JustifyContent::Center,
-            //                 ..default()
-            //             },
-            //             ..default()
-            //         })
-            //         .with_children(|parent| {
-            //             parent.spawn((
-            //                 SpriteSheetBundle {
-            //                     texture: texture_handle,
-            //                     atlas: TextureAtlas {
-            //                         layout: atlas_layout,
-            //                         index: 0,
-            //                     },
-            //                     transform: Transform::from_xyz(
-            //                         -window.width() / 2.0,
-            //                         -window.height() / 2.0,
-            //                         1.0,
-            //                     ),
-            //                     sprite: Sprite {
-            //                         custom_size: Some(Vec2::new(1920.0, 1080.0)),
-            //                         anchor: bevy::sprite::Anchor::Center,
-            //                         ..default()
-            //                     },
-            //                     ..default()
-            //                 },
-            //                 AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)),
-            //                 AnimationIndices {
-            //                     first: 0,
-            //                     last: 320,
-            //                 },
-            //             ));
-            //         });
             .with_children(|parent| {
                 parent
                     .spawn(NodeBundle {
                         style: Style {
-                            width: Val::Vw(100.0),
-                            height: Val::Vh(100.0),
+                            flex_direction: FlexDirection::Column,
                             align_items: AlignItems::Center,
-                            justify_content: JustifyContent::Center,
+                            padding: UiRect::all(Val::Px(20.0)),
                             ..default()
                         },
+                        background_color: theme.panel.into(),
                         ..default()
                     })
                     .with_children(|parent| {
-                        parent.spawn(SpriteBundle {
-                            texture: forest,
-                            transform: Transform::from_xyz(
-                                -window.width() / 2.0,
-                                -window.height() / 2.0,
-                                1.0,
-                            ),
-                            sprite: Sprite {
-                                custom_size: Some(Vec2::new(1920.0, 1080.0)),
-                                anchor: bevy::sprite::Anchor::Center,
-                                ..default()
-                            },
-                            ..default()
-                        });
+                        parent.spawn(TextBundle::from_section(
+                            "Pick a starting deck",
+                            button_text_style.clone(),
+                        ));
+                        for (i, arena_deck) in ARENA_DECKS.iter().enumerate() {
+                            let cards = arena_deck
+                                .cards
+                                .iter()
+                                .map(|card_type| format!("{card_type:?}"))
+                                .collect::<Vec<_>>()
+                                .join(", ");
+                            parent
+                                .spawn((
+                                    ui::button(&theme, button_style.clone()),
+                                    MenuButtonAction::StartArena(i),
+                                ))
+                                .with_children(|parent| {
+                                    parent.spawn(TextBundle::from_section(
+                                        arena_deck.name,
+                                        button_text_style.clone(),
+                                    ));
+                                    parent.spawn(TextBundle::from_section(
+                                        cards,
+                                        TextStyle {
+                                            font_size: 20.0,
+                                            ..button_text_style.clone()
+                                        },
+                                    ));
+                                });
+                        }
+                        ui::styled_button(
+                            parent,
+                            &theme,
+                            button_style,
+                            "Back",
+                            button_text_style,
+                            MenuButtonAction::BackToMainMenu,
+                        );
                     });
+            });
+    }
+
+    fn chapters_menu_setup(
+        mut commands: Commands,
+        save_data: Res<SaveData>,
+        story_flags: Res<StoryFlags>,
+        theme: Res<Theme>,
+    ) {
+        let button_style = Style {
+            width: Val::Px(520.0),
+            height: Val::Px(75.0),
+            margin: UiRect::all(Val::Px(12.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        };
+        let button_text_style = theme.text_style(36.0);
 
-                // Side character with health
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    ..default()
+                },
+                StateScoped(MenuState::Chapters),
+            ))
+            .with_children(|parent| {
                 parent
-                    .spawn((
-                        SpriteBundle {
-                            texture: side_character_texture,
-                            transform: Transform::from_xyz(
-                                -window.width() / 2.0 + char_x,
-                                char_y,
-                                2.0,
-                            ),
-                            sprite: Sprite {
-                                custom_size: Some(Vec2::new(400.0, 400.0)),
-                                anchor: bevy::sprite::Anchor::Center,
-                                ..default()
-                            },
+                    .spawn(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            padding: UiRect::all(Val::Px(20.0)),
                             ..default()
                         },
-                        SideCharacter,
-                        Health {
-                            current: 100.0,
-                            maximum: 100.0,
-                        },
-                    ))
-                    .with_children(|monster| {
-                        // Health bar background
-                        monster
-                            .spawn((
-                                SpriteBundle {
-                                    sprite: Sprite {
-                                        color: Color::srgb(0.2, 0.2, 0.2),
-                                        custom_size: Some(Vec2::new(150.0, 10.0)),
-                                        ..default()
+                        background_color: theme.panel.into(),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        // The chapters, then endless mode for those who finished them
+                        let entries = CHAPTERS
+                            .iter()
+                            .map(|chapter| {
+                                (
+                                    chapter.name,
+                                    chapter.start,
+                                    chapter.is_unlocked(&save_data, &story_flags),
+                                    chapter.requirement,
+                                )
+                            })
+                            .chain([(
+                                "Endless Mode",
+                                GameState::Endless,
+                                endless::is_unlocked(&save_data, &story_flags),
+                                "Finish Chapter 4 to unlock",
+                            )]);
+                        for (name, start, unlocked, requirement) in entries {
+                            if unlocked {
+                                ui::styled_button(
+                                    parent,
+                                    &theme,
+                                    button_style.clone(),
+                                    name,
+                                    button_text_style.clone(),
+                                    MenuButtonAction::StartChapter(start),
+                                );
+                                continue;
+                            }
+                            // Locked entries aren't buttons, so they can't be picked
+                            parent
+                                .spawn(NodeBundle {
+                                    style: Style {
+                                        flex_direction: FlexDirection::Column,
+                                        ..button_style.clone()
                                     },
-                                    transform: Transform::from_xyz(
-                                        0.0,    // Centered horizontally relative to parent
-                                        -175.0, // Below sprite with 20px padding
-                                        0.1,
-                                    ),
+                                    background_color: LOCKED_BUTTON.into(),
                                     ..default()
-                                },
-                                HealthBarContainer,
-                                OnChapterOneScreen,
-                            ))
-                            .with_children(|container| {
-                                // Actual health bar
-                                container.spawn((
-                                    SpriteBundle {
-                                        sprite: Sprite {
-                                            color: Color::srgb(0.0, 1.0, 0.0),
-                                            custom_size: Some(Vec2::new(150.0, 10.0)),
-                                            anchor: bevy::sprite::Anchor::CenterLeft,
+                                })
+                                .with_children(|parent| {
+                                    parent
+                                        .spawn(NodeBundle {
+                                            style: Style {
+                                                position_type: PositionType::Absolute,
+                                                left: Val::Px(16.0),
+                                                ..default()
+                                            },
                                             ..default()
+                                        })
+                                        .with_children(|parent| {
+                                            spawn_padlock(parent, theme.muted_text);
+                                        });
+                                    parent.spawn(TextBundle::from_section(
+                                        name,
+                                        TextStyle {
+                                            color: theme.muted_text,
+                                            ..button_text_style.clone()
                                         },
-                                        transform: Transform::from_xyz(-75.0, 0.0, 0.2),
-                                        ..default()
-                                    },
-                                    HealthBar,
-                                ));
-                            });
+                                    ));
+                                    parent.spawn(TextBundle::from_section(
+                                        requirement,
+                                        TextStyle {
+                                            font_size: 20.0,
+                                            color: theme.muted_text,
+                                            ..default()
+                                        },
+                                    ));
+                                });
+                        }
+                        ui::styled_button(
+                            parent,
+                            &theme,
+                            button_style,
+                            "Back",
+                            button_text_style,
+                            MenuButtonAction::BackToMainMenu,
+                        );
                     });
-                let monster1_damage = 100.0;
-                let monster2_damage = 10.0;
-                // Monster 1 with health
+            });
+    }
+
+    fn stats_menu_setup(
+        mut commands: Commands,
+        difficulty: Res<Difficulty>,
+        best_times: Res<BestTimes>,
+        save_data: Res<SaveData>,
+        theme: Res<Theme>,
+    ) {
+        let button_style = Style {
+            width: Val::Px(450.0),
+            height: Val::Px(65.0),
+            margin: UiRect::all(Val::Px(20.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        };
+        let button_text_style = theme.text_style(40.0);
+
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    ..default()
+                },
+                StateScoped(MenuState::Stats),
+            ))
+            .with_children(|parent| {
                 parent
-                    .spawn((
-                        SpriteBundle {
-                            texture: monster_texture,
-                            transform: Transform::from_xyz(
-                                char_x,
-                                char_y + window.height() / 16.0,
-                                2.0,
-                            ),
-                            sprite: Sprite {
-                                custom_size: Some(Vec2::new(400.0, 400.0)),
-                                anchor: bevy::sprite::Anchor::Center,
-                                ..default()
-                            },
+                    .spawn(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            padding: UiRect::all(Val::Px(20.0)),
                             ..default()
                         },
-                        Monster,
-                        Health {
-                            current: 44.0,
-                            maximum: 44.0,
-                        },
-                        Damage(monster1_damage), // This monster deals 15 damage
-                    ))
-                    .with_children(|monster| {
-                        // Spawn the black background sprite
-                        monster.spawn(SpriteBundle {
-                            sprite: Sprite {
-                                color: Color::BLACK,
-                                custom_size: Some(Vec2::new(50.0, 30.0)), // Adjust size as needed
-                                ..default()
-                            },
-                            transform: Transform::from_xyz(0.0, 210.0, 0.0),
-                            ..default()
-                        });
-                        // Spawn damage text above monster
-                        monster.spawn((
-                            Text2dBundle {
-                                text: Text::from_section(
-                                    format!("{}", monster1_damage),
-                                    TextStyle {
-                                        font_size: 24.0,
-                                        color: Color::rgb(1.0, 0.0, 0.0),
-                                        ..default()
-                                    },
-                                ),
-                                transform: Transform::from_xyz(0.0, 210.0, 0.1), // Position above monster
-                                ..default()
-                            },
-                            DamageDisplay,
+                        background_color: theme.panel.into(),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn((
+                            TextBundle::from_section(
+                                stats_summary(&difficulty, &save_data),
+                                TextStyle {
+                                    font_size: 30.0,
+                                    ..button_text_style.clone()
+                                },
+                            ),
+                            StatsText::Summary,
                         ));
-                        // Health bar background
-                        monster
+                        // Whether enemies adapt to how well the player is doing
+                        parent
+                            .spawn((
+                                ui::button(&theme, button_style.clone()),
+                                MenuButtonAction::ToggleAdaptive,
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    TextBundle::from_section(
+                                        adaptive_label(&difficulty),
+                                        button_text_style.clone(),
+                                    ),
+                                    StatsText::AdaptiveToggle,
+                                ));
+                            });
+                        // Whether dying costs gold and a card instead of the run
+                        parent
                             .spawn((
-                                SpriteBundle {
-                                    sprite: Sprite {
-                                        color: Color::srgb(0.2, 0.2, 0.2),
-                                        custom_size: Some(Vec2::new(150.0, 10.0)),
-                                        ..default()
-                                    },
-                                    transform: Transform::from_xyz(
-                                        0.0,    // Centered horizontally relative to parent
-                                        -215.0, // Below sprite with 20px padding
-                                        0.1,
+                                ui::button(&theme, button_style.clone()),
+                                MenuButtonAction::ToggleMerciful,
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    TextBundle::from_section(
+                                        merciful_label(&difficulty),
+                                        button_text_style.clone(),
                                     ),
-                                    ..default()
-                                },
-                                HealthBarContainer,
-                                OnChapterOneScreen,
+                                    StatsText::MercifulToggle,
+                                ));
+                            });
+                        // Whether New Game starts the speedrun timer
+                        parent
+                            .spawn((
+                                ui::button(&theme, button_style.clone()),
+                                MenuButtonAction::ToggleSpeedrunTimer,
                             ))
-                            .with_children(|container| {
-                                // Actual health bar
-                                container.spawn((
-                                    SpriteBundle {
-                                        sprite: Sprite {
-                                            color: Color::srgb(0.0, 1.0, 0.0),
-                                            custom_size: Some(Vec2::new(150.0, 10.0)),
-                                            anchor: bevy::sprite::Anchor::CenterLeft,
-                                            ..default()
-                                        },
-                                        transform: Transform::from_xyz(-75.0, 0.0, 0.2),
-                                        ..default()
-                                    },
-                                    HealthBar,
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    TextBundle::from_section(
+                                        speedrun_label(&best_times),
+                                        button_text_style.clone(),
+                                    ),
+                                    StatsText::SpeedrunToggle,
                                 ));
                             });
+                        ui::styled_button(
+                            parent,
+                            &theme,
+                            button_style.clone(),
+                            "Leaderboards",
+                            button_text_style.clone(),
+                            MenuButtonAction::Leaderboards,
+                        );
+                        ui::styled_button(
+                            parent,
+                            &theme,
+                            button_style,
+                            "Back",
+                            button_text_style,
+                            MenuButtonAction::BackToMainMenu,
+                        );
                     });
+            });
+    }
+
+    fn update_stats_screen(
+        difficulty: Res<Difficulty>,
+        best_times: Res<BestTimes>,
+        save_data: Res<SaveData>,
+        mut text_query: Query<(&mut Text, &StatsText)>,
+    ) {
+        if !difficulty.is_changed() && !best_times.is_changed() {
+            return;
+        }
+        for (mut text, stats_text) in text_query.iter_mut() {
+            text.sections[0].value = match stats_text {
+                StatsText::Summary => stats_summary(&difficulty, &save_data),
+                StatsText::AdaptiveToggle => adaptive_label(&difficulty),
+                StatsText::MercifulToggle => merciful_label(&difficulty),
+                StatsText::SpeedrunToggle => speedrun_label(&best_times),
+            };
+        }
+    }
 
-                // Add this to the chapter1_setup function after spawning the cards container
+    fn leaderboards_menu_setup(
+        mut commands: Commands,
+        leaderboards: Res<Leaderboards>,
+        theme: Res<Theme>,
+    ) {
+        leaderboards.refresh();
+        let button_text_style = theme.text_style(40.0);
+
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    ..default()
+                },
+                StateScoped(MenuState::Leaderboards),
+            ))
+            .with_children(|parent| {
                 parent
                     .spawn(NodeBundle {
                         style: Style {
-                            position_type: PositionType::Absolute,
-                            right: Val::Px(20.0),
-                            top: Val::Px(20.0), // Changed from top to bottom
-                            width: Val::Px(150.0),
-                            height: Val::Px(50.0),
+                            flex_direction: FlexDirection::Column,
                             align_items: AlignItems::Center,
-                            justify_content: JustifyContent::Center,
+                            padding: UiRect::all(Val::Px(20.0)),
                             ..default()
                         },
-                        background_color: Color::srgb(0.15, 0.15, 0.15).into(),
+                        background_color: theme.panel.into(),
                         ..default()
                     })
                     .with_children(|parent| {
-                        parent
-                            .spawn((
-                                ButtonBundle {
-                                    style: Style {
-                                        width: Val::Percent(100.0),
-                                        height: Val::Percent(100.0),
-                                        align_items: AlignItems::Center,
-                                        justify_content: JustifyContent::Center,
-                                        ..default()
-                                    },
-                                    background_color: Color::srgb(0.15, 0.15, 0.15).into(),
+                        parent.spawn(ui::title_text(&theme, "Leaderboards"));
+                        parent.spawn((
+                            NodeBundle {
+                                style: Style {
+                                    flex_direction: FlexDirection::Column,
+                                    align_items: AlignItems::Center,
+                                    margin: UiRect::all(Val::Px(20.0)),
+                                    row_gap: Val::Px(20.0),
                                     ..default()
                                 },
-                                EndTurnButton,
-                            ))
-                            .with_children(|parent| {
-                                parent.spawn((
-                                    TextBundle::from_section(
-                                        "End Turn",
-                                        TextStyle {
-                                            font_size: 20.0,
-                                            color: Color::WHITE,
-                                            ..default()
-                                        },
-                                    ),
-                                    ButtonText,
-                                ));
-                            });
+                                ..default()
+                            },
+                            LeaderboardTables,
+                        ));
+                        ui::styled_button(
+                            parent,
+                            &theme,
+                            Style {
+                                width: Val::Px(300.0),
+                                height: Val::Px(65.0),
+                                margin: UiRect::all(Val::Px(20.0)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            "Back",
+                            button_text_style,
+                            MenuButtonAction::Stats,
+                        );
                     });
-                // Cards container
+            });
+    }
+
+    // The boards side by side, each with its top scores
+    fn update_leaderboards_screen(
+        mut commands: Commands,
+        leaderboards: Res<Leaderboards>,
+        tables_query: Query<Entity, With<LeaderboardTables>>,
+        added_query: Query<(), Added<LeaderboardTables>>,
+        theme: Res<Theme>,
+    ) {
+        if !leaderboards.is_changed() && added_query.is_empty() {
+            return;
+        }
+        let Ok(tables) = tables_query.get_single() else {
+            return;
+        };
+        let text_style = theme.text_style(22.0);
+
+        commands
+            .entity(tables)
+            .despawn_descendants()
+            .with_children(|parent| {
+                parent.spawn(
+                    TextBundle::from_section(leaderboards.status(), text_style.clone())
+                        .with_text_justify(JustifyText::Center),
+                );
                 parent
                     .spawn(NodeBundle {
                         style: Style {
-                            width: Val::Percent(100.0),
-                            height: Val::Px(200.0),
-                            position_type: PositionType::Absolute,
-                            top: Val::Px(20.0),
-                            justify_content: JustifyContent::Center,
-                            align_items: AlignItems::Center,
-                            column_gap: Val::Px(20.0),
+                            column_gap: Val::Px(40.0),
                             ..default()
                         },
                         ..default()
                     })
                     .with_children(|parent| {
-                        // Define card types and their corresponding textures
-                        let cards = vec![
-                            (CardType::Earth, earth_card_texture.clone()),
-                            (CardType::Crystal, crystal_card_texture.clone()),
-                            (CardType::Fire, fire_card_texture.clone()),
-                            (CardType::Ice, ice_card_texture.clone()),
-                            (CardType::Heal, heal_card_texture.clone()),
-                        ];
-
-                        // Spawn three cards
-                        for (i, (card_type, card_texture)) in cards.into_iter().enumerate() {
-                            // Changed to into_iter()
-                            let x_position = (i as f32 - 1.0) * 220.0;
-
-                            parent.spawn((
-                                ImageBundle {
+                        for board in Board::shown() {
+                            parent
+                                .spawn(NodeBundle {
                                     style: Style {
-                                        width: Val::Px(180.0),
-                                        height: Val::Px(250.0),
-                                        margin: UiRect::horizontal(Val::Px(10.0)),
+                                        flex_direction: FlexDirection::Column,
+                                        row_gap: Val::Px(6.0),
+                                        min_width: Val::Px(260.0),
                                         ..default()
                                     },
-                                    image: UiImage::new(card_texture),
-                                    background_color: Color::WHITE.into(),
-                                    transform: Transform::from_xyz(x_position, 0.0, 0.0),
                                     ..default()
-                                },
-                                Interaction::None,
-                                Card,
-                                card_type, // No longer a reference
-                                OriginalPosition(Vec2::new(x_position, 0.0)),
-                            ));
+                                })
+                                .with_children(|parent| {
+                                    parent.spawn(TextBundle::from_section(
+                                        board.title(),
+                                        TextStyle {
+                                            font_size: 30.0,
+                                            ..text_style.clone()
+                                        },
+                                    ));
+                                    let rows = leaderboards.rows(&board);
+                                    if rows.is_empty() {
+                                        parent.spawn(TextBundle::from_section(
+                                            "No scores yet",
+                                            TextStyle {
+                                                color: theme.muted_text,
+                                                ..text_style.clone()
+                                            },
+                                        ));
+                                    }
+                                    for row in rows {
+                                        parent.spawn(TextBundle::from_section(
+                                            row,
+                                            text_style.clone(),
+                                        ));
+                                    }
+                                });
                         }
                     });
             });
     }
 
-    fn animate_sprite(
-        time: Res<Time>,
-        mut query: Query<(&mut TextureAtlas, &mut AnimationTimer, &AnimationIndices)>,
-    ) {
-        for (mut atlas, mut timer, indices) in &mut query {
-            timer.0.tick(time.delta());
-            if timer.0.just_finished() {
-                atlas.index = if atlas.index == indices.last {
-                    indices.first
-                } else {
-                    atlas.index + 1
-                };
+    fn menu_action(
+        mut commands: Commands,
+        interaction_query: Query<
+            (&Interaction, &MenuButtonAction),
+            (Changed<Interaction>, With<Button>),
+        >,
+        mut app_exit_events: EventWriter<AppExit>,
+        mut menu_state: ResMut<NextState<MenuState>>,
+        mut game_state: ResMut<NextState<GameState>>,
+        saved_run: SavedRun,
+        mut best_times: ResMut<BestTimes>,
+    ) {
+        let SavedRun {
+            mut save_data,
+            mut story_flags,
+            mut inventory,
+            mut gold,
+            mut deck,
+            mut rng,
+            mut difficulty,
+            mut run_effects,
+            mut player_state,
+        } = saved_run;
+        for (interaction, menu_button_action) in &interaction_query {
+            if *interaction == Interaction::Pressed {
+                match menu_button_action {
+                    MenuButtonAction::Quit => {
+                        app_exit_events.send(AppExit::Success);
+                    }
+                    MenuButtonAction::Continue => {
+                        let resumed = save_data
+                            .fight
+                            .as_ref()
+                            .map(|fight| fight.state)
+                            .or(save_data.cutscene.map(|cutscene| cutscene.state));
+                        if let Some(state) = resumed {
+                            game_state.set(state);
+                            menu_state.set(MenuState::Disabled);
+                        }
+                    }
+                    MenuButtonAction::Play
+                    | MenuButtonAction::PlayCoop
+                    | MenuButtonAction::StartChapter(_)
+                    | MenuButtonAction::StartDaily
+                    | MenuButtonAction::StartArena(_) => {
+                        // A new run starts with a clean slate
+                        save_data.fight = None;
+                        save_data.cutscene = None;
+                        save_data.daily = None;
+                        save_data.endless = None;
+                        story_flags.clear();
+                        inventory.clear();
+                        gold.clear();
+                        deck.reset();
+                        rng.reseed();
+                        run_effects.clear();
+                        player_state.clear();
+                        if matches!(menu_button_action, MenuButtonAction::StartDaily) {
+                            let daily = DailyChallenge::today();
+                            daily.begin(&mut rng, &mut deck, &mut gold);
+                            for modifier in &daily.modifiers {
+                                run_effects.add(*modifier);
+                            }
+                            save_data.daily = Some(daily.date);
+                        }
+                        if matches!(menu_button_action, MenuButtonAction::PlayCoop) {
+                            commands.insert_resource(HotSeat);
+                        }
+                        if let MenuButtonAction::StartArena(index) = menu_button_action {
+                            *deck = Deck::from_cards(ARENA_DECKS[*index].cards.to_vec());
+                            commands.insert_resource(ArenaWaves::new(&rng));
+                        }
+                        // Only a full run from New Game is timed
+                        if matches!(menu_button_action, MenuButtonAction::Play)
+                            && best_times.timer_enabled
+                        {
+                            commands.insert_resource(SpeedrunTimer::default());
+                        }
+                        // game_state.set(GameState::Chapter3);
+                        game_state.set(match menu_button_action {
+                            MenuButtonAction::StartChapter(state) => *state,
+                            MenuButtonAction::StartArena(_) => GameState::Arena,
+                            _ => GameState::Game,
+                        });
+
+                        menu_state.set(MenuState::Disabled);
+                    }
+                    MenuButtonAction::Settings => menu_state.set(MenuState::Settings),
+                    MenuButtonAction::SettingsDisplay => {
+                        menu_state.set(MenuState::SettingsDisplay);
+                    }
+                    MenuButtonAction::SettingsSound => {
+                        menu_state.set(MenuState::SettingsSound);
+                    }
+                    MenuButtonAction::SettingsControls => {
+                        menu_state.set(MenuState::SettingsControls);
+                    }
+                    MenuButtonAction::SwitchProfile => {
+                        game_state.set(GameState::Profiles);
+                        menu_state.set(MenuState::Disabled);
+                    }
+                    MenuButtonAction::Daily => menu_state.set(MenuState::Daily),
+                    MenuButtonAction::Chapters => menu_state.set(MenuState::Chapters),
+                    MenuButtonAction::Arena => menu_state.set(MenuState::Arena),
+                    MenuButtonAction::Duel => {
+                        game_state.set(GameState::Duel);
+                        menu_state.set(MenuState::Disabled);
+                    }
+                    MenuButtonAction::Stats => menu_state.set(MenuState::Stats),
+                    MenuButtonAction::Leaderboards => menu_state.set(MenuState::Leaderboards),
+                    MenuButtonAction::ToggleAdaptive => {
+                        difficulty.adaptive = !difficulty.adaptive;
+                    }
+                    MenuButtonAction::ToggleMerciful => {
+                        difficulty.merciful = !difficulty.merciful;
+                    }
+                    MenuButtonAction::ToggleSpeedrunTimer => {
+                        best_times.timer_enabled = !best_times.timer_enabled;
+                        best_times.save();
+                    }
+                    MenuButtonAction::BackToMainMenu => menu_state.set(MenuState::Main),
+                    MenuButtonAction::BackToSettings => {
+                        menu_state.set(MenuState::Settings);
+                    }
+                }
             }
         }
     }
+}
 
-    // Add these new components and structs in the chapter1 mod
-    #[derive(Component)]
-    struct VictoryScreen;
-
-    #[derive(Component)]
-    struct VictoryText;
-
-    #[derive(Component)]
-    struct ReturnToMenuVictoryTimer {
-        timer: Timer,
+// Generic system that takes a component as a parameter, and will despawn all entities with that component.
+// Screens belonging to a single state use `StateScoped` instead; this is for entities several states share
+fn despawn_screen<T: Component>(to_despawn: Query<Entity, With<T>>, mut commands: Commands) {
+    for entity in &to_despawn {
+        commands.entity(entity).despawn_recursive();
     }
+}
 
-    fn spawn_victory_screen(commands: &mut Commands, asset_server: &AssetServer) {
-        commands
-            .spawn((
-                NodeBundle {
-                    style: Style {
-                        width: Val::Percent(100.0),
-                        height: Val::Percent(100.0),
-                        position_type: PositionType::Absolute,
-                        align_items: AlignItems::Center,
-                        justify_content: JustifyContent::Center,
-                        flex_direction: FlexDirection::Column,
-                        ..default()
+mod chapter1 {
+    use super::GameState;
+    use crate::combat::{
+        add_combat_state, combat_setup, CardType, Encounter, LootTable, MonsterSpec,
+    };
+    use crate::run_effects::RunEffect;
+    use crate::story::StoryFlag;
+    use bevy::prelude::*;
+
+    pub fn chapter1_plugin(app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::Chapter1),
+            chapter1_setup.before(combat_setup),
+        );
+        add_combat_state(app, GameState::Chapter1);
+    }
+
+    fn chapter1_setup(mut commands: Commands) {
+        commands.insert_resource(Encounter {
+            background: "textures/1.png",
+            monsters: vec![
+                MonsterSpec {
+                    texture: "textures/monster.png",
+                    health: 40.0,
+                    damage: 15.0,
+                    size: 250.0,
+                    offset: Vec2::new(1.0 / 8.0, 0.0),
+                    lift: -75.0,
+                    label_height: 120.0,
+                    health_bar_depth: -100.0,
+                    moves: Vec::new(),
+                    loot: LootTable {
+                        gold: (2, 5),
+                        cards: vec![(CardType::Fire, 10)],
+                        relic: None,
                     },
-                    background_color: BackgroundColor(Color::rgba(0.0, 0.0, 0.0, 0.0)),
-                    ..default()
-                },
-                VictoryScreen,
-                FadeInEffect {
-                    timer: Timer::from_seconds(2.0, TimerMode::Once),
                 },
-                ReturnToMenuVictoryTimer {
-                    timer: Timer::from_seconds(5.0, TimerMode::Once),
-                },
-            ))
-            .with_children(|parent| {
-                parent.spawn((
-                    TextBundle::from_section(
-                        "VICTORY!",
-                        TextStyle {
-                            font_size: 120.0,
-                            color: Color::rgba(0.0, 0.8, 0.0, 0.0), // Start transparent, but green
-                            ..default()
-                        },
-                    ),
-                    VictoryText,
-                    FadeInEffect {
-                        timer: Timer::from_seconds(2.0, TimerMode::Once),
+                MonsterSpec {
+                    texture: "textures/monster_2.png",
+                    health: 40.0,
+                    damage: 10.0,
+                    size: 250.0,
+                    offset: Vec2::new(-1.0 / 8.0, 0.0),
+                    lift: -75.0,
+                    label_height: 120.0,
+                    health_bar_depth: -100.0,
+                    moves: Vec::new(),
+                    loot: LootTable {
+                        gold: (1, 4),
+                        cards: vec![(CardType::Ice, 10)],
+                        relic: Some((RunEffect::Windcharm, 10)),
                     },
-                ));
-            });
+                },
+            ],
+            next_state: GameState::Game2,
+            victory_flag: StoryFlag::ForestCleared,
+            wounded_flag: Some(StoryFlag::WoundedInForest),
+            reward: None,
+            gold_reward: 10,
+            card_rewards: vec![CardType::Heal],
+            enrage_after: None,
+            ritual: None,
+            effect_reward: Some(RunEffect::Vigor),
+            intro: None,
+        });
     }
+}
 
-    fn update_victory_screen(
-        mut commands: Commands,
-        time: Res<Time>,
-        mut query: Query<
-            (
-                Entity,
-                &mut BackgroundColor,
-                &mut FadeInEffect,
-                &mut ReturnToMenuVictoryTimer,
-            ),
-            With<VictoryScreen>,
-        >,
-        mut text_query: Query<&mut Text, With<VictoryText>>,
-        mut game_state: ResMut<NextState<GameState>>,
-        mut app_exit_events: EventWriter<AppExit>,
-    ) {
-        for (entity, mut bg_color, mut fade, mut return_timer) in query.iter_mut() {
-            fade.timer.tick(time.delta());
-            let alpha = fade.timer.fraction();
-            bg_color.0 = Color::rgba(0.0, 0.0, 0.0, alpha * 0.7);
+mod chapter2 {
+    use super::GameState;
+    use crate::combat::{
+        add_combat_state, combat_setup, CardType, Encounter, EnemyMove, LootTable, MonsterSpec,
+    };
+    use crate::inventory::KeyItem;
+    use crate::run_effects::RunEffect;
+    use crate::story::{StoryFlag, StoryFlags};
+    use bevy::prelude::*;
 
-            if let Ok(mut text) = text_query.get_single_mut() {
-                text.sections[0].style.color = Color::rgba(0.0, 0.8, 0.0, alpha);
-            }
+    pub fn chapter2_plugin(app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::Chapter2),
+            chapter2_setup.before(combat_setup),
+        );
+        add_combat_state(app, GameState::Chapter2);
+    }
+
+    fn chapter2_setup(mut commands: Commands, story_flags: Res<StoryFlags>) {
+        let mut monsters = vec![MonsterSpec {
+            texture: "textures/knight.png",
+            health: 21.0,
+            damage: 25.0,
+            size: 400.0,
+            offset: Vec2::new(1.0 / 8.0, 0.0),
+            lift: 0.0,
+            label_height: 120.0,
+            health_bar_depth: -170.0,
+            // Steels itself between blows, hitting harder each time
+            moves: vec![EnemyMove::Attack(25.0), EnemyMove::Buff(5.0)],
+            loot: knight_loot(),
+        }];
+        // The second knight only steps out for a player who came through the forest unhurt
+        if !story_flags.is_set(StoryFlag::WoundedInForest) {
+            monsters.push(MonsterSpec {
+                texture: "textures/knight.png",
+                health: 21.0,
+                damage: 10.0,
+                size: 400.0,
+                offset: Vec2::new(-1.0 / 8.0, 0.0),
+                lift: 0.0,
+                label_height: 120.0,
+                health_bar_depth: -170.0,
+                // Raises its shield after every swing
+                moves: vec![EnemyMove::Attack(10.0), EnemyMove::Defend(12.0)],
+                loot: knight_loot(),
+            });
+        }
 
-            return_timer.timer.tick(time.delta());
-            if return_timer.timer.finished() {
-                // game_state.set(GameState::Game3);
-                // commands.entity(entity).despawn_recursive(); // Clean up victory screen
-                app_exit_events.send(AppExit::Success);
-            }
+        commands.insert_resource(Encounter {
+            background: "textures/2.png",
+            monsters,
+            next_state: GameState::Game3,
+            victory_flag: StoryFlag::FortCleared,
+            wounded_flag: None,
+            reward: Some(KeyItem::Rune),
+            gold_reward: 15,
+            card_rewards: vec![CardType::Fire, CardType::Air],
+            enrage_after: None,
+            ritual: None,
+            effect_reward: Some(RunEffect::RuneWard),
+            intro: None,
+        });
+    }
+
+    fn knight_loot() -> LootTable {
+        LootTable {
+            gold: (4, 8),
+            cards: vec![(CardType::Earth, 15)],
+            relic: Some((RunEffect::HeartStone, 5)),
         }
     }
+}
 
-    fn check_victory_condition(
-        monster_query: Query<&Health, With<Monster>>,
-        victory_screen_query: Query<(), With<VictoryScreen>>,
-        mut commands: Commands,
-        asset_server: Res<AssetServer>,
-    ) {
-        if victory_screen_query.is_empty() {
-            // Only check if victory screen isn't already shown
-            let all_monsters_dead = monster_query.iter().all(|health| health.current <= 0.0);
+mod chapter3 {
+    use super::GameState;
+    use crate::boss_intro::{add_boss_intro, BossIntro};
+    use crate::combat::{
+        add_combat_state, combat_setup, CardType, Encounter, EnemyMove, LootTable, MonsterSpec,
+    };
+    use crate::cutscene::CutsceneLine;
+    use crate::run_effects::RunEffect;
+    use crate::story::StoryFlag;
+    use bevy::prelude::*;
 
-            if all_monsters_dead {
-                spawn_victory_screen(&mut commands, &asset_server);
-            }
-        }
+    pub fn chapter3_plugin(app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::Chapter3),
+            chapter3_setup.before(combat_setup),
+        );
+        add_combat_state(app, GameState::Chapter3);
+        add_boss_intro(app, GameState::Chapter3);
+    }
+
+    fn chapter3_setup(mut commands: Commands) {
+        commands.insert_resource(Encounter {
+            background: "textures/waterfall.png",
+            monsters: vec![MonsterSpec {
+                texture: "textures/angle.png",
+                health: 44.0,
+                damage: 50.0,
+                size: 400.0,
+                offset: Vec2::new(0.0, 1.0 / 16.0),
+                lift: 0.0,
+                label_height: 180.0,
+                health_bar_depth: -215.0,
+                // Bathes in the pool every other turn
+                moves: vec![EnemyMove::Attack(50.0), EnemyMove::Heal(15.0)],
+                loot: LootTable {
+                    gold: (6, 12),
+                    cards: vec![(CardType::Heal, 20)],
+                    relic: Some((RunEffect::PhoenixFeather, 10)),
+                },
+            }],
+            next_state: GameState::Game4,
+            victory_flag: StoryFlag::PoolCleared,
+            wounded_flag: None,
+            reward: None,
+            gold_reward: 20,
+            card_rewards: vec![CardType::Crystal],
+            // The angel loses patience if the fight drags on
+            enrage_after: Some(5),
+            ritual: None,
+            effect_reward: None,
+            intro: Some(BossIntro {
+                title: "Chapter 3: The Still Pool",
+                name: "The Pool Angel",
+                epithet: "Keeper of the waterfall",
+                lines: vec![
+                    CutsceneLine::new("The statue was no illusion..."),
+                    CutsceneLine::new("Stone wings unfold over the water"),
+                ],
+            }),
+        });
+    }
+}
+
+mod chapter4 {
+    use super::GameState;
+    use crate::boss_intro::{add_boss_intro, BossIntro};
+    use crate::combat::{
+        add_combat_state, combat_setup, CardType, Encounter, EnemyMove, LootTable, MonsterSpec,
+        Ritual,
+    };
+    use crate::cutscene::CutsceneLine;
+    use crate::inventory::KeyItem;
+    use crate::run_effects::RunEffect;
+    use crate::story::StoryFlag;
+    use bevy::prelude::*;
+
+    pub fn chapter3_plugin(app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::Chapter4),
+            chapter4_setup.before(combat_setup),
+        );
+        add_combat_state(app, GameState::Chapter4);
+        add_boss_intro(app, GameState::Chapter4);
+    }
+
+    fn chapter4_setup(mut commands: Commands) {
+        commands.insert_resource(Encounter {
+            background: "textures/Summon.png",
+            monsters: vec![MonsterSpec {
+                texture: "textures/mage.png",
+                health: 44.0,
+                damage: 100.0,
+                size: 400.0,
+                offset: Vec2::new(0.0, 1.0 / 16.0),
+                lift: 0.0,
+                label_height: 210.0,
+                health_bar_depth: -215.0,
+                // The mage calls imps to hold the player off and hexes them while it chants
+                moves: vec![
+                    EnemyMove::Summon(vec![MonsterSpec {
+                        texture: "textures/monster_2.png",
+                        health: 12.0,
+                        damage: 8.0,
+                        size: 200.0,
+                        offset: Vec2::ZERO,
+                        lift: 0.0,
+                        label_height: 110.0,
+                        health_bar_depth: -115.0,
+                        moves: Vec::new(),
+                        loot: LootTable {
+                            gold: (0, 2),
+                            ..default()
+                        },
+                    }]),
+                    EnemyMove::Hex(2),
+                    EnemyMove::Attack(15.0),
+                ],
+                loot: LootTable {
+                    gold: (10, 15),
+                    cards: vec![(CardType::Crystal, 25)],
+                    relic: Some((RunEffect::HeartStone, 15)),
+                },
+            }],
+            next_state: GameState::Ending,
+            victory_flag: StoryFlag::RitualBroken,
+            wounded_flag: None,
+            reward: None,
+            gold_reward: 30,
+            card_rewards: Vec::new(),
+            enrage_after: None,
+            // Stella luminara: three turns of chanting, broken by a heavy blow or a crystal
+            ritual: Some(Ritual {
+                turns: 3,
+                interrupt_damage: 20.0,
+                interrupt_card: Some(CardType::Crystal),
+            }),
+            effect_reward: None,
+            intro: Some(BossIntro {
+                title: "Chapter 4: The Summoning",
+                name: "The Summoner",
+                epithet: "Voice of the chant",
+                lines: vec![
+                    CutsceneLine::new("The rune in your pocket burns like a coal")
+                        .holding(KeyItem::Rune),
+                    CutsceneLine::new("Stella luminara... you are too late.").with_speed(0.14),
+                ],
+            }),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::GameState;
+    use crate::combat::OnCombatScreen;
+    use crate::test_app::TestApp;
+    use crate::theme::Contrast;
+
+    #[test]
+    fn new_game_reaches_the_first_fight() {
+        let mut app = TestApp::new();
+        assert_eq!(app.state(), GameState::Splash);
+        app.start_new_game();
+        assert_eq!(app.state(), GameState::Chapter1);
+        assert!(app.count::<OnCombatScreen>() > 0);
+    }
+
+    #[test]
+    fn high_contrast_shows_on_the_display_settings_straight_away() {
+        let mut app = TestApp::new();
+        app.step_until_state(40, GameState::Menu);
+        app.step(1);
+        app.press_button("Settings");
+        app.step(2);
+        app.press_button("Display");
+        app.step(2);
+
+        let world = app.world_mut();
+        let high = world
+            .query::<(Entity, &Contrast)>()
+            .iter(world)
+            .find(|(_, contrast)| **contrast == Contrast::High)
+            .map(|(entity, _)| entity)
+            .unwrap();
+        app.press(high);
+        app.step(2);
+
+        assert_eq!(*app.world().resource::<Contrast>(), Contrast::High);
+        // Built again in place of the old screen, rather than on top of it
+        assert!(app.world().get_entity(high).is_none());
+        let world = app.world_mut();
+        let labels = world
+            .query::<&Text>()
+            .iter(world)
+            .filter(|text| text.sections[0].value == "Contrast")
+            .count();
+        assert_eq!(labels, 1);
+        assert_eq!(app.count::<Contrast>(), 2);
     }
 }
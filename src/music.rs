@@ -0,0 +1,216 @@
+// Background music, decoded a little at a time while it plays. Looping a plain `AudioSource`
+// makes the audio backend keep every decoded sample of the track, which for a long piece is far
+// more memory than the file itself and takes a while to fill on slow machines. Music loaded as
+// `StreamedMusic` only keeps the compressed file and starts decoding it again from the top when
+// it runs out. Short sound effects are still loaded as `AudioSource` and kept decoded in memory.
+//
+// The music director below decides what plays. The exploration music loops from startup; a
+// chapter's fight fades it down and a battle loop up over it, and leaving the fight, won or not,
+// fades them back.
+use std::time::Duration;
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use bevy::audio::{AddAudioSource, Decodable, Source, Volume};
+use bevy::prelude::*;
+
+use crate::chapters::CHAPTERS;
+
+const EXPLORATION_MUSIC: &str = "sounds/Shadowy Whispers.ogg";
+const BATTLE_MUSIC: &str = "sounds/Epic orchestra music.ogg";
+
+// The exploration music's level under a fight, low enough to leave the battle loop in front
+const DUCKED_LEVEL: f32 = 0.15;
+
+// Seconds a loop takes to fade all the way in or out
+const FADE_SECONDS: f32 = 1.5;
+
+#[derive(Asset, TypePath)]
+pub struct StreamedMusic {
+    file: AudioSource,
+}
+
+type FileDecoder = <AudioSource as Decodable>::Decoder;
+type Sample = <AudioSource as Decodable>::DecoderItem;
+
+// Loops forever, so it should be played with `PlaybackSettings::ONCE`
+pub struct MusicDecoder {
+    file: AudioSource,
+    decoder: FileDecoder,
+}
+
+impl Decodable for StreamedMusic {
+    type DecoderItem = Sample;
+    type Decoder = MusicDecoder;
+
+    fn decoder(&self) -> MusicDecoder {
+        MusicDecoder {
+            file: self.file.clone(),
+            decoder: self.file.decoder(),
+        }
+    }
+}
+
+impl Iterator for MusicDecoder {
+    type Item = Sample;
+
+    fn next(&mut self) -> Option<Sample> {
+        if let Some(sample) = self.decoder.next() {
+            return Some(sample);
+        }
+        self.decoder = self.file.decoder();
+        // A file with no samples at all would otherwise be restarted forever
+        self.decoder.next()
+    }
+}
+
+impl Source for MusicDecoder {
+    // Every pass is the same file, so the channels and sample rate never change mid-track
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.decoder.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.decoder.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+// Only used when a handle asks for `StreamedMusic`. Loading the same files as `AudioSource` still
+// goes through Bevy's own loader.
+#[derive(Default)]
+struct StreamedMusicLoader;
+
+impl AssetLoader for StreamedMusicLoader {
+    type Asset = StreamedMusic;
+    type Settings = ();
+    type Error = std::io::Error;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<StreamedMusic, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(StreamedMusic {
+            file: AudioSource {
+                bytes: bytes.into(),
+            },
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ogg", "oga"]
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Track {
+    Exploration,
+    Battle,
+}
+
+// One loop the director is playing, and the level it's fading towards
+#[derive(Component)]
+struct MusicLayer {
+    track: Track,
+    level: f32,
+    target: f32,
+}
+
+pub fn music_plugin(app: &mut App) {
+    app.add_audio_source::<StreamedMusic>()
+        .init_asset_loader::<StreamedMusicLoader>()
+        .add_systems(Startup, start_exploration_music)
+        .add_systems(Update, fade_music);
+    for chapter in &CHAPTERS {
+        app.add_systems(OnEnter(chapter.fight), enter_battle)
+            .add_systems(OnExit(chapter.fight), leave_battle);
+    }
+}
+
+fn spawn_layer(commands: &mut Commands, asset_server: &AssetServer, track: Track, level: f32) {
+    let path = match track {
+        Track::Exploration => EXPLORATION_MUSIC,
+        Track::Battle => BATTLE_MUSIC,
+    };
+    commands.spawn((
+        AudioSourceBundle::<StreamedMusic> {
+            source: asset_server.load(path),
+            // Streamed music loops by itself
+            settings: PlaybackSettings::ONCE.with_volume(Volume::new(level)),
+        },
+        MusicLayer {
+            track,
+            level,
+            target: 1.0,
+        },
+    ));
+}
+
+fn start_exploration_music(mut commands: Commands, asset_server: Res<AssetServer>) {
+    spawn_layer(&mut commands, &asset_server, Track::Exploration, 1.0);
+}
+
+// A battle loop still fading out from a retried fight is brought back rather than doubled up
+fn enter_battle(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut layer_query: Query<&mut MusicLayer>,
+) {
+    let mut battle_playing = false;
+    for mut layer in layer_query.iter_mut() {
+        match layer.track {
+            Track::Exploration => layer.target = DUCKED_LEVEL,
+            Track::Battle => {
+                layer.target = 1.0;
+                battle_playing = true;
+            }
+        }
+    }
+    if !battle_playing {
+        spawn_layer(&mut commands, &asset_server, Track::Battle, 0.0);
+    }
+}
+
+fn leave_battle(mut layer_query: Query<&mut MusicLayer>) {
+    for mut layer in layer_query.iter_mut() {
+        layer.target = match layer.track {
+            Track::Exploration => 1.0,
+            Track::Battle => 0.0,
+        };
+    }
+}
+
+// In real time, so the music still fades while the game is paused
+fn fade_music(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut layer_query: Query<(Entity, &mut MusicLayer, Option<&AudioSink>)>,
+) {
+    let step = time.delta_seconds() / FADE_SECONDS;
+    for (entity, mut layer, sink) in layer_query.iter_mut() {
+        let level = if layer.level < layer.target {
+            (layer.level + step).min(layer.target)
+        } else {
+            (layer.level - step).max(layer.target)
+        };
+        layer.level = level;
+        if let Some(sink) = sink {
+            sink.set_volume(level);
+        }
+        // A battle loop faded all the way out isn't coming back
+        if layer.track == Track::Battle && layer.target == 0.0 && level == 0.0 {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,214 @@
+// Escape pauses the run: the game clock stops, and a menu offers to resume, to show the run
+// status panel, which lists the run's passive effects, or to report an issue.
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+
+use super::GameState;
+use crate::bug_report::ReportIssue;
+use crate::run_effects::RunEffects;
+use crate::theme::Theme;
+use crate::ui;
+
+#[derive(Component)]
+struct PauseMenu;
+
+#[derive(Component)]
+struct RunStatusPanel;
+
+#[derive(Component, Clone, Copy)]
+enum PauseAction {
+    Resume,
+    RunStatus,
+    ReportIssue,
+}
+
+pub fn pause_plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            toggle_pause.run_if(in_run),
+            pause_buttons,
+            close_on_state_change,
+        ),
+    );
+}
+
+// Only a run in progress can be paused, not the menus or the demo
+fn in_run(game_state: Res<State<GameState>>) -> bool {
+    !matches!(
+        game_state.get(),
+        GameState::Loading
+            | GameState::Profiles
+            | GameState::Splash
+            | GameState::Menu
+            | GameState::Demo
+    )
+}
+
+fn toggle_pause(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    menu_query: Query<Entity, With<PauseMenu>>,
+    mut time: ResMut<Time<Virtual>>,
+    theme: Res<Theme>,
+) {
+    if !keyboard.just_pressed(KeyCode::Escape) {
+        return;
+    }
+    if let Ok(menu) = menu_query.get_single() {
+        commands.entity(menu).despawn_recursive();
+        time.unpause();
+        return;
+    }
+    time.pause();
+
+    let text_style = theme.text_style(30.0);
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(16.0),
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.85).into(),
+                // Nothing under the menu can be clicked while paused
+                focus_policy: FocusPolicy::Block,
+                z_index: ZIndex::Global(25),
+                ..default()
+            },
+            PauseMenu,
+        ))
+        .with_children(|parent| {
+            parent.spawn(ui::title_text(&theme, "Paused"));
+            for (action, label) in [
+                (PauseAction::Resume, "Resume"),
+                (PauseAction::RunStatus, "Run status"),
+                (PauseAction::ReportIssue, "Report issue"),
+            ] {
+                ui::styled_button(
+                    parent,
+                    &theme,
+                    Style {
+                        width: Val::Px(260.0),
+                        padding: UiRect::vertical(Val::Px(12.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    label,
+                    text_style.clone(),
+                    action,
+                );
+            }
+        });
+}
+
+fn pause_buttons(
+    mut commands: Commands,
+    mut interaction_query: ui::ChangedActionButtons<PauseAction>,
+    // The run status panel shows inside the menu
+    pause_menu: ui::ScreenContent<PauseMenu, RunStatusPanel>,
+    run_effects: Res<RunEffects>,
+    mut time: ResMut<Time<Virtual>>,
+    mut reports: EventWriter<ReportIssue>,
+    theme: Res<Theme>,
+) {
+    for (interaction, action, mut color) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {}
+            Interaction::Hovered => {
+                *color = theme.button_hovered.into();
+                continue;
+            }
+            Interaction::None => {
+                *color = theme.button.into();
+                continue;
+            }
+        }
+        let Some(menu) = pause_menu.root() else {
+            return;
+        };
+
+        match action {
+            PauseAction::Resume => {
+                commands.entity(menu).despawn_recursive();
+                time.unpause();
+            }
+            PauseAction::RunStatus => match pause_menu.content() {
+                Some(panel) => commands.entity(panel).despawn_recursive(),
+                None => {
+                    let panel = spawn_run_status(&mut commands, &run_effects, &theme);
+                    commands.entity(menu).add_child(panel);
+                }
+            },
+            // Closes the menu first, so it isn't in the report's screenshot
+            PauseAction::ReportIssue => {
+                commands.entity(menu).despawn_recursive();
+                time.unpause();
+                reports.send(ReportIssue);
+            }
+        }
+        return;
+    }
+}
+
+fn spawn_run_status(commands: &mut Commands, run_effects: &RunEffects, theme: &Theme) -> Entity {
+    let text_style = theme.text_style(24.0);
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(8.0),
+                    padding: UiRect::all(Val::Px(16.0)),
+                    margin: UiRect::top(Val::Px(16.0)),
+                    ..default()
+                },
+                background_color: theme.button.into(),
+                ..default()
+            },
+            RunStatusPanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Effects on this run",
+                TextStyle {
+                    font_size: 32.0,
+                    ..text_style.clone()
+                },
+            ));
+            if run_effects.effects().is_empty() {
+                parent.spawn(TextBundle::from_section("Nothing yet", text_style.clone()));
+            }
+            for effect in run_effects.effects() {
+                parent.spawn(TextBundle::from_section(
+                    format!("{}: {}", effect.name(), effect.description()),
+                    text_style.clone(),
+                ));
+            }
+        })
+        .id()
+}
+
+// Should the run move on to another state while paused, the menu doesn't follow it there
+fn close_on_state_change(
+    mut commands: Commands,
+    game_state: Res<State<GameState>>,
+    menu_query: Query<Entity, With<PauseMenu>>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    if !game_state.is_changed() {
+        return;
+    }
+    for menu in menu_query.iter() {
+        commands.entity(menu).despawn_recursive();
+        time.unpause();
+    }
+}
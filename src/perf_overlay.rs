@@ -0,0 +1,161 @@
+// Performance overlay, toggled with F3. Shows FPS, a graph of recent frame times, the number of
+// entities and of playing sounds, so a player reporting stutter can pass the numbers along.
+use bevy::diagnostic::{
+    DiagnosticsStore, EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin,
+};
+use bevy::prelude::*;
+
+use crate::platform::SafeAnchor;
+use crate::theme::Theme;
+
+const TOGGLE_KEY: KeyCode = KeyCode::F3;
+
+// One bar per frame, newest on the right
+const GRAPH_BARS: usize = 60;
+const BAR_WIDTH: f32 = 3.0;
+const GRAPH_HEIGHT: f32 = 60.0;
+// Frame time that fills the graph's height
+const GRAPH_MAX_MS: f64 = 50.0;
+
+const FAST_FRAME_MS: f64 = 1000.0 / 60.0;
+const SLOW_FRAME_MS: f64 = 1000.0 / 30.0;
+
+#[derive(Component)]
+struct PerfOverlay;
+
+#[derive(Component)]
+struct PerfText;
+
+// The bar for the frame this many frames before the newest
+#[derive(Component)]
+struct FrameBar(usize);
+
+pub fn perf_overlay_plugin(app: &mut App) {
+    app.add_plugins((FrameTimeDiagnosticsPlugin, EntityCountDiagnosticsPlugin))
+        .add_systems(Startup, spawn_perf_overlay)
+        .add_systems(Update, (toggle_perf_overlay, update_perf_overlay).chain());
+}
+
+fn spawn_perf_overlay(mut commands: Commands, theme: Res<Theme>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(16.0),
+                    right: Val::Px(16.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(8.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.7).into(),
+                visibility: Visibility::Hidden,
+                z_index: ZIndex::Global(40),
+                ..default()
+            },
+            PerfOverlay,
+            SafeAnchor::bottom_right(16.0, 16.0),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section("", theme.text_style(18.0)),
+                PerfText,
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        height: Val::Px(GRAPH_HEIGHT),
+                        align_items: AlignItems::FlexEnd,
+                        ..default()
+                    },
+                    background_color: Color::srgba(1.0, 1.0, 1.0, 0.05).into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for age in (0..GRAPH_BARS).rev() {
+                        parent.spawn((
+                            NodeBundle {
+                                style: Style {
+                                    width: Val::Px(BAR_WIDTH),
+                                    height: Val::Px(0.0),
+                                    ..default()
+                                },
+                                ..default()
+                            },
+                            FrameBar(age),
+                        ));
+                    }
+                });
+        });
+}
+
+fn toggle_perf_overlay(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut overlay_query: Query<&mut Visibility, With<PerfOverlay>>,
+) {
+    if !keyboard.just_pressed(TOGGLE_KEY) {
+        return;
+    }
+    for mut visibility in overlay_query.iter_mut() {
+        *visibility = if *visibility == Visibility::Hidden {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn update_perf_overlay(
+    diagnostics: Res<DiagnosticsStore>,
+    overlay_query: Query<&Visibility, With<PerfOverlay>>,
+    mut text_query: Query<&mut Text, With<PerfText>>,
+    mut bar_query: Query<(&FrameBar, &mut Style, &mut BackgroundColor)>,
+    sink_query: Query<(), With<AudioSink>>,
+) {
+    // Nothing to keep up to date while it's hidden
+    if overlay_query
+        .iter()
+        .all(|visibility| *visibility == Visibility::Hidden)
+    {
+        return;
+    }
+
+    let fps = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+        .unwrap_or(0.0);
+    let frame_time = diagnostics.get(&FrameTimeDiagnosticsPlugin::FRAME_TIME);
+    let frame_ms = frame_time
+        .and_then(|frame_time| frame_time.smoothed())
+        .unwrap_or(0.0);
+    let entities = diagnostics
+        .get(&EntityCountDiagnosticsPlugin::ENTITY_COUNT)
+        .and_then(|entities| entities.value())
+        .unwrap_or(0.0);
+
+    if let Ok(mut text) = text_query.get_single_mut() {
+        text.sections[0].value = format!(
+            "FPS: {fps:.0}\nFrame time: {frame_ms:.1} ms\nEntities: {entities:.0}\nAudio sinks: {}",
+            sink_query.iter().count()
+        );
+    }
+
+    // Newest first, so each bar finds its frame by age
+    let mut recent: Vec<f64> = frame_time
+        .map(|frame_time| frame_time.values().copied().collect())
+        .unwrap_or_default();
+    recent.reverse();
+    for (bar, mut style, mut color) in bar_query.iter_mut() {
+        let ms = recent.get(bar.0).copied().unwrap_or(0.0);
+        style.height = Val::Px((ms / GRAPH_MAX_MS).min(1.0) as f32 * GRAPH_HEIGHT);
+        *color = if ms <= FAST_FRAME_MS {
+            Color::srgb(0.2, 0.8, 0.2)
+        } else if ms <= SLOW_FRAME_MS {
+            Color::srgb(0.9, 0.8, 0.2)
+        } else {
+            Color::srgb(0.9, 0.2, 0.2)
+        }
+        .into();
+    }
+}
@@ -0,0 +1,172 @@
+// What changes between desktop and Android. On Android the UI is drawn larger for fingers, files
+// the game writes go in the app's private storage, and HUD corners stay clear of the status bar,
+// navigation bar and camera cutouts. On every platform the game pauses its clock and its sounds
+// while the app is in the background, and picks them up again when it comes back.
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use bevy::window::AppLifecycle;
+#[cfg(target_os = "android")]
+use bevy::window::{PrimaryWindow, WindowResized};
+
+// Finger-sized buttons need more room than mouse-sized ones
+#[cfg(target_os = "android")]
+const TOUCH_UI_SCALE: f32 = 1.25;
+
+// How far each edge of the window is covered by system bars or cutouts, in logical pixels
+#[derive(Resource, Default, PartialEq, Clone, Copy, Debug)]
+pub struct SafeArea {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+}
+
+// Keeps an absolutely placed node this far inside the safe area from the edges it's anchored to.
+// Edges left as `None` are left alone.
+#[derive(Component, Default, Clone, Copy)]
+pub struct SafeAnchor {
+    pub top: Option<f32>,
+    pub right: Option<f32>,
+    pub bottom: Option<f32>,
+    pub left: Option<f32>,
+}
+
+impl SafeAnchor {
+    pub fn top_left(top: f32, left: f32) -> Self {
+        Self {
+            top: Some(top),
+            left: Some(left),
+            ..default()
+        }
+    }
+
+    pub fn top_right(top: f32, right: f32) -> Self {
+        Self {
+            top: Some(top),
+            right: Some(right),
+            ..default()
+        }
+    }
+
+    pub fn bottom_left(bottom: f32, left: f32) -> Self {
+        Self {
+            bottom: Some(bottom),
+            left: Some(left),
+            ..default()
+        }
+    }
+
+    pub fn bottom_right(bottom: f32, right: f32) -> Self {
+        Self {
+            bottom: Some(bottom),
+            right: Some(right),
+            ..default()
+        }
+    }
+}
+
+// A sound that was playing when the app went into the background
+#[derive(Component)]
+struct PausedInBackground;
+
+pub fn platform_plugin(app: &mut App) {
+    #[cfg(target_os = "android")]
+    app.insert_resource(UiScale(TOUCH_UI_SCALE))
+        .add_systems(Update, measure_safe_area.before(anchor_to_safe_area));
+
+    app.init_resource::<SafeArea>()
+        .add_systems(Update, (pause_in_background, anchor_to_safe_area));
+}
+
+// Where a file the game writes for itself should go. Desktop builds keep these next to the game;
+// on Android only the app's own storage can be written to.
+pub fn data_path(relative: &str) -> PathBuf {
+    #[cfg(target_os = "android")]
+    if let Some(dir) = bevy::winit::ANDROID_APP
+        .get()
+        .and_then(|android_app| android_app.internal_data_path())
+    {
+        return dir.join(relative);
+    }
+    PathBuf::from(relative)
+}
+
+fn pause_in_background(
+    mut commands: Commands,
+    mut lifecycle_events: EventReader<AppLifecycle>,
+    mut time: ResMut<Time<Virtual>>,
+    sink_query: Query<(Entity, &AudioSink)>,
+    paused_query: Query<(Entity, &AudioSink), With<PausedInBackground>>,
+) {
+    for event in lifecycle_events.read() {
+        match event {
+            AppLifecycle::WillSuspend | AppLifecycle::Suspended => {
+                time.pause();
+                for (entity, sink) in sink_query.iter() {
+                    if !sink.is_paused() {
+                        sink.pause();
+                        commands.entity(entity).insert(PausedInBackground);
+                    }
+                }
+            }
+            AppLifecycle::WillResume | AppLifecycle::Running => {
+                time.unpause();
+                // Only what was playing before, so sounds the game paused itself stay paused
+                for (entity, sink) in paused_query.iter() {
+                    sink.play();
+                    commands.entity(entity).remove::<PausedInBackground>();
+                }
+            }
+            AppLifecycle::Idle => {}
+        }
+    }
+}
+
+// Android reports the part of the window apps can use without being covered. Other platforms
+// have nothing covering the window, so their safe area stays empty.
+#[cfg(target_os = "android")]
+fn measure_safe_area(
+    mut resize_events: EventReader<WindowResized>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut safe_area: ResMut<SafeArea>,
+) {
+    if resize_events.read().last().is_none() {
+        return;
+    }
+    if let (Ok(window), Some(android_app)) =
+        (window_query.get_single(), bevy::winit::ANDROID_APP.get())
+    {
+        let content = android_app.content_rect();
+        let scale = window.scale_factor();
+        safe_area.set_if_neq(SafeArea {
+            top: content.top.max(0) as f32 / scale,
+            right: (window.physical_width() as i32 - content.right).max(0) as f32 / scale,
+            bottom: (window.physical_height() as i32 - content.bottom).max(0) as f32 / scale,
+            left: content.left.max(0) as f32 / scale,
+        });
+    }
+}
+
+fn anchor_to_safe_area(
+    safe_area: Res<SafeArea>,
+    mut anchor_query: Query<(Ref<SafeAnchor>, &mut Style)>,
+) {
+    for (anchor, mut style) in anchor_query.iter_mut() {
+        if !safe_area.is_changed() && !anchor.is_added() {
+            continue;
+        }
+        if let Some(top) = anchor.top {
+            style.top = Val::Px(top + safe_area.top);
+        }
+        if let Some(right) = anchor.right {
+            style.right = Val::Px(right + safe_area.right);
+        }
+        if let Some(bottom) = anchor.bottom {
+            style.bottom = Val::Px(bottom + safe_area.bottom);
+        }
+        if let Some(left) = anchor.left {
+            style.left = Val::Px(left + safe_area.left);
+        }
+    }
+}
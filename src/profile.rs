@@ -0,0 +1,500 @@
+// Player profiles, so people sharing a machine each keep their own progress, stats, unlocks and
+// settings. Every profile is a folder under `PROFILES_DIR` holding its own save and settings
+// files, and `PROFILE_LIST` names them and remembers which was played last. That one is active
+// from launch, so its window settings apply as soon as the window opens. After loading, a picker
+// lets the player choose who's playing, or make, rename and delete profiles.
+use std::fs;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::ButtonState;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::GameState;
+use crate::leaderboard::Leaderboards;
+use crate::platform::data_path;
+use crate::save::load_save;
+use crate::speedrun::BestTimes;
+use crate::telemetry::Telemetry;
+use crate::theme::Theme;
+use crate::window_config::WindowConfig;
+
+const PROFILES_DIR: &str = "profiles";
+const PROFILE_LIST: &str = "profiles/profiles.ron";
+
+// Where progress and settings were kept before there were profiles, and where they go in the
+// first profile
+const LEGACY_FILES: [(&str, &str); 2] = [
+    ("saves/save.ron", "save.ron"),
+    ("settings.ron", "settings.ron"),
+];
+
+const FIRST_PROFILE_NAME: &str = "Player 1";
+const MAX_NAME_LENGTH: usize = 20;
+
+const DELETE_BUTTON: Color = Color::srgb(0.5, 0.15, 0.15);
+
+// The folder of the profile being played
+static ACTIVE_DIR: RwLock<String> = RwLock::new(String::new());
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct Profile {
+    name: String,
+    // Named by number rather than by the player's name, so renaming never moves files
+    dir: String,
+}
+
+#[derive(Resource, Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct Profiles {
+    // In the order they were made
+    list: Vec<Profile>,
+    // Folder of the profile played last
+    last: String,
+    // Numbers the folders, so a deleted profile's folder is never reused
+    made: u32,
+}
+
+// Typing a name for a new profile, or a new name for the profile in this folder
+#[derive(Clone)]
+enum NameTarget {
+    New,
+    Rename(String),
+}
+
+// What the picker is in the middle of, if anything
+#[derive(Resource, Default)]
+struct Picker {
+    naming: Option<(NameTarget, String)>,
+    // Folder of the profile whose delete button was pressed once and waits for a second press
+    confirm_delete: Option<String>,
+}
+
+#[derive(Component, Clone)]
+enum ProfileAction {
+    Pick(String),
+    Rename(String),
+    Delete(String),
+    New,
+}
+
+// Holds the rows, which are rebuilt whenever the profiles or the picker change
+#[derive(Component)]
+struct ProfileRows;
+
+// A file of the active profile
+pub fn profile_path(file: &str) -> PathBuf {
+    data_path(PROFILES_DIR)
+        .join(active_profile_dir())
+        .join(file)
+}
+
+pub fn active_profile_dir() -> String {
+    ACTIVE_DIR.read().map(|dir| dir.clone()).unwrap_or_default()
+}
+
+fn set_active_profile_dir(dir: &str) {
+    if let Ok(mut active) = ACTIVE_DIR.write() {
+        *active = dir.to_string();
+    }
+}
+
+impl Profiles {
+    // Read the list and make the profile played last active. The first time, the one profile
+    // is made from whatever was saved before there were profiles.
+    pub fn load() -> Self {
+        let mut profiles: Self = fs::read_to_string(data_path(PROFILE_LIST))
+            .ok()
+            .and_then(|contents| ron::from_str(&contents).ok())
+            .unwrap_or_default();
+        if profiles.list.is_empty() {
+            let dir = profiles.create(FIRST_PROFILE_NAME.to_string());
+            for (old, new) in LEGACY_FILES {
+                // Nothing to move on a fresh install
+                let _ = fs::rename(data_path(old), data_path(PROFILES_DIR).join(&dir).join(new));
+            }
+            profiles.last = dir;
+            profiles.save();
+        }
+        if !profiles
+            .list
+            .iter()
+            .any(|profile| profile.dir == profiles.last)
+        {
+            profiles.last = profiles.list[0].dir.clone();
+        }
+        set_active_profile_dir(&profiles.last);
+        profiles
+    }
+
+    fn save(&self) {
+        let contents = match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(contents) => contents,
+            Err(err) => {
+                warn!("Could not serialize the profile list: {err}");
+                return;
+            }
+        };
+        if let Err(err) = fs::create_dir_all(data_path(PROFILES_DIR))
+            .and_then(|_| fs::write(data_path(PROFILE_LIST), contents))
+        {
+            warn!("Could not write the profile list: {err}");
+        }
+    }
+
+    // Returns the new profile's folder
+    fn create(&mut self, name: String) -> String {
+        self.made += 1;
+        let dir = format!("profile{}", self.made);
+        if let Err(err) = fs::create_dir_all(data_path(PROFILES_DIR).join(&dir)) {
+            warn!("Could not make a folder for profile {name}: {err}");
+        }
+        self.list.push(Profile {
+            name,
+            dir: dir.clone(),
+        });
+        dir
+    }
+
+    fn rename(&mut self, dir: &str, name: String) {
+        if let Some(profile) = self.list.iter_mut().find(|profile| profile.dir == dir) {
+            profile.name = name;
+        }
+    }
+
+    // The name of the profile being played
+    pub fn active_name(&self) -> &str {
+        self.list
+            .iter()
+            .find(|profile| profile.dir == self.last)
+            .map_or("", |profile| profile.name.as_str())
+    }
+
+    // The last profile can't be deleted, so there's always one to play
+    fn delete(&mut self, dir: &str) {
+        if self.list.len() <= 1 {
+            return;
+        }
+        self.list.retain(|profile| profile.dir != dir);
+        if let Err(err) = fs::remove_dir_all(data_path(PROFILES_DIR).join(dir)) {
+            warn!("Could not delete the profile's files: {err}");
+        }
+        if self.last == dir {
+            self.last = self.list[0].dir.clone();
+        }
+    }
+}
+
+pub fn profile_plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Profiles), profile_setup)
+        .add_systems(
+            Update,
+            (type_name, profile_buttons, rebuild_rows)
+                .chain()
+                .run_if(in_state(GameState::Profiles)),
+        )
+        .add_systems(OnExit(GameState::Profiles), close_picker);
+}
+
+fn profile_setup(mut commands: Commands, theme: Res<Theme>) {
+    commands.init_resource::<Picker>();
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(20.0),
+                    ..default()
+                },
+                ..default()
+            },
+            StateScoped(GameState::Profiles),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Who's playing?",
+                theme.text_style(50.0),
+            ));
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(10.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                ProfileRows,
+            ));
+        });
+}
+
+fn close_picker(mut commands: Commands) {
+    commands.remove_resource::<Picker>();
+}
+
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    label: &str,
+    width: f32,
+    color: Color,
+    action: ProfileAction,
+    theme: &Theme,
+) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(width),
+                    padding: UiRect::vertical(Val::Px(12.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: color.into(),
+                ..default()
+            },
+            action,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(label, theme.text_style(30.0)));
+        });
+}
+
+// The name being typed, with a caret
+fn name_text(parent: &mut ChildBuilder, name: &str, theme: &Theme) {
+    parent.spawn(TextBundle::from_section(
+        format!("{name}_"),
+        theme.text_style(30.0),
+    ));
+}
+
+fn rebuild_rows(
+    mut commands: Commands,
+    profiles: Res<Profiles>,
+    picker: Res<Picker>,
+    rows_query: Query<Entity, With<ProfileRows>>,
+    theme: Res<Theme>,
+) {
+    if !profiles.is_changed() && !picker.is_changed() {
+        return;
+    }
+    let Ok(rows) = rows_query.get_single() else {
+        return;
+    };
+    let row_style = Style {
+        align_items: AlignItems::Center,
+        column_gap: Val::Px(10.0),
+        ..default()
+    };
+    let can_delete = profiles.list.len() > 1;
+
+    commands
+        .entity(rows)
+        .despawn_descendants()
+        .with_children(|parent| {
+            for profile in &profiles.list {
+                parent
+                    .spawn(NodeBundle {
+                        style: row_style.clone(),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        match &picker.naming {
+                            Some((NameTarget::Rename(dir), name)) if *dir == profile.dir => {
+                                name_text(parent, name, &theme);
+                                return;
+                            }
+                            _ => {}
+                        }
+                        spawn_button(
+                            parent,
+                            &profile.name,
+                            360.0,
+                            theme.button,
+                            ProfileAction::Pick(profile.dir.clone()),
+                            &theme,
+                        );
+                        spawn_button(
+                            parent,
+                            "Rename",
+                            150.0,
+                            theme.button,
+                            ProfileAction::Rename(profile.dir.clone()),
+                            &theme,
+                        );
+                        if can_delete {
+                            let confirming = picker.confirm_delete.as_ref() == Some(&profile.dir);
+                            spawn_button(
+                                parent,
+                                if confirming { "Sure?" } else { "Delete" },
+                                150.0,
+                                if confirming {
+                                    DELETE_BUTTON
+                                } else {
+                                    theme.button
+                                },
+                                ProfileAction::Delete(profile.dir.clone()),
+                                &theme,
+                            );
+                        }
+                    });
+            }
+
+            match &picker.naming {
+                Some((NameTarget::New, name)) => name_text(parent, name, &theme),
+                _ => spawn_button(
+                    parent,
+                    "New profile",
+                    360.0,
+                    theme.button,
+                    ProfileAction::New,
+                    &theme,
+                ),
+            }
+            if picker.naming.is_some() {
+                parent.spawn(TextBundle::from_section(
+                    "Enter to save, Esc to cancel",
+                    theme.text_style(20.0),
+                ));
+            }
+        });
+}
+
+fn type_name(
+    mut key_events: EventReader<KeyboardInput>,
+    mut picker: ResMut<Picker>,
+    mut profiles: ResMut<Profiles>,
+) {
+    let Some((target, mut name)) = picker.naming.clone() else {
+        key_events.clear();
+        return;
+    };
+    for event in key_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Enter => {
+                let name = name.trim().to_string();
+                if name.is_empty() {
+                    continue;
+                }
+                match &target {
+                    NameTarget::New => {
+                        profiles.create(name);
+                    }
+                    NameTarget::Rename(dir) => profiles.rename(dir, name),
+                }
+                profiles.save();
+                picker.naming = None;
+                return;
+            }
+            Key::Escape => {
+                picker.naming = None;
+                return;
+            }
+            Key::Backspace => {
+                name.pop();
+            }
+            Key::Space if name.chars().count() < MAX_NAME_LENGTH => name.push(' '),
+            Key::Character(typed) => {
+                for character in typed.chars().filter(|character| !character.is_control()) {
+                    if name.chars().count() < MAX_NAME_LENGTH {
+                        name.push(character);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    if picker.naming.as_ref().map(|(_, typed)| typed) != Some(&name) {
+        picker.naming = Some((target, name));
+    }
+}
+
+fn profile_buttons(
+    mut commands: Commands,
+    mut interaction_query: Query<
+        (&Interaction, &ProfileAction, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    mut picker: ResMut<Picker>,
+    mut profiles: ResMut<Profiles>,
+    mut game_state: ResMut<NextState<GameState>>,
+    theme: Res<Theme>,
+) {
+    for (interaction, action, mut color) in interaction_query.iter_mut() {
+        let resting = match action {
+            ProfileAction::Delete(dir) if picker.confirm_delete.as_ref() == Some(dir) => {
+                DELETE_BUTTON
+            }
+            _ => theme.button,
+        };
+        match *interaction {
+            Interaction::Pressed => {}
+            Interaction::Hovered => {
+                *color = theme.button_hovered.into();
+                continue;
+            }
+            Interaction::None => {
+                *color = resting.into();
+                continue;
+            }
+        }
+        // Typing a name takes the keyboard, so finish that first
+        if picker.naming.is_some() {
+            continue;
+        }
+
+        match action {
+            ProfileAction::Pick(dir) => {
+                profiles.last = dir.clone();
+                profiles.save();
+                set_active_profile_dir(dir);
+                // Everything kept per profile is read again from the chosen one
+                commands.add(load_save);
+                commands.remove_resource::<WindowConfig>();
+                commands.insert_resource(WindowConfig::load());
+                commands.remove_resource::<Telemetry>();
+                commands.insert_resource(Telemetry::load());
+                commands.remove_resource::<BestTimes>();
+                commands.insert_resource(BestTimes::load());
+                commands.remove_resource::<Leaderboards>();
+                commands.insert_resource(Leaderboards::load());
+                game_state.set(GameState::Splash);
+            }
+            ProfileAction::Rename(dir) => {
+                let name = profiles
+                    .list
+                    .iter()
+                    .find(|profile| profile.dir == *dir)
+                    .map(|profile| profile.name.clone())
+                    .unwrap_or_default();
+                picker.confirm_delete = None;
+                picker.naming = Some((NameTarget::Rename(dir.clone()), name));
+            }
+            ProfileAction::Delete(dir) => {
+                if picker.confirm_delete.as_ref() == Some(dir) {
+                    picker.confirm_delete = None;
+                    profiles.delete(dir);
+                    profiles.save();
+                } else {
+                    picker.confirm_delete = Some(dir.clone());
+                }
+            }
+            ProfileAction::New => {
+                picker.confirm_delete = None;
+                picker.naming = Some((NameTarget::New, String::new()));
+            }
+        }
+        return;
+    }
+}
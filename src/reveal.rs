@@ -0,0 +1,228 @@
+// Reveal ceremony for newly earned cards. The cards are dealt face down, then flip over one by
+// one with a burst of sparkles and a sound, and are only set aside for the deck once shown.
+use bevy::prelude::*;
+
+use super::Volume;
+use crate::combat::CardType;
+use crate::deck::Deck;
+use crate::sfx::play_sfx;
+use crate::shared_assets::{SharedAssets, CARD_BACK, SPARKLE};
+use crate::tween::{animate_tweens, Ease, Tween, TweenFinished, TweenTarget};
+
+// Seconds before the first flip, between flips, for each half of a flip, and after the last
+const FIRST_FLIP_DELAY: f32 = 0.8;
+const FLIP_INTERVAL: f32 = 0.7;
+const HALF_FLIP: f32 = 0.15;
+const HOLD_AFTER_LAST: f32 = 1.5;
+
+const SPARKLES_PER_CARD: usize = 8;
+
+// Send this to show earned cards to the player and hand them to the deck
+#[derive(Event)]
+pub struct CardsEarned(pub Vec<CardType>);
+
+// The ceremony overlay. Anything that wants to wait for the reveal to finish can check for it.
+#[derive(Component)]
+pub struct RevealCeremony {
+    cards_left: usize,
+    hold: Timer,
+}
+
+#[derive(Component)]
+struct RevealCard {
+    card_type: CardType,
+    face_up: bool,
+}
+
+pub fn reveal_plugin(app: &mut App) {
+    // The ceremony is spawned in PostUpdate so it already exists the frame after the cards
+    // were earned, and nothing waiting on it can slip past
+    app.add_event::<CardsEarned>()
+        .add_systems(PostUpdate, start_reveal)
+        .add_systems(
+            Update,
+            (flip_revealed_cards.after(animate_tweens), finish_reveal).chain(),
+        );
+}
+
+fn start_reveal(
+    mut commands: Commands,
+    mut earned: EventReader<CardsEarned>,
+    shared_assets: Res<SharedAssets>,
+) {
+    for CardsEarned(cards) in earned.read() {
+        if cards.is_empty() {
+            continue;
+        }
+
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        position_type: PositionType::Absolute,
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        row_gap: Val::Px(30.0),
+                        ..default()
+                    },
+                    background_color: Color::srgba(0.0, 0.0, 0.0, 0.8).into(),
+                    z_index: ZIndex::Global(25),
+                    ..default()
+                },
+                RevealCeremony {
+                    cards_left: cards.len(),
+                    hold: Timer::from_seconds(HOLD_AFTER_LAST, TimerMode::Once),
+                },
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    "New cards!",
+                    TextStyle {
+                        font_size: 60.0,
+                        color: Color::srgb(1.0, 0.84, 0.0),
+                        ..default()
+                    },
+                ));
+
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            column_gap: Val::Px(30.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        for (i, card_type) in cards.iter().enumerate() {
+                            let (image, atlas) = shared_assets.icon(CARD_BACK);
+                            parent.spawn((
+                                ImageBundle {
+                                    style: Style {
+                                        width: Val::Px(180.0),
+                                        height: Val::Px(250.0),
+                                        ..default()
+                                    },
+                                    image,
+                                    ..default()
+                                },
+                                atlas,
+                                RevealCard {
+                                    card_type: *card_type,
+                                    face_up: false,
+                                },
+                                // First half of the flip: fold the back away
+                                Tween::new(
+                                    TweenTarget::Scale {
+                                        from: Vec3::ONE,
+                                        to: Vec3::new(0.0, 1.0, 1.0),
+                                    },
+                                    HALF_FLIP,
+                                    Ease::In,
+                                )
+                                .with_delay(FIRST_FLIP_DELAY + FLIP_INTERVAL * i as f32),
+                            ));
+                        }
+                    });
+            });
+    }
+}
+
+// When a card's back has folded away, show its face, unfold it, and celebrate
+fn flip_revealed_cards(
+    mut commands: Commands,
+    mut finished: EventReader<TweenFinished>,
+    mut card_query: Query<(&mut RevealCard, &mut UiImage, &mut TextureAtlas)>,
+    mut ceremony_query: Query<&mut RevealCeremony>,
+    mut deck: ResMut<Deck>,
+    shared_assets: Res<SharedAssets>,
+    volume: Res<Volume>,
+) {
+    for TweenFinished(entity) in finished.read() {
+        let Ok((mut card, mut image, mut atlas)) = card_query.get_mut(*entity) else {
+            continue;
+        };
+        if card.face_up {
+            continue;
+        }
+        card.face_up = true;
+        let (face, face_atlas) = shared_assets.card(card.card_type);
+        image.texture = face.texture;
+        *atlas = face_atlas;
+        deck.earn(card.card_type);
+
+        commands
+            .entity(*entity)
+            .insert(Tween::new(
+                TweenTarget::Scale {
+                    from: Vec3::new(0.0, 1.0, 1.0),
+                    to: Vec3::ONE,
+                },
+                HALF_FLIP,
+                Ease::Out,
+            ))
+            .with_children(|parent| spawn_sparkles(parent, &shared_assets));
+
+        play_sfx(
+            &mut commands,
+            &volume,
+            shared_assets.card_flip_sound.clone(),
+            PlaybackSettings::DESPAWN,
+        );
+
+        for mut ceremony in ceremony_query.iter_mut() {
+            ceremony.cards_left = ceremony.cards_left.saturating_sub(1);
+        }
+    }
+}
+
+// Little crystals that burst out from the middle of the card and vanish
+fn spawn_sparkles(parent: &mut ChildBuilder, shared_assets: &SharedAssets) {
+    let center = Vec2::new(80.0, 115.0);
+    for i in 0..SPARKLES_PER_CARD {
+        let angle = i as f32 / SPARKLES_PER_CARD as f32 * std::f32::consts::TAU;
+        let distance = 110.0 + 30.0 * (i % 2) as f32;
+        let (image, atlas) = shared_assets.icon(SPARKLE);
+        parent.spawn((
+            ImageBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(center.x),
+                    top: Val::Px(center.y),
+                    width: Val::Px(20.0),
+                    height: Val::Px(20.0),
+                    ..default()
+                },
+                image: image.with_color(Color::srgb(1.0, 0.95, 0.6)),
+                ..default()
+            },
+            atlas,
+            Tween::new(
+                TweenTarget::Offset {
+                    from: center,
+                    to: center + Vec2::from_angle(angle) * distance,
+                },
+                0.6,
+                Ease::Out,
+            )
+            .despawn_when_done(),
+        ));
+    }
+}
+
+fn finish_reveal(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut ceremony_query: Query<(Entity, &mut RevealCeremony)>,
+) {
+    for (entity, mut ceremony) in ceremony_query.iter_mut() {
+        if ceremony.cards_left > 0 {
+            continue;
+        }
+        if ceremony.hold.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,67 @@
+// Seeded random numbers for a run. Everything random in a run (shuffles, redraws) comes from
+// here, so a run can be replayed from its seed, and the state is kept in the save.
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+pub struct RunRng {
+    seed: u64,
+    state: u64,
+}
+
+impl Default for RunRng {
+    fn default() -> Self {
+        Self::from_seed(0)
+    }
+}
+
+impl RunRng {
+    pub fn from_seed(seed: u64) -> Self {
+        Self { seed, state: seed }
+    }
+
+    // The seed the run started from
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // Start over with a seed taken from the clock, for a new run
+    pub fn reseed(&mut self) {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+        *self = Self::from_seed(seed);
+    }
+
+    // SplitMix64
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // A number in `0..bound`
+    pub fn below(&mut self, bound: usize) -> usize {
+        if bound == 0 {
+            return 0;
+        }
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    // A number in `0.0..1.0`
+    pub fn fraction(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    // Fisher-Yates
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i + 1);
+            items.swap(i, j);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+// Keywords card text is written in. Each one is defined here once, with the line a tooltip
+// explains it with and the hooks combat resolves it through, so a card only lists its keywords
+// and every card sharing one behaves the same way.
+use crate::combat::CardType;
+
+// What damage to a vulnerable target is multiplied by
+pub const VULNERABLE_MULTIPLIER: f32 = 1.5;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Keyword {
+    // Sets the target burning for this much
+    Burn(f32),
+    // Deals nothing once a card of this type has been played the same turn
+    Fizzle(CardType),
+    // Hits this much harder as the first card of a turn
+    FirstStrike(f32),
+    // Gone for the rest of the fight once played
+    Exhaust,
+    // Leaves the target taking more damage for this many turns
+    Vulnerable(u32),
+}
+
+impl Keyword {
+    pub fn name(self) -> String {
+        match self {
+            Keyword::Burn(amount) => format!("Burn {amount}"),
+            Keyword::Fizzle(_) => "Fizzle".to_string(),
+            Keyword::FirstStrike(_) => "First Strike".to_string(),
+            Keyword::Exhaust => "Exhaust".to_string(),
+            Keyword::Vulnerable(turns) => format!("Vulnerable {turns}"),
+        }
+    }
+
+    pub fn description(self) -> String {
+        match self {
+            Keyword::Burn(amount) => format!(
+                "The target loses {amount} health at the start of each enemy turn, ignoring \
+                 block, and one less each time after"
+            ),
+            Keyword::Fizzle(spoiler) => {
+                format!("Deals nothing if a {spoiler:?} card was played earlier this turn")
+            }
+            Keyword::FirstStrike(bonus) => format!("+{bonus} damage as the first card of a turn"),
+            Keyword::Exhaust => "Leaves the deck for the rest of the fight once played".to_string(),
+            Keyword::Vulnerable(turns) => format!(
+                "The target takes {}% more damage for the next {turns} turns",
+                (VULNERABLE_MULTIPLIER - 1.0) * 100.0
+            ),
+        }
+    }
+
+    // The keyword as a tooltip explains it
+    pub fn explain(self) -> String {
+        format!("{}: {}", self.name(), self.description())
+    }
+
+    // Damage a card with the keyword deals, given what it would deal without it, whether it's
+    // the first card of the turn and the cards played before it this turn
+    pub fn damage(self, damage: f32, first_card: bool, played_this_turn: &[CardType]) -> f32 {
+        match self {
+            Keyword::FirstStrike(bonus) if first_card => damage + bonus,
+            _ if self.fizzles(played_this_turn) => 0.0,
+            _ => damage,
+        }
+    }
+
+    // Whether the keyword cancels its card after the cards played before it this turn
+    pub fn fizzles(self, played_this_turn: &[CardType]) -> bool {
+        matches!(self, Keyword::Fizzle(spoiler) if played_this_turn.contains(&spoiler))
+    }
+
+    // Burn a hit with the keyword sets on its target
+    pub fn burn(self) -> f32 {
+        match self {
+            Keyword::Burn(amount) => amount,
+            _ => 0.0,
+        }
+    }
+
+    // Turns of Vulnerable a hit with the keyword leaves on its target
+    pub fn vulnerable(self) -> u32 {
+        match self {
+            Keyword::Vulnerable(turns) => turns,
+            _ => 0,
+        }
+    }
+
+    pub fn exhausts(self) -> bool {
+        self == Keyword::Exhaust
+    }
+}
+
+// A burn ticking at the start of an enemy turn. Returns the damage it deals and the burn left.
+pub fn tick_burn(burn: f32) -> (f32, f32) {
+    (burn, (burn - 1.0).max(0.0))
+}
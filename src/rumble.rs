@@ -0,0 +1,116 @@
+// Gamepad rumble. The player's cards landing give a light buzz and enemy hits a heavier jolt,
+// both stronger the more damage was done, and hovering a card gives a short tick. How strong it
+// all is, or whether it happens at all, is picked on the controls settings screen.
+use std::time::Duration;
+
+use bevy::input::gamepad::{GamepadRumbleIntensity, GamepadRumbleRequest};
+use bevy::prelude::*;
+
+use super::GameState;
+use crate::combat::{CardType, FightEvent};
+
+// Damage that rumbles as hard as the setting allows
+const FULL_RUMBLE_DAMAGE: f32 = 20.0;
+const HIT_DURATION: Duration = Duration::from_millis(250);
+
+const TICK_STRENGTH: f32 = 0.15;
+const TICK_DURATION: Duration = Duration::from_millis(40);
+
+// One of the controls settings. It will be a resource in the app
+#[derive(Resource, Component, Default, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum RumbleIntensity {
+    Off,
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl RumbleIntensity {
+    pub const ALL: [RumbleIntensity; 4] = [
+        RumbleIntensity::Off,
+        RumbleIntensity::Low,
+        RumbleIntensity::Medium,
+        RumbleIntensity::High,
+    ];
+
+    fn scale(self) -> f32 {
+        match self {
+            RumbleIntensity::Off => 0.0,
+            RumbleIntensity::Low => 0.4,
+            RumbleIntensity::Medium => 0.7,
+            RumbleIntensity::High => 1.0,
+        }
+    }
+}
+
+pub fn rumble_plugin(app: &mut App) {
+    app.init_resource::<RumbleIntensity>().add_systems(
+        Update,
+        (rumble_on_hits, tick_on_card_hover)
+            // Nobody is holding the gamepad while the demo plays
+            .run_if(not(in_state(GameState::Demo))),
+    );
+}
+
+// Rumble every connected gamepad. `strong` drives the heavy motor, otherwise the light one.
+fn rumble(
+    gamepads: &Gamepads,
+    requests: &mut EventWriter<GamepadRumbleRequest>,
+    strength: f32,
+    strong: bool,
+    duration: Duration,
+) {
+    if strength <= 0.0 {
+        return;
+    }
+    let intensity = if strong {
+        GamepadRumbleIntensity::strong_motor(strength)
+    } else {
+        GamepadRumbleIntensity::weak_motor(strength)
+    };
+    for gamepad in gamepads.iter() {
+        requests.send(GamepadRumbleRequest::Add {
+            gamepad,
+            intensity,
+            duration,
+        });
+    }
+}
+
+fn rumble_on_hits(
+    mut fight_events: EventReader<FightEvent>,
+    intensity: Res<RumbleIntensity>,
+    gamepads: Res<Gamepads>,
+    mut requests: EventWriter<GamepadRumbleRequest>,
+) {
+    for event in fight_events.read() {
+        let (damage, strong) = match *event {
+            FightEvent::DamageDealt(_, damage) => (damage, false),
+            FightEvent::DamageTaken(damage) => (damage, true),
+            _ => continue,
+        };
+        let strength = (damage / FULL_RUMBLE_DAMAGE).min(1.0) * intensity.scale();
+        rumble(&gamepads, &mut requests, strength, strong, HIT_DURATION);
+    }
+}
+
+fn tick_on_card_hover(
+    card_query: Query<&Interaction, (Changed<Interaction>, With<CardType>)>,
+    intensity: Res<RumbleIntensity>,
+    gamepads: Res<Gamepads>,
+    mut requests: EventWriter<GamepadRumbleRequest>,
+) {
+    if card_query
+        .iter()
+        .any(|interaction| *interaction == Interaction::Hovered)
+    {
+        rumble(
+            &gamepads,
+            &mut requests,
+            TICK_STRENGTH * intensity.scale(),
+            false,
+            TICK_DURATION,
+        );
+    }
+}
@@ -0,0 +1,145 @@
+// Passive effects that last the rest of a run, like blessings won from fights. They are applied
+// as each fight is set up, kept in the save, and listed in the run status panel of the pause menu.
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::combat::CardType;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RunEffect {
+    // Won in the forest in Chapter 1
+    Vigor,
+    // Won with the rune in the fort in Chapter 2
+    RuneWard,
+    // A rare drop from the stronger enemies
+    HeartStone,
+    // Dropped now and then in the forest
+    Windcharm,
+    // Burns up to bring the player back the first time they fall. Found at the pool.
+    PhoenixFeather,
+    // Daily run modifiers, one or two of them picked by the date
+    ToughEnemies,
+    Frail,
+    AirStart,
+    Wealthy,
+}
+
+const VIGOR_HEALTH_PER_CHAPTER: f32 = 2.0;
+const RUNE_WARD_BLOCK: f32 = 5.0;
+const HEART_STONE_HEALTH: f32 = 10.0;
+const TOUGH_ENEMIES_HEALTH: f32 = 0.25;
+const FRAIL_HEALTH: f32 = 20.0;
+pub const AIR_START_CARDS: usize = 2;
+pub const WEALTHY_GOLD: u32 = 25;
+// Share of the maximum health the Phoenix Feather brings the player back with
+pub const PHOENIX_REVIVE_SHARE: f32 = 0.3;
+
+impl RunEffect {
+    pub fn name(self) -> &'static str {
+        match self {
+            RunEffect::Vigor => "Blessing of Vigor",
+            RunEffect::RuneWard => "Rune Ward",
+            RunEffect::HeartStone => "Heart Stone",
+            RunEffect::Windcharm => "Windcharm",
+            RunEffect::PhoenixFeather => "Phoenix Feather",
+            RunEffect::ToughEnemies => "Tough Enemies",
+            RunEffect::Frail => "Frail",
+            RunEffect::AirStart => "Tailwind",
+            RunEffect::Wealthy => "Wealthy",
+        }
+    }
+
+    pub fn description(self) -> String {
+        match self {
+            RunEffect::Vigor => {
+                format!("+{VIGOR_HEALTH_PER_CHAPTER} max health for each chapter reached")
+            }
+            RunEffect::RuneWard => format!("Start each fight with {RUNE_WARD_BLOCK} block"),
+            RunEffect::HeartStone => format!("+{HEART_STONE_HEALTH} max health"),
+            RunEffect::Windcharm => "Air cards cost 0".to_string(),
+            RunEffect::PhoenixFeather => format!(
+                "Once, rise again with {}% health instead of dying",
+                PHOENIX_REVIVE_SHARE * 100.0
+            ),
+            RunEffect::ToughEnemies => {
+                format!("All enemies have +{}% health", TOUGH_ENEMIES_HEALTH * 100.0)
+            }
+            RunEffect::Frail => format!("-{FRAIL_HEALTH} max health"),
+            RunEffect::AirStart => format!("Start with {AIR_START_CARDS} Air cards"),
+            RunEffect::Wealthy => format!("Start with {WEALTHY_GOLD} gold"),
+        }
+    }
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone, Default, Debug)]
+pub struct RunEffects(Vec<RunEffect>);
+
+impl RunEffects {
+    pub fn effects(&self) -> &[RunEffect] {
+        &self.0
+    }
+
+    // Returns whether the effect is new, as each is only had once
+    pub fn add(&mut self, effect: RunEffect) -> bool {
+        if self.0.contains(&effect) {
+            return false;
+        }
+        self.0.push(effect);
+        true
+    }
+
+    // Use up an effect that only works once. Returns whether there was one to use.
+    pub fn consume(&mut self, effect: RunEffect) -> bool {
+        let had = self.0.contains(&effect);
+        self.0.retain(|kept| *kept != effect);
+        had
+    }
+
+    // Drop every effect, for a new run
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    // Extra maximum health in a fight of the given chapter, counted from 1
+    pub fn bonus_health(&self, chapter: u32) -> f32 {
+        self.0
+            .iter()
+            .map(|effect| match effect {
+                RunEffect::Vigor => VIGOR_HEALTH_PER_CHAPTER * chapter as f32,
+                RunEffect::HeartStone => HEART_STONE_HEALTH,
+                RunEffect::Frail => -FRAIL_HEALTH,
+                _ => 0.0,
+            })
+            .sum()
+    }
+
+    // Block the player has when a fight begins
+    pub fn starting_block(&self) -> f32 {
+        self.0
+            .iter()
+            .map(|effect| match effect {
+                RunEffect::RuneWard => RUNE_WARD_BLOCK,
+                _ => 0.0,
+            })
+            .sum()
+    }
+
+    // The energy a card takes to play, once every effect has had its say
+    pub fn card_cost(&self, card_type: CardType) -> u32 {
+        self.0
+            .iter()
+            .fold(card_type.cost(), |cost, effect| match (effect, card_type) {
+                (RunEffect::Windcharm, CardType::Air) => 0,
+                _ => cost,
+            })
+    }
+
+    // What every enemy's health is multiplied by
+    pub fn enemy_health_scale(&self) -> f32 {
+        if self.0.contains(&RunEffect::ToughEnemies) {
+            1.0 + TOUGH_ENEMIES_HEALTH
+        } else {
+            1.0
+        }
+    }
+}
@@ -0,0 +1,429 @@
+// Progress that outlives a single run, stored as RON by every configured `SaveBackend`. Each
+// profile has its own, loaded when the profile is picked. If the backends disagree, the newest
+// save is used and the player is asked whether to keep it before the menu opens.
+use std::cmp::Reverse;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::GameState;
+use crate::combat::{FightSnapshot, PlayerState};
+use crate::cutscene::CutsceneProgress;
+use crate::deck::Deck;
+use crate::difficulty::Difficulty;
+use crate::ending::Ending;
+use crate::endless::EndlessRun;
+use crate::gold::Gold;
+use crate::inventory::Inventory;
+use crate::rng::RunRng;
+use crate::run_effects::RunEffects;
+use crate::save_backend::backends;
+use crate::story::StoryFlags;
+use crate::theme::Theme;
+use crate::ui;
+
+#[derive(Resource, Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct SaveData {
+    // Every ending the player has reached, in the order they first saw them
+    pub endings_seen: Vec<Ending>,
+    pub story_flags: StoryFlags,
+    // Every story flag set on any run, which unlocks chapters to start from
+    pub progress: StoryFlags,
+    pub inventory: Inventory,
+    pub gold: Gold,
+    pub deck: Deck,
+    pub rng: RunRng,
+    pub difficulty: Difficulty,
+    pub run_effects: RunEffects,
+    pub player: PlayerState,
+    // The date of the daily run this run is, if it is one
+    pub daily: Option<String>,
+    // The endless run under way, if this run is one
+    pub endless: Option<EndlessRun>,
+    // The furthest wave reached in endless mode, on any run
+    pub best_wave: u32,
+    // A fight the player quit part-way through, resumed from the menu
+    pub fight: Option<FightSnapshot>,
+    // An intro the player quit part-way through, resumed from the menu at the same line
+    pub cutscene: Option<CutsceneProgress>,
+    // Seconds since the Unix epoch when this was last written, to tell the newest save apart
+    pub saved_at: u64,
+    // The first fight's tutorial has been finished once, so it isn't shown again
+    pub tutorial_seen: bool,
+}
+
+// The save with the run it holds, as the resources the game plays from. Starting a new run and
+// picking between conflicting saves each change all of it at once.
+#[derive(SystemParam)]
+pub struct SavedRun<'w> {
+    pub save_data: ResMut<'w, SaveData>,
+    pub story_flags: ResMut<'w, StoryFlags>,
+    pub inventory: ResMut<'w, Inventory>,
+    pub gold: ResMut<'w, Gold>,
+    pub deck: ResMut<'w, Deck>,
+    pub rng: ResMut<'w, RunRng>,
+    pub difficulty: ResMut<'w, Difficulty>,
+    pub run_effects: ResMut<'w, RunEffects>,
+    pub player_state: ResMut<'w, PlayerState>,
+}
+
+// Two backends held different saves. `SaveData` holds the newest until the player decides.
+#[derive(Resource)]
+pub struct SaveConflict {
+    newest_from: &'static str,
+    newest_saved_at: u64,
+    other_from: &'static str,
+    other: SaveData,
+}
+
+#[derive(Component)]
+struct ConflictPrompt;
+
+#[derive(Component, Clone, Copy)]
+enum ConflictChoice {
+    KeepNewest,
+    UseOther,
+}
+
+impl SaveData {
+    // Missing or unreadable saves are skipped, and with none at all the game starts fresh
+    // rather than stopping. Returns the newest save, and any older one that differs from it.
+    fn load() -> (Self, Option<SaveConflict>) {
+        let mut saves: Vec<(&'static str, SaveData)> = backends()
+            .iter()
+            .filter_map(|backend| {
+                let contents = match backend.read() {
+                    Ok(contents) => contents?,
+                    Err(err) => {
+                        warn!("Could not read the save from {}: {err}", backend.name());
+                        return None;
+                    }
+                };
+                match ron::from_str(&contents) {
+                    Ok(data) => Some((backend.name(), data)),
+                    Err(err) => {
+                        warn!(
+                            "Could not understand the save from {}: {err}",
+                            backend.name()
+                        );
+                        None
+                    }
+                }
+            })
+            .collect();
+        saves.sort_by_key(|(_, data)| Reverse(data.saved_at));
+
+        let mut saves = saves.into_iter();
+        let Some((newest_from, newest)) = saves.next() else {
+            return (Self::default(), None);
+        };
+        let conflict = saves
+            .find(|(_, data)| data.saved_at != newest.saved_at)
+            .map(|(other_from, other)| SaveConflict {
+                newest_from,
+                newest_saved_at: newest.saved_at,
+                other_from,
+                other,
+            });
+        (newest, conflict)
+    }
+
+    pub fn save(&mut self) {
+        self.saved_at = now();
+        let contents = match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(contents) => contents,
+            Err(err) => {
+                warn!("Could not serialize save data: {err}");
+                return;
+            }
+        };
+        for backend in backends() {
+            if let Err(err) = backend.write(&contents) {
+                warn!("Could not write the save to {}: {err}", backend.name());
+            }
+        }
+    }
+
+    pub fn record_ending(&mut self, ending: Ending) {
+        if !self.endings_seen.contains(&ending) {
+            self.endings_seen.push(ending);
+        }
+        self.save();
+    }
+}
+
+pub fn save_plugin(app: &mut App) {
+    // Filled in from the save once a profile is picked
+    app.init_resource::<StoryFlags>()
+        .init_resource::<Inventory>()
+        .init_resource::<Gold>()
+        .init_resource::<Deck>()
+        .init_resource::<RunRng>()
+        .init_resource::<Difficulty>()
+        .init_resource::<RunEffects>()
+        .init_resource::<PlayerState>()
+        .init_resource::<SaveData>()
+        .add_systems(
+            Update,
+            (
+                persist_story_flags,
+                persist_inventory,
+                persist_gold,
+                persist_deck,
+                persist_rng,
+                persist_difficulty,
+                persist_run_effects,
+                persist_player_state,
+            )
+                // The demo borrows the run's resources and puts them back afterwards
+                .run_if(not(in_state(GameState::Demo))),
+        )
+        .add_systems(
+            OnEnter(GameState::Splash),
+            spawn_conflict_prompt.run_if(resource_exists::<SaveConflict>),
+        )
+        .add_systems(
+            Update,
+            resolve_conflict.run_if(resource_exists::<SaveConflict>),
+        );
+}
+
+// Load the active profile's save, replacing whatever progress came before it
+pub fn load_save(world: &mut World) {
+    let (save_data, conflict) = SaveData::load();
+    match conflict {
+        Some(conflict) => world.insert_resource(conflict),
+        None => {
+            world.remove_resource::<SaveConflict>();
+        }
+    }
+    // Removed first so they count as added rather than changed, and aren't written straight back
+    replace_resource(world, save_data.story_flags.clone());
+    replace_resource(world, save_data.inventory.clone());
+    replace_resource(world, save_data.gold);
+    replace_resource(world, save_data.deck.clone());
+    replace_resource(world, save_data.rng.clone());
+    replace_resource(world, save_data.difficulty.clone());
+    replace_resource(world, save_data.run_effects.clone());
+    replace_resource(world, save_data.player.clone());
+    replace_resource(world, save_data);
+}
+
+fn replace_resource<R: Resource>(world: &mut World, resource: R) {
+    world.remove_resource::<R>();
+    world.insert_resource(resource);
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+// How long ago a save was written, in the largest whole unit
+fn describe_age(saved_at: u64) -> String {
+    let seconds = now().saturating_sub(saved_at);
+    let (amount, unit) = match seconds {
+        0..=59 => return "just now".to_string(),
+        60..=3599 => (seconds / 60, "minute"),
+        3600..=86399 => (seconds / 3600, "hour"),
+        _ => (seconds / 86400, "day"),
+    };
+    let plural = if amount == 1 { "" } else { "s" };
+    format!("{amount} {unit}{plural} ago")
+}
+
+// Ask which save to carry on with. The splash screen waits until the player has answered.
+fn spawn_conflict_prompt(mut commands: Commands, conflict: Res<SaveConflict>, theme: Res<Theme>) {
+    let text_style = theme.text_style(30.0);
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(20.0),
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.9).into(),
+                z_index: ZIndex::Global(30),
+                ..default()
+            },
+            ConflictPrompt,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Your saves don't match",
+                TextStyle {
+                    font_size: 50.0,
+                    ..text_style.clone()
+                },
+            ));
+            parent.spawn(
+                TextBundle::from_section(
+                    format!(
+                        "The save on {} is from {}.\nThe save on {} is from {}.",
+                        conflict.newest_from,
+                        describe_age(conflict.newest_saved_at),
+                        conflict.other_from,
+                        describe_age(conflict.other.saved_at),
+                    ),
+                    text_style.clone(),
+                )
+                .with_text_justify(JustifyText::Center),
+            );
+
+            for (choice, label) in [
+                (
+                    ConflictChoice::KeepNewest,
+                    format!("Use the newer save ({})", conflict.newest_from),
+                ),
+                (
+                    ConflictChoice::UseOther,
+                    format!("Use the older save ({})", conflict.other_from),
+                ),
+            ] {
+                ui::styled_button(
+                    parent,
+                    &theme,
+                    Style {
+                        padding: UiRect::axes(Val::Px(30.0), Val::Px(15.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    label,
+                    text_style.clone(),
+                    choice,
+                );
+            }
+        });
+}
+
+// Load the chosen save and write it back everywhere, so the backends agree again
+fn resolve_conflict(
+    mut commands: Commands,
+    mut interaction_query: ui::ChangedActionButtons<ConflictChoice>,
+    prompt_query: Query<Entity, With<ConflictPrompt>>,
+    mut conflict: ResMut<SaveConflict>,
+    saved_run: SavedRun,
+    theme: Res<Theme>,
+) {
+    let SavedRun {
+        mut save_data,
+        mut story_flags,
+        mut inventory,
+        mut gold,
+        mut deck,
+        mut rng,
+        mut difficulty,
+        mut run_effects,
+        mut player_state,
+    } = saved_run;
+    for (interaction, choice, mut color) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {}
+            Interaction::Hovered => {
+                *color = theme.button_hovered.into();
+                continue;
+            }
+            Interaction::None => {
+                *color = theme.button.into();
+                continue;
+            }
+        }
+
+        if let ConflictChoice::UseOther = choice {
+            let other = std::mem::take(&mut conflict.other);
+            *story_flags = other.story_flags.clone();
+            *inventory = other.inventory.clone();
+            *gold = other.gold;
+            *deck = other.deck.clone();
+            *rng = other.rng.clone();
+            *difficulty = other.difficulty.clone();
+            *run_effects = other.run_effects.clone();
+            *player_state = other.player.clone();
+            *save_data = other;
+        }
+        save_data.save();
+
+        for prompt in prompt_query.iter() {
+            commands.entity(prompt).despawn_recursive();
+        }
+        commands.remove_resource::<SaveConflict>();
+        return;
+    }
+}
+
+// Write the story flags out whenever they change
+fn persist_story_flags(story_flags: Res<StoryFlags>, mut save_data: ResMut<SaveData>) {
+    if story_flags.is_changed() && !story_flags.is_added() {
+        save_data.story_flags = story_flags.clone();
+        save_data.progress.merge(&story_flags);
+        save_data.save();
+    }
+}
+
+// Write the inventory out whenever an item is picked up or the run is reset
+fn persist_inventory(inventory: Res<Inventory>, mut save_data: ResMut<SaveData>) {
+    if inventory.is_changed() && !inventory.is_added() {
+        save_data.inventory = inventory.clone();
+        save_data.save();
+    }
+}
+
+// Write the purse out whenever gold is won or spent
+fn persist_gold(gold: Res<Gold>, mut save_data: ResMut<SaveData>) {
+    if gold.is_changed() && !gold.is_added() {
+        save_data.gold = *gold;
+        save_data.save();
+    }
+}
+
+// Write the deck out whenever a card is added or the run is reset
+fn persist_deck(deck: Res<Deck>, mut save_data: ResMut<SaveData>) {
+    if deck.is_changed() && !deck.is_added() {
+        save_data.deck = deck.clone();
+        save_data.save();
+    }
+}
+
+// Write the difficulty out after each fight and when the player switches it on or off
+fn persist_difficulty(difficulty: Res<Difficulty>, mut save_data: ResMut<SaveData>) {
+    if difficulty.is_changed() && !difficulty.is_added() {
+        save_data.difficulty = difficulty.clone();
+        save_data.save();
+    }
+}
+
+// Write the random state out whenever it is used, so a loaded run keeps the same luck
+fn persist_rng(rng: Res<RunRng>, mut save_data: ResMut<SaveData>) {
+    if rng.is_changed() && !rng.is_added() {
+        save_data.rng = rng.clone();
+        save_data.save();
+    }
+}
+
+// Write the run's effects out whenever one is gained or the run is reset
+fn persist_run_effects(run_effects: Res<RunEffects>, mut save_data: ResMut<SaveData>) {
+    if run_effects.is_changed() && !run_effects.is_added() {
+        save_data.run_effects = run_effects.clone();
+        save_data.save();
+    }
+}
+
+// Write the player's health out after each fight won and when the run is reset
+fn persist_player_state(player_state: Res<PlayerState>, mut save_data: ResMut<SaveData>) {
+    if player_state.is_changed() && !player_state.is_added() {
+        save_data.player = player_state.clone();
+        save_data.save();
+    }
+}
@@ -0,0 +1,232 @@
+// Where save files are kept. The local file in the active profile's folder is always written;
+// setting `SAVE_URL_VAR` to an `http://host[:port]/path` address also keeps a copy on a server,
+// so a save can follow the player to another machine. Each profile's copy lives at
+// `path/<profile folder>`, and the server only has to answer GET and PUT on those.
+use std::env;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::profile::{active_profile_dir, profile_path};
+
+const SAVE_FILE: &str = "save.ron";
+
+pub const SAVE_URL_VAR: &str = "SPRITED_TOWARDS_SAVE_URL";
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Short, as the save is read before the first frame and an unreachable server holds the game
+// up for this long
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub trait SaveBackend: Send + Sync {
+    // Shown to the player when two saves disagree
+    fn name(&self) -> &'static str;
+
+    // The stored save, or `None` if there isn't one yet
+    fn read(&self) -> io::Result<Option<String>>;
+
+    fn write(&self, contents: &str) -> io::Result<()>;
+}
+
+// Every configured backend, local first
+pub fn backends() -> &'static [Box<dyn SaveBackend>] {
+    static BACKENDS: OnceLock<Vec<Box<dyn SaveBackend>>> = OnceLock::new();
+    BACKENDS.get_or_init(|| {
+        let mut backends: Vec<Box<dyn SaveBackend>> = vec![Box::new(LocalFile)];
+        if let Ok(url) = env::var(SAVE_URL_VAR) {
+            match HttpBackend::from_url(&url) {
+                Some(backend) => backends.push(Box::new(backend)),
+                None => warn!("Ignoring {SAVE_URL_VAR}: {url} is not an http:// address"),
+            }
+        }
+        backends
+    })
+}
+
+pub struct LocalFile;
+
+impl SaveBackend for LocalFile {
+    fn name(&self) -> &'static str {
+        "this computer"
+    }
+
+    fn read(&self) -> io::Result<Option<String>> {
+        match fs::read_to_string(profile_path(SAVE_FILE)) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn write(&self, contents: &str) -> io::Result<()> {
+        let path = profile_path(SAVE_FILE);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, contents)
+    }
+}
+
+// Plain HTTP, with uploads handed to a background thread so a slow server never stalls a frame.
+// Uploads go out one at a time, in order, so an older save can't land after a newer one.
+pub struct HttpBackend {
+    address: Address,
+    // Each upload goes to the path of the profile it was saved for, even if another profile is
+    // picked before it is sent
+    uploads: Mutex<Sender<(String, String)>>,
+}
+
+// A server reached over plain HTTP
+#[derive(Clone)]
+pub struct Address {
+    host: String,
+    port: u16,
+    path: String,
+}
+
+impl HttpBackend {
+    fn from_url(url: &str) -> Option<Self> {
+        let address = Address::from_url(url)?;
+
+        let (sender, receiver) = mpsc::channel::<(String, String)>();
+        let upload_address = address.clone();
+        thread::spawn(move || {
+            while let Ok(upload) = receiver.recv() {
+                // Only the newest of any queued saves for each profile matters
+                let mut queued = vec![upload];
+                while let Ok((path, contents)) = receiver.try_recv() {
+                    queued.retain(|(queued_path, _)| *queued_path != path);
+                    queued.push((path, contents));
+                }
+                for (path, contents) in queued {
+                    match upload_address.request("PUT", &path, &contents) {
+                        Ok((200..=299, _)) => {}
+                        Ok((status, _)) => warn!("Cloud save upload failed with status {status}"),
+                        Err(err) => warn!("Cloud save upload failed: {err}"),
+                    }
+                }
+            }
+        });
+
+        Some(Self {
+            address,
+            uploads: Mutex::new(sender),
+        })
+    }
+}
+
+impl Address {
+    // Only `http://host[:port]/path` addresses are understood
+    pub fn from_url(url: &str) -> Option<Self> {
+        let rest = url.strip_prefix("http://")?;
+        let (authority, path) = match rest.find('/') {
+            Some(index) => rest.split_at(index),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.split_once(':') {
+            Some((host, port)) => (host, port.parse().ok()?),
+            None => (authority, 80),
+        };
+        if host.is_empty() {
+            return None;
+        }
+        Some(Self {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        })
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    // Where the active profile's save is kept on the server
+    fn profile_path(&self) -> String {
+        format!(
+            "{}/{}",
+            self.path.trim_end_matches('/'),
+            active_profile_dir()
+        )
+    }
+
+    pub fn request(&self, method: &str, path: &str, body: &str) -> io::Result<(u16, String)> {
+        self.send(method, path, "text/plain", body.as_bytes())
+    }
+
+    // Each address the host resolves to in turn, giving up on one after `CONNECT_TIMEOUT` rather
+    // than the system's own timeout, which can be minutes
+    fn connect(&self) -> io::Result<TcpStream> {
+        let mut last_error = io::Error::new(io::ErrorKind::NotFound, "host has no address");
+        for address in (self.host.as_str(), self.port).to_socket_addrs()? {
+            match TcpStream::connect_timeout(&address, CONNECT_TIMEOUT) {
+                Ok(stream) => return Ok(stream),
+                Err(error) => last_error = error,
+            }
+        }
+        Err(last_error)
+    }
+
+    // HTTP/1.0 with a closed connection, so the body is simply the rest of the response
+    pub fn send(
+        &self,
+        method: &str,
+        path: &str,
+        content_type: &str,
+        body: &[u8],
+    ) -> io::Result<(u16, String)> {
+        let mut stream = self.connect()?;
+        stream.set_read_timeout(Some(HTTP_TIMEOUT))?;
+        stream.set_write_timeout(Some(HTTP_TIMEOUT))?;
+        write!(
+            stream,
+            "{method} {path} HTTP/1.0\r\nHost: {}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n\r\n",
+            self.host,
+            body.len(),
+        )?;
+        stream.write_all(body)?;
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+        let malformed = || io::Error::new(io::ErrorKind::InvalidData, "malformed HTTP response");
+        let (head, body) = response.split_once("\r\n\r\n").ok_or_else(malformed)?;
+        let status = head
+            .split_whitespace()
+            .nth(1)
+            .and_then(|status| status.parse().ok())
+            .ok_or_else(malformed)?;
+        Ok((status, body.to_string()))
+    }
+}
+
+impl SaveBackend for HttpBackend {
+    fn name(&self) -> &'static str {
+        "the cloud"
+    }
+
+    fn read(&self) -> io::Result<Option<String>> {
+        match self
+            .address
+            .request("GET", &self.address.profile_path(), "")?
+        {
+            (200, body) => Ok(Some(body)),
+            (404, _) => Ok(None),
+            (status, _) => Err(io::Error::other(format!("server answered {status}"))),
+        }
+    }
+
+    fn write(&self, contents: &str) -> io::Result<()> {
+        self.uploads
+            .lock()
+            .map_err(|_| io::Error::other("upload queue poisoned"))?
+            .send((self.address.profile_path(), contents.to_string()))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "upload thread stopped"))
+    }
+}
@@ -0,0 +1,44 @@
+// Sound effects, played at the volume picked in the sound settings. Every one-off sound goes
+// through `play_sfx` rather than spawning its own `AudioBundle`, so none of them ignores the
+// setting. Music and the ambient loops keep their own levels.
+use bevy::audio::Volume as Loudness;
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+
+use super::Volume;
+
+// The loudest step the sound settings offer
+const MAX_VOLUME: u32 = 9;
+
+// How loud effects play at this setting, from silent to full
+fn sfx_level(volume: &Volume) -> f32 {
+    volume.0.min(MAX_VOLUME) as f32 / MAX_VOLUME as f32
+}
+
+// Muting is turning the volume all the way down
+fn muted(volume: &Volume) -> bool {
+    volume.0 == 0
+}
+
+// Plays `source` with `settings` scaled to the sound settings. Nothing is spawned while muted;
+// otherwise the sound's entity is returned for anything that needs to find it again.
+pub fn play_sfx<'a>(
+    commands: &'a mut Commands,
+    volume: &Volume,
+    source: Handle<AudioSource>,
+    settings: PlaybackSettings,
+) -> Option<EntityCommands<'a>> {
+    if muted(volume) {
+        return None;
+    }
+    Some(commands.spawn(AudioBundle {
+        source,
+        settings: scaled(settings, volume),
+    }))
+}
+
+// The settings of a sound played again, scaled as it would be if spawned now
+pub fn scaled(settings: PlaybackSettings, volume: &Volume) -> PlaybackSettings {
+    let level = settings.volume.get() * sfx_level(volume);
+    settings.with_volume(Loudness::new(level))
+}
@@ -0,0 +1,163 @@
+// Assets used all over the game: the cutscene font, the typewriter clicks, the sound of a card
+// flipping over and the card icons.
+// They are loaded once before anything else starts and kept for the whole game, so screens
+// share the same handles instead of loading their own on entry.
+//
+// Once the card icons have loaded they are packed into one atlas texture, and card UI is drawn
+// from that instead of binding a texture per card.
+use std::collections::HashMap;
+
+use bevy::asset::LoadState;
+use bevy::prelude::*;
+
+use crate::combat::CardType;
+
+const FONT: &str = "joystix monospace.otf";
+const TYPEWRITER_SOUND: &str = "sounds/typewriter.ogg";
+const TYPEWRITER_SOFT_SOUND: &str = "sounds/typewriter_soft.wav";
+const CARD_FLIP_SOUND: &str = "sounds/breakout_collision.ogg";
+
+pub const CARD_BACK: &str = "textures/Game Icons/card.png";
+pub const SPARKLE: &str = "textures/Game Icons/crystal.png";
+
+// The card faces are large, so the atlas needs more room than the builder's default
+const ATLAS_MAX_SIZE: u32 = 4096;
+// Keeps neighbouring icons from bleeding into each other when scaled down
+const ATLAS_PADDING: u32 = 2;
+
+#[derive(Resource)]
+pub struct SharedAssets {
+    pub font: Handle<Font>,
+    pub typewriter_sound: Handle<AudioSource>,
+    // A quieter click for punctuation
+    pub typewriter_soft_sound: Handle<AudioSource>,
+    pub card_flip_sound: Handle<AudioSource>,
+    icons: Icons,
+}
+
+enum Icons {
+    // Each icon from its own file, until they have all loaded
+    Loading(HashMap<&'static str, Handle<Image>>),
+    // Packing failed, so the files are drawn on their own for good
+    Unpacked(HashMap<&'static str, Handle<Image>>),
+    Packed {
+        image: Handle<Image>,
+        layout: Handle<TextureAtlasLayout>,
+        indices: HashMap<&'static str, usize>,
+    },
+}
+
+impl SharedAssets {
+    // A UI image of one of the card icons. Before the atlas is packed the atlas part points at
+    // no layout, which draws the whole of the icon's own file.
+    pub fn icon(&self, path: &'static str) -> (UiImage, TextureAtlas) {
+        match &self.icons {
+            Icons::Loading(files) | Icons::Unpacked(files) => {
+                (UiImage::new(files[path].clone()), TextureAtlas::default())
+            }
+            Icons::Packed {
+                image,
+                layout,
+                indices,
+            } => (
+                UiImage::new(image.clone()),
+                TextureAtlas {
+                    layout: layout.clone(),
+                    index: indices[path],
+                },
+            ),
+        }
+    }
+
+    pub fn card(&self, card_type: CardType) -> (UiImage, TextureAtlas) {
+        self.icon(card_type.texture())
+    }
+
+    // Whether the icons are done loading, packed or not
+    pub fn icons_ready(&self) -> bool {
+        !matches!(self.icons, Icons::Loading(_))
+    }
+}
+
+pub fn shared_assets_plugin(app: &mut App) {
+    app.add_systems(PreStartup, load_shared_assets)
+        .add_systems(Update, pack_icons);
+}
+
+fn packed_icons() -> impl Iterator<Item = &'static str> {
+    CardType::ALL
+        .into_iter()
+        .chain([CardType::Curse])
+        .map(CardType::texture)
+        .chain([CARD_BACK, SPARKLE])
+}
+
+fn load_shared_assets(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(SharedAssets {
+        font: asset_server.load(FONT),
+        typewriter_sound: asset_server.load(TYPEWRITER_SOUND),
+        typewriter_soft_sound: asset_server.load(TYPEWRITER_SOFT_SOUND),
+        card_flip_sound: asset_server.load(CARD_FLIP_SOUND),
+        icons: Icons::Loading(
+            packed_icons()
+                .map(|path| (path, asset_server.load(path)))
+                .collect(),
+        ),
+    });
+}
+
+// Pack the icons as soon as they are all loaded. Their own files are let go afterwards.
+fn pack_icons(
+    asset_server: Res<AssetServer>,
+    mut shared_assets: ResMut<SharedAssets>,
+    mut images: ResMut<Assets<Image>>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+) {
+    let Icons::Loading(files) = &shared_assets.icons else {
+        return;
+    };
+    let failed = files.values().any(|handle| {
+        matches!(
+            asset_server.get_load_state(handle),
+            Some(LoadState::Failed(_))
+        )
+    });
+    if failed {
+        warn!("Could not load every card icon, so they won't be packed into an atlas");
+        shared_assets.icons = Icons::Unpacked(files.clone());
+        return;
+    }
+    let loaded: Option<Vec<(AssetId<Image>, &Image)>> = files
+        .values()
+        .map(|handle| Some((handle.id(), images.get(handle)?)))
+        .collect();
+    let Some(loaded) = loaded else {
+        return;
+    };
+
+    let mut builder = TextureAtlasBuilder::default();
+    builder
+        .max_size(UVec2::splat(ATLAS_MAX_SIZE))
+        .padding(UVec2::splat(ATLAS_PADDING));
+    for (id, image) in loaded {
+        builder.add_texture(Some(id), image);
+    }
+    let (layout, image) = match builder.build() {
+        Ok(atlas) => atlas,
+        Err(err) => {
+            warn!("Could not pack the card icons into an atlas: {err:?}");
+            shared_assets.icons = Icons::Unpacked(files.clone());
+            return;
+        }
+    };
+    let indices = files
+        .iter()
+        .filter_map(|(path, handle)| Some((*path, layout.get_texture_index(handle)?)))
+        .collect();
+
+    shared_assets.icons = Icons::Packed {
+        image: images.add(image),
+        layout: layouts.add(layout),
+        indices,
+    };
+}
@@ -0,0 +1,276 @@
+// Shop between the chapters of a run, after the deck screen and before the campfire. For now the
+// merchant sells one service: taking a card out of the deck for good. Each removal bought costs
+// more than the last, and the count is kept on the deck so the save and a new run handle it.
+use bevy::prelude::*;
+
+use super::GameState;
+use crate::deck::{Deck, MIN_DECK_SIZE};
+use crate::gold::{Gold, GOLD_COLOR};
+use crate::shared_assets::SharedAssets;
+use crate::theme::Theme;
+use crate::ui;
+
+const SHOP_BACKGROUND: &str = "textures/forest_fort.png";
+
+// The first removal's price, and how much each one bought adds to the next
+const REMOVAL_BASE_PRICE: u32 = 20;
+const REMOVAL_PRICE_STEP: u32 = 15;
+
+const CARD_WIDTH: f32 = 120.0;
+const CARD_HEIGHT: f32 = 167.0;
+
+#[derive(Component)]
+struct OnShopScreen;
+
+// Everything over the background, rebuilt whenever the deck, purse or visit changes
+#[derive(Component)]
+struct ShopContent;
+
+#[derive(Component, Clone, Copy)]
+enum ShopButton {
+    Removal,
+    // The deck card at this index, while browsing for one to remove
+    Card(usize),
+    Back,
+    Leave,
+}
+
+#[derive(Resource)]
+struct ShopVisit {
+    browsing: bool,
+    message: Option<String>,
+}
+
+pub fn shop_plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Shop), shop_setup)
+        .add_systems(
+            Update,
+            (rebuild_shop_screen, shop_buttons)
+                .chain()
+                .run_if(in_state(GameState::Shop)),
+        );
+}
+
+fn removal_price(deck: &Deck) -> u32 {
+    REMOVAL_BASE_PRICE + REMOVAL_PRICE_STEP * deck.removals_bought()
+}
+
+fn shop_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(ShopVisit {
+        browsing: false,
+        message: None,
+    });
+
+    commands.spawn((
+        ImageBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            image: UiImage::new(asset_server.load(SHOP_BACKGROUND)),
+            ..default()
+        },
+        OnShopScreen,
+        StateScoped(GameState::Shop),
+    ));
+}
+
+fn rebuild_shop_screen(
+    mut commands: Commands,
+    visit: Res<ShopVisit>,
+    deck: Res<Deck>,
+    gold: Res<Gold>,
+    screen: ui::ScreenContent<OnShopScreen, ShopContent>,
+    shared_assets: Res<SharedAssets>,
+    theme: Res<Theme>,
+) {
+    if !visit.is_changed() && !deck.is_changed() && !gold.is_changed() {
+        return;
+    }
+    let Some(root) = screen.clear(&mut commands) else {
+        return;
+    };
+
+    let text_style = theme.text_style(30.0);
+    let price = removal_price(&deck);
+
+    commands.entity(root).with_children(|parent| {
+        parent
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        row_gap: Val::Px(20.0),
+                        ..default()
+                    },
+                    background_color: Color::srgba(0.0, 0.0, 0.0, 0.6).into(),
+                    ..default()
+                },
+                ShopContent,
+            ))
+            .with_children(|parent| {
+                parent.spawn(ui::title_text(&theme, "Merchant"));
+                parent.spawn(TextBundle::from_section(
+                    format!("{} gold", gold.amount()),
+                    TextStyle {
+                        color: GOLD_COLOR,
+                        ..text_style.clone()
+                    },
+                ));
+
+                if visit.browsing {
+                    parent.spawn(TextBundle::from_section(
+                        format!("Pick a card to remove for {price} gold"),
+                        text_style.clone(),
+                    ));
+                    // The whole deck, wrapped onto as many rows as it takes
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                max_width: Val::Percent(80.0),
+                                flex_wrap: FlexWrap::Wrap,
+                                justify_content: JustifyContent::Center,
+                                column_gap: Val::Px(16.0),
+                                row_gap: Val::Px(16.0),
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            for (index, card_type) in deck.cards().iter().enumerate() {
+                                let (image, atlas) = shared_assets.card(*card_type);
+                                parent
+                                    .spawn((
+                                        ui::button(
+                                            &theme,
+                                            Style {
+                                                padding: UiRect::all(Val::Px(8.0)),
+                                                ..default()
+                                            },
+                                        ),
+                                        ShopButton::Card(index),
+                                    ))
+                                    .with_children(|parent| {
+                                        parent.spawn((
+                                            ImageBundle {
+                                                style: Style {
+                                                    width: Val::Px(CARD_WIDTH),
+                                                    height: Val::Px(CARD_HEIGHT),
+                                                    ..default()
+                                                },
+                                                image,
+                                                ..default()
+                                            },
+                                            atlas,
+                                        ));
+                                    });
+                            }
+                        });
+                    spawn_button(parent, ShopButton::Back, "Back", &text_style, &theme);
+                } else {
+                    spawn_button(
+                        parent,
+                        ShopButton::Removal,
+                        &format!("Remove a card\n{price} gold"),
+                        &text_style,
+                        &theme,
+                    );
+                    spawn_button(parent, ShopButton::Leave, "Leave", &text_style, &theme);
+                }
+
+                if let Some(message) = &visit.message {
+                    parent.spawn(TextBundle::from_section(
+                        message.clone(),
+                        TextStyle {
+                            font_size: 24.0,
+                            color: Color::srgb(1.0, 0.4, 0.3),
+                            ..default()
+                        },
+                    ));
+                }
+            });
+    });
+}
+
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    button: ShopButton,
+    label: &str,
+    text_style: &TextStyle,
+    theme: &Theme,
+) {
+    parent
+        .spawn((
+            ui::button(
+                theme,
+                Style {
+                    width: Val::Px(280.0),
+                    padding: UiRect::all(Val::Px(16.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+            ),
+            button,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(label, text_style.clone())
+                    .with_text_justify(JustifyText::Center),
+            );
+        });
+}
+
+fn shop_buttons(
+    mut interaction_query: ui::ChangedActionButtons<ShopButton>,
+    mut visit: ResMut<ShopVisit>,
+    mut deck: ResMut<Deck>,
+    mut gold: ResMut<Gold>,
+    mut game_state: ResMut<NextState<GameState>>,
+    theme: Res<Theme>,
+) {
+    for (interaction, button, mut color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {}
+            Interaction::Hovered => {
+                *color = theme.button_hovered.into();
+                continue;
+            }
+            Interaction::None => {
+                *color = theme.button.into();
+                continue;
+            }
+        }
+
+        visit.message = None;
+        match *button {
+            ShopButton::Removal => {
+                if deck.cards().len() <= MIN_DECK_SIZE {
+                    visit.message =
+                        Some(format!("Your deck needs at least {} cards", MIN_DECK_SIZE));
+                } else if gold.amount() < removal_price(&deck) {
+                    visit.message = Some("You can't afford that".to_string());
+                } else {
+                    visit.browsing = true;
+                }
+            }
+            ShopButton::Card(index) => {
+                let price = removal_price(&deck);
+                if gold.spend(price) && !deck.buy_removal(index) {
+                    // Refund the gold if the deck couldn't spare the card after all
+                    gold.earn(price);
+                }
+                visit.browsing = false;
+            }
+            ShopButton::Back => visit.browsing = false,
+            ShopButton::Leave => game_state.set(GameState::Campfire),
+        }
+        return;
+    }
+}
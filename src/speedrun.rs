@@ -0,0 +1,283 @@
+// An optional speedrun timer. When switched on from the stats screen, starting a New Game starts
+// a clock in the corner that takes a split as each chapter is finished. It runs on the game's own
+// clock, so it stops while the game is paused and doesn't count loading. A finished run's time
+// goes in a table of best times kept in the profile's folder, shown over the ending, and is sent
+// to the online leaderboard when there is one.
+use std::fs;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::GameState;
+use crate::chapters::CHAPTERS;
+use crate::gold::GOLD_COLOR;
+use crate::leaderboard::{Board, Leaderboards};
+use crate::platform::SafeAnchor;
+use crate::profile::{profile_path, Profiles};
+use crate::theme::Theme;
+
+const BEST_TIMES_FILE: &str = "best_times.ron";
+
+// Finished runs kept in the table
+const BEST_TIMES_KEPT: usize = 5;
+
+// The timer setting and the fastest finished runs, quickest first
+#[derive(Resource, Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct BestTimes {
+    pub timer_enabled: bool,
+    runs: Vec<FinishedRun>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct FinishedRun {
+    total: f32,
+    // Time on the clock as each chapter was finished
+    splits: Vec<f32>,
+}
+
+impl BestTimes {
+    // The active profile's table. An unreadable one starts again, with the timer off.
+    pub fn load() -> Self {
+        fs::read_to_string(profile_path(BEST_TIMES_FILE))
+            .ok()
+            .and_then(|contents| ron::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let contents = match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(contents) => contents,
+            Err(err) => {
+                warn!("Could not serialize the best times: {err}");
+                return;
+            }
+        };
+        if let Err(err) = fs::write(profile_path(BEST_TIMES_FILE), contents) {
+            warn!("Could not write {BEST_TIMES_FILE}: {err}");
+        }
+    }
+
+    // Adds a finished run, and returns its place in the table if it made it in
+    fn record(&mut self, run: FinishedRun) -> Option<usize> {
+        let place = self
+            .runs
+            .iter()
+            .position(|best| run.total < best.total)
+            .unwrap_or(self.runs.len());
+        self.runs.insert(place, run);
+        self.runs.truncate(BEST_TIMES_KEPT);
+        (place < BEST_TIMES_KEPT).then_some(place)
+    }
+}
+
+// The clock of a timed run, there from New Game until the run is over
+#[derive(Resource, Default)]
+pub struct SpeedrunTimer {
+    elapsed: f32,
+    splits: Vec<f32>,
+}
+
+#[derive(Component)]
+struct TimerOverlay;
+
+#[derive(Component)]
+struct TimerText;
+
+pub fn speedrun_plugin(app: &mut App) {
+    app.init_resource::<BestTimes>()
+        .add_systems(
+            Update,
+            (
+                spawn_timer_overlay.run_if(resource_added::<SpeedrunTimer>),
+                tick_timer,
+            )
+                .chain()
+                .run_if(resource_exists::<SpeedrunTimer>),
+        )
+        .add_systems(OnEnter(GameState::Menu), stop_timer)
+        .add_systems(
+            OnEnter(GameState::Ending),
+            finish_run.run_if(resource_exists::<SpeedrunTimer>),
+        );
+    // Reaching a chapter's opening finishes the one before it
+    for chapter in CHAPTERS.iter().skip(1) {
+        app.add_systems(
+            OnEnter(chapter.start),
+            take_split.run_if(resource_exists::<SpeedrunTimer>),
+        );
+    }
+}
+
+// Minutes, seconds and hundredths, like 12:04.31
+pub fn format_time(seconds: f32) -> String {
+    let hundredths = (seconds * 100.0) as u32;
+    format!(
+        "{}:{:02}.{:02}",
+        hundredths / 6000,
+        hundredths / 100 % 60,
+        hundredths % 100
+    )
+}
+
+fn spawn_timer_overlay(mut commands: Commands, theme: Res<Theme>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(16.0),
+                    left: Val::Px(16.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.6).into(),
+                z_index: ZIndex::Global(22),
+                ..default()
+            },
+            TimerOverlay,
+            SafeAnchor::bottom_left(16.0, 16.0),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_sections([
+                    TextSection::new(format_time(0.0), theme.text_style(28.0)),
+                    TextSection::new(
+                        "",
+                        TextStyle {
+                            font_size: 18.0,
+                            color: GOLD_COLOR,
+                            ..default()
+                        },
+                    ),
+                ]),
+                TimerText,
+            ));
+        });
+}
+
+fn tick_timer(
+    time: Res<Time>,
+    game_state: Res<State<GameState>>,
+    mut timer: ResMut<SpeedrunTimer>,
+    mut text_query: Query<&mut Text, With<TimerText>>,
+) {
+    if *game_state.get() == GameState::Loading {
+        return;
+    }
+    timer.elapsed += time.delta_seconds();
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = format_time(timer.elapsed);
+    }
+}
+
+fn take_split(mut timer: ResMut<SpeedrunTimer>, mut text_query: Query<&mut Text, With<TimerText>>) {
+    let split = timer.elapsed;
+    timer.splits.push(split);
+    let name = CHAPTERS
+        .get(timer.splits.len() - 1)
+        .map_or("", |chapter| chapter.name);
+    for mut text in text_query.iter_mut() {
+        text.sections[1].value = format!("\n{name}  {}", format_time(split));
+    }
+}
+
+// The last chapter is finished too, so the run is over
+fn finish_run(
+    mut commands: Commands,
+    mut timer: ResMut<SpeedrunTimer>,
+    mut best_times: ResMut<BestTimes>,
+    mut leaderboards: ResMut<Leaderboards>,
+    profiles: Res<Profiles>,
+    overlay_query: Query<Entity, With<TimerOverlay>>,
+    theme: Res<Theme>,
+) {
+    let total = timer.elapsed;
+    timer.splits.push(total);
+    let run = FinishedRun {
+        total,
+        splits: timer.splits.clone(),
+    };
+    let place = best_times.record(run.clone());
+    best_times.save();
+    leaderboards.submit(Board::Speedrun, profiles.active_name(), total);
+    commands.remove_resource::<SpeedrunTimer>();
+    for overlay in overlay_query.iter() {
+        commands.entity(overlay).despawn_recursive();
+    }
+    spawn_summary(&mut commands, &run, &best_times, place, &theme);
+}
+
+fn spawn_summary(
+    commands: &mut Commands,
+    run: &FinishedRun,
+    best_times: &BestTimes,
+    place: Option<usize>,
+    theme: &Theme,
+) {
+    let text_style = theme.text_style(22.0);
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(16.0),
+                    right: Val::Px(16.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(6.0),
+                    padding: UiRect::all(Val::Px(16.0)),
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.7).into(),
+                z_index: ZIndex::Global(22),
+                ..default()
+            },
+            StateScoped(GameState::Ending),
+            SafeAnchor::top_right(16.0, 16.0),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                format!("Run time {}", format_time(run.total)),
+                TextStyle {
+                    font_size: 32.0,
+                    color: GOLD_COLOR,
+                    ..default()
+                },
+            ));
+            for (chapter, split) in CHAPTERS.iter().zip(&run.splits) {
+                parent.spawn(TextBundle::from_section(
+                    format!("{}  {}", chapter.name, format_time(*split)),
+                    text_style.clone(),
+                ));
+            }
+            parent.spawn(TextBundle::from_section(
+                "\nBest times",
+                TextStyle {
+                    font_size: 28.0,
+                    ..text_style.clone()
+                },
+            ));
+            for (i, best) in best_times.runs.iter().enumerate() {
+                parent.spawn(TextBundle::from_section(
+                    format!("{}. {}", i + 1, format_time(best.total)),
+                    TextStyle {
+                        // This run, if it made the table
+                        color: if place == Some(i) {
+                            GOLD_COLOR
+                        } else {
+                            theme.text
+                        },
+                        ..text_style.clone()
+                    },
+                ));
+            }
+        });
+}
+
+// Leaving a run for the menu, finished or not, stops its clock
+fn stop_timer(mut commands: Commands, overlay_query: Query<Entity, With<TimerOverlay>>) {
+    commands.remove_resource::<SpeedrunTimer>();
+    for overlay in overlay_query.iter() {
+        commands.entity(overlay).despawn_recursive();
+    }
+}
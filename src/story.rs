@@ -0,0 +1,47 @@
+// World state that branches the story. Flags are set by victories and events, kept in the save,
+// and read by cutscenes and encounters to pick lines and enemies.
+use std::collections::BTreeSet;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum StoryFlag {
+    // Set when each chapter's fight is won
+    ForestCleared,
+    FortCleared,
+    PoolCleared,
+    RitualBroken,
+    // Set by each wave won in endless mode
+    WaveCleared,
+    // Left the forest fight with less than half health
+    WoundedInForest,
+    // Reacted in time when the twig-snapper leapt out in the forest
+    DodgedTwigSnapper,
+    // Spared the creatures the fort and pool intros put at the player's mercy
+    FreedFortGoblin,
+    FreedPoolSprite,
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone, Default, Debug)]
+pub struct StoryFlags(BTreeSet<StoryFlag>);
+
+impl StoryFlags {
+    pub fn set(&mut self, flag: StoryFlag) {
+        self.0.insert(flag);
+    }
+
+    pub fn is_set(&self, flag: StoryFlag) -> bool {
+        self.0.contains(&flag)
+    }
+
+    // Set every flag set in `other` as well
+    pub fn merge(&mut self, other: &StoryFlags) {
+        self.0.extend(other.0.iter().copied());
+    }
+
+    // Forget everything, for a new run
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+}
@@ -0,0 +1,297 @@
+// Anonymous gameplay numbers to help balance the fights: which chapters get finished, which
+// fights the player dies in, and which cards get played. Nothing is recorded until the player
+// says yes, which each profile is asked once on the splash screen. Totals are kept in the
+// profile's folder, and setting `TELEMETRY_URL_VAR` to an `http://host[:port]/path` address also
+// POSTs each fight's events there. No names, saves or anything else about the player are sent.
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::GameState;
+use crate::combat::{CardType, FightEvent};
+use crate::profile::profile_path;
+use crate::save::SaveConflict;
+use crate::save_backend::Address;
+use crate::theme::Theme;
+use crate::ui;
+
+const TELEMETRY_FILE: &str = "telemetry.ron";
+
+pub const TELEMETRY_URL_VAR: &str = "SPRITED_TOWARDS_TELEMETRY_URL";
+
+#[derive(Resource, Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct Telemetry {
+    // Whether the player agreed to share, or `None` until they've been asked
+    pub consent: Option<bool>,
+    // Fights won and lost, by the chapter they're in
+    pub chapters_completed: BTreeMap<String, u32>,
+    pub deaths: BTreeMap<String, u32>,
+    pub cards_played: BTreeMap<String, u32>,
+    pub runs_finished: u32,
+    // Events since the last fight ended, sent together when the next one does
+    #[serde(skip)]
+    unsent: Vec<TelemetryEvent>,
+}
+
+#[derive(Serialize, Clone, Copy, Debug)]
+enum TelemetryEvent {
+    ChapterCompleted(GameState),
+    Died(GameState),
+    CardPlayed(CardType),
+    RunFinished,
+}
+
+#[derive(Component)]
+struct ConsentPrompt;
+
+#[derive(Component, Clone, Copy)]
+struct ConsentChoice(bool);
+
+impl Telemetry {
+    // The active profile's totals. Unreadable ones start again, and ask again.
+    pub fn load() -> Self {
+        fs::read_to_string(profile_path(TELEMETRY_FILE))
+            .ok()
+            .and_then(|contents| ron::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        let contents = match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(contents) => contents,
+            Err(err) => {
+                warn!("Could not serialize the telemetry: {err}");
+                return;
+            }
+        };
+        if let Err(err) = fs::write(profile_path(TELEMETRY_FILE), contents) {
+            warn!("Could not write {TELEMETRY_FILE}: {err}");
+        }
+    }
+
+    fn record(&mut self, event: TelemetryEvent) {
+        let (totals, key) = match event {
+            TelemetryEvent::ChapterCompleted(state) => {
+                (&mut self.chapters_completed, format!("{state:?}"))
+            }
+            TelemetryEvent::Died(state) => (&mut self.deaths, format!("{state:?}")),
+            TelemetryEvent::CardPlayed(card_type) => {
+                (&mut self.cards_played, format!("{card_type:?}"))
+            }
+            TelemetryEvent::RunFinished => {
+                self.runs_finished += 1;
+                self.unsent.push(event);
+                return;
+            }
+        };
+        *totals.entry(key).or_default() += 1;
+        self.unsent.push(event);
+    }
+}
+
+// The endpoint's upload queue, if one is configured. Uploads go out on a background thread so
+// a slow server never stalls a frame, and ones that fail are dropped.
+fn uploads() -> Option<&'static Mutex<Sender<String>>> {
+    static UPLOADS: OnceLock<Option<Mutex<Sender<String>>>> = OnceLock::new();
+    UPLOADS
+        .get_or_init(|| {
+            let url = env::var(TELEMETRY_URL_VAR).ok()?;
+            let Some(address) = Address::from_url(&url) else {
+                warn!("Ignoring {TELEMETRY_URL_VAR}: {url} is not an http:// address");
+                return None;
+            };
+            let (sender, receiver) = mpsc::channel::<String>();
+            thread::spawn(move || {
+                while let Ok(events) = receiver.recv() {
+                    match address.request("POST", address.path(), &events) {
+                        Ok((200..=299, _)) => {}
+                        Ok((status, _)) => warn!("Telemetry upload failed with status {status}"),
+                        Err(err) => warn!("Telemetry upload failed: {err}"),
+                    }
+                }
+            });
+            Some(Mutex::new(sender))
+        })
+        .as_ref()
+}
+
+pub fn telemetry_plugin(app: &mut App) {
+    app.init_resource::<Telemetry>()
+        .add_systems(
+            Update,
+            (
+                spawn_consent_prompt.run_if(
+                    in_state(GameState::Splash)
+                        .and_then(not(resource_exists::<SaveConflict>))
+                        .and_then(|telemetry: Res<Telemetry>| telemetry.consent.is_none()),
+                ),
+                answer_consent,
+            ),
+        )
+        .add_systems(
+            Update,
+            (record_fights, persist_telemetry)
+                .chain()
+                // The demo's fights say nothing about the player
+                .run_if(not(in_state(GameState::Demo))),
+        )
+        .add_systems(OnEnter(GameState::Ending), record_run_finished);
+}
+
+// Ask once per profile, after any save conflict is settled. The splash screen waits for it.
+fn spawn_consent_prompt(
+    mut commands: Commands,
+    prompt_query: Query<(), With<ConsentPrompt>>,
+    theme: Res<Theme>,
+) {
+    if !prompt_query.is_empty() {
+        return;
+    }
+    let text_style = theme.text_style(30.0);
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(20.0),
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.9).into(),
+                z_index: ZIndex::Global(30),
+                ..default()
+            },
+            ConsentPrompt,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Help balance the game?",
+                TextStyle {
+                    font_size: 50.0,
+                    ..text_style.clone()
+                },
+            ));
+            parent.spawn(
+                TextBundle::from_section(
+                    "The game can count which chapters you finish, where you die and which cards\nyou play, to help tune the fights. Nothing about you is recorded.",
+                    text_style.clone(),
+                )
+                .with_text_justify(JustifyText::Center),
+            );
+
+            for (choice, label) in [(true, "Share"), (false, "Don't share")] {
+                ui::styled_button(parent, &theme, Style {
+                                padding: UiRect::axes(Val::Px(30.0), Val::Px(15.0)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            }, label, text_style.clone(), ConsentChoice(choice));
+            }
+        });
+}
+
+fn answer_consent(
+    mut commands: Commands,
+    mut interaction_query: Query<
+        (&Interaction, &ConsentChoice, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    prompt_query: Query<Entity, With<ConsentPrompt>>,
+    mut telemetry: ResMut<Telemetry>,
+    theme: Res<Theme>,
+) {
+    for (interaction, choice, mut color) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {}
+            Interaction::Hovered => {
+                *color = theme.button_hovered.into();
+                continue;
+            }
+            Interaction::None => {
+                *color = theme.button.into();
+                continue;
+            }
+        }
+
+        telemetry.consent = Some(choice.0);
+        for prompt in prompt_query.iter() {
+            commands.entity(prompt).despawn_recursive();
+        }
+        return;
+    }
+}
+
+fn record_fights(
+    mut fight_events: EventReader<FightEvent>,
+    game_state: Res<State<GameState>>,
+    mut telemetry: ResMut<Telemetry>,
+) {
+    if telemetry.consent != Some(true) {
+        fight_events.clear();
+        return;
+    }
+    for event in fight_events.read() {
+        let fight_over = match *event {
+            FightEvent::CardPlayed(card_type) => {
+                telemetry.record(TelemetryEvent::CardPlayed(card_type));
+                false
+            }
+            FightEvent::Won(_) => {
+                telemetry.record(TelemetryEvent::ChapterCompleted(*game_state.get()));
+                true
+            }
+            FightEvent::Lost => {
+                telemetry.record(TelemetryEvent::Died(*game_state.get()));
+                true
+            }
+            _ => false,
+        };
+        if fight_over {
+            send_unsent(&mut telemetry);
+        }
+    }
+}
+
+fn record_run_finished(mut telemetry: ResMut<Telemetry>) {
+    if telemetry.consent == Some(true) {
+        telemetry.record(TelemetryEvent::RunFinished);
+        send_unsent(&mut telemetry);
+    }
+}
+
+fn send_unsent(telemetry: &mut Telemetry) {
+    let events = std::mem::take(&mut telemetry.unsent);
+    let Some(uploads) = uploads() else {
+        return;
+    };
+    let contents = match ron::to_string(&events) {
+        Ok(contents) => contents,
+        Err(err) => {
+            warn!("Could not serialize the telemetry events: {err}");
+            return;
+        }
+    };
+    if let Ok(uploads) = uploads.lock() {
+        // The upload thread only stops if it panicked, and then there's nowhere to send to
+        let _ = uploads.send(contents);
+    }
+}
+
+// Write the totals out at the end of each fight, and when the player answers the prompt
+fn persist_telemetry(telemetry: Res<Telemetry>) {
+    if telemetry.is_changed() && !telemetry.is_added() && telemetry.unsent.is_empty() {
+        telemetry.save();
+    }
+}
@@ -0,0 +1,183 @@
+// The game run headless for tests: its own plugins on top of just enough of Bevy to run without a
+// GPU, a sound device or an event loop. There's a window entity for screens to lay themselves out
+// against, but nothing draws it. No asset is ever found, so every load fails the way a missing
+// file does in the game, and screens fall back to what they show without it.
+//
+// The game keeps its saves and settings in the working directory, so each test gets an empty
+// one of its own. It's the same for the whole process, so tests take turns.
+use std::env;
+use std::fs;
+use std::ops::{Deref, DerefMut};
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use bevy::asset::AssetPlugin;
+use bevy::input::InputPlugin;
+use bevy::prelude::*;
+use bevy::render::view::screenshot::ScreenshotManager;
+use bevy::state::app::StatesPlugin;
+use bevy::text::Font;
+use bevy::time::TimeUpdateStrategy;
+use bevy::window::WindowPlugin;
+use bevy::winit::WinitWindows;
+
+use super::{game_plugins, GameState};
+use crate::profile::Profiles;
+use crate::telemetry::Telemetry;
+use crate::window_config::WindowConfig;
+
+// How far the clock moves each frame, the same every frame so timers are predictable
+const FRAME: Duration = Duration::from_millis(50);
+
+static WORKING_DIR: Mutex<()> = Mutex::new(());
+
+pub struct TestApp {
+    app: App,
+    dir: PathBuf,
+    _turn: MutexGuard<'static, ()>,
+}
+
+impl TestApp {
+    // The game as it starts, already past the loading screen and the profile picker, on the
+    // splash screen
+    pub fn new() -> Self {
+        let turn = WORKING_DIR
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let dir = env::temp_dir().join(format!("sprited_towards_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("the test directory can be created");
+        env::set_current_dir(&dir).expect("the test directory can be entered");
+
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            StatesPlugin,
+            HierarchyPlugin,
+            TransformPlugin,
+            InputPlugin,
+            AssetPlugin {
+                file_path: dir.join("assets").to_string_lossy().into_owned(),
+                ..default()
+            },
+            // Finds no sound device, so nothing is heard
+            bevy::audio::AudioPlugin::default(),
+            WindowPlugin::default(),
+        ))
+        .init_asset::<Image>()
+        .init_asset::<TextureAtlasLayout>()
+        .init_asset::<Font>()
+        // Asked for by the systems that follow the window's monitor and take bug report
+        // screenshots, and left empty
+        .init_non_send_resource::<WinitWindows>()
+        .init_resource::<ScreenshotManager>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME))
+        .insert_resource(Profiles::default())
+        .insert_resource(WindowConfig::default())
+        .add_plugins(game_plugins);
+        // The telemetry prompt is already answered, as it is for anyone past their first launch
+        app.world_mut().resource_mut::<Telemetry>().consent = Some(false);
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Splash);
+
+        let mut test_app = Self {
+            app,
+            dir,
+            _turn: turn,
+        };
+        test_app.step(1);
+        test_app
+    }
+
+    pub fn step(&mut self, frames: usize) {
+        for _ in 0..frames {
+            self.app.update();
+        }
+    }
+
+    // Steps until `done` holds, failing the test if it doesn't within `frames`
+    pub fn step_until(&mut self, frames: usize, mut done: impl FnMut(&mut World) -> bool) {
+        for _ in 0..frames {
+            self.app.update();
+            if done(self.app.world_mut()) {
+                return;
+            }
+        }
+        panic!("still waiting after {frames} frames");
+    }
+
+    pub fn state(&self) -> GameState {
+        *self.world().resource::<State<GameState>>().get()
+    }
+
+    // From the splash screen through the menu's New Game and the intro into the first fight
+    pub fn start_new_game(&mut self) {
+        self.step_until_state(40, GameState::Menu);
+        // The main menu opens the frame after
+        self.step(1);
+        self.press_button("New Game");
+        self.step_until_state(5, GameState::Game);
+        // With no script to read, the intro is over as soon as it starts
+        self.step_until_state(5, GameState::Chapter1);
+        self.step(1);
+    }
+
+    // Steps until the game reaches `state`
+    pub fn step_until_state(&mut self, frames: usize, state: GameState) {
+        self.step_until(frames, |world| {
+            *world.resource::<State<GameState>>().get() == state
+        });
+    }
+
+    // Presses the button showing `label`, as a click on it would, for the next frame to act on
+    pub fn press_button(&mut self, label: &str) {
+        let world = self.world_mut();
+        let button = world
+            .query::<(&Text, &Parent)>()
+            .iter(world)
+            .find(|(text, _)| text.sections.iter().any(|section| section.value == label))
+            .map(|(_, parent)| parent.get())
+            .unwrap_or_else(|| panic!("no button shows {label:?}"));
+        self.press(button);
+    }
+
+    pub fn press(&mut self, entity: Entity) {
+        self.world_mut()
+            .entity_mut(entity)
+            .insert(Interaction::Pressed);
+    }
+
+    // How many entities have `T`
+    pub fn count<T: Component>(&mut self) -> usize {
+        let world = self.world_mut();
+        world.query_filtered::<(), With<T>>().iter(world).count()
+    }
+
+    // The one entity with `T`
+    pub fn single<T: Component>(&mut self) -> Entity {
+        let world = self.world_mut();
+        world.query_filtered::<Entity, With<T>>().single(world)
+    }
+}
+
+impl Deref for TestApp {
+    type Target = App;
+
+    fn deref(&self) -> &App {
+        &self.app
+    }
+}
+
+impl DerefMut for TestApp {
+    fn deref_mut(&mut self) -> &mut App {
+        &mut self.app
+    }
+}
+
+impl Drop for TestApp {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
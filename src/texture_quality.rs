@@ -0,0 +1,45 @@
+// The cutscene sprite sheets and the full-screen backgrounds also ship at half size, next to the
+// originals as `name@0.5x.png`. With the display quality on Low screens load those instead, which
+// needs a quarter of the video memory and far less reading and decoding. The sprites are still
+// drawn at the same size on screen, only less sharp.
+use bevy::prelude::*;
+
+use super::DisplayQuality;
+
+// The cutscene sprite sheets are a grid of frames this size at full quality
+const SHEET_FRAME: UVec2 = UVec2::new(576, 324);
+const SHEET_COLUMNS: u32 = 5;
+const SHEET_ROWS: u32 = 64;
+
+const HALF_SIZE: [&str; 8] = [
+    "textures/intro_game_sprite.png",
+    "textures/forest_fort.png",
+    "textures/pool.png",
+    "textures/summoning.png",
+    "textures/1.png",
+    "textures/2.png",
+    "textures/waterfall.png",
+    "textures/Summon.png",
+];
+
+fn half_size(quality: DisplayQuality) -> bool {
+    quality == DisplayQuality::Low
+}
+
+// The file to load for a texture at this quality
+pub fn texture_path(path: &'static str, quality: DisplayQuality) -> String {
+    match path.strip_suffix(".png") {
+        Some(name) if half_size(quality) && HALF_SIZE.contains(&path) => format!("{name}@0.5x.png"),
+        _ => path.to_string(),
+    }
+}
+
+// The frames of a cutscene sprite sheet loaded through `texture_path` at the same quality
+pub fn sheet_layout(quality: DisplayQuality) -> TextureAtlasLayout {
+    let frame = if half_size(quality) {
+        SHEET_FRAME / 2
+    } else {
+        SHEET_FRAME
+    };
+    TextureAtlasLayout::from_grid(frame, SHEET_COLUMNS, SHEET_ROWS, None, None)
+}
@@ -0,0 +1,212 @@
+// The look the screens share: text and button colors, the menus' panels, the font, the common
+// font sizes and spacing, the health bars and the box behind dialogue. It's read from
+// `THEME_PATH` while the game loads, so restyling the game is an edit to that file rather than to
+// every screen. Anything the file leaves out keeps its built-in value, and if the file can't be
+// read the built-in look is used. The display settings' contrast switch swaps in the look from
+// `HIGH_CONTRAST_THEME_PATH` instead, for text and buttons that stand out over busy art.
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext, UntypedAssetId};
+use bevy::color::palettes::css::CRIMSON;
+use bevy::prelude::*;
+use serde::{Deserialize, Deserializer};
+
+const THEME_PATH: &str = "ui/default.theme.ron";
+const HIGH_CONTRAST_THEME_PATH: &str = "ui/high_contrast.theme.ron";
+
+// One of the display settings. It will be a resource in the app
+#[derive(Resource, Component, Default, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Contrast {
+    #[default]
+    Normal,
+    High,
+}
+
+#[derive(Asset, Resource, TypePath, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Theme {
+    #[serde(deserialize_with = "hex_color")]
+    pub text: Color,
+    // Text for what can't be picked yet, like locked chapters
+    #[serde(deserialize_with = "hex_color")]
+    pub muted_text: Color,
+    #[serde(deserialize_with = "hex_color")]
+    pub button: Color,
+    #[serde(deserialize_with = "hex_color")]
+    pub button_hovered: Color,
+    // A settings button showing the current choice, and the same while hovered
+    #[serde(deserialize_with = "hex_color")]
+    pub button_selected: Color,
+    #[serde(deserialize_with = "hex_color")]
+    pub button_selected_hovered: Color,
+    // The outline around buttons, none where the width is 0
+    #[serde(deserialize_with = "hex_color")]
+    pub button_border: Color,
+    pub button_border_width: f32,
+    // Behind the groups of buttons on the menu screens
+    #[serde(deserialize_with = "hex_color")]
+    pub panel: Color,
+    // Behind tooltips, over whatever they cover
+    #[serde(deserialize_with = "hex_color")]
+    pub backdrop: Color,
+    #[serde(deserialize_with = "hex_color")]
+    pub bar_track: Color,
+    // Around the health bars' tracks, none where the width is 0
+    #[serde(deserialize_with = "hex_color")]
+    pub bar_border: Color,
+    pub bar_border_width: f32,
+    #[serde(deserialize_with = "hex_color")]
+    pub healthy: Color,
+    #[serde(deserialize_with = "hex_color")]
+    pub wounded: Color,
+    #[serde(deserialize_with = "hex_color")]
+    pub dying: Color,
+    // Behind the lines of the cutscenes, dimming the art they're read over
+    #[serde(deserialize_with = "hex_color")]
+    pub dialogue_backdrop: Color,
+    // A path under `assets`, or empty for Bevy's own font
+    font: String,
+    #[serde(skip)]
+    #[dependency]
+    pub font_handle: Handle<Font>,
+    pub title_size: f32,
+    pub button_text_size: f32,
+    // Around buttons and between the rows of a screen
+    pub spacing: f32,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            text: Color::srgb(0.9, 0.9, 0.9),
+            muted_text: Color::srgb(0.5, 0.5, 0.5),
+            button: Color::srgb(0.15, 0.15, 0.15),
+            button_hovered: Color::srgb(0.25, 0.25, 0.25),
+            button_selected: Color::srgb(0.35, 0.75, 0.35),
+            button_selected_hovered: Color::srgb(0.25, 0.65, 0.25),
+            button_border: Color::NONE,
+            button_border_width: 0.0,
+            panel: CRIMSON.into(),
+            backdrop: Color::srgba(0.0, 0.0, 0.0, 0.75),
+            bar_track: Color::srgb(0.2, 0.2, 0.2),
+            bar_border: Color::NONE,
+            bar_border_width: 0.0,
+            healthy: Color::srgb(0.0, 1.0, 0.0),
+            wounded: Color::srgb(1.0, 0.65, 0.0),
+            dying: Color::srgb(1.0, 0.0, 0.0),
+            dialogue_backdrop: Color::srgba(0.0, 0.0, 0.0, 0.7),
+            font: String::new(),
+            font_handle: Handle::default(),
+            title_size: 60.0,
+            button_text_size: 40.0,
+            spacing: 20.0,
+        }
+    }
+}
+
+impl Theme {
+    // Text in the theme's font and color
+    pub fn text_style(&self, font_size: f32) -> TextStyle {
+        TextStyle {
+            font: self.font_handle.clone(),
+            font_size,
+            color: self.text,
+        }
+    }
+}
+
+// Colors are written as hex, like "#e6e6e6" or with an alpha, "#000000bf"
+fn hex_color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+    let hex = String::deserialize(deserializer)?;
+    Srgba::hex(&hex)
+        .map(Color::from)
+        .map_err(serde::de::Error::custom)
+}
+
+#[derive(Default)]
+struct ThemeLoader;
+
+impl AssetLoader for ThemeLoader {
+    type Asset = Theme;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Theme, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let mut theme: Theme = ron::de::from_bytes(&bytes)?;
+        if !theme.font.is_empty() {
+            theme.font_handle = load_context.load(theme.font.clone());
+        }
+        Ok(theme)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["theme.ron"]
+    }
+}
+
+// Both theme files while they load, for the loading screen to wait on
+#[derive(Resource)]
+pub struct ThemeHandle {
+    normal: Handle<Theme>,
+    high_contrast: Handle<Theme>,
+}
+
+impl ThemeHandle {
+    pub fn ids(&self) -> [UntypedAssetId; 2] {
+        [
+            self.normal.id().untyped(),
+            self.high_contrast.id().untyped(),
+        ]
+    }
+
+    fn id(&self, contrast: Contrast) -> AssetId<Theme> {
+        match contrast {
+            Contrast::Normal => self.normal.id(),
+            Contrast::High => self.high_contrast.id(),
+        }
+    }
+}
+
+pub fn theme_plugin(app: &mut App) {
+    app.init_asset::<Theme>()
+        .init_asset_loader::<ThemeLoader>()
+        .init_resource::<Theme>()
+        .init_resource::<Contrast>()
+        .add_systems(PreStartup, load_theme)
+        .add_systems(Update, apply_theme);
+}
+
+fn load_theme(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(ThemeHandle {
+        normal: asset_server.load(THEME_PATH),
+        high_contrast: asset_server.load(HIGH_CONTRAST_THEME_PATH),
+    });
+}
+
+// Screens built from here on take the look from the file the contrast setting picks, once it's
+// loaded and again whenever the setting changes
+pub fn apply_theme(
+    mut events: EventReader<AssetEvent<Theme>>,
+    handle: Res<ThemeHandle>,
+    contrast: Res<Contrast>,
+    themes: Res<Assets<Theme>>,
+    mut theme: ResMut<Theme>,
+) {
+    let id = handle.id(*contrast);
+    let reloaded = events.read().any(|event| {
+        matches!(
+            *event,
+            AssetEvent::LoadedWithDependencies { id: loaded } | AssetEvent::Modified { id: loaded }
+                if loaded == id
+        )
+    });
+    if reloaded || contrast.is_changed() {
+        *theme = themes.get(id).cloned().unwrap_or_default();
+    }
+}
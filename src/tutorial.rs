@@ -0,0 +1,307 @@
+// Guided first fight. The first time a player reaches Chapter 1, a script of tips walks them
+// through the mulligan, their first Fire card and ending the turn. Tips that only explain
+// something have to be dismissed; the rest wait for the player to do what they ask, with the
+// fight locked so nothing else can be done meanwhile.
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+
+use super::GameState;
+use crate::combat::{combat_setup, CardType, CombatSet, FightEvent, PlayLock};
+use crate::deck::Deck;
+use crate::save::SaveData;
+use crate::theme::Theme;
+use crate::ui;
+
+const HIGHLIGHT_COLOR: Color = Color::srgb(1.0, 0.84, 0.0);
+
+#[derive(Clone, Copy, PartialEq)]
+enum Highlight {
+    Hand,
+    EndTurn,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Until {
+    Dismissed,
+    Event(FightEvent),
+}
+
+struct TutorialStep {
+    text: &'static str,
+    highlight: Option<Highlight>,
+    until: Until,
+}
+
+const SCRIPT: [TutorialStep; 7] = [
+    TutorialStep {
+        text: "Your first fight! The number above each monster shows what it will do on its turn.",
+        highlight: None,
+        until: Until::Dismissed,
+    },
+    TutorialStep {
+        text: "Before the first turn you may click cards to redraw them. Press the button under your hand when you're happy.",
+        highlight: Some(Highlight::Hand),
+        until: Until::Event(FightEvent::MulliganDone),
+    },
+    TutorialStep {
+        text: "This is your hand. Clicking a card plays it against every monster.",
+        highlight: Some(Highlight::Hand),
+        until: Until::Dismissed,
+    },
+    TutorialStep {
+        text: "Fire hits harder when it's the first card you play in a turn. Play Fire now.",
+        highlight: Some(Highlight::Hand),
+        until: Until::Event(FightEvent::CardPlayed(CardType::Fire)),
+    },
+    TutorialStep {
+        text: "Well done! Every card has a trick like that: Ice hits twice as hard straight after Fire, and Earth grows stronger each turn.",
+        highlight: None,
+        until: Until::Dismissed,
+    },
+    TutorialStep {
+        text: "Play more cards if you like, then press End Turn to let the monsters act.",
+        highlight: Some(Highlight::EndTurn),
+        until: Until::Event(FightEvent::TurnEnded),
+    },
+    TutorialStep {
+        text: "That's all there is to it. Good luck!",
+        highlight: None,
+        until: Until::Dismissed,
+    },
+];
+
+#[derive(Resource)]
+struct Tutorial {
+    step: usize,
+}
+
+#[derive(Component)]
+struct TutorialOverlay;
+
+#[derive(Component)]
+struct TutorialHighlight;
+
+#[derive(Component)]
+struct DismissButton;
+
+pub fn tutorial_plugin(app: &mut App) {
+    app.add_systems(
+        OnEnter(GameState::Chapter1),
+        start_tutorial.before(combat_setup),
+    )
+    .add_systems(
+        Update,
+        (
+            advance_tutorial,
+            show_tutorial_step,
+            pulse_highlight,
+            dismiss_button_colors,
+        )
+            .chain()
+            // Ahead of the fight's rules, so a finished step unlocks the next before it's needed
+            .in_set(CombatSet::Input)
+            .run_if(in_state(GameState::Chapter1))
+            .run_if(resource_exists::<Tutorial>),
+    )
+    .add_systems(OnExit(GameState::Chapter1), end_tutorial);
+}
+
+// What the player may do at a step: only the card the script asks for next, and no ending the
+// turn until the script gets there
+fn lock_for(step: usize) -> PlayLock {
+    let ahead = &SCRIPT[step..];
+    let ends_turn = |step: &TutorialStep| step.until == Until::Event(FightEvent::TurnEnded);
+    PlayLock {
+        only_card: ahead.iter().find_map(|step| match step.until {
+            Until::Event(FightEvent::CardPlayed(card_type)) => Some(card_type),
+            _ => None,
+        }),
+        end_turn_locked: ahead.iter().any(ends_turn) && !ends_turn(&ahead[0]),
+    }
+}
+
+fn start_tutorial(
+    mut commands: Commands,
+    save_data: Res<SaveData>,
+    deck: Res<Deck>,
+    mut play_lock: ResMut<PlayLock>,
+) {
+    // Not when picking a fight back up, and not for a deck that can't follow the script
+    let resuming = save_data
+        .fight
+        .as_ref()
+        .is_some_and(|fight| fight.state == GameState::Chapter1);
+    if save_data.tutorial_seen || resuming || !deck.cards().contains(&CardType::Fire) {
+        return;
+    }
+    commands.insert_resource(Tutorial { step: 0 });
+    *play_lock = lock_for(0);
+}
+
+fn advance_tutorial(
+    mut commands: Commands,
+    mut tutorial: ResMut<Tutorial>,
+    mut fight_events: EventReader<FightEvent>,
+    dismiss_query: Query<&Interaction, (Changed<Interaction>, With<DismissButton>)>,
+    mut play_lock: ResMut<PlayLock>,
+    mut save_data: ResMut<SaveData>,
+) {
+    let events: Vec<FightEvent> = fight_events.read().copied().collect();
+    let done = match SCRIPT[tutorial.step].until {
+        Until::Dismissed => dismiss_query
+            .iter()
+            .any(|interaction| *interaction == Interaction::Pressed),
+        Until::Event(wanted) => events.contains(&wanted),
+    };
+    if !done {
+        return;
+    }
+
+    tutorial.step += 1;
+    if tutorial.step < SCRIPT.len() {
+        *play_lock = lock_for(tutorial.step);
+        return;
+    }
+    // Finished, so it isn't shown again
+    *play_lock = PlayLock::default();
+    save_data.tutorial_seen = true;
+    save_data.save();
+    commands.remove_resource::<Tutorial>();
+}
+
+// Replace the tip whenever the step changes. Tips to dismiss cover the fight so nothing else
+// can be clicked.
+fn show_tutorial_step(
+    mut commands: Commands,
+    tutorial: Res<Tutorial>,
+    overlay_query: Query<Entity, With<TutorialOverlay>>,
+    theme: Res<Theme>,
+) {
+    if !tutorial.is_changed() {
+        return;
+    }
+    for entity in overlay_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    let step = &SCRIPT[tutorial.step];
+    let modal = step.until == Until::Dismissed;
+
+    if let Some(highlight) = step.highlight {
+        let style = match highlight {
+            Highlight::Hand => Style {
+                top: Val::Px(10.0),
+                left: Val::Percent(20.0),
+                right: Val::Percent(20.0),
+                height: Val::Px(270.0),
+                ..default()
+            },
+            Highlight::EndTurn => Style {
+                top: Val::Px(10.0),
+                right: Val::Px(10.0),
+                width: Val::Px(170.0),
+                height: Val::Px(70.0),
+                ..default()
+            },
+        };
+        commands.spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    border: UiRect::all(Val::Px(4.0)),
+                    ..style
+                },
+                border_color: HIGHLIGHT_COLOR.into(),
+                z_index: ZIndex::Global(14),
+                ..default()
+            },
+            TutorialHighlight,
+            TutorialOverlay,
+            StateScoped(GameState::Chapter1),
+        ));
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::FlexEnd,
+                    justify_content: JustifyContent::Center,
+                    padding: UiRect::bottom(Val::Px(40.0)),
+                    ..default()
+                },
+                background_color: if modal {
+                    Color::srgba(0.0, 0.0, 0.0, 0.5).into()
+                } else {
+                    Color::NONE.into()
+                },
+                focus_policy: if modal {
+                    FocusPolicy::Block
+                } else {
+                    FocusPolicy::Pass
+                },
+                z_index: ZIndex::Global(15),
+                ..default()
+            },
+            TutorialOverlay,
+            StateScoped(GameState::Chapter1),
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(700.0),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(16.0),
+                        padding: UiRect::all(Val::Px(20.0)),
+                        border: UiRect::all(Val::Px(2.0)),
+                        ..default()
+                    },
+                    background_color: Color::srgba(0.05, 0.05, 0.08, 0.95).into(),
+                    border_color: HIGHLIGHT_COLOR.into(),
+                    focus_policy: FocusPolicy::Block,
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(
+                        TextBundle::from_section(step.text, theme.text_style(26.0))
+                            .with_text_justify(JustifyText::Center),
+                    );
+                    if modal {
+                        ui::styled_button(
+                            parent,
+                            &theme,
+                            Style {
+                                padding: UiRect::axes(Val::Px(30.0), Val::Px(10.0)),
+                                ..default()
+                            },
+                            "Got it",
+                            theme.text_style(26.0),
+                            DismissButton,
+                        );
+                    }
+                });
+        });
+}
+
+fn pulse_highlight(time: Res<Time>, mut query: Query<&mut BorderColor, With<TutorialHighlight>>) {
+    let alpha = 0.6 + 0.4 * (time.elapsed_seconds() * 4.0).sin();
+    for mut border in query.iter_mut() {
+        border.0 = HIGHLIGHT_COLOR.with_alpha(alpha);
+    }
+}
+
+fn dismiss_button_colors(mut query: ui::ChangedButtons<DismissButton>, theme: Res<Theme>) {
+    for (interaction, mut color) in query.iter_mut() {
+        *color = ui::button_color(&theme, *interaction);
+    }
+}
+
+// Leaving the fight early, by winning or losing, drops the tutorial without finishing it
+fn end_tutorial(mut commands: Commands, mut play_lock: ResMut<PlayLock>) {
+    commands.remove_resource::<Tutorial>();
+    *play_lock = PlayLock::default();
+}
@@ -0,0 +1,178 @@
+// Small tweening helpers for UI presentation. Put a `Tween` on an entity to ease one property
+// from one value to another, optionally after a delay. A `TweenFinished` event is sent when it
+// completes, and the component is removed (or the whole entity, if it asked to be).
+use bevy::prelude::*;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Ease {
+    In,
+    Out,
+}
+
+impl Ease {
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Ease::In => t * t,
+            Ease::Out => 1.0 - (1.0 - t) * (1.0 - t),
+        }
+    }
+}
+
+// How a UI node is held apart from its place in the layout: its scale, how far it's lifted above
+// that place in pixels, and how far it's tilted in radians
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pose {
+    pub scale: f32,
+    pub lift: f32,
+    pub tilt: f32,
+}
+
+impl Pose {
+    pub const REST: Pose = Pose {
+        scale: 1.0,
+        lift: 0.0,
+        tilt: 0.0,
+    };
+
+    // Where a node is held right now, for a tween to start from even part-way through another
+    pub fn of(transform: &Transform, style: &Style) -> Self {
+        let lift = match style.top {
+            Val::Px(top) => -top,
+            _ => 0.0,
+        };
+        Self {
+            scale: transform.scale.x,
+            lift,
+            tilt: transform.rotation.to_euler(EulerRot::XYZ).2,
+        }
+    }
+
+    fn lerp(self, to: Pose, t: f32) -> Pose {
+        Pose {
+            scale: self.scale + (to.scale - self.scale) * t,
+            lift: self.lift + (to.lift - self.lift) * t,
+            tilt: self.tilt + (to.tilt - self.tilt) * t,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum TweenTarget {
+    // `Transform::scale`
+    Scale { from: Vec3, to: Vec3 },
+    // `Style::left` and `Style::top`, in pixels
+    Offset { from: Vec2, to: Vec2 },
+    // `Transform::scale` and `Transform::rotation`, and `Style::top` for the lift
+    Pose { from: Pose, to: Pose },
+}
+
+#[derive(Component)]
+pub struct Tween {
+    target: TweenTarget,
+    ease: Ease,
+    delay: f32,
+    duration: f32,
+    elapsed: f32,
+    despawn_when_done: bool,
+}
+
+impl Tween {
+    pub fn new(target: TweenTarget, duration: f32, ease: Ease) -> Self {
+        Self {
+            target,
+            ease,
+            delay: 0.0,
+            duration,
+            elapsed: 0.0,
+            despawn_when_done: false,
+        }
+    }
+
+    pub fn with_delay(mut self, seconds: f32) -> Self {
+        self.delay = seconds;
+        self
+    }
+
+    pub fn despawn_when_done(mut self) -> Self {
+        self.despawn_when_done = true;
+        self
+    }
+
+    // Eased progress from 0 to 1, or `None` while still waiting out the delay
+    fn progress(&self) -> Option<f32> {
+        if self.elapsed < self.delay {
+            return None;
+        }
+        let t = if self.duration > 0.0 {
+            ((self.elapsed - self.delay) / self.duration).min(1.0)
+        } else {
+            1.0
+        };
+        Some(self.ease.apply(t))
+    }
+
+    fn finished(&self) -> bool {
+        self.elapsed >= self.delay + self.duration
+    }
+}
+
+#[derive(Event)]
+pub struct TweenFinished(pub Entity);
+
+pub fn tween_plugin(app: &mut App) {
+    app.add_event::<TweenFinished>()
+        .add_systems(Update, animate_tweens);
+}
+
+pub fn animate_tweens(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(
+        Entity,
+        &mut Tween,
+        Option<&mut Transform>,
+        Option<&mut Style>,
+    )>,
+    mut finished: EventWriter<TweenFinished>,
+) {
+    for (entity, mut tween, transform, style) in query.iter_mut() {
+        tween.elapsed += time.delta_seconds();
+        let Some(progress) = tween.progress() else {
+            continue;
+        };
+
+        match tween.target {
+            TweenTarget::Scale { from, to } => {
+                if let Some(mut transform) = transform {
+                    transform.scale = from.lerp(to, progress);
+                }
+            }
+            TweenTarget::Offset { from, to } => {
+                if let Some(mut style) = style {
+                    let offset = from.lerp(to, progress);
+                    style.left = Val::Px(offset.x);
+                    style.top = Val::Px(offset.y);
+                }
+            }
+            TweenTarget::Pose { from, to } => {
+                let pose = from.lerp(to, progress);
+                if let Some(mut transform) = transform {
+                    transform.scale = Vec3::splat(pose.scale);
+                    transform.rotation = Quat::from_rotation_z(pose.tilt);
+                }
+                if let Some(mut style) = style {
+                    style.top = Val::Px(-pose.lift);
+                }
+            }
+        }
+
+        if tween.finished() {
+            finished.send(TweenFinished(entity));
+            if tween.despawn_when_done {
+                commands.entity(entity).despawn_recursive();
+            } else {
+                commands.entity(entity).remove::<Tween>();
+            }
+        }
+    }
+}
@@ -0,0 +1,210 @@
+// Widgets the screens share: buttons, titles, modal overlays and health bars. Each screen still
+// lays itself out, but what these pieces look like is decided here, in the colors and sizes of
+// the `Theme`, so restyling one restyles it everywhere.
+use bevy::ecs::system::{EntityCommands, SystemParam};
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+
+use crate::theme::Theme;
+
+// A button in the resting color and the theme's outline, for buttons holding more than a line of
+// text
+pub fn button(theme: &Theme, style: Style) -> ButtonBundle {
+    ButtonBundle {
+        style: Style {
+            border: UiRect::all(Val::Px(theme.button_border_width)),
+            ..style
+        },
+        background_color: theme.button.into(),
+        border_color: theme.button_border.into(),
+        ..default()
+    }
+}
+
+// A button with a single line of text, tagged with `marker` so its system knows which it is
+pub fn styled_button<'a>(
+    parent: &'a mut ChildBuilder,
+    theme: &Theme,
+    style: Style,
+    label: impl Into<String>,
+    text_style: TextStyle,
+    marker: impl Bundle,
+) -> EntityCommands<'a> {
+    let mut entity = parent.spawn((button(theme, style), marker));
+    entity.with_children(|parent| {
+        parent.spawn(TextBundle::from_section(label, text_style));
+    });
+    entity
+}
+
+// Lit while the cursor is over it, for buttons that don't stay selected
+pub fn button_color(theme: &Theme, interaction: Interaction) -> BackgroundColor {
+    match interaction {
+        Interaction::None => theme.button,
+        Interaction::Hovered | Interaction::Pressed => theme.button_hovered,
+    }
+    .into()
+}
+
+// The buttons marked `T` whose interaction changed this frame, to recolor and act on
+pub type ChangedButtons<'w, 's, T> = Query<
+    'w,
+    's,
+    (&'static Interaction, &'static mut BackgroundColor),
+    (Changed<Interaction>, With<T>),
+>;
+
+// Like `ChangedButtons`, for screens whose buttons each carry the action `A` they stand for
+pub type ChangedActionButtons<'w, 's, A> = Query<
+    'w,
+    's,
+    (
+        &'static Interaction,
+        &'static A,
+        &'static mut BackgroundColor,
+    ),
+    (Changed<Interaction>, With<Button>),
+>;
+
+// A screen marked `Root` with content marked `Content` under it, for screens that throw their
+// content away and build it again as what it shows changes
+#[derive(SystemParam)]
+pub struct ScreenContent<'w, 's, Root: Component, Content: Component> {
+    roots: Query<'w, 's, Entity, With<Root>>,
+    contents: Query<'w, 's, Entity, With<Content>>,
+}
+
+impl<Root: Component, Content: Component> ScreenContent<'_, '_, Root, Content> {
+    // The screen, while it's up
+    pub fn root(&self) -> Option<Entity> {
+        self.roots.get_single().ok()
+    }
+
+    pub fn content(&self) -> Option<Entity> {
+        self.contents.get_single().ok()
+    }
+
+    // Despawns the content and hands back the screen to build the new content under. Leaves
+    // the content be while there is no screen.
+    pub fn clear(&self, commands: &mut Commands) -> Option<Entity> {
+        let root = self.root()?;
+        for entity in self.contents.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        Some(root)
+    }
+}
+
+// The heading at the top of a screen
+pub fn title_text(theme: &Theme, title: impl Into<String>) -> TextBundle {
+    TextBundle::from_section(title, theme.text_style(theme.title_size))
+}
+
+// A full-screen overlay over whatever is behind it, its contents stacked in the middle. It
+// starts clear, for the screens using it to fade it in.
+pub fn modal() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            position_type: PositionType::Absolute,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            flex_direction: FlexDirection::Column,
+            ..default()
+        },
+        background_color: BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.0)),
+        ..default()
+    }
+}
+
+// One of the buttons stacked under a modal's message
+pub fn modal_button(
+    parent: &mut ChildBuilder,
+    theme: &Theme,
+    label: impl Into<String>,
+    marker: impl Bundle,
+) {
+    styled_button(
+        parent,
+        theme,
+        Style {
+            width: Val::Px(300.0),
+            margin: UiRect::top(Val::Px(theme.spacing)),
+            padding: UiRect::vertical(Val::Px(12.0)),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        label,
+        theme.text_style(theme.button_text_size),
+        marker,
+    );
+}
+
+// A health bar of `size`, `depth` below the sprite it belongs to: a dark track in the theme's
+// border, and the bar itself tagged with `marker`. Anything else shown on the bar goes in as a
+// child of the track.
+pub fn health_bar<'a>(
+    parent: &'a mut ChildBuilder,
+    theme: &Theme,
+    size: Vec2,
+    depth: f32,
+    marker: impl Bundle,
+) -> EntityCommands<'a> {
+    let mut track = parent.spawn(bar_track(theme, size, depth));
+    track.with_children(|track| {
+        if theme.bar_border_width > 0.0 {
+            track.spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: theme.bar_border,
+                    custom_size: Some(size + Vec2::splat(theme.bar_border_width * 2.0)),
+                    ..default()
+                },
+                // Behind the track, showing only around its edge
+                transform: Transform::from_xyz(0.0, 0.0, -0.05),
+                ..default()
+            });
+        }
+        track.spawn((bar_segment(theme.healthy, size, size.x), marker));
+    });
+    track
+}
+
+// The dark track along a bar, `height` above the middle of what it belongs to
+pub fn bar_track(theme: &Theme, size: Vec2, height: f32) -> SpriteBundle {
+    SpriteBundle {
+        sprite: Sprite {
+            color: theme.bar_track,
+            custom_size: Some(size),
+            ..default()
+        },
+        transform: Transform::from_xyz(0.0, height, 0.1),
+        ..default()
+    }
+}
+
+// A stretch of color along a bar's `track`, starting at its left end and `width` long. Its width
+// can change without moving where it starts.
+pub fn bar_segment(color: Color, track: Vec2, width: f32) -> SpriteBundle {
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(Vec2::new(width, track.y)),
+            anchor: Anchor::CenterLeft,
+            ..default()
+        },
+        transform: Transform::from_xyz(-track.x / 2.0, 0.0, 0.2),
+        ..default()
+    }
+}
+
+// Green down to half health, orange down to a quarter, then red
+pub fn health_color(theme: &Theme, share: f32) -> Color {
+    if share > 0.5 {
+        theme.healthy
+    } else if share > 0.25 {
+        theme.wounded
+    } else {
+        theme.dying
+    }
+}
@@ -0,0 +1,393 @@
+// How the game window opens: its title, icon, size, position and whether it can be resized. Read
+// from `SETTINGS_PATH` in the last played profile's folder before the app is built, and again
+// from the picked profile's folder when one is picked. Anything left out of the file
+// keeps its default, and without the file the game opens with those defaults. A file with
+// missing, unknown or out-of-range settings is repaired rather than thrown away: each bad setting
+// goes back to its default or is clamped, the rest are kept, and the fixed file is written back.
+//
+// The screen mode picked in the display settings is written back to the same file, once for
+// each monitor, and put back whenever the window is on that monitor again.
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+
+use bevy::asset::LoadState;
+use bevy::prelude::*;
+use bevy::render::render_resource::TextureFormat;
+use bevy::window::{PrimaryWindow, WindowMode, WindowPosition, WindowResolution};
+use bevy::winit::WinitWindows;
+use serde::{Deserialize, Serialize};
+
+use crate::profile::profile_path;
+
+const SETTINGS_PATH: &str = "settings.ron";
+
+const SETTINGS: [&str; 7] = [
+    "title",
+    "icon",
+    "width",
+    "height",
+    "position",
+    "resizable",
+    "screen_modes",
+];
+
+// Window sizes outside these can't be shown, or hide the whole game
+const MIN_SIZE: Vec2 = Vec2::new(320.0, 240.0);
+const MAX_SIZE: Vec2 = Vec2::new(7680.0, 4320.0);
+
+#[derive(Resource, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct WindowConfig {
+    pub title: String,
+    // Asset path of the window's icon, or none to keep the platform's own
+    pub icon: Option<String>,
+    pub width: f32,
+    pub height: f32,
+    // Top-left corner of the window on screen, or none to let the platform place it
+    pub position: Option<(i32, i32)>,
+    pub resizable: bool,
+    // The screen mode last picked on each monitor, by the monitor's name
+    pub screen_modes: BTreeMap<String, ScreenMode>,
+    // Why the settings file couldn't be used, and what had to be repaired in it. Logging isn't
+    // running yet when it is read, so these are reported once the app starts.
+    #[serde(skip)]
+    load_error: Option<String>,
+    #[serde(skip)]
+    repairs: Vec<String>,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            title: "Sprited Towards".to_string(),
+            icon: Some("branding/icon.png".to_string()),
+            width: 1280.0,
+            height: 720.0,
+            position: None,
+            resizable: true,
+            screen_modes: BTreeMap::new(),
+            load_error: None,
+            repairs: Vec::new(),
+        }
+    }
+}
+
+// One of the display settings. Borderless covers the screen with an ordinary window, so
+// switching to another program is instant; fullscreen takes the screen over for itself.
+#[derive(
+    Resource, Component, Serialize, Deserialize, Default, PartialEq, Eq, Clone, Copy, Debug,
+)]
+pub enum ScreenMode {
+    #[default]
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+impl ScreenMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            ScreenMode::Windowed => "Windowed",
+            ScreenMode::Borderless => "Borderless",
+            ScreenMode::Fullscreen => "Fullscreen",
+        }
+    }
+
+    fn window_mode(self) -> WindowMode {
+        match self {
+            ScreenMode::Windowed => WindowMode::Windowed,
+            ScreenMode::Borderless => WindowMode::BorderlessFullscreen,
+            ScreenMode::Fullscreen => WindowMode::Fullscreen,
+        }
+    }
+}
+
+// The name of the monitor the window is on, once the platform has told us
+#[derive(Resource, Default)]
+struct CurrentMonitor(Option<String>);
+
+// The icon is waiting to load before it can be put on the window
+#[derive(Resource)]
+struct WindowIcon(Handle<Image>);
+
+impl WindowConfig {
+    pub fn load() -> Self {
+        let contents = match fs::read_to_string(profile_path(SETTINGS_PATH)) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Self::default(),
+            Err(err) => {
+                return Self {
+                    load_error: Some(format!("Could not read {SETTINGS_PATH}: {err}")),
+                    ..default()
+                }
+            }
+        };
+        let settings = match ron::from_str(&contents) {
+            Ok(ron::Value::Map(settings)) => settings,
+            Ok(_) => {
+                return Self {
+                    load_error: Some(format!("{SETTINGS_PATH} doesn't hold a list of settings")),
+                    ..default()
+                }
+            }
+            Err(err) => {
+                return Self {
+                    load_error: Some(format!("Could not understand {SETTINGS_PATH}: {err}")),
+                    ..default()
+                }
+            }
+        };
+
+        let mut repairs = Vec::new();
+        let keys: Vec<String> = settings
+            .keys()
+            .filter_map(|key| key.clone().into_rust().ok())
+            .collect();
+        for setting in SETTINGS {
+            if !keys.iter().any(|key| key == setting) {
+                repairs.push(format!(
+                    "`{setting}` was missing, so it was set to its default"
+                ));
+            }
+        }
+        for key in &keys {
+            if !SETTINGS.contains(&key.as_str()) {
+                repairs.push(format!("`{key}` isn't a setting, so it was removed"));
+            }
+        }
+
+        // Read it as a whole when every value has the right type. Otherwise go one setting at a
+        // time, so only the broken ones are lost.
+        let mut config = ron::from_str(&contents)
+            .unwrap_or_else(|_| Self::from_each_setting(settings, &mut repairs));
+        config.clamp(&mut repairs);
+        config.repairs = repairs;
+        config
+    }
+
+    fn from_each_setting(settings: ron::Map, repairs: &mut Vec<String>) -> Self {
+        let mut config = Self::default();
+        for (key, value) in settings {
+            let Ok(key) = key.into_rust::<String>() else {
+                continue;
+            };
+            let read = match key.as_str() {
+                "title" => value.into_rust().map(|title| config.title = title),
+                "icon" => value.into_rust().map(|icon| config.icon = icon),
+                "width" => value.into_rust().map(|width| config.width = width),
+                "height" => value.into_rust().map(|height| config.height = height),
+                "position" => value.into_rust().map(|position| config.position = position),
+                "resizable" => value
+                    .into_rust()
+                    .map(|resizable| config.resizable = resizable),
+                "screen_modes" => value
+                    .into_rust()
+                    .map(|screen_modes| config.screen_modes = screen_modes),
+                _ => continue,
+            };
+            if let Err(err) = read {
+                repairs.push(format!(
+                    "`{key}` was not understood ({err}), so it was reset"
+                ));
+            }
+        }
+        config
+    }
+
+    // Pull settings that were read fine but make no sense back into range
+    fn clamp(&mut self, repairs: &mut Vec<String>) {
+        if self.title.trim().is_empty() {
+            self.title = Self::default().title;
+            repairs.push("`title` was empty, so it was set to its default".to_string());
+        }
+        for (name, size, min, max) in [
+            ("width", &mut self.width, MIN_SIZE.x, MAX_SIZE.x),
+            ("height", &mut self.height, MIN_SIZE.y, MAX_SIZE.y),
+        ] {
+            let clamped = if size.is_nan() {
+                min
+            } else {
+                size.clamp(min, max)
+            };
+            if clamped != *size {
+                repairs.push(format!("`{name}` of {size} was changed to {clamped}"));
+                *size = clamped;
+            }
+        }
+    }
+
+    fn save(&self) {
+        let contents = match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(contents) => contents,
+            Err(err) => {
+                warn!("Could not serialize the window settings: {err}");
+                return;
+            }
+        };
+        if let Err(err) = fs::write(profile_path(SETTINGS_PATH), contents) {
+            warn!("Could not write {SETTINGS_PATH}: {err}");
+        }
+    }
+
+    // The window settings to build `DefaultPlugins` with
+    pub fn window_plugin(&self) -> WindowPlugin {
+        WindowPlugin {
+            primary_window: Some(Window {
+                title: self.title.clone(),
+                resolution: WindowResolution::new(self.width, self.height),
+                position: match self.position {
+                    Some((x, y)) => WindowPosition::At(IVec2::new(x, y)),
+                    None => WindowPosition::Automatic,
+                },
+                resizable: self.resizable,
+                ..default()
+            }),
+            ..default()
+        }
+    }
+}
+
+pub fn window_config_plugin(app: &mut App) {
+    app.init_resource::<ScreenMode>()
+        .init_resource::<CurrentMonitor>()
+        .add_systems(
+            Update,
+            (
+                // Whenever a profile's settings are read, including the ones the game opened with
+                (
+                    report_settings_problems,
+                    load_window_icon,
+                    apply_loaded_config,
+                )
+                    .run_if(resource_added::<WindowConfig>),
+                set_window_icon.run_if(resource_exists::<WindowIcon>),
+                (follow_monitor, apply_screen_mode).chain(),
+            ),
+        );
+}
+
+// Say what was wrong with the settings file, and write the repaired one back
+fn report_settings_problems(config: Res<WindowConfig>) {
+    if let Some(err) = &config.load_error {
+        warn!("{err}, so the window opens with its default settings");
+    }
+    for repair in &config.repairs {
+        warn!("Repaired {SETTINGS_PATH}: {repair}");
+    }
+    if !config.repairs.is_empty() {
+        config.save();
+    }
+}
+
+// Give the window the size, title and screen mode of settings read after it opened
+fn apply_loaded_config(
+    config: Res<WindowConfig>,
+    mut current_monitor: ResMut<CurrentMonitor>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    for mut window in window_query.iter_mut() {
+        window.title.clone_from(&config.title);
+        window.resolution.set(config.width, config.height);
+        window.resizable = config.resizable;
+    }
+    // So the screen mode remembered for the monitor is looked up again
+    current_monitor.0 = None;
+}
+
+fn load_window_icon(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<WindowConfig>,
+) {
+    if let Some(icon) = &config.icon {
+        commands.insert_resource(WindowIcon(asset_server.load(icon.clone())));
+    }
+}
+
+// Bevy's window has no icon setting, so the icon goes straight to the platform window once both
+// the image and the window exist
+fn set_window_icon(
+    mut commands: Commands,
+    icon: Res<WindowIcon>,
+    asset_server: Res<AssetServer>,
+    images: Res<Assets<Image>>,
+    winit_windows: NonSend<WinitWindows>,
+    window_query: Query<Entity, With<PrimaryWindow>>,
+) {
+    if matches!(
+        asset_server.get_load_state(&icon.0),
+        Some(LoadState::Failed(_))
+    ) {
+        warn!("Could not load the window icon, so the default one is kept");
+        commands.remove_resource::<WindowIcon>();
+        return;
+    }
+    let Some(image) = images.get(&icon.0) else {
+        return;
+    };
+    let Some(window) = window_query
+        .get_single()
+        .ok()
+        .and_then(|entity| winit_windows.get_window(entity))
+    else {
+        return;
+    };
+    commands.remove_resource::<WindowIcon>();
+
+    let Some(rgba) = image.convert(TextureFormat::Rgba8UnormSrgb) else {
+        warn!("The window icon is not in a format that can be used as an icon");
+        return;
+    };
+    let (width, height) = (rgba.width(), rgba.height());
+    match winit::window::Icon::from_rgba(rgba.data, width, height) {
+        Ok(icon) => window.set_window_icon(Some(icon)),
+        Err(err) => warn!("Could not use the window icon: {err}"),
+    }
+}
+
+// Switch to the screen mode remembered for a monitor when the window first shows up on it
+fn follow_monitor(
+    winit_windows: NonSend<WinitWindows>,
+    window_query: Query<Entity, With<PrimaryWindow>>,
+    config: Res<WindowConfig>,
+    mut current_monitor: ResMut<CurrentMonitor>,
+    mut screen_mode: ResMut<ScreenMode>,
+) {
+    let Some(name) = window_query
+        .get_single()
+        .ok()
+        .and_then(|entity| winit_windows.get_window(entity))
+        .and_then(|window| window.current_monitor())
+        .and_then(|monitor| monitor.name())
+    else {
+        return;
+    };
+    if current_monitor.0.as_ref() == Some(&name) {
+        return;
+    }
+    screen_mode.set_if_neq(config.screen_modes.get(&name).copied().unwrap_or_default());
+    current_monitor.0 = Some(name);
+}
+
+// Put the window in the chosen mode, and remember it for the monitor the window is on
+fn apply_screen_mode(
+    screen_mode: Res<ScreenMode>,
+    current_monitor: Res<CurrentMonitor>,
+    mut config: ResMut<WindowConfig>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !screen_mode.is_changed() {
+        return;
+    }
+    for mut window in window_query.iter_mut() {
+        window.mode = screen_mode.window_mode();
+    }
+    // A monitor with nothing remembered is windowed, so there's nothing to write for it yet
+    if let Some(name) = &current_monitor.0 {
+        if config.screen_modes.get(name).copied().unwrap_or_default() != *screen_mode {
+            config.screen_modes.insert(name.clone(), *screen_mode);
+            config.save();
+        }
+    }
+}